@@ -0,0 +1,99 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use futures::executor::block_on;
+use futures::{future, stream};
+use vapix::v3::parameters::parse_parameter_list;
+use vapix::{Transport, TransportError as Error};
+
+const API_LIST: &str = r#"{"method": "getApiList", "apiVersion": "1.0", "data": {"apiList": [{"id": "disk-management", "version": "1.0", "name": "Edge storage Disk management", "docLink": ""}]}}"#;
+
+/// A transport which answers API discovery with `API_LIST`, and every other request with the same
+/// response.
+struct Fixture {
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Transport for Fixture {
+    type Output = future::Ready<Result<http::Response<Self::Body>, Error>>;
+    type Body = stream::Iter<std::vec::IntoIter<Result<Vec<u8>, Error>>>;
+    type Chunk = Vec<u8>;
+
+    fn roundtrip(&self, request: http::Request<Vec<u8>>) -> Self::Output {
+        let (content_type, body) = if request.uri().path() == "/axis-cgi/apidiscovery.cgi" {
+            ("application/json", API_LIST.as_bytes().to_vec())
+        } else {
+            (self.content_type, self.body.clone())
+        };
+
+        let response = http::Response::builder()
+            .status(http::StatusCode::OK)
+            .header(http::header::CONTENT_TYPE, content_type)
+            .body(stream::iter(vec![Ok(body)]))
+            .unwrap();
+        future::ready(Ok(response))
+    }
+}
+
+fn client(content_type: &'static str, body: &str) -> vapix::Client<Fixture> {
+    let transport = Fixture {
+        content_type,
+        body: body.as_bytes().to_vec(),
+    };
+    vapix::Client::try_new(transport, http::Uri::from_static("http://192.0.2.1")).unwrap()
+}
+
+/// Repeat the `<disk>` elements of a disk list until there are at least `count` of them.
+///
+/// No real device has this many disks, so large disk lists are synthesized from a two-disk
+/// response in `disks/9.80.2.2.xml`.
+fn disk_list(xml: &str, count: usize) -> String {
+    let start = xml.find("<disk ").unwrap();
+    let end = xml.find("</disks>").unwrap();
+    let disks = &xml[start..end];
+    let mut repeated = String::from(&xml[..start]);
+    while repeated.matches("<disk ").count() < count {
+        repeated.push_str(disks);
+    }
+    repeated.push_str(&xml[end..]);
+    repeated
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    for (version, xml) in &[
+        ("5.51.7", include_str!("parameters/5.51.7.xml")),
+        ("9.80.2.2", include_str!("parameters/9.80.2.2.xml")),
+    ] {
+        let client = client("text/xml", xml);
+        c.bench_function(&format!("parameter definitions {}", version), |b| {
+            b.iter(|| black_box(block_on(client.parameters().list_definitions(None)).unwrap()))
+        });
+    }
+
+    for (version, list) in &[
+        ("5.51.7", include_str!("parameters/5.51.7.txt")),
+        ("9.80.2.2", include_str!("parameters/9.80.2.2.txt")),
+    ] {
+        let client = client("text/plain", list);
+        c.bench_function(&format!("parameter list {}", version), |b| {
+            b.iter(|| black_box(block_on(client.parameters().list(None)).unwrap()))
+        });
+        c.bench_function(&format!("parameter import {}", version), |b| {
+            b.iter(|| black_box(parse_parameter_list(black_box(list))))
+        });
+    }
+
+    let xml = include_str!("disks/9.80.2.2.xml");
+    for &count in &[2, 1_000] {
+        let client = client("text/xml", &disk_list(xml, count));
+        let disk_management = block_on(client.services())
+            .unwrap()
+            .disk_management
+            .unwrap();
+        c.bench_function(&format!("disk list {}", count), |b| {
+            b.iter(|| black_box(block_on(disk_management.list()).unwrap()))
+        });
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
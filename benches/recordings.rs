@@ -0,0 +1,45 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use vapix::v3::recordings::*;
+
+fn list_response(count: usize) -> String {
+    let mut xml = format!(
+        "<?xml version=\"1.0\"?>\n<root>\n  <recordings totalnumberofrecordings=\"{0}\" numberofrecordings=\"{0}\">\n",
+        count
+    );
+    for i in 0..count {
+        xml.push_str(&format!(
+            "    <recording diskid=\"SD_DISK\" recordingid=\"20201009_{0:06}_9E3C_ACCC8E000000\" \
+             starttime=\"2020-10-09T15:30:02.123Z\" starttimelocal=\"2020-10-09T17:30:02.123+02:00\" \
+             stoptime=\"2020-10-09T15:31:02.456Z\" stoptimelocal=\"2020-10-09T17:31:02.456+02:00\" \
+             recordingtype=\"triggered\" eventid=\"motion\" eventtrigger=\"motion\" \
+             recordingstatus=\"completed\" source=\"1\" size=\"8388608\"></recording>\n",
+            i
+        ));
+    }
+    xml.push_str("  </recordings>\n</root>\n");
+    xml
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    for &count in &[100, 10_000] {
+        let xml = list_response(count);
+        c.bench_function(&format!("stream {} recordings", count), |b| {
+            b.iter(|| {
+                let reader = RecordingsReader::new(black_box(xml.as_bytes()));
+                for recording in reader {
+                    black_box(recording.unwrap());
+                }
+            })
+        });
+        c.bench_function(&format!("deserialize {} recordings", count), |b| {
+            b.iter(|| {
+                for recording in parse_list_response(black_box(xml.as_bytes())).unwrap() {
+                    black_box(recording);
+                }
+            })
+        });
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
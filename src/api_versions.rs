@@ -0,0 +1,180 @@
+use crate::{Client, Result, Transport};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// The version of one of a device's API families, like `2.01` for RTSP.
+///
+/// Versions are compared numerically, component by component, so `2.10` is newer than `2.01` and
+/// `10.0` is newer than `9.0`. Devices pad the minor version inconsistently, so `1.0` and `1.00`
+/// are equal.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ApiVersion {
+    /// The major version, e.g. `2` in `2.01`.
+    pub major: u16,
+    /// The minor version, e.g. `1` in `2.01`.
+    pub minor: u16,
+}
+
+impl ApiVersion {
+    /// Creates a new `ApiVersion`.
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{:02}", self.major, self.minor)
+    }
+}
+
+/// An error returned when parsing an `ApiVersion` fails.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ApiVersionParseError;
+
+impl fmt::Display for ApiVersionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid API version")
+    }
+}
+
+impl std::error::Error for ApiVersionParseError {}
+
+impl FromStr for ApiVersion {
+    type Err = ApiVersionParseError;
+
+    /// Parse a major version optionally followed by a dot and a minor version, as reported by
+    /// `Properties.API.*.Version`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |component: &str| {
+            if component.is_empty() || !component.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(ApiVersionParseError);
+            }
+            component.parse().map_err(|_| ApiVersionParseError)
+        };
+
+        let mut components = s.trim().splitn(2, '.');
+        let major = number(components.next().unwrap_or_default())?;
+        let minor = match components.next() {
+            Some(minor) => number(minor)?,
+            None => 0,
+        };
+        Ok(Self::new(major, minor))
+    }
+}
+
+impl TryFrom<String> for ApiVersion {
+    type Error = ApiVersionParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ApiVersion> for String {
+    fn from(version: ApiVersion) -> Self {
+        version.to_string()
+    }
+}
+
+/// The versions of the API families a device offers, as reported by the `Properties.API`
+/// parameter group.
+///
+/// Each version is `None` if the device does not offer that API family, or reports a version
+/// which can't be parsed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+pub struct ApiVersions {
+    /// The version of the VAPIX HTTP API, from `Properties.API.HTTP.Version`.
+    pub http: Option<ApiVersion>,
+    /// The version of the RTSP API, from `Properties.API.RTSP.Version`.
+    pub rtsp: Option<ApiVersion>,
+    /// The version of the metadata stream, from `Properties.API.Metadata.Version`.
+    pub metadata: Option<ApiVersion>,
+}
+
+impl ApiVersions {
+    /// Build `ApiVersions` from the contents of the `Properties.API` parameter group, keyed
+    /// relative to `root.Properties.API`, e.g. `HTTP.Version`.
+    pub(crate) fn from_params(params: &BTreeMap<&str, &str>) -> Self {
+        let get = |key: &str| params.get(key).and_then(|value| value.parse().ok());
+
+        Self {
+            http: get("HTTP.Version"),
+            rtsp: get("RTSP.Version"),
+            metadata: get("Metadata.Version"),
+        }
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Retrieve the device's `ApiVersions` from the `Properties.API` parameter group.
+    ///
+    /// Compare versions with `ApiVersion`'s ordering, rather than comparing the strings reported
+    /// by the device, to decide whether a feature is available.
+    pub async fn api_versions(&self) -> Result<ApiVersions> {
+        let params = self
+            .parameters()
+            .list(Some(&["root.Properties.API"][..]))
+            .await?;
+        let params: BTreeMap<&str, &str> = params
+            .iter()
+            .map(|(k, v)| (k.trim_start_matches("root.Properties.API."), v.as_str()))
+            .collect();
+
+        Ok(ApiVersions::from_params(&params))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!("3".parse(), Ok(ApiVersion::new(3, 0)));
+        assert_eq!("2.01".parse(), Ok(ApiVersion::new(2, 1)));
+        assert_eq!("1.0".parse::<ApiVersion>(), "1.00".parse());
+        assert_eq!(ApiVersion::new(2, 1).to_string(), "2.01");
+        for invalid in &["", "2.", ".1", "2.0.1", "v2", "2.x"] {
+            assert_eq!(invalid.parse::<ApiVersion>(), Err(ApiVersionParseError));
+        }
+
+        // Compared numerically, not lexicographically
+        assert!(ApiVersion::new(10, 0) > "9.99".parse().unwrap());
+        assert!(ApiVersion::new(2, 10) > "2.01".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn from_parameters() {
+        let device = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().unwrap().as_str(),
+                "/axis-cgi/param.cgi?action=list&group=root.Properties.API"
+            );
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.Properties.API.HTTP.AdminPath=/index.html
+root.Properties.API.HTTP.Version=3
+root.Properties.API.Metadata.Metadata=yes
+root.Properties.API.Metadata.Version=1.0
+root.Properties.API.RTSP.RTSPAuth=yes
+root.Properties.API.RTSP.Version=2.01
+"
+                .to_vec()])
+        });
+
+        assert_eq!(
+            device.api_versions().await.unwrap(),
+            ApiVersions {
+                http: Some(ApiVersion::new(3, 0)),
+                rtsp: Some(ApiVersion::new(2, 1)),
+                metadata: Some(ApiVersion::new(1, 0)),
+            }
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! An `AsyncRead` adapter for streaming response bodies.
+
+use futures::ready;
+use futures::TryStream;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A response body which can be read as a byte stream.
+///
+/// `BodyReader` implements `futures::io::AsyncRead`, and `tokio::io::AsyncRead` when the `tokio`
+/// feature is enabled, so a body can be piped into existing IO code like file writers or media
+/// demuxers. Transport errors are returned as `std::io::Error`s, preserving the `ErrorKind` of
+/// any underlying IO error.
+///
+/// The body is boxed, so a `BodyReader` is always `Unpin`.
+pub struct BodyReader<B: TryStream> {
+    body: Pin<Box<B>>,
+    chunk: Option<B::Ok>,
+    offset: usize,
+    content_length: Option<u64>,
+}
+
+// Chunks are never pinned, and the body is pinned on the heap
+impl<B: TryStream> Unpin for BodyReader<B> {}
+
+impl<B> BodyReader<B>
+where
+    B: TryStream<Error = crate::transport::Error>,
+    B::Ok: AsRef<[u8]>,
+{
+    pub(crate) fn new(parts: &http::response::Parts, body: B) -> Self {
+        let content_length = parts
+            .headers
+            .get(http::header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok());
+
+        Self {
+            body: Box::pin(body),
+            chunk: None,
+            offset: 0,
+            content_length,
+        }
+    }
+
+    /// The total size of the body, if the device reported a `Content-Length`.
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    fn poll_read_inner(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        loop {
+            if let Some(chunk) = self.chunk.as_ref() {
+                let remaining = &chunk.as_ref()[self.offset..];
+                if !remaining.is_empty() {
+                    let n = remaining.len().min(buf.len());
+                    buf[..n].copy_from_slice(&remaining[..n]);
+                    self.offset += n;
+                    return Poll::Ready(Ok(n));
+                }
+            }
+
+            self.chunk = None;
+            self.offset = 0;
+            match ready!(self.body.as_mut().try_poll_next(cx)) {
+                Some(Ok(chunk)) => self.chunk = Some(chunk),
+                Some(Err(e)) => return Poll::Ready(Err(io_error(e))),
+                None => return Poll::Ready(Ok(0)),
+            }
+        }
+    }
+}
+
+fn io_error(e: crate::transport::Error) -> io::Error {
+    let e = e.into_inner();
+    let kind = {
+        let mut source: Option<&(dyn std::error::Error + 'static)> = Some(&*e);
+        let mut kind = io::ErrorKind::Other;
+        while let Some(e) = source {
+            if let Some(io) = e.downcast_ref::<io::Error>() {
+                kind = io.kind();
+                break;
+            }
+            source = e.source();
+        }
+        kind
+    };
+    // `transport::Error` isn't `Sync`, so it can't be carried by an `io::Error` directly
+    io::Error::new(kind, e.to_string())
+}
+
+impl<B> futures::io::AsyncRead for BodyReader<B>
+where
+    B: TryStream<Error = crate::transport::Error>,
+    B::Ok: AsRef<[u8]>,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_read_inner(cx, buf)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<B> tokio::io::AsyncRead for BodyReader<B>
+where
+    B: TryStream<Error = crate::transport::Error>,
+    B::Ok: AsRef<[u8]>,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_read_inner(cx, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::io::AsyncReadExt;
+
+    fn reader(
+        chunks: Vec<Result<Vec<u8>, crate::transport::Error>>,
+    ) -> BodyReader<impl TryStream<Ok = Vec<u8>, Error = crate::transport::Error>> {
+        let (parts, ()) = http::Response::builder()
+            .header(http::header::CONTENT_LENGTH, "11")
+            .body(())
+            .unwrap()
+            .into_parts();
+        BodyReader::new(&parts, futures::stream::iter(chunks))
+    }
+
+    #[tokio::test]
+    async fn read() {
+        let mut body = reader(vec![
+            Ok(b"hello".to_vec()),
+            Ok(vec![]),
+            Ok(b" world".to_vec()),
+        ]);
+        assert_eq!(body.content_length(), Some(11));
+
+        let mut buf = [0u8; 3];
+        assert_eq!(body.read(&mut buf).await.unwrap(), 3);
+        assert_eq!(&buf, b"hel");
+
+        let mut rest = Vec::new();
+        body.read_to_end(&mut rest).await.unwrap();
+        assert_eq!(rest, b"lo world");
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        let mut body = reader(vec![
+            Ok(b"partial".to_vec()),
+            Err(crate::transport::Error::new(io::Error::from(
+                io::ErrorKind::ConnectionReset,
+            ))),
+        ]);
+
+        let mut buf = Vec::new();
+        let e = body.read_to_end(&mut buf).await.unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::ConnectionReset);
+        assert_eq!(buf, b"partial");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn tokio() {
+        use tokio::io::AsyncReadExt;
+
+        let mut body = reader(vec![Ok(b"hello".to_vec()), Ok(b" world".to_vec())]);
+        let mut buf = Vec::new();
+        AsyncReadExt::read_to_end(&mut body, &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(buf, b"hello world");
+    }
+}
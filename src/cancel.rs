@@ -0,0 +1,130 @@
+use crate::{Error, Result};
+use futures::channel::oneshot;
+use futures::future::{self, Either, Shared};
+use futures::prelude::*;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// A token for cancelling long-running operations, like uploads, downloads, and event streams.
+///
+/// Every clone of a `CancelHandle` refers to the same token, so one clone can be handed to the
+/// operation and another kept by a UI's cancel button. Once cancelled, a token stays cancelled.
+///
+/// `CancelHandle` does not depend on any particular async runtime.
+///
+/// # Drop semantics
+///
+/// Every future and stream in this crate can also be cancelled by dropping it. This abandons any
+/// request in flight, but cannot recall a request which the device has already received in full:
+/// an uploaded firmware image or application package is installed regardless. Operations which
+/// hold state on the device, like `v4::events::Subscription`, document what dropping leaves
+/// behind, and offer a way to cancel which cleans up after itself.
+#[derive(Clone)]
+pub struct CancelHandle {
+    sender: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+    receiver: Shared<oneshot::Receiver<()>>,
+}
+
+impl CancelHandle {
+    /// Create a token which has not been cancelled.
+    pub fn new() -> Self {
+        let (sender, receiver) = oneshot::channel();
+        Self {
+            sender: Arc::new(Mutex::new(Some(sender))),
+            receiver: receiver.shared(),
+        }
+    }
+
+    /// Cancel every operation using this token.
+    pub fn cancel(&self) {
+        let sender = self.sender.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(sender) = sender {
+            let _ = sender.send(());
+        }
+    }
+
+    /// Has this token been cancelled?
+    pub fn is_cancelled(&self) -> bool {
+        self.sender
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_none()
+    }
+
+    /// A future which resolves once this token is cancelled.
+    pub fn cancelled(&self) -> impl Future<Output = ()> + Send + Unpin + 'static {
+        // The future holds a clone of the handle, keeping the sender alive, so that the receiver
+        // resolves only when cancelled
+        let handle = self.clone();
+        handle.receiver.clone().map(move |_| drop(handle))
+    }
+
+    /// Run `future` until it completes or this token is cancelled, in which case `future` is
+    /// dropped and the result is `Error::Cancelled`.
+    pub async fn run<F, R>(&self, future: F) -> Result<R>
+    where
+        F: Future<Output = Result<R>>,
+    {
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        futures::pin_mut!(future);
+        match future::select(future, self.cancelled()).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(Error::Cancelled),
+        }
+    }
+
+    /// Yield items from `stream` until this token is cancelled, at which point `stream` ends.
+    pub fn run_stream<S: Stream>(&self, stream: S) -> impl Stream<Item = S::Item> {
+        stream.take_until(self.cancelled())
+    }
+}
+
+impl Default for CancelHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for CancelHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelHandle")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn run() {
+        let handle = CancelHandle::new();
+        assert_eq!(handle.run(async { Ok(1) }).await.unwrap(), 1);
+
+        let pending = handle.run(future::pending::<Result<()>>());
+        let canceller = handle.clone();
+        let (result, _) = future::join(pending, async move { canceller.cancel() }).await;
+        match result {
+            Err(Error::Cancelled) => {}
+            other => panic!("expected Cancelled, got {:?}", other),
+        }
+
+        assert!(handle.is_cancelled());
+        match handle.run(async { Ok(2) }).await {
+            Err(Error::Cancelled) => {}
+            other => panic!("expected Cancelled, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn run_stream() {
+        let handle = CancelHandle::new();
+        let mut stream = Box::pin(handle.run_stream(stream::iter(0..)));
+        assert_eq!(stream.next().await, Some(0));
+        handle.cancel();
+        assert_eq!(stream.next().await, None);
+    }
+}
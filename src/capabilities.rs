@@ -0,0 +1,218 @@
+//! What is known about the APIs offered by particular firmware versions.
+//!
+//! Devices don't describe everything they can do, and what they do describe isn't always
+//! accurate. `hints()` looks up a `FirmwareVersion` in a table compiled into the crate, so callers
+//! can decide which APIs to try, or which quirks to expect, before making any requests.
+
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// An AXIS firmware version, like `5.51.7` or `9.80.2.2`.
+///
+/// Versions are compared component by component. Omitted components are zero, so `8.50` and
+/// `8.50.0.0` are equal.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct FirmwareVersion {
+    /// The major version, e.g. `9` in `9.80.2.2`.
+    pub major: u16,
+    /// The minor version, e.g. `80` in `9.80.2.2`.
+    pub minor: u16,
+    /// The patch version, e.g. `2` in `9.80.2.2`.
+    pub patch: u16,
+    /// The build number, e.g. `2` in `9.80.2.2`.
+    pub build: u16,
+}
+
+impl FirmwareVersion {
+    /// Creates a new `FirmwareVersion`.
+    pub const fn new(major: u16, minor: u16, patch: u16, build: u16) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            build,
+        }
+    }
+
+    /// The hints which apply to this version.
+    pub fn hints(self) -> Vec<Hint> {
+        hints(self)
+    }
+}
+
+impl fmt::Display for FirmwareVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)?;
+        if self.patch != 0 || self.build != 0 {
+            write!(f, ".{}", self.patch)?;
+        }
+        if self.build != 0 {
+            write!(f, ".{}", self.build)?;
+        }
+        Ok(())
+    }
+}
+
+/// An error returned when parsing a `FirmwareVersion` fails.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FirmwareVersionParseError;
+
+impl fmt::Display for FirmwareVersionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid firmware version")
+    }
+}
+
+impl std::error::Error for FirmwareVersionParseError {}
+
+impl FromStr for FirmwareVersion {
+    type Err = FirmwareVersionParseError;
+
+    /// Parse a version of two to four dot-separated numbers, as reported by
+    /// `Properties.Firmware.Version`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut components = [0u16; 4];
+        let mut count = 0;
+        for component in s.trim().split('.') {
+            if count == components.len()
+                || component.is_empty()
+                || !component.bytes().all(|b| b.is_ascii_digit())
+            {
+                return Err(FirmwareVersionParseError);
+            }
+            components[count] = component.parse().map_err(|_| FirmwareVersionParseError)?;
+            count += 1;
+        }
+        if count < 2 {
+            return Err(FirmwareVersionParseError);
+        }
+
+        let [major, minor, patch, build] = components;
+        Ok(Self::new(major, minor, patch, build))
+    }
+}
+
+impl TryFrom<String> for FirmwareVersion {
+    type Error = FirmwareVersionParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<FirmwareVersion> for String {
+    fn from(version: FirmwareVersion) -> Self {
+        version.to_string()
+    }
+}
+
+/// Something known about the APIs offered by a range of firmware versions.
+///
+/// Hints are either APIs which become available in some version, or quirks which affect a range
+/// of versions. Each hint applies from `since()` (inclusive) until `until()` (exclusive).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Hint {
+    /// `/axis-cgi/apidiscovery.cgi` is available, so `Client::services()` can discover v4 APIs.
+    ///
+    /// Older devices offer only v3 APIs, which must be probed individually.
+    ApiDiscovery,
+    /// WebRTC signaling is available through `Client::webrtc()`.
+    WebRtc,
+}
+
+impl Hint {
+    /// Every hint in the table.
+    pub const ALL: &'static [Hint] = &[Hint::ApiDiscovery, Hint::WebRtc];
+
+    /// The first version to which this hint applies, if it has a lower bound.
+    pub fn since(self) -> Option<FirmwareVersion> {
+        match self {
+            Hint::ApiDiscovery => Some(FirmwareVersion::new(8, 50, 0, 0)),
+            Hint::WebRtc => Some(FirmwareVersion::new(11, 0, 0, 0)),
+        }
+    }
+
+    /// The first version to which this hint no longer applies, if it has an upper bound.
+    pub fn until(self) -> Option<FirmwareVersion> {
+        match self {
+            Hint::ApiDiscovery | Hint::WebRtc => None,
+        }
+    }
+
+    /// Does this hint apply to `version`?
+    pub fn applies_to(self, version: FirmwareVersion) -> bool {
+        self.since().map(|since| version >= since).unwrap_or(true)
+            && self.until().map(|until| version < until).unwrap_or(true)
+    }
+
+    /// A short description of this hint.
+    pub fn description(self) -> &'static str {
+        match self {
+            Hint::ApiDiscovery => "apidiscovery.cgi added in 8.50",
+            Hint::WebRtc => "WebRTC signaling added in AXIS OS 11",
+        }
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+/// The hints which apply to a firmware version.
+pub fn hints(version: FirmwareVersion) -> Vec<Hint> {
+    Hint::ALL
+        .iter()
+        .copied()
+        .filter(|hint| hint.applies_to(version))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!("5.51.7".parse(), Ok(FirmwareVersion::new(5, 51, 7, 0)));
+        assert_eq!("9.80.2.2".parse(), Ok(FirmwareVersion::new(9, 80, 2, 2)));
+        assert_eq!("10.12".parse(), Ok(FirmwareVersion::new(10, 12, 0, 0)));
+        assert_eq!(FirmwareVersion::new(9, 80, 2, 2).to_string(), "9.80.2.2");
+        assert_eq!(FirmwareVersion::new(10, 12, 0, 0).to_string(), "10.12");
+        assert_eq!(FirmwareVersion::new(8, 50, 0, 1).to_string(), "8.50.0.1");
+
+        for bad in &["", "9", "9.", "9.80.2.2.1", "9.x", "9.-1", "9.80.99999"] {
+            assert_eq!(
+                bad.parse::<FirmwareVersion>(),
+                Err(FirmwareVersionParseError),
+                "{:?}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn table() {
+        let version = |s: &str| s.parse::<FirmwareVersion>().unwrap();
+        assert!(version("8.50") > version("8.40.1"));
+        assert!(version("10.2") < version("10.12"));
+
+        assert_eq!(hints(version("5.51.7")), vec![]);
+        assert_eq!(hints(version("8.50")), vec![Hint::ApiDiscovery]);
+        assert_eq!(
+            version("11.9.60").hints(),
+            vec![Hint::ApiDiscovery, Hint::WebRtc]
+        );
+
+        for hint in Hint::ALL {
+            if let (Some(since), Some(until)) = (hint.since(), hint.until()) {
+                assert!(since < until, "{:?} has an empty range", hint);
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::num::NonZeroU32;
+
+/// A video channel.
+///
+/// VAPIX is inconsistent about channel numbering: CGI arguments like `camera=1` and recording
+/// sources count from 1, while parameter groups like `ImageSource.I0` count from 0. `Channel`
+/// stores the 1-based channel number and converts explicitly to and from 0-based indices, so that
+/// the two can't be confused.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Channel(NonZeroU32);
+
+impl Channel {
+    /// Construct a `Channel` from a 1-based channel number.
+    ///
+    /// Returns `None` if `number` is 0.
+    pub fn new(number: u32) -> Option<Self> {
+        NonZeroU32::new(number).map(Self)
+    }
+
+    /// Construct a `Channel` from a 0-based index, as used in parameter groups like
+    /// `ImageSource.I0`.
+    pub fn from_index(index: u32) -> Self {
+        Self(NonZeroU32::new(index.saturating_add(1)).unwrap())
+    }
+
+    /// The first channel, which every video device has.
+    pub fn first() -> Self {
+        Self::from_index(0)
+    }
+
+    /// The 1-based channel number.
+    pub fn number(self) -> u32 {
+        self.0.get()
+    }
+
+    /// The 0-based channel index.
+    pub fn index(self) -> u32 {
+        self.0.get() - 1
+    }
+
+    /// Ensure this channel exists on a device with `channel_count` channels.
+    pub fn validate(self, channel_count: u32) -> Result<Self> {
+        if self.number() <= channel_count {
+            Ok(self)
+        } else {
+            Err(Error::Other(
+                "channel number exceeds the device's channel count",
+            ))
+        }
+    }
+}
+
+impl Default for Channel {
+    fn default() -> Self {
+        Self::first()
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<NonZeroU32> for Channel {
+    fn from(number: NonZeroU32) -> Self {
+        Self(number)
+    }
+}
+
+impl From<Channel> for NonZeroU32 {
+    fn from(channel: Channel) -> Self {
+        channel.0
+    }
+}
+
+impl TryFrom<u32> for Channel {
+    type Error = Error;
+
+    fn try_from(number: u32) -> Result<Self> {
+        Self::new(number).ok_or(Error::Other("channel numbers start at 1"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbering() {
+        let channel = Channel::from_index(0);
+        assert_eq!(channel, Channel::first());
+        assert_eq!(channel.number(), 1);
+        assert_eq!(channel.index(), 0);
+        assert_eq!(Channel::new(3).unwrap().index(), 2);
+        assert_eq!(Channel::new(0), None);
+        assert_eq!(channel.to_string(), "1");
+
+        assert!(Channel::new(4).unwrap().validate(4).is_ok());
+        assert!(Channel::new(5).unwrap().validate(4).is_err());
+    }
+}
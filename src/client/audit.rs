@@ -0,0 +1,87 @@
+use super::*;
+
+impl<T: Transport> Client<T> {
+    /// Record an audit trail on the device of changes made through this client.
+    ///
+    /// AXIS devices do not provide a general-purpose endpoint for writing to the system log, so
+    /// this requires a CGI, typically provided by an ACAP, which logs the `message` query
+    /// parameter of each `GET` request it receives. Once configured, each mutating operation sends
+    /// a one-line description of the change to `path` after the change succeeds, e.g.
+    /// `vapix: updated parameters Network.HostName`.
+    ///
+    /// Audit entries are best-effort: failing to write one does not cause the operation itself to
+    /// fail. Parameter values are never logged, since they may contain secrets.
+    pub fn with_audit_log<P: Into<String>>(mut self, path: P) -> Self {
+        self.audit_log = Some(path.into());
+        self
+    }
+
+    /// Write an entry to the device audit log, if configured.
+    pub(crate) async fn audit<F: FnOnce() -> String>(&self, description: F) {
+        let path = match &self.audit_log {
+            Some(path) => path,
+            None => return,
+        };
+
+        let message = format!("vapix: {}", description());
+        let uri = match self.uri_for_args(path, [("message", message)]) {
+            Ok(uri) => uri,
+            Err(_) => return,
+        };
+        let req = match http::Request::builder()
+            .method(http::Method::GET)
+            .uri(uri)
+            .body(Vec::new())
+        {
+            Ok(req) => req,
+            Err(_) => return,
+        };
+        let (mut parts, body) = req.into_parts();
+        parts
+            .headers
+            .insert(http::header::ACCEPT, http::HeaderValue::from_static("*/*"));
+
+        if let Ok((_, body)) = self.send_authenticated(&parts, &body, &self.timeouts).await {
+            let _ = Self::read_body(body).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn parameter_update() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let requests_ = requests.clone();
+
+        let device = crate::mock_client(move |req| {
+            requests_
+                .lock()
+                .unwrap()
+                .push(req.uri().path_and_query().unwrap().to_string());
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"OK".to_vec()])
+        })
+        .with_audit_log("/local/auditlog/log.cgi");
+
+        device
+            .parameters()
+            .update(vec![("Network.HostName", "lobby")])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            requests.lock().unwrap().as_slice(),
+            &[
+                "/axis-cgi/param.cgi?Network.HostName=lobby&action=update".to_string(),
+                "/local/auditlog/log.cgi?message=vapix%3A+updated+parameters+Network.HostName"
+                    .to_string(),
+            ]
+        );
+    }
+}
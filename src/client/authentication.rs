@@ -1,31 +1,150 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
+use zeroize::Zeroizing;
 
-#[derive(Debug)]
 pub(crate) struct Authentication {
-    username: String,
-    password: String,
+    /// Credentials to try, in order of preference. Never empty.
+    credentials: Vec<Credentials>,
+    /// The index of the credentials in use, which is the last ones the device accepted.
+    current: AtomicUsize,
     prompt: Mutex<Option<digest_auth::WwwAuthenticateHeader>>,
 }
 
+/// A username and password, both zeroized on drop.
+#[derive(Clone)]
+struct Credentials {
+    username: Zeroizing<String>,
+    password: Password,
+}
+
 impl Clone for Authentication {
     fn clone(&self) -> Self {
         Authentication {
-            username: self.username.clone(),
-            password: self.password.clone(),
-            prompt: Mutex::new(self.prompt.lock().unwrap().as_ref().cloned()),
+            credentials: self.credentials.clone(),
+            current: AtomicUsize::new(self.current()),
+            prompt: Mutex::new(
+                self.prompt
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .as_ref()
+                    .cloned(),
+            ),
         }
     }
 }
 
+impl std::fmt::Debug for Authentication {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Authentication")
+            .field("username", &self.username())
+            .field("password", &"[redacted]")
+            .finish()
+    }
+}
+
+/// A password, held in a `secrecy::SecretString`, which is zeroized on drop.
+#[cfg(feature = "secrecy")]
+struct Password(secrecy::SecretString);
+
+#[cfg(feature = "secrecy")]
+impl Password {
+    fn new(password: &str) -> Self {
+        Password(secrecy::SecretString::new(password.to_owned()))
+    }
+
+    fn expose(&self) -> &str {
+        secrecy::ExposeSecret::expose_secret(&self.0).as_str()
+    }
+}
+
+/// A password, which is zeroized on drop.
+#[cfg(not(feature = "secrecy"))]
+struct Password(Zeroizing<String>);
+
+#[cfg(not(feature = "secrecy"))]
+impl Password {
+    fn new(password: &str) -> Self {
+        Password(Zeroizing::new(password.to_owned()))
+    }
+
+    fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Clone for Password {
+    fn clone(&self) -> Self {
+        Password::new(self.expose())
+    }
+}
+
 impl Authentication {
     pub fn new(username: &str, password: &str) -> Self {
         Self {
-            username: username.to_owned(),
-            password: password.to_owned(),
+            credentials: vec![Credentials {
+                username: Zeroizing::new(username.to_owned()),
+                password: Password::new(password),
+            }],
+            current: AtomicUsize::new(0),
             prompt: Mutex::new(None),
         }
     }
 
+    /// Add credentials to try if the device rejects all the preceding ones.
+    pub fn with_fallback(mut self, username: &str, password: &str) -> Self {
+        self.credentials.push(Credentials {
+            username: Zeroizing::new(username.to_owned()),
+            password: Password::new(password),
+        });
+        self
+    }
+
+    fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    fn credentials(&self) -> &Credentials {
+        &self.credentials[self.current() % self.credentials.len()]
+    }
+
+    pub fn username(&self) -> &str {
+        self.credentials().username.as_str()
+    }
+
+    pub fn password(&self) -> &str {
+        self.credentials().password.expose()
+    }
+
+    /// Returns a token identifying the credentials currently in use, for `try_next()`.
+    pub fn attempt(&self) -> usize {
+        self.current()
+    }
+
+    /// Move on to the next credentials after the device rejected those identified by `rejected`.
+    ///
+    /// Returns `false` once every credential has been tried since `first`, leaving the current
+    /// credentials unchanged.
+    pub fn try_next(&self, first: usize, rejected: usize) -> bool {
+        let next = (rejected + 1) % self.credentials.len();
+        if next == first % self.credentials.len() {
+            return false;
+        }
+
+        // Another request may have moved on already, in which case it's fine to use its choice
+        let _ = self
+            .current
+            .compare_exchange(rejected, next, Ordering::Relaxed, Ordering::Relaxed);
+        true
+    }
+
+    /// Has the device sent a digest prompt, so that requests can be authenticated up front?
+    pub fn has_prompt(&self) -> bool {
+        self.prompt
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_some()
+    }
+
     pub fn should_retry(&self, parts: &http::response::Parts) -> bool {
         // get the header as bytes
         let header = match parts.headers.get(http::header::WWW_AUTHENTICATE) {
@@ -46,7 +165,10 @@ impl Authentication {
         };
 
         // store this header
-        self.prompt.lock().unwrap().replace(header);
+        self.prompt
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .replace(header);
 
         // we've updated our WWW-Authenticate header
         // the requester should retry iff the response has a 401 code
@@ -59,9 +181,10 @@ impl Authentication {
         path_and_query: &http::uri::PathAndQuery,
         body: &[u8],
     ) -> Option<String> {
+        let credentials = self.credentials();
         let ctx = digest_auth::AuthContext::new_with_method(
-            &self.username,
-            &self.password,
+            credentials.username.as_str(),
+            credentials.password.expose(),
             path_and_query.as_str(),
             if !body.is_empty() { Some(body) } else { None },
             match method.as_str() {
@@ -78,7 +201,7 @@ impl Authentication {
 
         self.prompt
             .lock()
-            .unwrap()
+            .unwrap_or_else(|e| e.into_inner())
             .as_mut()
             .and_then(|prompt| prompt.respond(&ctx).ok())
             .map(|h| h.to_header_string())
@@ -227,4 +350,40 @@ mod tests {
             auth2.prompt.lock().unwrap().as_ref()
         );
     }
+
+    #[test]
+    fn fallback() {
+        let auth = Authentication::new("root", "pass")
+            .with_fallback("root", "new-pass")
+            .with_fallback("admin", "admin");
+
+        let first = auth.attempt();
+        assert_eq!((auth.username(), auth.password()), ("root", "pass"));
+        assert!(auth.try_next(first, auth.attempt()));
+        assert_eq!((auth.username(), auth.password()), ("root", "new-pass"));
+        assert!(auth.try_next(first, auth.attempt()));
+        assert_eq!((auth.username(), auth.password()), ("admin", "admin"));
+        assert!(!auth.try_next(first, auth.attempt()));
+        assert_eq!((auth.username(), auth.password()), ("admin", "admin"));
+
+        // the last credentials tried are remembered, even by clones
+        let auth = auth.clone();
+        let first = auth.attempt();
+        assert!(auth.try_next(first, auth.attempt()));
+        assert_eq!((auth.username(), auth.password()), ("root", "pass"));
+    }
+
+    #[test]
+    fn debug_redacts_password() {
+        let auth = Authentication::new("user", "hunter22");
+        let debug = format!("{:?}", auth);
+        assert!(debug.contains("user"));
+        assert!(!debug.contains("hunter22"));
+
+        let auth = auth.with_fallback("admin", "admin");
+        assert_eq!(
+            format!("{:?}", auth),
+            r#"Authentication { username: "user", password: "[redacted]" }"#
+        );
+    }
 }
@@ -0,0 +1,122 @@
+use super::*;
+use tokio::io::AsyncWriteExt;
+
+/// The progress of a download to a file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DownloadProgress {
+    /// The number of bytes written to the file so far.
+    pub bytes_written: u64,
+    /// The total size of the download, if the device reported a `Content-Length`.
+    pub content_length: Option<u64>,
+}
+
+impl<T: Transport> Client<T> {
+    /// Make a request, writing the body of a successful response to `file` as it arrives.
+    ///
+    /// `progress` is called after each chunk is written. Returns the number of bytes written.
+    ///
+    /// Dropping the future stops the download, leaving whatever was written so far in `file`.
+    pub(crate) async fn download_to_file<P>(
+        &self,
+        req: http::Request<Vec<u8>>,
+        expected_content_type: &'static str,
+        file: &mut tokio::fs::File,
+        mut progress: P,
+    ) -> Result<u64>
+    where
+        P: FnMut(DownloadProgress),
+    {
+        let (parts, body) = self.roundtrip_streaming(req, expected_content_type).await?;
+        let content_length = parts
+            .headers
+            .get(http::header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok());
+
+        let mut body = Box::pin(body);
+        let mut bytes_written = 0;
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk?;
+            file.write_all(chunk.as_ref()).await.map_err(Error::Io)?;
+            bytes_written += chunk.as_ref().len() as u64;
+            progress(DownloadProgress {
+                bytes_written,
+                content_length,
+            });
+        }
+        file.flush().await.map_err(Error::Io)?;
+
+        Ok(bytes_written)
+    }
+
+    /// Generate a server report, writing it to `file` as a zip archive as it arrives.
+    ///
+    /// Server reports include the device's logs and configuration along with a snapshot from each
+    /// channel, and can run to several megabytes. Returns the number of bytes written.
+    ///
+    /// Dropping the future, for example through `CancelHandle::run()`, stops the download and
+    /// leaves a partial archive in `file`, which the caller should discard.
+    pub async fn server_report_to_file<P>(
+        &self,
+        file: &mut tokio::fs::File,
+        progress: P,
+    ) -> Result<u64>
+    where
+        P: FnMut(DownloadProgress),
+    {
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.uri_for("/axis-cgi/serverreport.cgi?mode=zip_with_image")?)
+            .body(Vec::new())?;
+
+        self.download_to_file(req, "application/zip", file, progress)
+            .await
+            .map_404_to_feature_unavailable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn server_report() {
+        let device = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().unwrap().as_str(),
+                "/axis-cgi/serverreport.cgi?mode=zip_with_image"
+            );
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/zip")
+                .header(http::header::CONTENT_LENGTH, "8")
+                .body(vec![b"PK\x03\x04".to_vec(), b"\0\0\0\0".to_vec()])
+        });
+
+        let path = std::env::temp_dir().join(format!("vapix-report-{}.zip", std::process::id()));
+        let mut file = tokio::fs::File::create(&path).await.unwrap();
+        let mut updates = Vec::new();
+        let written = device
+            .server_report_to_file(&mut file, |p| updates.push(p))
+            .await
+            .unwrap();
+        drop(file);
+
+        assert_eq!(written, 8);
+        assert_eq!(
+            updates,
+            vec![
+                DownloadProgress {
+                    bytes_written: 4,
+                    content_length: Some(8),
+                },
+                DownloadProgress {
+                    bytes_written: 8,
+                    content_length: Some(8),
+                },
+            ]
+        );
+        assert_eq!(std::fs::read(&path).unwrap(), b"PK\x03\x04\0\0\0\0");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,231 @@
+use super::*;
+use std::time::Duration;
+
+/// How a `Client` retries requests which the device rejects as temporarily overloaded.
+///
+/// Devices under load answer some CGIs with `503 Service Unavailable` or `429 Too Many Requests`,
+/// often with a `Retry-After:` header. These requests are retried after the delay the device asks
+/// for, or after `default_delay` if it doesn't say, up to `max_retries` times. If the device asks
+/// for a delay longer than `max_delay`, the request fails immediately instead of waiting.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    default_delay: Duration,
+    max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// A policy which retries up to `max_retries` times, waiting 1 second by default and at most
+    /// 10 seconds between attempts.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            default_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+
+    /// A policy which never retries.
+    pub fn never() -> Self {
+        Self::new(0)
+    }
+
+    /// Set the delay used when the device does not send `Retry-After:`.
+    pub fn with_default_delay(mut self, default_delay: Duration) -> Self {
+        self.default_delay = default_delay;
+        self
+    }
+
+    /// Set the longest delay the client is willing to wait before a retry.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Determine how long to wait before retrying a response, or `None` if it should not be
+    /// retried.
+    pub(crate) fn delay_for<N>(
+        &self,
+        attempt: u32,
+        response_parts: &http::response::Parts,
+        now: N,
+    ) -> Option<Duration>
+    where
+        N: FnOnce() -> chrono::DateTime<chrono::FixedOffset>,
+    {
+        match response_parts.status {
+            http::StatusCode::SERVICE_UNAVAILABLE | http::StatusCode::TOO_MANY_REQUESTS => {}
+            _ => return None,
+        }
+        if attempt >= self.max_retries {
+            return None;
+        }
+
+        let delay = match response_parts.headers.get(http::header::RETRY_AFTER) {
+            Some(value) => {
+                parse_retry_after(value, response_parts.headers.get(http::header::DATE), now)?
+            }
+            None => self.default_delay,
+        };
+
+        if delay <= self.max_delay {
+            Some(delay)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(2)
+    }
+}
+
+/// Parse a `Retry-After:` value, which is either a number of seconds or an HTTP date. Dates are
+/// interpreted relative to the response's `Date:` header if present, so that clock skew between
+/// client and device doesn't matter, and otherwise relative to `now`.
+fn parse_retry_after<N>(
+    value: &http::HeaderValue,
+    date: Option<&http::HeaderValue>,
+    now: N,
+) -> Option<Duration>
+where
+    N: FnOnce() -> chrono::DateTime<chrono::FixedOffset>,
+{
+    let value = value.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let parse_date = |v: &str| chrono::DateTime::parse_from_rfc2822(v).ok();
+    let retry_at = parse_date(value)?;
+    let now = date
+        .and_then(|d| d.to_str().ok())
+        .and_then(parse_date)
+        .unwrap_or_else(now);
+
+    Some((retry_at - now).to_std().unwrap_or_default())
+}
+
+impl<T: Transport> Client<T> {
+    /// Set the policy for retrying requests the device rejects as temporarily overloaded.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Set the retry policy for requests to paths beginning with `path_prefix`, overriding the
+    /// client-wide policy. The longest matching prefix wins.
+    pub fn with_endpoint_retry_policy<P: Into<String>>(
+        mut self,
+        path_prefix: P,
+        retry_policy: RetryPolicy,
+    ) -> Self {
+        self.endpoint_retry_policies
+            .push((path_prefix.into(), retry_policy));
+        self
+    }
+
+    /// Return the retry policy applicable to a request path.
+    pub(crate) fn retry_policy_for(&self, path: &str) -> &RetryPolicy {
+        self.endpoint_retry_policies
+            .iter()
+            .filter(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, policy)| policy)
+            .unwrap_or(&self.retry_policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn overloaded_then_ok(
+        failures: usize,
+        retry_after: &'static str,
+    ) -> (Arc<AtomicUsize>, Client<impl Transport>) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let client = crate::mock_client({
+            let count = count.clone();
+            move |_| {
+                if count.fetch_add(1, Ordering::SeqCst) < failures {
+                    http::Response::builder()
+                        .status(http::StatusCode::SERVICE_UNAVAILABLE)
+                        .header(http::header::RETRY_AFTER, retry_after)
+                        .body(vec![Vec::new()])
+                } else {
+                    http::Response::builder()
+                        .status(http::StatusCode::OK)
+                        .header(http::header::CONTENT_TYPE, "text/plain")
+                        .body(vec![b"OK".to_vec()])
+                }
+            }
+        });
+        (count, client)
+    }
+
+    fn request<T: Transport>(client: &Client<T>) -> http::Request<Vec<u8>> {
+        http::Request::builder()
+            .uri(client.uri_for("/axis-cgi/param.cgi").unwrap())
+            .body(Vec::new())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn retry_after() {
+        let (count, client) = overloaded_then_ok(2, "0");
+        client
+            .roundtrip(request(&client), "text/plain")
+            .await
+            .unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+
+        // too many failures
+        let (count, client) = overloaded_then_ok(3, "0");
+        let e = client
+            .roundtrip(request(&client), "text/plain")
+            .await
+            .unwrap_err();
+        assert!(e.is_transient());
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+
+        // too long a delay
+        let (count, client) = overloaded_then_ok(1, "3600");
+        assert!(client
+            .roundtrip(request(&client), "text/plain")
+            .await
+            .is_err());
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        // per-endpoint override
+        let (count, client) = overloaded_then_ok(1, "0");
+        let client = client.with_endpoint_retry_policy("/axis-cgi/param", RetryPolicy::never());
+        assert!(client
+            .roundtrip(request(&client), "text/plain")
+            .await
+            .is_err());
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn retry_after_date() {
+        let value = http::HeaderValue::from_static("Fri, 09 Oct 2020 15:30:32 GMT");
+        let date = http::HeaderValue::from_static("Fri, 09 Oct 2020 15:30:02 GMT");
+        let now = || panic!("the device's date should be used");
+        assert_eq!(
+            parse_retry_after(&value, Some(&date), now),
+            Some(Duration::from_secs(30))
+        );
+
+        use chrono::TimeZone;
+        let clock = crate::FixedClock::new(chrono::Utc.ymd(2020, 10, 9).and_hms(15, 30, 22));
+        assert_eq!(
+            parse_retry_after(&value, None, || clock.now()),
+            Some(Duration::from_secs(10))
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use super::*;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+/// A request about to be sent, as presented to a request signer.
+///
+/// Its `Debug` output redacts the `Authorization:` header.
+#[derive(Clone)]
+pub struct SignableRequest {
+    /// The request method.
+    pub method: http::Method,
+    /// The absolute URI of the request.
+    pub uri: http::Uri,
+    /// The request headers, including any `Authorization:` header.
+    pub headers: http::HeaderMap,
+    /// The request body, or `None` if the body is streamed and can't be read in advance.
+    pub body: Option<Vec<u8>>,
+}
+
+impl fmt::Debug for SignableRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers: Vec<(&http::header::HeaderName, &dyn fmt::Debug)> = self
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                let value: &dyn fmt::Debug = if name == http::header::AUTHORIZATION {
+                    &"[redacted]"
+                } else {
+                    value
+                };
+                (name, value)
+            })
+            .collect();
+        f.debug_struct("SignableRequest")
+            .field("method", &self.method)
+            .field("uri", &self.uri)
+            .field("headers", &headers)
+            .field("body", &self.body.as_ref().map(Vec::len))
+            .finish()
+    }
+}
+
+type SignerFn =
+    Arc<dyn Fn(SignableRequest) -> BoxFuture<'static, Result<http::HeaderMap>> + Send + Sync>;
+
+/// The request signer configured on a `Client`, if any.
+#[derive(Clone, Default)]
+pub(crate) struct Signer(Option<SignerFn>);
+
+impl fmt::Debug for Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Signer").field(&self.0.is_some()).finish()
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Sign every request this client sends with `signer`, for deployments which route requests
+    /// through a gateway requiring a signature header.
+    ///
+    /// `signer` is called with each request just before it is sent, after any `Authorization:`
+    /// header has been added, and returns headers to add to the request. It is called again for
+    /// each retry, redirect, and authentication round trip, since each of these is a new request.
+    /// If it fails, the request is not sent and the error is returned.
+    pub fn with_request_signer<F, Fut>(mut self, signer: F) -> Self
+    where
+        F: Fn(SignableRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<http::HeaderMap>> + Send + 'static,
+    {
+        self.signer = Signer(Some(Arc::new(move |request| signer(request).boxed())));
+        self
+    }
+
+    /// Add the signer's headers to `request`. `streamed` requests present no body to the signer.
+    pub(crate) async fn sign(
+        &self,
+        request: &mut http::Request<Vec<u8>>,
+        streamed: bool,
+    ) -> Result<()> {
+        let signer = match &self.signer.0 {
+            Some(signer) => signer,
+            None => return Ok(()),
+        };
+
+        let headers = signer(SignableRequest {
+            method: request.method().clone(),
+            uri: request.uri().clone(),
+            headers: request.headers().clone(),
+            body: if streamed {
+                None
+            } else {
+                Some(request.body().clone())
+            },
+        })
+        .await?;
+
+        // Iterating a `HeaderMap` names only the first of each header's values
+        let mut current = None;
+        for (name, value) in headers {
+            if let Some(name) = name {
+                // The signer's headers replace any of the same name
+                request.headers_mut().remove(&name);
+                current = Some(name);
+            }
+            if let Some(name) = &current {
+                request.headers_mut().append(name.clone(), value);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn signed_requests() {
+        fn signature(method: &http::Method, uri: &http::Uri, body: Option<&[u8]>) -> String {
+            format!(
+                "{} {} {}",
+                method,
+                uri.path_and_query().unwrap(),
+                body.map_or(0, |b| b.len())
+            )
+        }
+
+        let device = crate::mock_client(|req| {
+            let expected = signature(req.method(), req.uri(), Some(req.body()));
+            assert_eq!(req.headers()["x-signature"], expected.as_str());
+            let tags: Vec<_> = req.headers().get_all("x-tag").iter().collect();
+            assert_eq!(tags, vec!["a", "b"]);
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(vec![br#"{"apiVersion":"1.0","data":{}}"#.to_vec()])
+        })
+        .with_request_signer(|req: SignableRequest| async move {
+            let mut headers = http::HeaderMap::new();
+            headers.insert(
+                "x-signature",
+                signature(&req.method, &req.uri, req.body.as_deref())
+                    .parse()
+                    .unwrap(),
+            );
+            headers.append("x-tag", http::HeaderValue::from_static("a"));
+            headers.append("x-tag", http::HeaderValue::from_static("b"));
+            Ok(headers)
+        });
+
+        let _: serde_json::Value =
+            crate::v4::JsonService::new(&device, "/axis-cgi/example.cgi", "1.0".into())
+                .call_method_bare("example")
+                .await
+                .unwrap();
+
+        // a failing signer prevents the request from being sent
+        let device = crate::mock_client(|_| -> http::Result<http::Response<Vec<Vec<u8>>>> {
+            panic!("an unsigned request was sent")
+        })
+        .with_request_signer(|_| async { Err(Error::Other("signing service unavailable")) });
+        match device.restart().await {
+            Err(Error::Other("signing service unavailable")) => {}
+            other => panic!("expected the signer's error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn debug_redacts_authorization() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert(
+            http::header::AUTHORIZATION,
+            http::HeaderValue::from_static("Basic cm9vdDpwYXNz"),
+        );
+        headers.insert("x-request-id", http::HeaderValue::from_static("42"));
+        let request = SignableRequest {
+            method: http::Method::GET,
+            uri: http::Uri::from_static("http://1.2.3.4/axis-cgi/param.cgi"),
+            headers,
+            body: None,
+        };
+
+        let debug = format!("{:?}", request);
+        assert!(!debug.contains("cm9vdDpwYXNz"), "{}", debug);
+        assert!(debug.contains("[redacted]"), "{}", debug);
+        assert!(debug.contains("x-request-id"), "{}", debug);
+    }
+}
@@ -0,0 +1,139 @@
+use super::*;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A snapshot of the requests a `Client` has made, keyed by request path, like
+/// `/axis-cgi/param.cgi`.
+pub type Stats = BTreeMap<String, EndpointStats>;
+
+/// Usage statistics for one endpoint.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct EndpointStats {
+    /// The number of requests made, including failed requests.
+    pub calls: u64,
+    /// The number of requests which failed.
+    pub errors: u64,
+    /// The time spent on all requests, from sending each request until its response was read or
+    /// it failed. Streaming requests are counted until the response headers arrive.
+    pub total_latency: Duration,
+}
+
+impl EndpointStats {
+    /// The average time spent on each request, or `None` if no requests were made.
+    pub fn mean_latency(&self) -> Option<Duration> {
+        if self.calls == 0 {
+            return None;
+        }
+        Some(Duration::from_nanos(
+            (self.total_latency.as_nanos() / u128::from(self.calls)) as u64,
+        ))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    calls: AtomicU64,
+    errors: AtomicU64,
+    latency_nanos: AtomicU64,
+}
+
+/// The counters behind `Client::stats()`, shared between clones of a `Client`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StatsRecorder(Arc<Mutex<BTreeMap<String, Arc<Counters>>>>);
+
+impl StatsRecorder {
+    fn counters(&self, path: &str) -> Arc<Counters> {
+        let mut endpoints = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match endpoints.get(path) {
+            Some(counters) => counters.clone(),
+            None => endpoints.entry(path.to_string()).or_default().clone(),
+        }
+    }
+
+    /// Count a request to `path` which started at `started` and ended with `result`.
+    pub(crate) fn record<R>(&self, path: &str, started: Instant, result: &Result<R>) {
+        let counters = self.counters(path);
+        let elapsed = started.elapsed().as_nanos().min(u128::from(u64::MAX)) as u64;
+        counters.calls.fetch_add(1, Ordering::Relaxed);
+        if result.is_err() {
+            counters.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        counters.latency_nanos.fetch_add(elapsed, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> Stats {
+        let endpoints = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        endpoints
+            .iter()
+            .map(|(path, counters)| {
+                (
+                    path.clone(),
+                    EndpointStats {
+                        calls: counters.calls.load(Ordering::Relaxed),
+                        errors: counters.errors.load(Ordering::Relaxed),
+                        total_latency: Duration::from_nanos(
+                            counters.latency_nanos.load(Ordering::Relaxed),
+                        ),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn reset(&self) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Retrieve per-endpoint statistics of the requests this client has made.
+    ///
+    /// Requests are counted once however many times they were retried, redirected, or
+    /// re-authenticated, and are keyed by path without the query string. Clones of a `Client`
+    /// share their statistics.
+    pub fn stats(&self) -> Stats {
+        self.stats.snapshot()
+    }
+
+    /// Clear the statistics returned by `stats()`, for this client and its clones.
+    pub fn reset_stats(&self) {
+        self.stats.reset()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stats() {
+        let device = crate::mock_client(|req| match req.uri().path() {
+            "/axis-cgi/param.cgi" => http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.Brand.ProdNbr=P1448-LE\n".to_vec()]),
+            _ => http::Response::builder()
+                .status(http::StatusCode::NOT_FOUND)
+                .body(vec![]),
+        });
+        assert!(device.stats().is_empty());
+
+        device.ping().await.unwrap();
+        device.ping().await.unwrap();
+        assert!(device.restart().await.is_err());
+
+        let stats = device.stats();
+        assert_eq!(stats.len(), 2);
+        let param = stats["/axis-cgi/param.cgi"];
+        assert_eq!((param.calls, param.errors), (2, 0));
+        assert!(param.mean_latency().unwrap() <= param.total_latency);
+        let restart = stats["/axis-cgi/restart.cgi"];
+        assert_eq!((restart.calls, restart.errors), (1, 1));
+
+        device.reset_stats();
+        assert!(device.stats().is_empty());
+        assert_eq!(EndpointStats::default().mean_latency(), None);
+    }
+}
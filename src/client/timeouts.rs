@@ -0,0 +1,275 @@
+use super::*;
+use futures::future::Either;
+use futures::Stream;
+use pin_project::pin_project;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// How long a `Client` waits on the device before giving up on a request.
+///
+/// `headers` limits the wait for the response headers, while `body_idle` limits the wait for
+/// each chunk of the response body. `total` limits the whole request, including the time spent
+/// reading the body, and applies only to requests whose body is read in full before returning.
+///
+/// Streaming responses like event subscriptions stay open indefinitely, so they are subject to a
+/// separate set of timeouts set by `Client::with_streaming_timeouts()`, which never include a
+/// total timeout. All timeouts are disabled by default.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Timeouts {
+    headers: Option<Duration>,
+    body_idle: Option<Duration>,
+    total: Option<Duration>,
+}
+
+impl Timeouts {
+    /// No timeouts.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Limit the wait for the response headers.
+    pub fn with_headers(mut self, timeout: Duration) -> Self {
+        self.headers = Some(timeout);
+        self
+    }
+
+    /// Limit the wait between chunks of the response body.
+    pub fn with_body_idle(mut self, timeout: Duration) -> Self {
+        self.body_idle = Some(timeout);
+        self
+    }
+
+    /// Limit the duration of the whole request. Ignored for streaming responses.
+    pub fn with_total(mut self, timeout: Duration) -> Self {
+        self.total = Some(timeout);
+        self
+    }
+
+    pub(crate) fn headers(&self) -> Option<Duration> {
+        self.headers
+    }
+
+    pub(crate) fn total(&self) -> Option<Duration> {
+        self.total
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Set the timeouts for requests whose response is read in full, which is most requests.
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Set the timeouts for streaming responses, like event subscriptions and downloads.
+    ///
+    /// Long-poll endpoints hold their connection open until they have something to say, so these
+    /// should be generous compared to `with_timeouts()`: a stream is only considered dead once it
+    /// has been idle for longer than the device's keepalive interval.
+    pub fn with_streaming_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.streaming_timeouts = Timeouts {
+            total: None,
+            ..timeouts
+        };
+        self
+    }
+}
+
+/// A streaming response body, which fails if the device stops sending data for longer than the
+/// client's body idle timeout.
+///
+/// Idle timeouts are reported as transport errors with `std::io::ErrorKind::TimedOut`, so
+/// `Error::connection_error_kind()` classifies them as `ConnectionErrorKind::Timeout`.
+#[pin_project]
+pub struct ResponseBody<B> {
+    #[pin]
+    body: B,
+    idle_timeout: Option<Duration>,
+    delay: Option<futures_timer::Delay>,
+}
+
+impl<B> ResponseBody<B> {
+    pub(crate) fn new(body: B, timeouts: &Timeouts) -> Self {
+        Self {
+            body,
+            idle_timeout: timeouts.body_idle,
+            delay: None,
+        }
+    }
+}
+
+impl<B, C> Stream for ResponseBody<B>
+where
+    B: Stream<Item = std::result::Result<C, crate::transport::Error>>,
+{
+    type Item = B::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        match this.body.poll_next(cx) {
+            Poll::Ready(item) => {
+                *this.delay = None;
+                Poll::Ready(item)
+            }
+            Poll::Pending => {
+                if let Some(idle_timeout) = *this.idle_timeout {
+                    let delay = this
+                        .delay
+                        .get_or_insert_with(|| futures_timer::Delay::new(idle_timeout));
+                    if Pin::new(delay).poll(cx).is_ready() {
+                        *this.delay = None;
+                        return Poll::Ready(Some(Err(timed_out("response body idle timeout"))));
+                    }
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+fn timed_out(what: &'static str) -> crate::transport::Error {
+    crate::transport::Error::new(std::io::Error::new(std::io::ErrorKind::TimedOut, what))
+}
+
+/// Await `future`, failing with a timeout error if it takes longer than `timeout`.
+pub(crate) async fn within<F, R>(
+    timeout: Option<Duration>,
+    what: &'static str,
+    future: F,
+) -> Result<R>
+where
+    F: Future<Output = Result<R>>,
+{
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return future.await,
+    };
+
+    futures::pin_mut!(future);
+    match futures::future::select(future, futures_timer::Delay::new(timeout)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => Err(timed_out(what).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future::BoxFuture;
+    use futures::stream::BoxStream;
+    use futures::FutureExt;
+
+    /// A transport which takes its time.
+    struct Slow {
+        header_delay: Duration,
+        chunk_delay: Duration,
+        chunks: usize,
+    }
+
+    impl Transport for Slow {
+        type Output = BoxFuture<
+            'static,
+            std::result::Result<http::Response<Self::Body>, crate::transport::Error>,
+        >;
+        type Body = BoxStream<'static, std::result::Result<Vec<u8>, crate::transport::Error>>;
+        type Chunk = Vec<u8>;
+
+        fn roundtrip(&self, _request: http::Request<Vec<u8>>) -> Self::Output {
+            let (header_delay, chunk_delay, chunks) =
+                (self.header_delay, self.chunk_delay, self.chunks);
+            async move {
+                futures_timer::Delay::new(header_delay).await;
+                let body = futures::stream::iter(0..chunks)
+                    .then(move |_| async move {
+                        futures_timer::Delay::new(chunk_delay).await;
+                        Ok(b"x".to_vec())
+                    })
+                    .boxed();
+                Ok(http::Response::builder()
+                    .status(http::StatusCode::OK)
+                    .header(http::header::CONTENT_TYPE, "text/plain")
+                    .body(body)
+                    .unwrap())
+            }
+            .boxed()
+        }
+    }
+
+    fn request() -> http::Request<Vec<u8>> {
+        http::Request::get("http://1.2.3.4/axis-cgi/whatever.cgi")
+            .body(Vec::new())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn body_idle_timeout() {
+        let stalled = futures::stream::iter(vec![Ok(b"data".to_vec())])
+            .chain(futures::stream::pending::<std::result::Result<Vec<u8>, _>>());
+        let timeouts = Timeouts::none().with_body_idle(Duration::from_millis(20));
+        let mut body = Box::pin(ResponseBody::new(stalled, &timeouts));
+
+        assert_eq!(body.next().await.unwrap().unwrap(), b"data");
+        let e: Error = body.next().await.unwrap().unwrap_err().into();
+        assert_eq!(
+            e.connection_error_kind(),
+            Some(ConnectionErrorKind::Timeout)
+        );
+    }
+
+    #[tokio::test]
+    async fn header_timeout() {
+        let transport = Slow {
+            header_delay: Duration::from_millis(200),
+            chunk_delay: Duration::from_millis(0),
+            chunks: 1,
+        };
+        let device = Client::try_new(transport, http::Uri::from_static("http://1.2.3.4"))
+            .unwrap()
+            .with_streaming_timeouts(Timeouts::none().with_headers(Duration::from_millis(20)));
+
+        let e = device
+            .roundtrip_streaming(request(), "text/plain")
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(
+            e.connection_error_kind(),
+            Some(ConnectionErrorKind::Timeout)
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_outlive_total_timeout() {
+        let transport = Slow {
+            header_delay: Duration::from_millis(0),
+            chunk_delay: Duration::from_millis(30),
+            chunks: 5,
+        };
+        let device = Client::try_new(transport, http::Uri::from_static("http://1.2.3.4"))
+            .unwrap()
+            .with_timeouts(Timeouts::none().with_total(Duration::from_millis(60)))
+            .with_streaming_timeouts(
+                Timeouts::none()
+                    .with_body_idle(Duration::from_millis(500))
+                    .with_total(Duration::from_millis(60)),
+            );
+
+        // A buffered request is subject to the total timeout
+        let e = device.roundtrip(request(), "text/plain").await.unwrap_err();
+        assert_eq!(
+            e.connection_error_kind(),
+            Some(ConnectionErrorKind::Timeout)
+        );
+
+        // A streaming request only times out if it goes idle
+        let (_, body) = device
+            .roundtrip_streaming(request(), "text/plain")
+            .await
+            .unwrap();
+        let chunks: Vec<_> = body.collect().await;
+        assert_eq!(chunks.len(), 5);
+        assert!(chunks.iter().all(|chunk| chunk.is_ok()));
+    }
+}
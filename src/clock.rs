@@ -0,0 +1,75 @@
+use chrono::prelude::*;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// A source of the current time, used by a `Client` wherever it needs the time without asking
+/// the device.
+///
+/// Clients use `SystemClock` unless configured otherwise with `Client::with_clock()`. Tests and
+/// simulations can substitute a `FixedClock` so that results don't depend on when or in which
+/// time zone they run.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// The current time, with the offset of the clock's local time zone.
+    fn now(&self) -> DateTime<FixedOffset>;
+}
+
+/// The system clock, in the system's local time zone.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        Local::now().into()
+    }
+}
+
+/// A clock which stands still until it is set or advanced.
+///
+/// Every clone of a `FixedClock` shares the same time, so a test can keep one clone and hand
+/// another to a `Client`.
+#[derive(Debug, Clone)]
+pub struct FixedClock(Arc<Mutex<DateTime<FixedOffset>>>);
+
+impl FixedClock {
+    /// Create a clock which reads `now`.
+    pub fn new<Tz: TimeZone>(now: DateTime<Tz>) -> Self {
+        Self(Arc::new(Mutex::new(now.with_timezone(&now.offset().fix()))))
+    }
+
+    /// Set the clock to `now`.
+    pub fn set<Tz: TimeZone>(&self, now: DateTime<Tz>) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = now.with_timezone(&now.offset().fix());
+    }
+
+    /// Move the clock forward by `duration`.
+    pub fn advance(&self, duration: chrono::Duration) {
+        let mut now = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        *now = *now + duration;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed() {
+        let clock = FixedClock::new(Utc.ymd(2020, 10, 9).and_hms(15, 30, 2));
+        let shared = clock.clone();
+        assert_eq!(clock.now(), Utc.ymd(2020, 10, 9).and_hms(15, 30, 2));
+        assert_eq!(clock.now().offset().local_minus_utc(), 0);
+
+        shared.advance(chrono::Duration::seconds(30));
+        assert_eq!(clock.now(), Utc.ymd(2020, 10, 9).and_hms(15, 30, 32));
+
+        let offset = FixedOffset::east(2 * 3600);
+        shared.set(offset.ymd(2020, 10, 9).and_hms(17, 0, 0));
+        assert_eq!(clock.now().to_rfc3339(), "2020-10-09T17:00:00+02:00");
+    }
+}
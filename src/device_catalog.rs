@@ -0,0 +1,99 @@
+//! An offline catalog of AXIS products.
+//!
+//! The catalog maps a device's `Properties.System.HardwareID` or `Brand.ProdNbr` parameter to
+//! information about the product, which is useful for enriching an inventory without making
+//! additional requests. It is compiled into the crate from `device_catalog.csv`, and is
+//! necessarily incomplete.
+
+const CATALOG: &str = include_str!("device_catalog.csv");
+
+/// Information about an AXIS product.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ProductInfo {
+    /// The product number, as reported by `Brand.ProdNbr`, e.g. `"P5512"`.
+    pub prod_nbr: &'static str,
+    /// The hardware ID, as reported by `Properties.System.HardwareID` without any `.` suffix.
+    pub hardware_id: Option<&'static str>,
+    /// The product family or series, e.g. `"P55"`.
+    pub family: &'static str,
+    /// The number of video channels.
+    pub channels: u32,
+    /// Does the product have mechanical pan/tilt/zoom?
+    pub ptz: bool,
+    /// Has the product been discontinued?
+    pub end_of_life: bool,
+}
+
+/// Look up a product by hardware ID or product number.
+///
+/// Hardware IDs may include the revision suffix reported by the device, as in `"18C.2"`. Product
+/// numbers may include the `AXIS` prefix, as in `"AXIS P5512"`. Matching is case-insensitive.
+pub fn lookup(key: &str) -> Option<ProductInfo> {
+    let key = key.trim();
+    let key = key
+        .strip_prefix("AXIS ")
+        .or_else(|| key.strip_prefix("axis "))
+        .unwrap_or(key);
+    let hardware_id = key.splitn(2, '.').next().unwrap_or(key);
+
+    products().find(|product| {
+        product.prod_nbr.eq_ignore_ascii_case(key)
+            || product
+                .hardware_id
+                .map(|id| id.eq_ignore_ascii_case(hardware_id))
+                .unwrap_or(false)
+    })
+}
+
+/// Iterate over every product in the catalog.
+pub fn products() -> impl Iterator<Item = ProductInfo> {
+    CATALOG.lines().filter_map(parse_line)
+}
+
+fn parse_line(line: &'static str) -> Option<ProductInfo> {
+    if line.trim().is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let mut fields = line.split(',').map(str::trim);
+    let mut next = || fields.next();
+    let yes = |field: &str| field == "yes";
+
+    Some(ProductInfo {
+        prod_nbr: next()?,
+        hardware_id: next().filter(|id| !id.is_empty()),
+        family: next()?,
+        channels: next()?.parse().ok()?,
+        ptz: yes(next()?),
+        end_of_life: yes(next()?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalog_parses() {
+        let rows = CATALOG
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .count();
+        assert_eq!(products().count(), rows);
+    }
+
+    #[test]
+    fn lookup() {
+        let p5512 = super::lookup("18C.2").unwrap();
+        assert_eq!(p5512.prod_nbr, "P5512");
+        assert!(p5512.ptz);
+        assert_eq!(super::lookup("AXIS P5512"), Some(p5512));
+        assert_eq!(super::lookup("p5512"), Some(p5512));
+
+        let companion = super::lookup("C Bullet LE").unwrap();
+        assert_eq!(companion.hardware_id, Some("805"));
+        assert_eq!(companion.family, "Companion");
+
+        assert_eq!(super::lookup("nonexistent"), None);
+    }
+}
@@ -1,4 +1,4 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// A `Result` returned by the `vapix` crate.
@@ -15,11 +15,211 @@ pub enum Error {
     UnparseableResponseError(UnparseableResponseError),
     /// The API call returned a structured error.
     ApiError(ApiError),
+    /// A v3 CGI returned a structured error in its XML response.
+    CgiError(CgiError),
     /// An error which isn't yet properly itemized.
     Other(&'static str),
+    /// An error annotated with the service call which produced it.
+    Context(Box<ErrorContext>),
+    /// Writing a response to a local file failed.
+    Io(std::io::Error),
+    /// The call would change the device, but the client is in read-only mode.
+    ReadOnlyMode,
+    /// The operation was cancelled through a `CancelHandle`.
+    Cancelled,
+    /// The disk's filesystem does not support the requested operation.
+    UnsupportedFilesystem(crate::v4::disk_management::Filesystem),
+    /// The device refused the call because this client's credentials lack the access level it
+    /// requires.
+    ///
+    /// This is reported for `403 Forbidden` and equivalent responses. A `401 Unauthorized` which
+    /// persists after authenticating means the credentials were rejected outright, and is reported
+    /// as `HttpRequestFailed`; `is_authentication_failure()` is true for both.
+    InsufficientPrivileges {
+        /// The access level the call requires, if known.
+        required: Option<crate::v3::parameters::AccessLevel>,
+    },
+    /// The device refused to update some of the parameters passed to `Parameters::update()`.
+    ParameterUpdate {
+        /// The device's report for each parameter it could not update.
+        failed: Vec<crate::v3::parameters::ParameterUpdateError>,
+        /// The parameters the device did not report as failing.
+        ///
+        /// This is empty if any failure did not name its parameter, since the failure could concern
+        /// any of them.
+        succeeded: Vec<String>,
+    },
+    /// `Parameters::import()` or `Parameters::restore()` stopped part way through.
+    ///
+    /// Parameters are written a few at a time, and the import stops at the first write which
+    /// fails. Parameters in none of these lists were part of that failed write but their outcome
+    /// is unknown; see `error`.
+    PartialImport {
+        /// The parameters which were written before the import stopped, including those which the
+        /// failed write reported as succeeding.
+        applied: Vec<String>,
+        /// The failure which stopped the import. An `Error::ParameterUpdate` lists which of the
+        /// parameters in that write failed.
+        error: Box<Error>,
+        /// The parameters which were not attempted.
+        unattempted: Vec<String>,
+    },
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Context(c) => Some(&c.error),
+            Error::Io(e) => Some(e),
+            Error::PartialImport { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl Error {
+    /// Classify a failed HTTP request by its underlying cause.
+    ///
+    /// Returns `None` for errors other than `Error::HttpRequestFailed`, and for request failures
+    /// which don't match any `ConnectionErrorKind`.
+    pub fn connection_error_kind(&self) -> Option<ConnectionErrorKind> {
+        match self.without_context() {
+            Error::HttpRequestFailed(e) => ConnectionErrorKind::classify(&**e),
+            _ => None,
+        }
+    }
+
+    /// Is this error likely to be temporary, such that retrying the operation later may succeed?
+    ///
+    /// This includes devices reporting themselves overloaded with `503 Service Unavailable` or
+    /// `429 Too Many Requests` after any retries permitted by the client's `RetryPolicy`, as well
+    /// as timeouts and dropped connections.
+    pub fn is_transient(&self) -> bool {
+        match self.without_context() {
+            Error::HttpRequestFailed(e) => {
+                if let Some(HttpStatusCodeError(status)) = e.downcast_ref() {
+                    return *status == http::StatusCode::SERVICE_UNAVAILABLE
+                        || *status == http::StatusCode::TOO_MANY_REQUESTS;
+                }
+                match self.connection_error_kind() {
+                    Some(ConnectionErrorKind::Timeout) | Some(ConnectionErrorKind::ResetByPeer) => {
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    /// Did the device reject this client's credentials?
+    ///
+    /// This is true for `401 Unauthorized` responses which persist after authenticating, for
+    /// `403 Forbidden` responses, and for other ways devices report `InsufficientPrivileges`.
+    pub fn is_authentication_failure(&self) -> bool {
+        match self.without_context() {
+            Error::InsufficientPrivileges { .. } => true,
+            Error::HttpRequestFailed(e) => match e.downcast_ref() {
+                Some(HttpStatusCodeError(status)) => *status == http::StatusCode::UNAUTHORIZED,
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns the service call which produced this error, if known.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::Context(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Returns the underlying error, without any `ErrorContext`.
+    pub fn without_context(&self) -> &Error {
+        match self {
+            Error::Context(c) => c.error.without_context(),
+            other => other,
+        }
+    }
+
+    /// Consumes this error, returning the underlying error without any `ErrorContext`.
+    pub fn into_without_context(self) -> Error {
+        match self {
+            Error::Context(c) => c.error.into_without_context(),
+            other => other,
+        }
+    }
+
+    /// Annotate transport, parsing, and unclassified errors with the service call which produced
+    /// them. `FeatureUnavailable`, `InsufficientPrivileges`, `ParameterUpdate`, `ApiError`, and
+    /// `CgiError` are returned as-is, since they are meaningful on their own and callers match on
+    /// them directly.
+    pub(crate) fn with_context(self, context: impl FnOnce() -> ErrorContext) -> Self {
+        match self {
+            e @ Error::HttpRequestFailed(_)
+            | e @ Error::UnparseableResponseError(_)
+            | e @ Error::Other(_) => {
+                let mut context = context();
+                context.error = e;
+                Error::Context(Box::new(context))
+            }
+            other => other,
+        }
+    }
+}
+
+/// A description of the service call which produced an error.
+#[derive(Debug)]
+pub struct ErrorContext {
+    service: Option<&'static str>,
+    method: Option<String>,
+    path: String,
+    error: Error,
+}
+
+impl ErrorContext {
+    pub(crate) fn new(service: Option<&'static str>, method: Option<&str>, path: &str) -> Self {
+        Self {
+            service,
+            method: method.map(String::from),
+            path: path.to_string(),
+            error: Error::Other("no error"),
+        }
+    }
+
+    /// The identifier of the service, as reported by API discovery, e.g. `"basic-device-info"`.
+    pub fn service(&self) -> Option<&str> {
+        self.service
+    }
+
+    /// The name of the method which was called, for JSON services.
+    pub fn method(&self) -> Option<&str> {
+        self.method.as_deref()
+    }
+
+    /// The path of the endpoint which was called.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The underlying error.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(service) = self.service {
+            write!(f, "{} ", service)?;
+        }
+        if let Some(method) = &self.method {
+            write!(f, "{} ", method)?;
+        }
+        write!(f, "on {}", self.path)
+    }
+}
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -28,7 +228,39 @@ impl fmt::Display for Error {
             Error::FeatureUnavailable => write!(f, "this device does not support that feature"),
             Error::UnparseableResponseError(e) => write!(f, "unparseable response: {:?}", e),
             Error::ApiError(e) => write!(f, "JSON API error: {:?}", e),
+            Error::CgiError(e) => write!(f, "CGI error: {}", e),
             Error::Other(e) => write!(f, "error: {}", e),
+            Error::Context(c) => write!(f, "{}: {}", c, c.error),
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::ReadOnlyMode => write!(f, "client is in read-only mode"),
+            Error::Cancelled => write!(f, "operation was cancelled"),
+            Error::UnsupportedFilesystem(fs) => {
+                write!(f, "the {:?} filesystem does not support that operation", fs)
+            }
+            Error::InsufficientPrivileges { required: None } => {
+                write!(f, "insufficient privileges")
+            }
+            Error::InsufficientPrivileges {
+                required: Some(level),
+            } => write!(f, "insufficient privileges, {:?} required", level),
+            Error::ParameterUpdate { failed, .. } => {
+                write!(f, "parameter update failed")?;
+                for (i, e) in failed.iter().enumerate() {
+                    write!(f, "{} {}", if i == 0 { ":" } else { ";" }, e)?;
+                }
+                Ok(())
+            }
+            Error::PartialImport {
+                applied,
+                error,
+                unattempted,
+            } => write!(
+                f,
+                "parameter import stopped after writing {} parameters, with {} not attempted: {}",
+                applied.len(),
+                unattempted.len(),
+                error
+            ),
         }
     }
 }
@@ -39,9 +271,18 @@ impl From<crate::transport::Error> for Error {
     }
 }
 
+impl From<http::Error> for Error {
+    fn from(e: http::Error) -> Self {
+        Error::HttpRequestFailed(Box::new(e))
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(e: serde_json::Error) -> Self {
-        Error::UnparseableResponseError(UnparseableResponseError::JsonDeError(e))
+        Error::UnparseableResponseError(UnparseableResponseError::JsonDeError {
+            error: e,
+            payload: None,
+        })
     }
 }
 
@@ -57,14 +298,131 @@ impl From<ApiError> for Error {
     }
 }
 
+/// The reason an HTTP request failed to reach the device, as determined by
+/// `Error::connection_error_kind()`.
+///
+/// This distinguishes "the device is offline" from "the device is misconfigured" without
+/// inspecting error messages.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ConnectionErrorKind {
+    /// The device's hostname could not be resolved.
+    DnsFailure,
+    /// The device actively refused the connection.
+    ConnectionRefused,
+    /// The TLS handshake with the device failed, as reported by the transport with a `TlsError`.
+    TlsError,
+    /// The connection attempt or the request timed out.
+    Timeout,
+    /// The device reset or closed the connection.
+    ResetByPeer,
+}
+
+impl ConnectionErrorKind {
+    fn classify(error: &(dyn std::error::Error + 'static)) -> Option<Self> {
+        let chain = {
+            let mut chain = vec![error];
+            while let Some(source) = chain.last().and_then(|e| e.source()) {
+                chain.push(source);
+            }
+            chain
+        };
+
+        // hyper does not export its connector error type, but it prefixes resolver failures with
+        // a fixed description
+        if chain.iter().any(|e| e.to_string().starts_with("dns error")) {
+            return Some(ConnectionErrorKind::DnsFailure);
+        }
+
+        // Prefer the most specific cause
+        for e in chain.iter().rev() {
+            if let Some(e) = e.downcast_ref::<std::io::Error>() {
+                use std::io::ErrorKind;
+                match e.kind() {
+                    ErrorKind::ConnectionRefused => {
+                        return Some(ConnectionErrorKind::ConnectionRefused)
+                    }
+                    ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::BrokenPipe
+                    | ErrorKind::UnexpectedEof => return Some(ConnectionErrorKind::ResetByPeer),
+                    ErrorKind::TimedOut => return Some(ConnectionErrorKind::Timeout),
+                    _ => {}
+                }
+            }
+
+            #[cfg(feature = "hyper")]
+            {
+                if let Some(e) = e.downcast_ref::<::hyper::Error>() {
+                    if e.is_timeout() {
+                        return Some(ConnectionErrorKind::Timeout);
+                    }
+                }
+            }
+        }
+
+        // TLS implementations are pluggable, so transports mark their errors as such, possibly
+        // inside an `io::Error`, whose `source()` skips the error it wraps
+        if chain.iter().any(|e| {
+            e.is::<crate::transport::TlsError>()
+                || e.downcast_ref::<std::io::Error>()
+                    .and_then(|e| e.get_ref())
+                    .map_or(false, |e| e.is::<crate::transport::TlsError>())
+        }) {
+            return Some(ConnectionErrorKind::TlsError);
+        }
+
+        #[cfg(feature = "hyper")]
+        {
+            if chain.iter().any(|e| {
+                e.downcast_ref::<::hyper::Error>()
+                    .map(|e| e.is_closed() || e.is_incomplete_message())
+                    .unwrap_or(false)
+            }) {
+                return Some(ConnectionErrorKind::ResetByPeer);
+            }
+        }
+
+        None
+    }
+}
+
 #[derive(Debug)]
 pub enum UnparseableResponseError {
     /// JSON deserialization failed.
-    JsonDeError(serde_json::Error),
+    JsonDeError {
+        /// The error returned by `serde_json`.
+        error: serde_json::Error,
+        /// The response body which failed to deserialize, truncated to `MAX_CAPTURED_PAYLOAD`
+        /// bytes, if it was captured.
+        payload: Option<String>,
+    },
     /// XML deserialization failed.
     XmlDeError(quick_xml::DeError),
 }
 
+impl UnparseableResponseError {
+    /// The maximum number of bytes of a response body retained by `JsonDeError`.
+    pub const MAX_CAPTURED_PAYLOAD: usize = 4096;
+
+    /// Build a `JsonDeError`, capturing (a prefix of) the body which failed to deserialize.
+    pub(crate) fn json(error: serde_json::Error, body: &[u8]) -> Self {
+        let body = &body[..body.len().min(Self::MAX_CAPTURED_PAYLOAD)];
+        UnparseableResponseError::JsonDeError {
+            error,
+            payload: Some(String::from_utf8_lossy(body).into_owned()),
+        }
+    }
+
+    /// Returns the captured response body, if any.
+    pub fn payload(&self) -> Option<&str> {
+        match self {
+            UnparseableResponseError::JsonDeError { payload, .. } => payload.as_deref(),
+            UnparseableResponseError::XmlDeError(_) => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum ApiError {
     InvalidParameter,
@@ -94,7 +452,7 @@ impl From<RawJsonApiError> for ApiError {
     }
 }
 
-#[derive(Debug, Deserialize, Clone, Eq, PartialEq)]
+#[derive(Debug, Deserialize, Clone, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RawJsonApiError {
     pub code: u32,
@@ -103,7 +461,44 @@ pub struct RawJsonApiError {
 
 impl From<RawJsonApiError> for Error {
     fn from(e: RawJsonApiError) -> Self {
-        Error::ApiError(e.into())
+        match e.into() {
+            ApiError::AccessForbidden => Error::InsufficientPrivileges { required: None },
+            other => Error::ApiError(other),
+        }
+    }
+}
+
+/// An error reported by a v3 CGI in its XML response, like:
+///
+/// ```xml
+/// <GeneralError>
+///   <ErrorCode>4</ErrorCode>
+///   <ErrorDescription>Invalid port</ErrorDescription>
+/// </GeneralError>
+/// ```
+///
+/// Codes are specific to each CGI.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CgiError {
+    /// The error code.
+    pub code: u32,
+    /// The error description, if the device provided one.
+    pub description: Option<String>,
+}
+
+impl fmt::Display for CgiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.description {
+            Some(description) => write!(f, "{} (code {})", description, self.code),
+            None => write!(f, "code {}", self.code),
+        }
+    }
+}
+
+impl From<CgiError> for Error {
+    fn from(e: CgiError) -> Self {
+        Error::CgiError(e)
     }
 }
 
@@ -117,10 +512,30 @@ impl fmt::Display for HttpStatusCodeError {
 }
 impl From<HttpStatusCodeError> for Error {
     fn from(e: HttpStatusCodeError) -> Self {
-        Error::HttpRequestFailed(Box::new(e))
+        match e.0 {
+            // 401 means the credentials were rejected, not that they were insufficient
+            http::StatusCode::FORBIDDEN => Error::InsufficientPrivileges { required: None },
+            _ => Error::HttpRequestFailed(Box::new(e)),
+        }
     }
 }
 
+/// Does an HTML response body describe an access denial?
+///
+/// Some CGIs report a lack of privileges with `200 OK` and an HTML error page rather than a
+/// status code.
+pub(crate) fn is_html_access_denied(body: &[u8]) -> bool {
+    let body = String::from_utf8_lossy(&body[..body.len().min(4096)]).to_ascii_lowercase();
+    [
+        "401 unauthorized",
+        "403 forbidden",
+        "access denied",
+        "access forbidden",
+    ]
+    .iter()
+    .any(|needle| body.contains(needle))
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) struct HttpContentTypeError(Option<Vec<u8>>, &'static str);
 impl HttpContentTypeError {
@@ -150,17 +565,224 @@ impl From<HttpContentTypeError> for Error {
 
 pub(crate) trait ResultExt {
     fn map_404_to_feature_unavailable(self) -> Self;
+
+    /// Note the access level a call requires on any `InsufficientPrivileges` it returns.
+    fn requiring(self, level: crate::v3::parameters::AccessLevel) -> Self;
 }
 
 impl<T> ResultExt for Result<T> {
     fn map_404_to_feature_unavailable(self) -> Self {
         match self {
-            Err(Error::HttpRequestFailed(e))
-                if e.downcast_ref() == Some(&HttpStatusCodeError(http::StatusCode::NOT_FOUND)) =>
-            {
-                Err(Error::FeatureUnavailable)
-            }
+            Err(e) => match e.without_context() {
+                Error::HttpRequestFailed(inner)
+                    if inner.downcast_ref()
+                        == Some(&HttpStatusCodeError(http::StatusCode::NOT_FOUND)) =>
+                {
+                    Err(Error::FeatureUnavailable)
+                }
+                _ => Err(e),
+            },
+            other => other,
+        }
+    }
+
+    fn requiring(self, level: crate::v3::parameters::AccessLevel) -> Self {
+        match self {
+            Err(Error::InsufficientPrivileges { required }) => Err(Error::InsufficientPrivileges {
+                required: required.or(Some(level)),
+            }),
             other => other,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Wrapper(&'static str, std::io::Error);
+    impl std::error::Error for Wrapper {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.1)
+        }
+    }
+    impl fmt::Display for Wrapper {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{}: {}", self.0, self.1)
+        }
+    }
+
+    fn request_failed<E: std::error::Error + Send + 'static>(e: E) -> Error {
+        crate::transport::Error::new(e).into()
+    }
+
+    #[test]
+    fn connection_error_kind() {
+        use std::io::{Error as IoError, ErrorKind};
+
+        assert_eq!(
+            request_failed(IoError::from(ErrorKind::ConnectionRefused)).connection_error_kind(),
+            Some(ConnectionErrorKind::ConnectionRefused)
+        );
+        assert_eq!(
+            request_failed(IoError::from(ErrorKind::ConnectionReset)).connection_error_kind(),
+            Some(ConnectionErrorKind::ResetByPeer)
+        );
+        assert_eq!(
+            request_failed(Wrapper(
+                "tcp connect error",
+                IoError::from(ErrorKind::TimedOut)
+            ))
+            .connection_error_kind(),
+            Some(ConnectionErrorKind::Timeout)
+        );
+        assert_eq!(
+            request_failed(Wrapper(
+                "dns error",
+                IoError::new(ErrorKind::Other, "failed to lookup address information")
+            ))
+            .connection_error_kind(),
+            Some(ConnectionErrorKind::DnsFailure)
+        );
+        let tls_error = || {
+            crate::TlsError::new(IoError::new(
+                ErrorKind::InvalidData,
+                "invalid certificate: UnknownIssuer",
+            ))
+        };
+        assert_eq!(
+            request_failed(tls_error()).connection_error_kind(),
+            Some(ConnectionErrorKind::TlsError)
+        );
+        assert_eq!(
+            request_failed(Wrapper(
+                "error trying to connect",
+                IoError::new(ErrorKind::InvalidData, tls_error())
+            ))
+            .connection_error_kind(),
+            Some(ConnectionErrorKind::TlsError)
+        );
+
+        // Errors which merely mention TLS aren't TLS failures
+        assert_eq!(
+            request_failed(IoError::new(ErrorKind::Other, "certificate upload failed"))
+                .connection_error_kind(),
+            None
+        );
+
+        // Other failures remain unclassified
+        assert_eq!(
+            request_failed(IoError::new(ErrorKind::Other, "something else"))
+                .connection_error_kind(),
+            None
+        );
+        assert_eq!(Error::FeatureUnavailable.connection_error_kind(), None);
+        assert_eq!(
+            Error::from(HttpStatusCodeError(http::StatusCode::UNAUTHORIZED))
+                .connection_error_kind(),
+            None
+        );
+    }
+
+    #[test]
+    fn context() {
+        let e = Error::from(serde_json::from_str::<u32>("{").unwrap_err()).with_context(|| {
+            ErrorContext::new(
+                Some("basic-device-info"),
+                Some("getProperties"),
+                "/axis-cgi/basicdeviceinfo.cgi",
+            )
+        });
+
+        let context = e.context().unwrap();
+        assert_eq!(context.service(), Some("basic-device-info"));
+        assert_eq!(context.method(), Some("getProperties"));
+        assert!(e
+            .to_string()
+            .starts_with("basic-device-info getProperties on /axis-cgi/basicdeviceinfo.cgi: "));
+        match e.without_context() {
+            Error::UnparseableResponseError(_) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // semantic errors are left alone
+        let e = Error::FeatureUnavailable.with_context(|| ErrorContext::new(None, None, "/"));
+        assert!(e.context().is_none());
+
+        // the 404 mapping looks through context
+        let e: Result<()> = Err(
+            Error::from(HttpStatusCodeError(http::StatusCode::NOT_FOUND))
+                .with_context(|| ErrorContext::new(None, None, "/")),
+        );
+        match e.map_404_to_feature_unavailable() {
+            Err(Error::FeatureUnavailable) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn insufficient_privileges() {
+        use crate::v3::parameters::AccessLevel;
+
+        let e = Error::from(HttpStatusCodeError(http::StatusCode::FORBIDDEN));
+        match e {
+            Error::InsufficientPrivileges { required: None } => {}
+            ref other => panic!("unexpected {:?}", other),
+        }
+        assert!(e.is_authentication_failure());
+
+        // rejected credentials are not a lack of privileges
+        let e = Error::from(HttpStatusCodeError(http::StatusCode::UNAUTHORIZED));
+        match e {
+            Error::HttpRequestFailed(_) => {}
+            ref other => panic!("unexpected {:?}", other),
+        }
+        assert!(e.is_authentication_failure());
+        match Err::<(), _>(e).requiring(AccessLevel::AdministratorAccess) {
+            Err(Error::HttpRequestFailed(_)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        let e = Error::from(RawJsonApiError {
+            code: 2001,
+            message: Some("Access forbidden".into()),
+        });
+        assert!(e.is_authentication_failure());
+
+        // the required level is filled in only if it isn't already known
+        let r: Result<()> = Err(e);
+        let r = r.requiring(AccessLevel::OperatorAccess);
+        match r.requiring(AccessLevel::AdministratorAccess) {
+            Err(Error::InsufficientPrivileges {
+                required: Some(AccessLevel::OperatorAccess),
+            }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        match Err::<(), _>(Error::FeatureUnavailable).requiring(AccessLevel::ViewerAccess) {
+            Err(Error::FeatureUnavailable) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        assert!(is_html_access_denied(
+            b"<html><head><title>403 Forbidden</title></head></html>"
+        ));
+        assert!(!is_html_access_denied(
+            b"<html><body>Restarting...</body></html>"
+        ));
+    }
+
+    #[test]
+    fn json_payload_capture() {
+        let body = format!("{{\"unexpected\": \"{}\"}}", "x".repeat(10000));
+        let e = UnparseableResponseError::json(
+            serde_json::from_str::<u32>(&body).unwrap_err(),
+            body.as_bytes(),
+        );
+        let payload = e.payload().unwrap();
+        assert_eq!(
+            payload.len(),
+            UnparseableResponseError::MAX_CAPTURED_PAYLOAD
+        );
+        assert!(payload.starts_with("{\"unexpected\""));
+    }
+}
@@ -0,0 +1,238 @@
+use crate::v3::snapshot::SnapshotOptions;
+use crate::{Client, KeepAlive, KeepAliveHandle, Result, Transport};
+use futures::future;
+use futures::prelude::*;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// A collection of devices, keyed by serial number, for operating on many devices at once.
+///
+/// Operations across the fleet run concurrently, but no more than a configurable number at a
+/// time, so that a large fleet doesn't overwhelm the network or the devices. Each operation
+/// returns a result per device, so one unreachable device doesn't spoil the rest.
+#[derive(Debug)]
+pub struct Fleet<T: Transport> {
+    devices: BTreeMap<String, Client<T>>,
+}
+
+/// Options for capturing a snapshot from every device in a `Fleet`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FleetSnapshotOptions {
+    /// The options for each device's snapshot.
+    pub snapshot: SnapshotOptions,
+    /// The maximum number of snapshots to capture at once.
+    pub concurrency: usize,
+    /// How long to wait for each device's snapshot before giving up on it.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for FleetSnapshotOptions {
+    fn default() -> Self {
+        Self {
+            snapshot: SnapshotOptions::default(),
+            concurrency: 8,
+            timeout: Some(Duration::from_secs(10)),
+        }
+    }
+}
+
+impl<T: Transport> Default for Fleet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Transport> Fleet<T> {
+    /// Create an empty fleet.
+    pub fn new() -> Self {
+        Self {
+            devices: BTreeMap::new(),
+        }
+    }
+
+    /// Add a device with a known serial number, returning any device it replaces.
+    pub fn insert<S: Into<String>>(
+        &mut self,
+        serial_number: S,
+        client: Client<T>,
+    ) -> Option<Client<T>> {
+        self.devices.insert(serial_number.into(), client)
+    }
+
+    /// Add a device, asking it for its serial number. Returns the serial number.
+    pub async fn add(&mut self, client: Client<T>) -> Result<String> {
+        let serial_number = client.system_properties().await?.serial_number;
+        self.devices.insert(serial_number.clone(), client);
+        Ok(serial_number)
+    }
+
+    /// Remove a device from the fleet, returning its client.
+    pub fn remove(&mut self, serial_number: &str) -> Option<Client<T>> {
+        self.devices.remove(serial_number)
+    }
+
+    /// The client for the device with the given serial number.
+    pub fn get(&self, serial_number: &str) -> Option<&Client<T>> {
+        self.devices.get(serial_number)
+    }
+
+    /// Iterate over the devices in the fleet, in order of serial number.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Client<T>)> {
+        self.devices
+            .iter()
+            .map(|(serial, client)| (serial.as_str(), client))
+    }
+
+    /// The number of devices in the fleet.
+    pub fn len(&self) -> usize {
+        self.devices.len()
+    }
+
+    /// Is the fleet empty?
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty()
+    }
+
+    /// Capture a JPEG snapshot from every device, returning the raw JPEG bytes or the error for
+    /// each device by serial number.
+    ///
+    /// Devices which don't respond within `options.timeout` fail with a timeout error, which
+    /// `Error::connection_error_kind()` classifies as `ConnectionErrorKind::Timeout`.
+    pub async fn snapshot_all(
+        &self,
+        options: &FleetSnapshotOptions,
+    ) -> BTreeMap<String, Result<Vec<u8>>> {
+        stream::iter(self.devices.iter())
+            .map(|(serial_number, client)| async move {
+                let jpeg = crate::client::within(options.timeout, "snapshot timeout", async {
+                    client
+                        .snapshot()
+                        .capture_with_options(&options.snapshot)
+                        .await
+                })
+                .await;
+                (serial_number.clone(), jpeg)
+            })
+            .buffer_unordered(options.concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Start a keep-alive for every device, returning a future which runs them all and a handle
+    /// for each device by serial number.
+    ///
+    /// Each device's ticks are jittered independently. The future completes once every handle has
+    /// been stopped.
+    pub fn keep_alive_all(
+        &self,
+        keep_alive: KeepAlive,
+    ) -> (
+        impl Future<Output = ()> + '_,
+        BTreeMap<String, KeepAliveHandle>,
+    ) {
+        let mut tasks = Vec::with_capacity(self.devices.len());
+        let mut handles = BTreeMap::new();
+        for (serial_number, client) in &self.devices {
+            let (task, handle) = client.keep_alive(keep_alive);
+            tasks.push(task);
+            handles.insert(serial_number.clone(), handle);
+        }
+        (future::join_all(tasks).map(|_| ()), handles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn keep_alive_all() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let mut fleet = Fleet::new();
+        for (serial_number, host) in &[("ACCC8E000001", "10.0.0.1"), ("ACCC8E000002", "10.0.0.2")] {
+            let requests = requests.clone();
+            let client = crate::test_utils::mock_client_for_uri(
+                format!("http://{}", host).parse().unwrap(),
+                move |req| {
+                    requests.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(req.uri().path(), "/axis-cgi/param.cgi");
+                    if req.uri().host() == Some("10.0.0.2") {
+                        return http::Response::builder()
+                            .status(http::StatusCode::INTERNAL_SERVER_ERROR)
+                            .body(vec![]);
+                    }
+                    http::Response::builder()
+                        .status(http::StatusCode::OK)
+                        .header(http::header::CONTENT_TYPE, "text/plain")
+                        .body(vec![b"root.Brand.ProdNbr=P5512\n".to_vec()])
+                },
+            );
+            fleet.insert(*serial_number, client);
+        }
+
+        let (task, handles) = fleet.keep_alive_all(KeepAlive::new(Duration::from_millis(5)));
+        assert_eq!(handles.len(), 2);
+        let stopper = async {
+            while handles.values().any(|h| {
+                let status = h.status();
+                status.last_success.is_none() && status.consecutive_failures == 0
+            }) {
+                futures_timer::Delay::new(Duration::from_millis(1)).await;
+            }
+            handles.values().for_each(KeepAliveHandle::stop);
+        };
+        future::join(task, stopper).await;
+
+        assert!(requests.load(Ordering::SeqCst) >= 2);
+        assert!(handles["ACCC8E000001"].status().last_success.is_some());
+        let failing = handles["ACCC8E000002"].status();
+        assert!(failing.last_success.is_none());
+        assert!(failing.consecutive_failures > 0);
+    }
+
+    #[tokio::test]
+    async fn snapshot_all() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let mut fleet = Fleet::new();
+        for (serial_number, host) in &[("ACCC8E000001", "10.0.0.1"), ("ACCC8E000002", "10.0.0.2")] {
+            let requests = requests.clone();
+            let client = crate::test_utils::mock_boxed_client_for_uri(
+                format!("http://{}", host).parse().unwrap(),
+                move |req| {
+                    requests.fetch_add(1, Ordering::SeqCst);
+                    assert_eq!(req.uri().path(), "/axis-cgi/jpg/image.cgi");
+                    if req.uri().host() == Some("10.0.0.2") {
+                        return http::Response::builder()
+                            .status(http::StatusCode::NOT_FOUND)
+                            .body(vec![]);
+                    }
+                    http::Response::builder()
+                        .status(http::StatusCode::OK)
+                        .header(http::header::CONTENT_TYPE, "image/jpeg")
+                        .body(vec![b"\xff\xd8\xff\xd9".to_vec()])
+                },
+            );
+            assert!(fleet.insert(*serial_number, client).is_none());
+        }
+
+        let snapshots = fleet
+            .snapshot_all(&FleetSnapshotOptions {
+                concurrency: 1,
+                ..FleetSnapshotOptions::default()
+            })
+            .await;
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(
+            snapshots["ACCC8E000001"].as_ref().unwrap(),
+            b"\xff\xd8\xff\xd9"
+        );
+        assert!(matches!(
+            snapshots["ACCC8E000002"],
+            Err(Error::FeatureUnavailable)
+        ));
+    }
+}
@@ -36,7 +36,7 @@ impl Default for HyperTransport<::hyper::client::HttpConnector, ::hyper::body::B
 impl<C, B> Transport for HyperTransport<C, B>
 where
     C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
-    B: hyper::body::HttpBody + Send + 'static + From<Vec<u8>>,
+    B: hyper::body::HttpBody + Send + 'static + From<Vec<u8>> + From<hyper::Body>,
     B::Data: Send,
     B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
@@ -49,6 +49,16 @@ where
         let request = hyper::Request::from_parts(parts, body.into());
         HyperResponseFuture(self.0.request(request))
     }
+
+    fn roundtrip_streaming_request(
+        &self,
+        request: Request<crate::RequestBody>,
+    ) -> Result<Self::Output, Box<Request<crate::RequestBody>>> {
+        let (parts, body) = request.into_parts();
+        let body = hyper::Body::wrap_stream(body);
+        let request = hyper::Request::from_parts(parts, body.into());
+        Ok(HyperResponseFuture(self.0.request(request)))
+    }
 }
 
 #[pin_project]
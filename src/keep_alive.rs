@@ -0,0 +1,191 @@
+use crate::{Client, Transport};
+use futures::future::{self, AbortHandle};
+use futures::prelude::*;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// A periodic keep-alive for long-lived connections to a device.
+///
+/// Each tick performs a cheap authenticated request, which keeps the client's digest
+/// authentication state and the transport's connection pool warm. Ticks are spread out by a
+/// random jitter so that keep-alives for many devices don't synchronize.
+///
+/// `KeepAlive` does not depend on any particular async runtime. `start()` returns a future which
+/// the caller should spawn, along with a handle to stop it and to observe the device's health.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct KeepAlive {
+    interval: Duration,
+    jitter: Duration,
+}
+
+impl KeepAlive {
+    /// Create a keep-alive which ticks every `interval`, with a jitter of ±10%.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            jitter: interval / 10,
+        }
+    }
+
+    /// Set the maximum jitter applied to each interval.
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter.min(self.interval);
+        self
+    }
+
+    /// Start the keep-alive for a `Client`.
+    ///
+    /// `client` may be a `&Client` or an `Arc<Client>`; the latter results in a `'static` future
+    /// suitable for spawning. The future runs until stopped via the returned handle.
+    pub fn start<C, T>(self, client: C) -> (impl Future<Output = ()>, KeepAliveHandle)
+    where
+        C: Deref<Target = Client<T>>,
+        T: Transport,
+    {
+        let status = Arc::new(Mutex::new(KeepAliveStatus::default()));
+        let task_status = status.clone();
+
+        let task = async move {
+            let mut rng = Jitter::seeded();
+            loop {
+                futures_timer::Delay::new(rng.apply(self.interval, self.jitter)).await;
+
+                let result = client
+                    .parameters()
+                    .list(Some(&["root.Brand.ProdNbr"][..]))
+                    .await;
+
+                let mut status = task_status.lock().unwrap_or_else(|e| e.into_inner());
+                match result {
+                    Ok(_) => {
+                        status.last_success = Some(Instant::now());
+                        status.consecutive_failures = 0;
+                    }
+                    Err(_) => status.consecutive_failures += 1,
+                }
+            }
+        };
+
+        let (task, abort) = future::abortable(task);
+        (task.map(|_| ()), KeepAliveHandle { abort, status })
+    }
+}
+
+/// A handle to a running `KeepAlive`.
+///
+/// Dropping the handle does not stop the keep-alive.
+#[derive(Debug, Clone)]
+pub struct KeepAliveHandle {
+    abort: AbortHandle,
+    status: Arc<Mutex<KeepAliveStatus>>,
+}
+
+impl KeepAliveHandle {
+    /// Stop the keep-alive, causing its future to complete.
+    pub fn stop(&self) {
+        self.abort.abort();
+    }
+
+    /// Return the health of the device as observed by the keep-alive.
+    pub fn status(&self) -> KeepAliveStatus {
+        *self.status.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The health of a device as observed by a `KeepAlive`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct KeepAliveStatus {
+    /// When the most recent successful request completed, if any.
+    pub last_success: Option<Instant>,
+    /// The number of requests which have failed since the last success.
+    pub consecutive_failures: u32,
+}
+
+/// A small xorshift generator, which is plenty random enough to spread out ticks.
+struct Jitter(u64);
+
+/// The number of generators seeded so far, which keeps keep-alives started in the same clock tick
+/// apart.
+static SEEDED: AtomicU64 = AtomicU64::new(0);
+
+impl Jitter {
+    fn seeded() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        let count = SEEDED.fetch_add(1, Ordering::Relaxed);
+        Self((nanos ^ count.wrapping_mul(0x9e37_79b9_7f4a_7c15)) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn apply(&mut self, interval: Duration, jitter: Duration) -> Duration {
+        let jitter_nanos = jitter.as_nanos() as u64;
+        if jitter_nanos == 0 {
+            return interval;
+        }
+
+        let offset = Duration::from_nanos(self.next() % (2 * jitter_nanos));
+        (interval + offset).checked_sub(jitter).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn jitter() {
+        let mut rng = Jitter(12345);
+        for _ in 0..1000 {
+            let d = rng.apply(Duration::from_secs(10), Duration::from_secs(1));
+            assert!(d >= Duration::from_secs(9) && d < Duration::from_secs(11));
+        }
+        assert_eq!(
+            rng.apply(Duration::from_secs(10), Duration::from_secs(0)),
+            Duration::from_secs(10)
+        );
+
+        assert_ne!(Jitter::seeded().0, Jitter::seeded().0);
+    }
+
+    #[tokio::test]
+    async fn ticks_until_stopped() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let requests_ = requests.clone();
+        let client = Arc::new(crate::mock_client(move |req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?action=list&group=root.Brand.ProdNbr")
+            );
+            requests_.fetch_add(1, Ordering::SeqCst);
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.Brand.ProdNbr=P5512\n".to_vec()])
+        }));
+
+        let (task, handle) = KeepAlive::new(Duration::from_millis(5)).start(client);
+        let stopper = async {
+            while requests.load(Ordering::SeqCst) < 3 {
+                futures_timer::Delay::new(Duration::from_millis(1)).await;
+            }
+            handle.stop();
+        };
+        future::join(task, stopper).await;
+
+        let status = handle.status();
+        assert!(status.last_success.is_some());
+        assert_eq!(status.consecutive_failures, 0);
+    }
+}
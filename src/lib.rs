@@ -6,9 +6,23 @@
 //#![forbid(missing_docs)]
 #![forbid(unused_variables)]
 
+mod api_versions;
+mod body_reader;
+mod cancel;
+mod channel;
 mod client;
+mod clock;
 mod error;
+mod fleet;
+mod keep_alive;
+mod pagination;
+mod product_family;
+mod reconnect;
+mod storage_monitor;
+mod system_properties;
 mod transport;
+mod with_headers;
+mod xml;
 
 /// Define a type T which is `impl From<String> for T`, `impl From<T> for String`, and associated
 /// string-ish behaviors.
@@ -68,13 +82,39 @@ macro_rules! string_type {
     };
 }
 
+pub mod capabilities;
+pub mod device_catalog;
+pub mod prelude;
 pub mod v3;
 pub mod v4;
 
-pub use client::Client;
+pub use api_versions::{ApiVersion, ApiVersionParseError, ApiVersions};
+pub use body_reader::BodyReader;
+pub use cancel::CancelHandle;
+pub use channel::Channel;
+#[cfg(feature = "tokio")]
+pub use client::DownloadProgress;
+pub use client::{
+    Client, EndpointStats, ResponseBody, RetryPolicy, RtspCredentials, RtspUrlOptions,
+    SignableRequest, Stats, Timeouts,
+};
+pub use clock::{Clock, FixedClock, SystemClock};
 pub(crate) use error::ResultExt;
-pub use error::{Error, Result};
-pub use transport::Transport;
+pub use error::{
+    CgiError, ConnectionErrorKind, Error, ErrorContext, Result, UnparseableResponseError,
+};
+pub use fleet::{Fleet, FleetSnapshotOptions};
+pub use keep_alive::{KeepAlive, KeepAliveHandle, KeepAliveStatus};
+pub use pagination::{Page, Pagination, Paginator};
+pub use product_family::ProductFamily;
+pub use reconnect::{Reconnect, StreamEvent, StreamItem};
+pub use storage_monitor::{StorageAlert, StorageMonitor, StorageNotification, StorageThresholds};
+pub use system_properties::SystemProperties;
+pub use transport::{BoxTransport, Error as TransportError, RequestBody, TlsError, Transport};
+pub use with_headers::WithHeaders;
+
+#[cfg(feature = "time")]
+pub mod time_interop;
 
 #[cfg(all(feature = "hyper"))]
 pub mod hyper;
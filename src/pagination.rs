@@ -0,0 +1,186 @@
+use crate::Result;
+use futures::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// The position and size of a page within a list-style endpoint.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct Pagination {
+    /// The number of records to return.
+    pub page_size: Option<u64>,
+    /// The offset of the first record.
+    pub offset: Option<u64>,
+}
+
+/// A page of results from a list-style endpoint.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    /// The records on this page.
+    pub items: Vec<T>,
+    /// The offset of the first record on this page.
+    pub offset: u64,
+    /// The total number of records available, if the device reports it.
+    pub total: Option<u64>,
+}
+
+impl<T> Page<T> {
+    /// Determine the `Pagination` which would request the page following this one, given the
+    /// page size used to request this page.
+    ///
+    /// Returns `None` if this is the last page.
+    pub fn next(&self, page_size: Option<u64>) -> Option<Pagination> {
+        if self.items.is_empty() {
+            return None;
+        }
+
+        let next_offset = self.offset + self.items.len() as u64;
+        match (self.total, page_size) {
+            (Some(total), _) if next_offset >= total => None,
+            (_, Some(page_size)) if (self.items.len() as u64) < page_size => None,
+            _ => Some(Pagination {
+                page_size,
+                offset: Some(next_offset),
+            }),
+        }
+    }
+}
+
+/// Walks a list-style endpoint page by page.
+///
+/// A `Paginator` wraps a function which fetches a single `Page` given a `Pagination`, and calls
+/// it repeatedly until the endpoint is exhausted or an error occurs.
+///
+/// Only endpoints which accept an offset and a page size can be paginated. Others, such as
+/// `UserManagement::list()`, return every record at once.
+pub struct Paginator<T, F> {
+    fetch: F,
+    next: Option<Pagination>,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T, F, Fut> Paginator<T, F>
+where
+    F: FnMut(Pagination) -> Fut,
+    Fut: Future<Output = Result<Page<T>>>,
+{
+    /// Create a `Paginator` which starts from the position described by `pagination`.
+    pub fn new(pagination: Pagination, fetch: F) -> Self {
+        Self {
+            fetch,
+            next: Some(pagination),
+            _item: PhantomData,
+        }
+    }
+
+    /// Fetch the next page, returning `None` once every page has been returned.
+    ///
+    /// Pagination stops after the first error.
+    pub async fn next_page(&mut self) -> Option<Result<Page<T>>> {
+        let pagination = self.next.take()?;
+        let page_size = pagination.page_size;
+
+        let result = (self.fetch)(pagination).await;
+        if let Ok(page) = &result {
+            self.next = page.next(page_size);
+        }
+        Some(result)
+    }
+
+    /// Convert this `Paginator` into a `Stream` of individual records.
+    pub fn into_stream(self) -> impl Stream<Item = Result<T>> {
+        stream::unfold(self, |mut paginator| async move {
+            paginator.next_page().await.map(|page| (page, paginator))
+        })
+        .map(|page| match page {
+            Ok(page) => stream::iter(page.items.into_iter().map(Ok)).left_stream(),
+            Err(e) => stream::once(future::ready(Err(e))).right_stream(),
+        })
+        .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    fn fetch(pagination: Pagination) -> future::Ready<Result<Page<u64>>> {
+        let offset = pagination.offset.unwrap_or(0);
+        let page_size = pagination.page_size.unwrap_or(u64::MAX);
+        future::ready(Ok(Page {
+            items: (offset..10).take(page_size as usize).collect(),
+            offset,
+            total: None,
+        }))
+    }
+
+    #[tokio::test]
+    async fn next_page() {
+        let mut paginator = Paginator::new(
+            Pagination {
+                page_size: Some(4),
+                offset: None,
+            },
+            fetch,
+        );
+
+        let mut pages = Vec::new();
+        while let Some(page) = paginator.next_page().await {
+            pages.push(page.unwrap().items);
+        }
+        assert_eq!(pages, vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7], vec![8, 9]]);
+    }
+
+    #[tokio::test]
+    async fn into_stream() {
+        let items: Vec<u64> = Paginator::new(
+            Pagination {
+                page_size: Some(3),
+                offset: Some(2),
+            },
+            fetch,
+        )
+        .into_stream()
+        .try_collect()
+        .await
+        .unwrap();
+        assert_eq!(items, (2..10).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn stops_after_error() {
+        let mut calls = 0;
+        let results: Vec<Result<u64>> = Paginator::new(Pagination::default(), |_| {
+            calls += 1;
+            future::ready(Err(Error::Other("nope")))
+        })
+        .into_stream()
+        .collect()
+        .await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn next_uses_total() {
+        let page = Page {
+            items: vec![(); 5],
+            offset: 5,
+            total: Some(10),
+        };
+        assert_eq!(page.next(None), None);
+
+        let page = Page {
+            total: Some(11),
+            ..page
+        };
+        assert_eq!(
+            page.next(None),
+            Some(Pagination {
+                page_size: None,
+                offset: Some(10)
+            })
+        );
+    }
+}
@@ -0,0 +1,32 @@
+//! The types and traits most programs using this crate need, for glob import:
+//!
+//! ```
+//! use vapix::prelude::*;
+//! ```
+//!
+//! The prelude is deliberately small. Items are added to it only when nearly every user of the
+//! crate needs them, and are never removed or renamed outside a major version. Extension traits
+//! are imported anonymously, so their methods are available without their names colliding with
+//! the caller's own imports.
+
+pub use crate::{
+    BoxTransport, CancelHandle, Channel, Client, Error, Reconnect, RequestBody, Result,
+    RetryPolicy, StreamItem, Timeouts, Transport,
+};
+
+#[cfg(feature = "hyper")]
+pub use crate::HyperTransport;
+
+pub use futures::{StreamExt as _, TryStreamExt as _};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn extension_traits() {
+        let stream = futures::stream::iter(vec![Ok::<_, Error>(1), Ok(2)]);
+        let items: Vec<u32> = stream.map_ok(|n| n * 2).try_collect().await.unwrap();
+        assert_eq!(items, vec![2, 4]);
+    }
+}
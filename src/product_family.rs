@@ -0,0 +1,190 @@
+use crate::{device_catalog, Client, Result, Transport};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A broad classification of AXIS products.
+///
+/// AXIS sells many product lines which share firmware and APIs but differ in what is worth asking
+/// them. `Client::product_family()` classifies a device heuristically, so tooling can branch on
+/// the kind of device without maintaining its own list of models.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProductFamily {
+    /// A camera with a fixed field of view, possibly offering digital PTZ.
+    FixedCamera,
+    /// A camera with mechanical pan/tilt/zoom.
+    Ptz,
+    /// A video encoder, which streams analog cameras.
+    Encoder,
+    /// A network speaker or other audio-only device.
+    Speaker,
+    /// A door station or intercom.
+    DoorStation,
+    /// A security radar.
+    Radar,
+    /// None of the above, or not enough information to tell.
+    Unknown,
+}
+
+impl ProductFamily {
+    /// Classify a device from the contents of its `Brand` and `Properties` parameter groups,
+    /// keyed relative to `root`, e.g. `Brand.ProdType`.
+    ///
+    /// `Brand.ProdType` is the most reliable indicator when it is familiar. Otherwise, the
+    /// product number is looked up in the `device_catalog`, and finally the device's reported
+    /// properties are considered.
+    pub fn classify(params: &BTreeMap<&str, &str>) -> Self {
+        let get = |key: &str| params.get(key).copied().unwrap_or("");
+        let yes = |key: &str| get(key) == "yes";
+
+        if let Some(family) = Self::from_prod_type(get("Brand.ProdType")) {
+            return family;
+        }
+
+        let product = device_catalog::lookup(get("Brand.ProdNbr"))
+            .or_else(|| device_catalog::lookup(get("Properties.System.HardwareID")));
+        match product {
+            Some(product) if product.ptz => return ProductFamily::Ptz,
+            Some(product) if product.channels > 0 => return ProductFamily::FixedCamera,
+            _ => {}
+        }
+
+        let has_video = params
+            .keys()
+            .any(|key| key.starts_with("Properties.Image."));
+        if yes("Properties.PTZ.PTZ") && !yes("Properties.PTZ.DigitalPTZ") {
+            ProductFamily::Ptz
+        } else if has_video {
+            ProductFamily::FixedCamera
+        } else if yes("Properties.Audio.Audio") {
+            ProductFamily::Speaker
+        } else {
+            ProductFamily::Unknown
+        }
+    }
+
+    fn from_prod_type(prod_type: &str) -> Option<Self> {
+        let prod_type = prod_type.to_ascii_lowercase();
+        let has = |word: &str| prod_type.contains(word);
+
+        if has("door station") || has("intercom") {
+            Some(ProductFamily::DoorStation)
+        } else if has("radar") {
+            Some(ProductFamily::Radar)
+        } else if has("encoder") || has("video server") {
+            Some(ProductFamily::Encoder)
+        } else if has("speaker") || has("audio") {
+            Some(ProductFamily::Speaker)
+        } else if has("ptz") {
+            Some(ProductFamily::Ptz)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Classify the device into a `ProductFamily` using its `Brand` and `Properties` parameter
+    /// groups.
+    ///
+    /// This is a heuristic. Devices which defy classification are `ProductFamily::Unknown`.
+    pub async fn product_family(&self) -> Result<ProductFamily> {
+        let params = self
+            .parameters()
+            .list(Some(&["root.Brand", "root.Properties"][..]))
+            .await?;
+        let params: BTreeMap<&str, &str> = params
+            .iter()
+            .map(|(k, v)| (k.trim_start_matches("root."), v.as_str()))
+            .collect();
+
+        Ok(ProductFamily::classify(&params))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(params: &[(&str, &str)]) -> ProductFamily {
+        ProductFamily::classify(&params.iter().copied().collect())
+    }
+
+    #[test]
+    fn heuristics() {
+        assert_eq!(
+            classify(&[("Brand.ProdType", "PTZ Dome Network Camera")]),
+            ProductFamily::Ptz
+        );
+        assert_eq!(
+            classify(&[("Brand.ProdType", "Network Door Station")]),
+            ProductFamily::DoorStation
+        );
+        assert_eq!(
+            classify(&[("Brand.ProdType", "Network Video Encoder")]),
+            ProductFamily::Encoder
+        );
+        assert_eq!(
+            classify(&[("Brand.ProdType", "Network Horn Speaker")]),
+            ProductFamily::Speaker
+        );
+        assert_eq!(
+            classify(&[("Brand.ProdType", "Security Radar")]),
+            ProductFamily::Radar
+        );
+
+        // A dome camera with mechanical PTZ, which doesn't say so in its ProdType
+        assert_eq!(
+            classify(&[
+                ("Brand.ProdNbr", "M5525-E"),
+                ("Brand.ProdType", "Network Camera"),
+            ]),
+            ProductFamily::Ptz
+        );
+        assert_eq!(
+            classify(&[
+                ("Brand.ProdNbr", "P1435-LE"),
+                ("Brand.ProdType", "Network Camera"),
+                ("Properties.Image.Format", "jpeg,mjpeg,h264"),
+                ("Properties.PTZ.DigitalPTZ", "yes"),
+                ("Properties.PTZ.PTZ", "yes"),
+            ]),
+            ProductFamily::FixedCamera
+        );
+        assert_eq!(
+            classify(&[("Brand.ProdNbr", "C1410")]),
+            ProductFamily::Unknown
+        );
+        assert_eq!(
+            classify(&[
+                ("Brand.ProdNbr", "C1410"),
+                ("Properties.Audio.Audio", "yes")
+            ]),
+            ProductFamily::Speaker
+        );
+        assert_eq!(classify(&[]), ProductFamily::Unknown);
+    }
+
+    #[tokio::test]
+    async fn product_family() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().unwrap().as_str(),
+                "/axis-cgi/param.cgi?action=list&group=root.Brand%2Croot.Properties"
+            );
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.Brand.Brand=AXIS
+root.Brand.ProdFullName=AXIS P5512 PTZ Dome Network Camera
+root.Brand.ProdNbr=P5512
+root.Brand.ProdShortName=AXIS P5512
+root.Brand.ProdType=PTZ Dome Network Camera
+root.Properties.PTZ.PTZ=yes
+"
+                .to_vec()])
+        });
+
+        assert_eq!(client.product_family().await.unwrap(), ProductFamily::Ptz);
+    }
+}
@@ -0,0 +1,482 @@
+use crate::{CancelHandle, Error, Result};
+use futures::future::{self, Either};
+use futures::prelude::*;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// An item produced by a long-running stream, such as a frame or an event.
+pub trait StreamItem {
+    /// The information needed to resume the stream after this item, if the protocol allows it.
+    type Resume: Clone;
+
+    /// The number of bytes this item represents, for instrumentation.
+    fn byte_len(&self) -> usize;
+
+    /// The point from which a reconnected stream should resume, having delivered this item.
+    fn resume_point(&self) -> Option<Self::Resume> {
+        None
+    }
+}
+
+impl StreamItem for Vec<u8> {
+    type Resume = ();
+
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+}
+
+/// A lifecycle event of a supervised stream.
+#[derive(Debug)]
+pub enum StreamEvent<'a> {
+    /// The stream connected for the first time.
+    Started,
+    /// The stream produced an item.
+    Received {
+        /// The size of the item.
+        bytes: usize,
+        /// The total size of all items received so far, across reconnects.
+        total_bytes: u64,
+    },
+    /// The stream produced nothing for the configured stall timeout.
+    Stalled {
+        /// How long the stream had been idle.
+        idle: Duration,
+    },
+    /// The stream was lost and will be reconnected.
+    Disconnected {
+        /// The number of consecutive reconnect attempts, including this one.
+        attempt: u32,
+        /// The error which caused the disconnect, or `None` if the stream ended or stalled.
+        cause: Option<&'a Error>,
+    },
+    /// The stream connected again after a disconnect.
+    Reconnected {
+        /// The number of consecutive reconnect attempts it took.
+        attempt: u32,
+    },
+    /// The stream has ended and will not be reconnected.
+    Ended,
+}
+
+type Observer = Arc<dyn Fn(&StreamEvent) + Send + Sync>;
+
+/// A policy for supervising long-running streams, like MJPEG video or event subscriptions.
+///
+/// A supervised stream reports its lifecycle to an optional observer, which is a convenient place
+/// to forward events to `tracing`, `log`, or metrics. When the connection drops, ends, or stalls,
+/// the stream is reconnected from the last item's `StreamItem::resume_point()`, so consumers see
+/// a single uninterrupted stream. Errors which reconnecting can't fix, like authentication
+/// failures, are passed through and end the stream.
+///
+/// A supervised stream can be ended with a `CancelHandle` passed to `with_cancel()`, which also
+/// interrupts any wait between reconnect attempts.
+///
+/// `Reconnect` does not depend on any particular async runtime.
+#[derive(Clone)]
+pub struct Reconnect {
+    max_attempts: u32,
+    delay: Duration,
+    stall_timeout: Option<Duration>,
+    observer: Option<Observer>,
+    cancel: Option<CancelHandle>,
+}
+
+impl fmt::Debug for Reconnect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reconnect")
+            .field("max_attempts", &self.max_attempts)
+            .field("delay", &self.delay)
+            .field("stall_timeout", &self.stall_timeout)
+            .field("observer", &self.observer.is_some())
+            .field("cancel", &self.cancel)
+            .finish()
+    }
+}
+
+impl Default for Reconnect {
+    fn default() -> Self {
+        Self::new(5)
+    }
+}
+
+impl Reconnect {
+    /// Reconnect up to `max_attempts` consecutive times, waiting one second between attempts.
+    ///
+    /// The attempt counter resets whenever the stream produces an item.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            delay: Duration::from_secs(1),
+            stall_timeout: None,
+            observer: None,
+            cancel: None,
+        }
+    }
+
+    /// Never reconnect, but still instrument the stream.
+    pub fn never() -> Self {
+        Self::new(0)
+    }
+
+    /// Set the delay between reconnect attempts.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Treat a stream which produces nothing for `timeout` as disconnected.
+    pub fn with_stall_timeout(mut self, timeout: Duration) -> Self {
+        self.stall_timeout = Some(timeout);
+        self
+    }
+
+    /// Report lifecycle events to `observer`.
+    pub fn with_observer<F>(mut self, observer: F) -> Self
+    where
+        F: Fn(&StreamEvent) + Send + Sync + 'static,
+    {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// End the stream once `cancel` is cancelled.
+    ///
+    /// The stream ends without reconnecting as soon as the current connection ends. Streams
+    /// supervised by this crate, like `Events::supervise()`, end their connection promptly on
+    /// cancellation; other streams can be made to with `CancelHandle::run_stream()`.
+    pub fn with_cancel(mut self, cancel: CancelHandle) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// The handle passed to `with_cancel()`, or else one which is never cancelled.
+    pub(crate) fn cancel_handle(&self) -> CancelHandle {
+        self.cancel.clone().unwrap_or_default()
+    }
+
+    /// Supervise the stream returned by `connect`.
+    ///
+    /// `connect` is called with `None` to start the stream, and again after each disconnect with
+    /// the resume point of the last item received, if any.
+    pub fn supervise<T, S, C, Fut>(self, connect: C) -> impl Stream<Item = Result<T>>
+    where
+        T: StreamItem,
+        C: FnMut(Option<T::Resume>) -> Fut,
+        Fut: Future<Output = Result<S>>,
+        S: Stream<Item = Result<T>>,
+    {
+        let supervisor = Supervisor {
+            policy: self,
+            connect,
+            stream: None,
+            resume: None,
+            attempt: 0,
+            connected: false,
+            total_bytes: 0,
+            done: false,
+        };
+        stream::unfold(supervisor, |mut supervisor| async move {
+            supervisor.next().await.map(|item| (item, supervisor))
+        })
+    }
+}
+
+struct Supervisor<T: StreamItem, S, C> {
+    policy: Reconnect,
+    connect: C,
+    stream: Option<Pin<Box<S>>>,
+    resume: Option<T::Resume>,
+    attempt: u32,
+    connected: bool,
+    total_bytes: u64,
+    done: bool,
+}
+
+enum Outcome<T> {
+    Item(T),
+    Failed(Error),
+    Ended,
+    Stalled(Duration),
+}
+
+impl<T, S, C, Fut> Supervisor<T, S, C>
+where
+    T: StreamItem,
+    C: FnMut(Option<T::Resume>) -> Fut,
+    Fut: Future<Output = Result<S>>,
+    S: Stream<Item = Result<T>>,
+{
+    fn emit(&self, event: StreamEvent) {
+        if let Some(observer) = &self.policy.observer {
+            observer(&event);
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.policy
+            .cancel
+            .as_ref()
+            .map(CancelHandle::is_cancelled)
+            .unwrap_or(false)
+    }
+
+    async fn next(&mut self) -> Option<Result<T>> {
+        while !self.done {
+            if self.is_cancelled() {
+                return self.end(None);
+            }
+
+            let outcome = match &mut self.stream {
+                None => {
+                    let cancel = self.policy.cancel_handle();
+                    if self.attempt > 0 {
+                        let delay = futures_timer::Delay::new(self.policy.delay);
+                        if cancel.run(delay.map(Ok)).await.is_err() {
+                            continue;
+                        }
+                    }
+                    match cancel.run((self.connect)(self.resume.clone())).await {
+                        Ok(stream) => {
+                            self.stream = Some(Box::pin(stream));
+                            if self.connected {
+                                self.emit(StreamEvent::Reconnected {
+                                    attempt: self.attempt,
+                                });
+                            } else {
+                                self.connected = true;
+                                self.emit(StreamEvent::Started);
+                            }
+                            continue;
+                        }
+                        Err(e) => Outcome::Failed(e),
+                    }
+                }
+                Some(stream) => match self.policy.stall_timeout {
+                    Some(timeout) => {
+                        let delay = futures_timer::Delay::new(timeout);
+                        match future::select(stream.next(), delay).await {
+                            Either::Left((item, _)) => item.into(),
+                            Either::Right(_) => Outcome::Stalled(timeout),
+                        }
+                    }
+                    None => stream.next().await.into(),
+                },
+            };
+
+            match outcome {
+                Outcome::Item(item) => {
+                    let bytes = item.byte_len();
+                    self.total_bytes += bytes as u64;
+                    self.attempt = 0;
+                    if let Some(resume) = item.resume_point() {
+                        self.resume = Some(resume);
+                    }
+                    self.emit(StreamEvent::Received {
+                        bytes,
+                        total_bytes: self.total_bytes,
+                    });
+                    return Some(Ok(item));
+                }
+                _ if self.is_cancelled() => return self.end(None),
+                Outcome::Failed(e) => {
+                    if !is_reconnectable(&e) || !self.reconnect(Some(&e)) {
+                        return self.end(Some(e));
+                    }
+                }
+                Outcome::Ended => {
+                    if !self.reconnect(None) {
+                        return self.end(None);
+                    }
+                }
+                Outcome::Stalled(idle) => {
+                    self.emit(StreamEvent::Stalled { idle });
+                    if !self.reconnect(None) {
+                        return self.end(Some(Error::Other("stream stalled")));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Drop the current stream, returning whether another connection attempt is permitted.
+    fn reconnect(&mut self, cause: Option<&Error>) -> bool {
+        self.stream = None;
+        if self.attempt >= self.policy.max_attempts {
+            return false;
+        }
+
+        self.attempt += 1;
+        self.emit(StreamEvent::Disconnected {
+            attempt: self.attempt,
+            cause,
+        });
+        true
+    }
+
+    fn end(&mut self, error: Option<Error>) -> Option<Result<T>> {
+        self.stream = None;
+        self.done = true;
+        self.emit(StreamEvent::Ended);
+        error.map(Err)
+    }
+}
+
+impl<T> From<Option<Result<T>>> for Outcome<T> {
+    fn from(item: Option<Result<T>>) -> Self {
+        match item {
+            Some(Ok(item)) => Outcome::Item(item),
+            Some(Err(e)) => Outcome::Failed(e),
+            None => Outcome::Ended,
+        }
+    }
+}
+
+/// Can this error plausibly be fixed by connecting again?
+fn is_reconnectable(error: &Error) -> bool {
+    use crate::ConnectionErrorKind::*;
+    error.is_transient()
+        || match error.connection_error_kind() {
+            Some(DnsFailure) | Some(ConnectionRefused) | Some(Timeout) | Some(ResetByPeer) => true,
+            Some(TlsError) | None => false,
+        }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    struct Event(u32);
+
+    impl StreamItem for Event {
+        type Resume = u32;
+
+        fn byte_len(&self) -> usize {
+            4
+        }
+
+        fn resume_point(&self) -> Option<u32> {
+            Some(self.0)
+        }
+    }
+
+    fn reset() -> Error {
+        Error::HttpRequestFailed(Box::new(std::io::Error::from(
+            std::io::ErrorKind::ConnectionReset,
+        )))
+    }
+
+    #[tokio::test]
+    async fn resumes_after_disconnect() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_ = events.clone();
+        let connects = Arc::new(Mutex::new(Vec::new()));
+        let connects_ = connects.clone();
+
+        let stream = Reconnect::new(2)
+            .with_delay(Duration::from_millis(1))
+            .with_observer(move |event| events_.lock().unwrap().push(format!("{:?}", event)))
+            .supervise(move |resume: Option<u32>| {
+                connects_.lock().unwrap().push(resume);
+                let start = resume.map(|n| n + 1).unwrap_or(0);
+                let items = vec![Ok(Event(start)), Ok(Event(start + 1)), Err(reset())];
+                future::ready(Ok(stream::iter(items)))
+            });
+
+        let items: Vec<Result<Event>> = stream.take(5).collect().await;
+        let items: Vec<Event> = items.into_iter().map(|r| r.unwrap()).collect();
+        assert_eq!(items, (0..5).map(Event).collect::<Vec<_>>());
+        assert_eq!(
+            connects.lock().unwrap().as_slice(),
+            &[None, Some(1), Some(3)]
+        );
+
+        let events = events.lock().unwrap();
+        assert_eq!(events[0], "Started");
+        assert!(events
+            .iter()
+            .any(|e| e.starts_with("Disconnected { attempt: 1")));
+        assert!(events.contains(&"Reconnected { attempt: 1 }".to_string()));
+        assert!(events.contains(&"Received { bytes: 4, total_bytes: 20 }".to_string()));
+    }
+
+    #[tokio::test]
+    async fn gives_up() {
+        let stream = Reconnect::new(2)
+            .with_delay(Duration::from_millis(1))
+            .supervise(|_| future::ready(Err::<stream::Empty<Result<Event>>, _>(reset())));
+        let items: Vec<Result<Event>> = stream.collect().await;
+        assert_eq!(items.len(), 1);
+        assert!(items[0].as_ref().unwrap_err().is_transient());
+
+        // Errors which reconnecting won't fix are passed through immediately
+        let mut connects = 0;
+        let stream = Reconnect::new(2).supervise(|_| {
+            connects += 1;
+            future::ready(Err::<stream::Empty<Result<Event>>, _>(
+                Error::FeatureUnavailable,
+            ))
+        });
+        let items: Vec<Result<Event>> = stream.collect().await;
+        assert!(matches!(items.as_slice(), [Err(Error::FeatureUnavailable)]));
+        assert_eq!(connects, 1);
+    }
+
+    #[tokio::test]
+    async fn cancel() {
+        let cancel = CancelHandle::new();
+        let connects = Arc::new(Mutex::new(0));
+        let connects_ = connects.clone();
+
+        let mut stream = Box::pin(
+            Reconnect::new(5)
+                .with_delay(Duration::from_secs(60))
+                .with_cancel(cancel.clone())
+                .supervise(move |_| {
+                    *connects_.lock().unwrap() += 1;
+                    future::ready(Ok(stream::iter(vec![Ok(Event(0))])))
+                }),
+        );
+        assert_eq!(stream.next().await.unwrap().unwrap(), Event(0));
+
+        // Cancelling interrupts the wait before reconnecting
+        let (item, _) = future::join(stream.next(), async { cancel.cancel() }).await;
+        assert!(item.is_none());
+        assert_eq!(*connects.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn stall() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_ = events.clone();
+
+        let stream = Reconnect::never()
+            .with_stall_timeout(Duration::from_millis(5))
+            .with_observer(move |event| events_.lock().unwrap().push(format!("{:?}", event)))
+            .supervise(|_| {
+                future::ready(Ok(
+                    stream::once(future::ready(Ok(b"frame".to_vec()))).chain(stream::pending())
+                ))
+            });
+
+        let items: Vec<Result<Vec<u8>>> = stream.collect().await;
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].as_ref().unwrap(), b"frame");
+        assert!(matches!(items[1], Err(Error::Other("stream stalled"))));
+        assert_eq!(
+            events.lock().unwrap().as_slice(),
+            &[
+                "Started".to_string(),
+                "Received { bytes: 5, total_bytes: 5 }".to_string(),
+                "Stalled { idle: 5ms }".to_string(),
+                "Ended".to_string(),
+            ]
+        );
+    }
+}
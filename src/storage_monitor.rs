@@ -0,0 +1,393 @@
+use crate::v4::disk_management::{DiskInfo, DiskManagement};
+use crate::v4::events::{EventNotification, STORAGE_ALERT_TOPIC};
+use crate::{Client, Error, Fleet, Reconnect, Result, Transport};
+use futures::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// Watches the storage of every device in a `Fleet`, reporting disks which are running out of
+/// space or SD cards which are wearing out.
+///
+/// Free space is polled from each device's disk list, while SD card wear is reported by the
+/// device's `STORAGE_ALERT_TOPIC` events. Notifications are sent when a threshold is crossed in
+/// either direction, so each problem is reported once when it begins and once when it clears,
+/// which suits paging integrations. Problems which exist when monitoring starts are reported
+/// immediately.
+///
+/// `StorageMonitor` does not depend on any particular async runtime.
+#[derive(Debug, Clone)]
+pub struct StorageMonitor {
+    thresholds: StorageThresholds,
+    interval: Duration,
+    reconnect: Reconnect,
+}
+
+/// The levels at which a `StorageMonitor` raises notifications.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageThresholds {
+    /// Notify when a disk's free space falls below this percentage of its size.
+    pub min_free_percent: u8,
+    /// Notify when an SD card's wear reaches this percentage of its rated lifetime.
+    pub max_wear_percent: u8,
+}
+
+impl Default for StorageThresholds {
+    fn default() -> Self {
+        Self {
+            min_free_percent: 10,
+            max_wear_percent: 90,
+        }
+    }
+}
+
+/// A threshold crossing reported by a `StorageMonitor`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageNotification {
+    /// The disk concerned, as in `DiskInfo::disk_id`.
+    pub disk_id: String,
+    /// What happened.
+    pub alert: StorageAlert,
+}
+
+/// A change in the condition of a disk.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageAlert {
+    /// The disk's free space fell below `StorageThresholds::min_free_percent`.
+    #[serde(rename_all = "camelCase")]
+    LowFreeSpace {
+        /// The free space of the disk in bytes.
+        free_size: u64,
+        /// The formatted size of the disk in bytes.
+        total_size: u64,
+    },
+    /// The disk's free space, previously low, rose back above the threshold.
+    #[serde(rename_all = "camelCase")]
+    FreeSpaceRecovered {
+        /// The free space of the disk in bytes.
+        free_size: u64,
+        /// The formatted size of the disk in bytes.
+        total_size: u64,
+    },
+    /// The SD card's wear reached `StorageThresholds::max_wear_percent`.
+    HighWear {
+        /// The card's wear, in percent of its rated lifetime.
+        wear: u8,
+    },
+    /// The SD card's wear, previously high, is now below the threshold, as when the card is
+    /// replaced.
+    WearRecovered {
+        /// The card's wear, in percent of its rated lifetime.
+        wear: u8,
+    },
+}
+
+impl StorageMonitor {
+    /// Create a monitor which raises notifications at `thresholds`, checking free space every
+    /// five minutes.
+    pub fn new(thresholds: StorageThresholds) -> Self {
+        Self {
+            thresholds,
+            interval: Duration::from_secs(300),
+            reconnect: Reconnect::default(),
+        }
+    }
+
+    /// Set how often each device's free space is checked.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the policy for resubscribing to each device's SD card health events.
+    pub fn with_reconnect(mut self, reconnect: Reconnect) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Watch every device in `fleet`, yielding each notification or error along with the serial
+    /// number of the device concerned.
+    ///
+    /// A device which fails to list its disks is tried again after the usual interval. Devices
+    /// without an event service are monitored for free space only.
+    pub fn watch<'a, T: Transport>(
+        &self,
+        fleet: &'a Fleet<T>,
+    ) -> impl Stream<Item = (String, Result<StorageNotification>)> + 'a {
+        let mut streams: Vec<Pin<Box<dyn Stream<Item = Observation> + 'a>>> = Vec::new();
+        for (serial_number, client) in fleet.iter() {
+            streams.push(Box::pin(poll_disks(serial_number, client, self.interval)));
+            streams.push(Box::pin(watch_wear(
+                serial_number,
+                client,
+                self.reconnect.clone(),
+            )));
+        }
+
+        let mut tracker = Tracker::new(self.thresholds);
+        stream::select_all(streams)
+            .flat_map(move |observation| stream::iter(tracker.observe(observation)))
+    }
+}
+
+/// Something learned about a device's storage.
+enum Observation {
+    Disks(String, Result<Vec<DiskInfo>>),
+    Health(String, Result<EventNotification>),
+}
+
+fn poll_disks<'a, T: Transport>(
+    serial_number: &'a str,
+    client: &'a Client<T>,
+    interval: Duration,
+) -> impl Stream<Item = Observation> + 'a {
+    stream::unfold(true, move |first| async move {
+        if !first {
+            futures_timer::Delay::new(interval).await;
+        }
+        let disks = DiskManagement::new(client, "1.0".to_string()).list().await;
+        Some((Observation::Disks(serial_number.to_string(), disks), false))
+    })
+}
+
+fn watch_wear<'a, T: Transport>(
+    serial_number: &'a str,
+    client: &'a Client<T>,
+    reconnect: Reconnect,
+) -> impl Stream<Item = Observation> + 'a {
+    client
+        .events()
+        .supervise(vec![STORAGE_ALERT_TOPIC.to_string()], reconnect)
+        .filter(|notification| {
+            future::ready(!matches!(
+                notification.as_ref().map_err(Error::without_context),
+                Err(Error::FeatureUnavailable)
+            ))
+        })
+        .map(move |notification| Observation::Health(serial_number.to_string(), notification))
+}
+
+/// The thresholds each disk has crossed, by serial number and disk ID.
+struct Tracker {
+    thresholds: StorageThresholds,
+    low_free_space: BTreeSet<(String, String)>,
+    high_wear: BTreeSet<(String, String)>,
+}
+
+impl Tracker {
+    fn new(thresholds: StorageThresholds) -> Self {
+        Self {
+            thresholds,
+            low_free_space: BTreeSet::new(),
+            high_wear: BTreeSet::new(),
+        }
+    }
+
+    fn observe(&mut self, observation: Observation) -> Vec<(String, Result<StorageNotification>)> {
+        match observation {
+            Observation::Disks(serial_number, Ok(disks)) => disks
+                .iter()
+                .filter_map(|disk| self.disk(&serial_number, disk))
+                .map(|notification| (serial_number.clone(), Ok(notification)))
+                .collect(),
+            Observation::Health(serial_number, Ok(event)) => self
+                .health(&serial_number, &event)
+                .map(|notification| (serial_number, Ok(notification)))
+                .into_iter()
+                .collect(),
+            Observation::Disks(serial_number, Err(e))
+            | Observation::Health(serial_number, Err(e)) => vec![(serial_number, Err(e))],
+        }
+    }
+
+    fn disk(&mut self, serial_number: &str, disk: &DiskInfo) -> Option<StorageNotification> {
+        // Disks which aren't mounted report no size
+        if disk.total_size == 0 {
+            return None;
+        }
+
+        let key = (serial_number.to_string(), disk.disk_id.clone());
+        let low = disk.free_size.saturating_mul(100)
+            < disk
+                .total_size
+                .saturating_mul(self.thresholds.min_free_percent as u64);
+        let (free_size, total_size) = (disk.free_size, disk.total_size);
+        let alert = if low && self.low_free_space.insert(key.clone()) {
+            StorageAlert::LowFreeSpace {
+                free_size,
+                total_size,
+            }
+        } else if !low && self.low_free_space.remove(&key) {
+            StorageAlert::FreeSpaceRecovered {
+                free_size,
+                total_size,
+            }
+        } else {
+            return None;
+        };
+
+        Some(StorageNotification {
+            disk_id: key.1,
+            alert,
+        })
+    }
+
+    fn health(
+        &mut self,
+        serial_number: &str,
+        event: &EventNotification,
+    ) -> Option<StorageNotification> {
+        if event.topic != STORAGE_ALERT_TOPIC {
+            return None;
+        }
+        let disk_id = event.source.get("disk_id")?;
+        let wear: u8 = event.data.get("wear")?.parse().ok()?;
+
+        let key = (serial_number.to_string(), disk_id.clone());
+        let high = wear >= self.thresholds.max_wear_percent;
+        let alert = if high && self.high_wear.insert(key.clone()) {
+            StorageAlert::HighWear { wear }
+        } else if !high && self.high_wear.remove(&key) {
+            StorageAlert::WearRecovered { wear }
+        } else {
+            return None;
+        };
+
+        Some(StorageNotification {
+            disk_id: key.1,
+            alert,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn disk(free_size: u64) -> DiskInfo {
+        DiskInfo {
+            disk_id: "SD_DISK".into(),
+            name: "".into(),
+            total_size: 1000,
+            free_size,
+            cleanup_level: 90,
+            cleanup_max_age: 7,
+            cleanup_policy: crate::v4::disk_management::CleanupPolicy::FIFO,
+            locked: false,
+            full: false,
+            readonly: false,
+            status: "OK".into(),
+            filesystem: crate::v4::disk_management::Filesystem::EXT4,
+            group: "S0".into(),
+            required_filesystem: crate::v4::disk_management::Filesystem::None,
+            encryption_enabled: false,
+            disk_encrypted: false,
+        }
+    }
+
+    fn health(wear: u8) -> EventNotification {
+        let mut source = BTreeMap::new();
+        source.insert("disk_id".to_string(), "SD_DISK".to_string());
+        let mut data = BTreeMap::new();
+        data.insert("wear".to_string(), wear.to_string());
+        EventNotification {
+            topic: STORAGE_ALERT_TOPIC.to_string(),
+            utc_time: None,
+            property_operation: None,
+            source,
+            data,
+        }
+    }
+
+    fn alerts(tracker: &mut Tracker, observation: Observation) -> Vec<StorageAlert> {
+        tracker
+            .observe(observation)
+            .into_iter()
+            .map(|(serial_number, notification)| {
+                assert_eq!(serial_number, "ACCC8E000001");
+                notification.unwrap().alert
+            })
+            .collect()
+    }
+
+    #[test]
+    fn crossings() {
+        let serial_number = || "ACCC8E000001".to_string();
+        let mut tracker = Tracker::new(StorageThresholds::default());
+
+        let disks = |free_size| Observation::Disks(serial_number(), Ok(vec![disk(free_size)]));
+        assert_eq!(alerts(&mut tracker, disks(500)), vec![]);
+        assert_eq!(
+            alerts(&mut tracker, disks(99)),
+            vec![StorageAlert::LowFreeSpace {
+                free_size: 99,
+                total_size: 1000
+            }]
+        );
+        assert_eq!(alerts(&mut tracker, disks(50)), vec![]);
+        assert_eq!(
+            alerts(&mut tracker, disks(100)),
+            vec![StorageAlert::FreeSpaceRecovered {
+                free_size: 100,
+                total_size: 1000
+            }]
+        );
+
+        let wear = |wear| Observation::Health(serial_number(), Ok(health(wear)));
+        assert_eq!(
+            alerts(&mut tracker, wear(95)),
+            vec![StorageAlert::HighWear { wear: 95 }]
+        );
+        assert_eq!(alerts(&mut tracker, wear(96)), vec![]);
+        assert_eq!(
+            alerts(&mut tracker, wear(0)),
+            vec![StorageAlert::WearRecovered { wear: 0 }]
+        );
+    }
+
+    #[tokio::test]
+    async fn watch() {
+        let mut fleet = Fleet::new();
+        fleet.insert(
+            "ACCC8E000001",
+            crate::mock_client(|req| match req.uri().path() {
+                "/axis-cgi/disks/list.cgi" => http::Response::builder()
+                    .status(http::StatusCode::OK)
+                    .header(http::header::CONTENT_TYPE, "text/xml")
+                    .body(vec![br#"<?xml version="1.0"?>
+<root>
+  <disks numberofdisks="1">
+    <disk diskid="SD_DISK" name="" totalsize="1000" freesize="20" cleanuplevel="90" cleanupmaxage="7" cleanuppolicy="fifo" locked="no" full="no" readonly="no" status="OK" filesystem="ext4" group="S0" requiredfilesystem="none" encryptionenabled="false" diskencrypted="false"/>
+  </disks>
+</root>"#
+                        .to_vec()]),
+                // No event service
+                _ => http::Response::builder()
+                    .status(http::StatusCode::NOT_FOUND)
+                    .body(vec![]),
+            }),
+        );
+
+        let monitor = StorageMonitor::new(StorageThresholds::default())
+            .with_interval(Duration::from_millis(1));
+        let notifications: Vec<(String, Result<StorageNotification>)> =
+            monitor.watch(&fleet).take(1).collect().await;
+        let (serial_number, notification) = &notifications[0];
+        assert_eq!(serial_number, "ACCC8E000001");
+        assert_eq!(
+            notification.as_ref().unwrap(),
+            &StorageNotification {
+                disk_id: "SD_DISK".into(),
+                alert: StorageAlert::LowFreeSpace {
+                    free_size: 20,
+                    total_size: 1000,
+                },
+            }
+        );
+    }
+}
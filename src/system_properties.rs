@@ -0,0 +1,124 @@
+use crate::v4::basic_device_info::Properties;
+use crate::{Client, Error, Result, Transport};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Identifying properties of a device's hardware and firmware.
+///
+/// These are available on every device through the `Properties` parameter group, and on newer
+/// devices through the basic device info API as well.
+#[derive(Debug, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+pub struct SystemProperties {
+    /// The device's serial number, which is also its MAC address.
+    pub serial_number: String,
+    /// The architecture of the system-on-chip inside the device, e.g. `"armv7hf"`.
+    pub architecture: Option<String>,
+    /// The name of the system-on-chip inside the device, e.g. `"Axis Artpec-6"`.
+    pub soc: Option<String>,
+    /// The hardware ID, believed to be an internal AXIS part identifier.
+    pub hardware_id: Option<String>,
+    /// The firmware's build date, expressed as a string.
+    pub firmware_build_date: Option<String>,
+}
+
+impl SystemProperties {
+    /// Build `SystemProperties` from the contents of the `Properties` parameter group, keyed
+    /// relative to `root.Properties`, e.g. `System.SerialNumber`.
+    pub(crate) fn from_params(params: &BTreeMap<&str, &str>) -> Result<Self> {
+        let get = |key: &str| {
+            params
+                .get(key)
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_string())
+        };
+
+        Ok(Self {
+            serial_number: get("System.SerialNumber")
+                .ok_or(Error::Other("serial number unavailable"))?,
+            architecture: get("System.Architecture"),
+            soc: get("System.Soc"),
+            hardware_id: get("System.HardwareID"),
+            firmware_build_date: get("Firmware.BuildDate"),
+        })
+    }
+}
+
+impl From<&Properties> for SystemProperties {
+    fn from(properties: &Properties) -> Self {
+        let non_empty = |value: &String| Some(value.clone()).filter(|value| !value.is_empty());
+
+        Self {
+            serial_number: properties.serial_number.clone(),
+            architecture: non_empty(&properties.soc_architecture),
+            soc: non_empty(&properties.soc),
+            hardware_id: non_empty(&properties.hardware_id),
+            firmware_build_date: non_empty(&properties.firmware_build_date),
+        }
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Retrieve the device's `SystemProperties` from the `Properties` parameter group.
+    ///
+    /// Every device supports this. Devices offering the basic device info API provide the same
+    /// information via `BasicDeviceInfo::properties()`, which converts into `SystemProperties`.
+    pub async fn system_properties(&self) -> Result<SystemProperties> {
+        let params = self
+            .parameters()
+            .list(Some(
+                &["root.Properties.System", "root.Properties.Firmware"][..],
+            ))
+            .await?;
+        let params: BTreeMap<&str, &str> = params
+            .iter()
+            .map(|(k, v)| (k.trim_start_matches("root.Properties."), v.as_str()))
+            .collect();
+
+        SystemProperties::from_params(&params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn from_parameters() {
+        let device = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().unwrap().as_str(),
+                "/axis-cgi/param.cgi?action=list&group=root.Properties.System%2Croot.Properties.Firmware"
+            );
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.Properties.System.Architecture=armv7hf
+root.Properties.System.HardwareID=714.4
+root.Properties.System.Language=English
+root.Properties.System.SerialNumber=ACCC8E000000
+root.Properties.System.Soc=Axis Artpec-6
+root.Properties.Firmware.BuildDate=Feb 26 2019 16:36
+root.Properties.Firmware.Version=9.80.1
+"
+                .to_vec()])
+        });
+
+        assert_eq!(
+            device.system_properties().await.unwrap(),
+            SystemProperties {
+                serial_number: "ACCC8E000000".into(),
+                architecture: Some("armv7hf".into()),
+                soc: Some("Axis Artpec-6".into()),
+                hardware_id: Some("714.4".into()),
+                firmware_build_date: Some("Feb 26 2019 16:36".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn missing_serial_number() {
+        let params: BTreeMap<&str, &str> =
+            vec![("System.Soc", "Axis Artpec-6")].into_iter().collect();
+        assert!(SystemProperties::from_params(&params).is_err());
+    }
+}
@@ -2,7 +2,7 @@ mod device_info;
 pub use device_info::DeviceInfo;
 
 mod mock_client;
-pub use mock_client::mock_client;
+pub use mock_client::{mock_boxed_client_for_uri, mock_client, mock_client_for_uri};
 
 pub mod recorded_device;
 pub use recorded_device::{
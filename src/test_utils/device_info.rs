@@ -1,39 +1,30 @@
 use crate::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DeviceInfo {
     pub model: String,
-    pub serial_number: String,
     pub firmware_version: String,
-    pub firmware_build_date: Option<String>,
-    pub architecture: Option<String>,
-    pub soc: Option<String>,
-    pub hardware_id: Option<String>,
-}
-
-impl Default for DeviceInfo {
-    fn default() -> Self {
-        Self {
-            model: "".to_string(),
-            serial_number: "".to_string(),
-            firmware_version: "".to_string(),
-            firmware_build_date: None,
-            architecture: None,
-            soc: None,
-            hardware_id: None,
-        }
-    }
+    #[serde(flatten)]
+    pub system: SystemProperties,
 }
 
 impl DeviceInfo {
     pub async fn retrieve<T: Transport>(device: &Client<T>) -> Result<Self, Error> {
-        let definitions = device
+        let definitions = match device
             .parameters()
             .list_definitions(Some(
                 &["root.Properties.Firmware", "root.Properties.System"][..],
             ))
-            .await?;
+            .await
+        {
+            Ok(definitions) => definitions,
+            Err(e) if crate::v3::parameters::definitions_unsupported(&e) => {
+                return Self::retrieve_values(device).await
+            }
+            Err(e) => return Err(e),
+        };
 
         let crate::v3::parameters::ParameterDefinitions {
             model,
@@ -42,16 +33,6 @@ impl DeviceInfo {
             ..
         } = definitions;
 
-        let mut metadata = Self {
-            model: model.expect("model"),
-            serial_number: "".into(),
-            firmware_version: firmware_version.expect("firmware version"),
-            firmware_build_date: None,
-            architecture: None,
-            soc: None,
-            hardware_id: None,
-        };
-
         let root = groups
             .iter()
             .find(|g| g.name == "root")
@@ -59,30 +40,55 @@ impl DeviceInfo {
 
         let properties = root.group("Properties").expect("Properties group");
 
-        let firmware = properties.group("Firmware").expect("Firmware group");
-        for param in &firmware.parameters {
-            if let "BuildDate" = param.name.as_str() {
-                metadata.firmware_build_date = param.current_value.clone()
-            }
-        }
-
-        let system = properties.group("System").expect("System group");
-        for param in &system.parameters {
-            match param.name.as_str() {
-                "Architecture" => metadata.architecture = param.current_value.clone(),
-                "Soc" => metadata.soc = param.current_value.clone(),
-                "HardwareID" => metadata.hardware_id = param.current_value.clone(),
-                "SerialNumber" => {
-                    metadata.serial_number = param.current_value.clone().expect("serial number")
+        let mut params = BTreeMap::new();
+        for group_name in &["Firmware", "System"] {
+            let group = properties.group(group_name).expect("Properties subgroup");
+            for param in &group.parameters {
+                if let Some(value) = &param.current_value {
+                    params.insert(format!("{}.{}", group_name, param.name), value.as_str());
                 }
-                _ => (),
             }
         }
+        let params = params.iter().map(|(k, v)| (k.as_str(), *v)).collect();
 
-        if metadata.serial_number == "" {
-            return Err(Error::Other("serial number unavailable"));
-        }
+        Ok(Self {
+            model: model.expect("model"),
+            firmware_version: firmware_version.expect("firmware version"),
+            system: SystemProperties::from_params(&params)?,
+        })
+    }
+
+    /// Retrieve the same information for devices which answer `list()` but not
+    /// `list_definitions()`, taking the model from `root.Brand.ProdNbr`.
+    async fn retrieve_values<T: Transport>(device: &Client<T>) -> Result<Self, Error> {
+        let values = device
+            .parameters()
+            .list(Some(
+                &[
+                    "root.Brand",
+                    "root.Properties.Firmware",
+                    "root.Properties.System",
+                ][..],
+            ))
+            .await?;
+        let params: BTreeMap<&str, &str> = values
+            .iter()
+            .map(|(k, v)| {
+                (
+                    k.trim_start_matches("root.")
+                        .trim_start_matches("Properties."),
+                    v.as_str(),
+                )
+            })
+            .collect();
 
-        Ok(metadata)
+        Ok(Self {
+            model: params.get("Brand.ProdNbr").expect("model").to_string(),
+            firmware_version: params
+                .get("Firmware.Version")
+                .expect("firmware version")
+                .to_string(),
+            system: SystemProperties::from_params(&params)?,
+        })
     }
 }
@@ -10,10 +10,33 @@ where
     B: IntoIterator<Item = Vec<u8>>,
     B::IntoIter: Unpin,
     E: std::error::Error + Send + 'static,
+{
+    mock_client_for_uri(http::Uri::from_static("http://1.2.3.4"), f)
+}
+
+pub fn mock_client_for_uri<F, B, E>(uri: http::Uri, f: F) -> crate::Client<impl crate::Transport>
+where
+    F: FnMut(http::Request<Vec<u8>>) -> Result<http::Response<B>, E>,
+    B: IntoIterator<Item = Vec<u8>>,
+    B::IntoIter: Unpin,
+    E: std::error::Error + Send + 'static,
+{
+    let t = TransportAdapter(Mutex::new(f));
+    crate::Client::try_new(t, uri).unwrap()
+}
+
+pub fn mock_boxed_client_for_uri<F, B, E>(
+    uri: http::Uri,
+    f: F,
+) -> crate::Client<crate::BoxTransport>
+where
+    F: FnMut(http::Request<Vec<u8>>) -> Result<http::Response<B>, E> + Send + 'static,
+    B: IntoIterator<Item = Vec<u8>>,
+    B::IntoIter: Unpin + Send + 'static,
+    E: std::error::Error + Send + 'static,
 {
     let t = TransportAdapter(Mutex::new(f));
-    let uri = http::Uri::from_static("http://1.2.3.4");
-    crate::Client::new(t, uri)
+    crate::Client::try_new(t, uri).unwrap().boxed()
 }
 
 struct TransportAdapter<F>(Mutex<F>);
@@ -21,7 +21,7 @@ pub async fn new_eavesdrop_device(
     ),
     Error,
 > {
-    let device = Client::new(HyperTransport::default(), uri.clone());
+    let device = Client::try_new(HyperTransport::default(), uri.clone())?;
     let device_info = DeviceInfo::retrieve(&device).await;
     let device_info = device_info?;
 
@@ -42,15 +42,27 @@ pub async fn new_eavesdrop_device(
 
 pub struct EavesdropRecorder {
     recording: Mutex<Option<Recording>>,
+    previous: HashMap<RecordedHttpRequest, RecordedHttpResponse>,
 }
 
 impl EavesdropRecorder {
+    /// Start recording a device.
+    ///
+    /// Transactions from an existing fixture for this device are kept unless a test repeats them,
+    /// so running a subset of the test suite adds to a fixture rather than truncating it.
     fn new(device_info: DeviceInfo) -> Self {
+        let previous = std::fs::read(fixture_filename(&device_info))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Recording>(&bytes).ok())
+            .map(|recording| recording.transactions)
+            .unwrap_or_default();
+
         Self {
             recording: Mutex::new(Some(Recording {
                 device_info,
                 transactions: HashMap::new(),
             })),
+            previous,
         }
     }
 
@@ -79,9 +91,21 @@ impl EavesdropRecorder {
         let lock = self.recording.lock().unwrap();
         let recording = lock.as_ref().unwrap();
 
+        let mut transactions = self.previous.clone();
+        transactions.extend(
+            recording
+                .transactions
+                .iter()
+                .map(|(req, resp)| (req.clone(), resp.clone())),
+        );
+        let recording = Recording {
+            device_info: recording.device_info.clone(),
+            transactions,
+        };
+
         let f = std::fs::File::create(fixture_filename(&recording.device_info)).expect("open file");
 
-        serde_json::to_writer_pretty(&f, recording).expect("write fixture");
+        serde_json::to_writer_pretty(&f, &recording).expect("write fixture");
 
         f.sync_all().expect("write fixture");
     }
@@ -97,7 +97,7 @@ pub fn fixture_dir() -> std::path::PathBuf {
 pub fn fixture_filename(device_info: &DeviceInfo) -> std::path::PathBuf {
     let filename = PathBuf::from(format!(
         "{} v{}.json",
-        &device_info.serial_number, &device_info.firmware_version,
+        &device_info.system.serial_number, &device_info.firmware_version,
     ));
     fixture_dir().join(&filename)
 }
@@ -0,0 +1,98 @@
+//! Conversions between the `chrono` types used throughout this crate and the `time` crate.
+//!
+//! Enabled by the `time` feature. `chrono` types can't implement `From` for `time` types or vice
+//! versa from within this crate, so conversions are provided as functions:
+//!
+//! ```ignore
+//! let started = vapix::time_interop::offset_date_time(&recording.start_time)?;
+//! ```
+//!
+//! `time` supports years -9999 through 9999, while `chrono` supports a much wider range, so
+//! conversions to `time` fail with `time::error::ComponentRange` for dates outside it.
+
+use chrono::prelude::*;
+use time::error::ComponentRange;
+
+/// Convert a `chrono` timestamp with an offset to a `time::OffsetDateTime`, preserving the
+/// offset.
+pub fn offset_date_time(
+    dt: &DateTime<FixedOffset>,
+) -> Result<time::OffsetDateTime, ComponentRange> {
+    let offset = time::UtcOffset::seconds(dt.offset().local_minus_utc());
+    Ok(primitive_date_time(&dt.naive_local())?.assume_offset(offset))
+}
+
+/// Convert a `time::OffsetDateTime` to a `chrono` timestamp, preserving the offset.
+pub fn chrono_date_time(dt: time::OffsetDateTime) -> DateTime<FixedOffset> {
+    FixedOffset::east(dt.offset().as_seconds()).timestamp(dt.timestamp(), dt.nanosecond())
+}
+
+/// Convert a `chrono` timestamp without an offset to a `time::PrimitiveDateTime`.
+///
+/// `chrono` represents leap seconds as a nanosecond value past one second; these are clamped to
+/// the end of the preceding second.
+pub fn primitive_date_time(dt: &NaiveDateTime) -> Result<time::PrimitiveDateTime, ComponentRange> {
+    let date = time::Date::try_from_ymd(dt.year(), dt.month() as u8, dt.day() as u8)?;
+    let time = time::Time::try_from_hms_nano(
+        dt.hour() as u8,
+        dt.minute() as u8,
+        dt.second() as u8,
+        dt.nanosecond().min(999_999_999),
+    )?;
+    Ok(time::PrimitiveDateTime::new(date, time))
+}
+
+impl crate::v3::system_log::Timestamp {
+    /// Convert this timestamp to a `time::OffsetDateTime`, if it includes an offset and falls
+    /// within the range `time` supports.
+    pub fn to_offset_date_time(&self) -> Option<time::OffsetDateTime> {
+        match self {
+            crate::v3::system_log::Timestamp::Naive(_) => None,
+            crate::v3::system_log::Timestamp::FixedOffset(dt) => offset_date_time(dt).ok(),
+        }
+    }
+
+    /// Convert this timestamp to a `time::PrimitiveDateTime` in the device's local time,
+    /// discarding any offset.
+    pub fn to_primitive_date_time(&self) -> Result<time::PrimitiveDateTime, ComponentRange> {
+        match self {
+            crate::v3::system_log::Timestamp::Naive(dt) => primitive_date_time(dt),
+            crate::v3::system_log::Timestamp::FixedOffset(dt) => {
+                primitive_date_time(&dt.naive_local())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let dt = DateTime::parse_from_rfc3339("2020-10-09T17:30:02.123456789+02:00").unwrap();
+        let converted = offset_date_time(&dt).unwrap();
+        assert_eq!(converted.offset().as_seconds(), 7200);
+        assert_eq!(converted.hour(), 17);
+        assert_eq!(converted.nanosecond(), 123_456_789);
+        assert_eq!(chrono_date_time(converted), dt);
+
+        let naive = dt.naive_local();
+        let converted = primitive_date_time(&naive).unwrap();
+        assert_eq!(
+            (converted.year(), converted.month(), converted.day()),
+            (2020, 10, 9)
+        );
+        assert_eq!(converted.nanosecond(), 123_456_789);
+    }
+
+    #[test]
+    fn out_of_range() {
+        let naive = NaiveDate::from_ymd(10000, 1, 1).and_hms(0, 0, 0);
+        assert!(primitive_date_time(&naive).is_err());
+        assert!(offset_date_time(&FixedOffset::east(0).from_utc_datetime(&naive)).is_err());
+
+        let naive = NaiveDate::from_ymd(9999, 12, 31).and_hms(23, 59, 59);
+        assert_eq!(primitive_date_time(&naive).unwrap().year(), 9999);
+    }
+}
@@ -1,6 +1,9 @@
-use futures::Stream;
+use futures::future::BoxFuture;
+use futures::{FutureExt, Stream, StreamExt, TryFutureExt, TryStreamExt};
 use std::fmt;
 use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
 /// An HTTP transport.
 pub trait Transport {
@@ -15,6 +18,268 @@ pub trait Transport {
 
     /// Perform an HTTP roundtrip.
     fn roundtrip(&self, request: http::Request<Vec<u8>>) -> Self::Output;
+
+    /// Perform an HTTP roundtrip, sending the request body as it is produced.
+    ///
+    /// Transports which can stream request bodies should implement this, so that large uploads
+    /// like firmware images aren't held in memory. The default implementation declines by
+    /// returning the request, in which case `Client` collects the body and calls `roundtrip()`.
+    fn roundtrip_streaming_request(
+        &self,
+        request: http::Request<RequestBody>,
+    ) -> Result<Self::Output, Box<http::Request<RequestBody>>> {
+        Err(Box::new(request))
+    }
+}
+
+/// A request body, which is either held in memory or produced as a stream of chunks.
+///
+/// Streamed bodies let large uploads, like firmware images and application packages, be read
+/// from disk as they are sent. A streamed body can be sent only once, so requests carrying one
+/// are never retried.
+pub struct RequestBody(RequestBodyInner);
+
+type ChunkStream = Pin<Box<dyn Stream<Item = std::io::Result<Vec<u8>>> + Send>>;
+
+enum RequestBodyInner {
+    Buffered(Option<Vec<u8>>),
+    Streamed {
+        stream: ChunkStream,
+        content_length: Option<u64>,
+    },
+}
+
+impl RequestBody {
+    /// Create a body from a stream of chunks.
+    ///
+    /// `content_length` should be the total size of the chunks, if known in advance. Devices
+    /// generally require it for uploads.
+    pub fn from_stream<S>(stream: S, content_length: Option<u64>) -> Self
+    where
+        S: Stream<Item = std::io::Result<Vec<u8>>> + Send + 'static,
+    {
+        RequestBody(RequestBodyInner::Streamed {
+            stream: Box::pin(stream),
+            content_length,
+        })
+    }
+
+    /// Create a body which streams the remaining contents of `file`.
+    #[cfg(feature = "tokio")]
+    pub async fn from_file(file: tokio::fs::File) -> std::io::Result<Self> {
+        use tokio::io::AsyncReadExt;
+
+        let content_length = file.metadata().await?.len();
+        let chunks = futures::stream::try_unfold(file, |mut file| async move {
+            let mut chunk = vec![0; 64 * 1024];
+            let len = file.read(&mut chunk).await?;
+            if len == 0 {
+                return Ok(None);
+            }
+            chunk.truncate(len);
+            Ok(Some((chunk, file)))
+        });
+        Ok(Self::from_stream(chunks, Some(content_length)))
+    }
+
+    /// The size of the body in bytes, if known.
+    pub fn content_length(&self) -> Option<u64> {
+        match &self.0 {
+            RequestBodyInner::Buffered(bytes) => {
+                Some(bytes.as_ref().map(|b| b.len() as u64).unwrap_or(0))
+            }
+            RequestBodyInner::Streamed { content_length, .. } => *content_length,
+        }
+    }
+
+    /// Is this body produced as a stream, rather than held in memory?
+    pub fn is_streamed(&self) -> bool {
+        match self.0 {
+            RequestBodyInner::Buffered(_) => false,
+            RequestBodyInner::Streamed { .. } => true,
+        }
+    }
+
+    /// Surround this body with `prefix` and `suffix`, as when framing it in a multipart request.
+    pub(crate) fn framed(self, prefix: Vec<u8>, suffix: Vec<u8>) -> Self {
+        match self.0 {
+            RequestBodyInner::Buffered(bytes) => {
+                let mut framed = prefix;
+                framed.extend_from_slice(&bytes.unwrap_or_default());
+                framed.extend_from_slice(&suffix);
+                framed.into()
+            }
+            RequestBodyInner::Streamed {
+                stream,
+                content_length,
+            } => {
+                let framing = (prefix.len() + suffix.len()) as u64;
+                let stream = futures::stream::once(async { Ok(prefix) })
+                    .chain(stream)
+                    .chain(futures::stream::once(async { Ok(suffix) }));
+                Self::from_stream(stream, content_length.map(|len| len + framing))
+            }
+        }
+    }
+
+    /// Collect the whole body into memory.
+    pub async fn into_bytes(self) -> std::io::Result<Vec<u8>> {
+        match self.0 {
+            RequestBodyInner::Buffered(bytes) => Ok(bytes.unwrap_or_default()),
+            RequestBodyInner::Streamed { stream, .. } => {
+                stream
+                    .try_fold(Vec::new(), |mut bytes, chunk| async move {
+                        bytes.extend_from_slice(&chunk);
+                        Ok(bytes)
+                    })
+                    .await
+            }
+        }
+    }
+}
+
+impl From<Vec<u8>> for RequestBody {
+    fn from(bytes: Vec<u8>) -> Self {
+        RequestBody(RequestBodyInner::Buffered(Some(bytes)))
+    }
+}
+
+impl From<&[u8]> for RequestBody {
+    fn from(bytes: &[u8]) -> Self {
+        bytes.to_vec().into()
+    }
+}
+
+impl fmt::Debug for RequestBody {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RequestBody")
+            .field("streamed", &self.is_streamed())
+            .field("content_length", &self.content_length())
+            .finish()
+    }
+}
+
+impl Stream for RequestBody {
+    type Item = std::io::Result<Vec<u8>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match &mut self.0 {
+            RequestBodyInner::Buffered(bytes) => Poll::Ready(bytes.take().map(Ok)),
+            RequestBodyInner::Streamed { stream, .. } => stream.as_mut().poll_next(cx),
+        }
+    }
+}
+
+/// A type-erased `Transport`.
+///
+/// `Client<BoxTransport>` can wrap any transport, so clients using different transports can be
+/// stored together without carrying the transport type through every signature. Use
+/// `Client::boxed()` to convert a client.
+pub struct BoxTransport(Box<dyn ErasedTransport>);
+
+impl BoxTransport {
+    /// Erase the type of a `Transport`.
+    pub fn new<T>(transport: T) -> Self
+    where
+        T: Transport + Send + Sync + 'static,
+        T::Output: Send + 'static,
+        T::Body: Send + 'static,
+        T::Chunk: Send + 'static,
+    {
+        BoxTransport(Box::new(transport))
+    }
+}
+
+impl fmt::Debug for BoxTransport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("BoxTransport")
+    }
+}
+
+impl Transport for BoxTransport {
+    type Output = BoxFuture<'static, Result<http::Response<BoxBody>, Error>>;
+    type Body = BoxBody;
+    type Chunk = BoxChunk;
+
+    fn roundtrip(&self, request: http::Request<Vec<u8>>) -> Self::Output {
+        self.0.roundtrip_boxed(request)
+    }
+
+    fn roundtrip_streaming_request(
+        &self,
+        request: http::Request<RequestBody>,
+    ) -> Result<Self::Output, Box<http::Request<RequestBody>>> {
+        self.0.roundtrip_streaming_request_boxed(request)
+    }
+}
+
+type BoxOutput = BoxFuture<'static, Result<http::Response<BoxBody>, Error>>;
+
+trait ErasedTransport: Send + Sync {
+    fn roundtrip_boxed(&self, request: http::Request<Vec<u8>>) -> BoxOutput;
+
+    fn roundtrip_streaming_request_boxed(
+        &self,
+        request: http::Request<RequestBody>,
+    ) -> Result<BoxOutput, Box<http::Request<RequestBody>>>;
+}
+
+impl<T> ErasedTransport for T
+where
+    T: Transport + Send + Sync,
+    T::Output: Send + 'static,
+    T::Body: Send + 'static,
+    T::Chunk: Send + 'static,
+{
+    fn roundtrip_boxed(&self, request: http::Request<Vec<u8>>) -> BoxOutput {
+        box_output::<T>(self.roundtrip(request))
+    }
+
+    fn roundtrip_streaming_request_boxed(
+        &self,
+        request: http::Request<RequestBody>,
+    ) -> Result<BoxOutput, Box<http::Request<RequestBody>>> {
+        self.roundtrip_streaming_request(request)
+            .map(box_output::<T>)
+    }
+}
+
+fn box_output<T>(output: T::Output) -> BoxOutput
+where
+    T: Transport,
+    T::Output: Send + 'static,
+    T::Body: Send + 'static,
+    T::Chunk: Send + 'static,
+{
+    output
+        .map_ok(|response| {
+            response.map(|body| {
+                BoxBody(Box::pin(
+                    body.map(|chunk| chunk.map(|c| BoxChunk(Box::new(c)))),
+                ))
+            })
+        })
+        .boxed()
+}
+
+/// The response body of a `BoxTransport`.
+pub struct BoxBody(Pin<Box<dyn Stream<Item = Result<BoxChunk, Error>> + Send>>);
+
+impl Stream for BoxBody {
+    type Item = Result<BoxChunk, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.as_mut().poll_next(cx)
+    }
+}
+
+/// A chunk of a `BoxBody`.
+pub struct BoxChunk(Box<dyn AsRef<[u8]> + Send>);
+
+impl AsRef<[u8]> for BoxChunk {
+    fn as_ref(&self) -> &[u8] {
+        (*self.0).as_ref()
+    }
 }
 
 /// An error returned by a `vapix::Transport`.
@@ -50,3 +315,39 @@ impl fmt::Debug for Error {
         self.0.fmt(f)
     }
 }
+
+/// A TLS failure, like a failed handshake or an untrusted certificate.
+///
+/// TLS implementations are pluggable, so their error types are unknown to this crate. A
+/// `Transport` (or a `HyperTransport` connector) should wrap its TLS library's errors in a
+/// `TlsError` so that `vapix::Error::connection_error_kind()` reports them as
+/// `ConnectionErrorKind::TlsError`.
+pub struct TlsError(Box<dyn std::error::Error + Send + Sync + 'static>);
+
+impl TlsError {
+    /// Create a `TlsError` from the TLS library's error.
+    pub fn new<E: std::error::Error + Send + Sync + 'static>(error: E) -> Self {
+        TlsError(Box::new(error))
+    }
+
+    /// Consume the `TlsError`, returning the TLS library's error.
+    pub fn into_inner(self) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self.0
+    }
+}
+
+impl std::error::Error for TlsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&*self.0)
+    }
+}
+impl fmt::Display for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TLS error: {}", self.0)
+    }
+}
+impl fmt::Debug for TlsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("TlsError").field(&self.0).finish()
+    }
+}
@@ -19,10 +19,42 @@
 // However, the HTML+JS in more recent firmware clearly encodes UTF-8. We do the same.
 
 pub mod application;
+pub mod audio_clips;
+pub mod audio_detection;
+pub mod date_time;
+pub mod day_night;
+pub mod image_buffer;
+pub mod io_ports;
+pub mod mjpeg;
+pub mod multicast;
 pub mod parameters;
+pub mod privacy;
+pub mod ptz;
 pub mod recordings;
+pub mod remote_service;
+pub mod scheduled_events;
+pub mod shock_detection;
+pub mod snapshot;
 pub mod system_log;
+pub mod virtual_input;
+pub mod wireless;
 
 pub use application::Applications;
+pub use audio_clips::AudioClips;
+pub use audio_detection::AudioDetection;
+pub use date_time::DateTime;
+pub use day_night::DayNight;
+pub use image_buffer::ImageBuffer;
+pub use io_ports::IoPorts;
+pub use mjpeg::{JpegFrame, Mjpeg, MjpegOptions};
+pub use multicast::Multicast;
 pub use parameters::Parameters;
+pub use privacy::Privacy;
+pub use ptz::Ptz;
+pub use remote_service::RemoteService;
+pub use scheduled_events::ScheduledEvents;
+pub use shock_detection::ShockDetection;
+pub use snapshot::{Snapshot, SnapshotOptions};
 pub use system_log::SystemLog;
+pub use virtual_input::VirtualInputs;
+pub use wireless::Wireless;
@@ -1,6 +1,7 @@
 //! The VAPIX application interface at `/axis-cgi/applications/*`.
 
 use crate::*;
+use serde::{Deserialize, Serialize};
 
 mod enums;
 pub use enums::*;
@@ -68,22 +69,97 @@ impl<'a, T: Transport> Applications<'a, T> {
     }
 
     /// Upload an application package to the device.
-    pub async fn upload(&self, application_package_data: &[u8]) -> Result<()> {
+    ///
+    /// The package may be in memory, or streamed from disk with `RequestBody::from_file()`.
+    /// Dropping the future abandons the upload if the package has not yet been sent in full, in
+    /// which case the device discards it.
+    pub async fn upload<B: Into<RequestBody>>(&self, application_package: B) -> Result<()> {
+        self.device.check_writable()?;
+        let request_body = application_package.into().framed(
+            b"--fileboundary\r\n\
+            Content-Disposition: form-data; name=\"packfil\"; filename=\"application.eap\"\r\n\
+            Content-Type: application/octet-stream\r\n\
+            \r\n"
+                .to_vec(),
+            b"\r\n--fileboundary--\r\n\r\n".to_vec(),
+        );
+
+        let req = http::Request::builder()
+            .method(http::Method::POST)
+            .uri(self.device.uri_for("/axis-cgi/applications/upload.cgi")?)
+            .header(
+                http::header::CONTENT_TYPE,
+                "multipart/form-data; boundary=fileboundary",
+            )
+            .body(request_body)?;
+
+        let (_resp, resp_body) = self
+            .device
+            .roundtrip_upload(req, "text/plain")
+            .await
+            .requiring(v3::parameters::AccessLevel::AdministratorAccess)?;
+
+        let resp_body =
+            std::str::from_utf8(resp_body.as_slice()).map_err(|_| Error::Other("invalid UTF-8"))?;
+
+        if resp_body.starts_with("OK") {
+            Ok(())
+        } else {
+            // TODO: smuggle out the error value
+            Err(Error::Other("application upload failed"))
+        }
+    }
+
+    /// List the applications installed on the device.
+    pub async fn list(&self) -> Result<Vec<ApplicationInfo>> {
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.device.uri_for("/axis-cgi/applications/list.cgi")?)
+            .body(Vec::new())?;
+
+        let (_resp, resp_body) = self.device.roundtrip(req, "text/xml").await?;
+
+        #[derive(Deserialize)]
+        struct Reply {
+            result: String,
+            #[serde(rename = "application", default)]
+            applications: Vec<ApplicationInfo>,
+        }
+        let reply: Reply = crate::xml::from_slice(&resp_body)?;
+        if reply.result != "ok" {
+            return Err(Error::Other("application list failed"));
+        }
+        Ok(reply.applications)
+    }
+
+    /// Install a license key for an installed application, as named by `ApplicationInfo::name`.
+    ///
+    /// `license_key` is the XML license key file issued by AXIS for this device.
+    pub async fn install_license(&self, name: &str, license_key: &[u8]) -> Result<()> {
+        self.device.check_writable()?;
+        #[derive(Serialize)]
+        struct Req<'a> {
+            action: &'static str,
+            package: &'a str,
+        }
+
         let mut request_body = b"--fileboundary\r\n\
-        Content-Disposition: form-data; name=\"packfil\"; filename=\"application.eap\"\r\n\
-        Content-Type: application/octet-stream\r\n\
+        Content-Disposition: form-data; name=\"fileName\"; filename=\"license.xml\"\r\n\
+        Content-Type: text/xml\r\n\
         \r\n"
             .to_vec();
-        request_body.extend_from_slice(application_package_data);
+        request_body.extend_from_slice(license_key);
         request_body.extend_from_slice(b"\r\n--fileboundary--\r\n\r\n");
 
         let req = http::Request::builder()
             .method(http::Method::POST)
-            .uri(
-                self.device
-                    .uri_for("/axis-cgi/applications/upload.cgi")
-                    .unwrap(),
-            )
+            .uri(self.device.uri_for_args(
+                "/axis-cgi/applications/license.cgi",
+                Req {
+                    action: "uploadlicensekey",
+                    package: name,
+                },
+            )?)
             .header(
                 http::header::CONTENT_TYPE,
                 "multipart/form-data; boundary=fileboundary",
@@ -92,26 +168,246 @@ impl<'a, T: Transport> Applications<'a, T> {
                 http::header::CONTENT_LENGTH,
                 format!("{}", request_body.len()),
             )
-            .body(request_body)
-            .unwrap();
+            .body(request_body)?;
 
-        let (_resp, resp_body) = self.device.roundtrip(req, "text/plain").await?;
+        let (_resp, resp_body) = self
+            .device
+            .roundtrip(req, "text/plain")
+            .await
+            .requiring(v3::parameters::AccessLevel::AdministratorAccess)?;
 
-        let resp_body =
-            std::str::from_utf8(resp_body.as_slice()).map_err(|_| Error::Other("invalid UTF-8"))?;
+        // Failures are reported in plain text, like `Error: 30`
+        if !resp_body.starts_with(b"OK") {
+            return Err(Error::Other("license installation failed"));
+        }
+        self.device
+            .audit(|| format!("installed a license for application {:?}", name))
+            .await;
+        Ok(())
+    }
 
-        if resp_body.starts_with("OK") {
-            Ok(())
-        } else {
-            // TODO: smuggle out the error value
-            Err(Error::Other("application upload failed"))
+    /// Retrieve the state of an installed application's license, as named by
+    /// `ApplicationInfo::name`.
+    ///
+    /// Returns `Error::Other` if no such application is installed.
+    pub async fn license_status(&self, name: &str) -> Result<LicenseStatus> {
+        self.list()
+            .await?
+            .into_iter()
+            .find(|application| application.name == name)
+            .map(|application| application.license.unwrap_or(LicenseStatus::NotRequired))
+            .ok_or(Error::Other("no such application"))
+    }
+
+    /// Start, stop, or restart an installed application, as named by `ApplicationInfo::name`.
+    pub async fn control(&self, name: &str, action: ApplicationAction) -> Result<()> {
+        let action = match action {
+            ApplicationAction::Start => "start",
+            ApplicationAction::Stop => "stop",
+            ApplicationAction::Restart => "restart",
+        };
+        self.control_cgi(name, action).await
+    }
+
+    /// Remove an installed application, as named by `ApplicationInfo::name`, stopping it first
+    /// if it is running.
+    pub async fn remove(&self, name: &str) -> Result<()> {
+        self.control_cgi(name, "remove").await
+    }
+
+    async fn control_cgi(&self, name: &str, action: &'static str) -> Result<()> {
+        self.device.check_writable()?;
+        #[derive(Serialize)]
+        struct Req<'a> {
+            action: &'static str,
+            package: &'a str,
         }
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.device.uri_for_args(
+                "/axis-cgi/applications/control.cgi",
+                Req {
+                    action,
+                    package: name,
+                },
+            )?)
+            .body(Vec::new())?;
+
+        let (_resp, resp_body) = self
+            .device
+            .roundtrip(req, "text/plain")
+            .await
+            .requiring(v3::parameters::AccessLevel::AdministratorAccess)?;
+
+        // Failures are reported in plain text, like `Error: 6`
+        if !resp_body.starts_with(b"OK") {
+            return Err(Error::Other("application control failed"));
+        }
+        self.device
+            .audit(|| format!("application {:?}: {}", name, action))
+            .await;
+        Ok(())
     }
 }
 
+/// An application installed on the device.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ApplicationInfo {
+    /// The application's package name, as passed to `Applications::control()`.
+    #[serde(rename = "Name")]
+    pub name: String,
+    /// The name of the application to display to the user.
+    #[serde(rename = "NiceName")]
+    pub nice_name: String,
+    /// The application's vendor.
+    #[serde(rename = "Vendor")]
+    pub vendor: String,
+    /// The application's version, like `4.2-5`.
+    #[serde(rename = "Version")]
+    pub version: String,
+    /// The application's ID in the AXIS license system, if it has one.
+    #[serde(rename = "ApplicationID", default)]
+    pub application_id: Option<String>,
+    /// The state of the application's license, if reported.
+    #[serde(rename = "License", default)]
+    pub license: Option<LicenseStatus>,
+    /// Whether the application is running.
+    #[serde(rename = "Status")]
+    pub status: ApplicationStatus,
+    /// The path of the application's configuration page on the device, if it has one.
+    #[serde(rename = "ConfigurationPage", default)]
+    pub configuration_page: Option<String>,
+}
+
+/// Whether an application is running.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ApplicationStatus {
+    /// The application is running.
+    Running,
+    /// The application is stopped.
+    Stopped,
+    /// The application is running, but waiting for something, like a valid license.
+    Idle,
+    /// A status this crate does not recognize.
+    #[serde(other)]
+    Unknown,
+}
+
+/// The state of an application's license.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum LicenseStatus {
+    /// A valid license key is installed.
+    Valid,
+    /// The installed license key is not valid for this device or application.
+    Invalid,
+    /// The application requires a license key, but none is installed.
+    Missing,
+    /// The application manages its own licensing.
+    Custom,
+    /// The application does not require a license key.
+    #[serde(rename = "None")]
+    NotRequired,
+    /// A state this crate does not recognize.
+    #[serde(other)]
+    Unknown,
+}
+
+/// An action to take on an installed application.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ApplicationAction {
+    /// Start the application.
+    Start,
+    /// Stop the application.
+    Stop,
+    /// Stop and start the application again.
+    Restart,
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::v3::application::{Architecture, SOC};
+    use crate::v3::application::{
+        ApplicationAction, ApplicationStatus, Applications, Architecture, LicenseStatus, SOC,
+    };
+    use crate::Error;
+
+    #[tokio::test]
+    async fn lifecycle() {
+        let client = crate::mock_client(|req| {
+            let (content_type, body): (_, &[u8]) =
+                match req.uri().path_and_query().unwrap().as_str() {
+                    "/axis-cgi/applications/list.cgi" => (
+                        "text/xml",
+                        br#"<reply result="ok">
+ <application Name="vmd" NiceName="AXIS Video Motion Detection" Vendor="Axis Communications" Version="4.2-5" ApplicationID="143440" License="None" Status="Running" ConfigurationPage="local/vmd/config.html" VendorHomePage="http://www.axis.com" />
+ <application Name="counter" NiceName="People Counter" Vendor="Example" Version="1.0" Status="Stopped" />
+</reply>"#,
+                    ),
+                    "/axis-cgi/applications/license.cgi?action=uploadlicensekey&package=counter" => {
+                        let body = String::from_utf8_lossy(req.body());
+                        assert!(body.contains("filename=\"license.xml\""));
+                        assert!(body.contains("\r\n\r\n<LicenseKey/>\r\n--fileboundary--"));
+                        ("text/plain", b"OK\n")
+                    }
+                    "/axis-cgi/applications/control.cgi?action=restart&package=vmd" => {
+                        ("text/plain", b"OK\n")
+                    }
+                    "/axis-cgi/applications/control.cgi?action=remove&package=counter" => {
+                        ("text/plain", b"Error: 6\n")
+                    }
+                    other => panic!("unexpected request {:?}", other),
+                };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, content_type)
+                .body(vec![body.to_vec()])
+        });
+        let applications = Applications {
+            device: &client,
+            _embedded_development_version: "2.13".into(),
+            firmware_version: None,
+            soc: None,
+            architecture: None,
+        };
+
+        let list = applications.list().await.unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].name, "vmd");
+        assert_eq!(list[0].nice_name, "AXIS Video Motion Detection");
+        assert_eq!(list[0].application_id.as_deref(), Some("143440"));
+        assert_eq!(list[0].status, ApplicationStatus::Running);
+        assert_eq!(list[1].status, ApplicationStatus::Stopped);
+        assert_eq!(list[1].configuration_page, None);
+        assert_eq!(list[0].license, Some(LicenseStatus::NotRequired));
+
+        applications
+            .install_license("counter", b"<LicenseKey/>")
+            .await
+            .unwrap();
+        assert_eq!(
+            applications.license_status("counter").await.unwrap(),
+            LicenseStatus::NotRequired
+        );
+        assert!(applications.license_status("nonexistent").await.is_err());
+
+        applications
+            .control("vmd", ApplicationAction::Restart)
+            .await
+            .unwrap();
+        assert!(applications.remove("counter").await.is_err());
+
+        let client = client.with_read_only(true);
+        let applications = Applications {
+            device: &client,
+            _embedded_development_version: "2.13".into(),
+            firmware_version: None,
+            soc: None,
+            architecture: None,
+        };
+        match applications.control("vmd", ApplicationAction::Stop).await {
+            Err(Error::ReadOnlyMode) => {}
+            other => panic!("expected ReadOnlyMode, got {:?}", other),
+        }
+    }
 
     #[test]
     fn new() {
@@ -0,0 +1,376 @@
+//! The VAPIX media clip interface for audio-capable devices like network speakers.
+//!
+//! Clips are stored on the device and described by the `MediaClip` parameter group. They are
+//! uploaded via `/axis-cgi/uploadmedia.cgi`, and played and removed via `/axis-cgi/mediaclip.cgi`.
+//! Older firmware plays clips via `/axis-cgi/playclip.cgi` and `/axis-cgi/stopclip.cgi` instead,
+//! which are used when `mediaclip.cgi` can't play clips.
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// A device's audio clip interface.
+pub struct AudioClips<'a, T: Transport>(&'a Client<T>);
+
+/// An audio clip stored on the device.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioClip {
+    /// The clip's index, as used in the `MediaClip.M{id}` parameter group.
+    pub id: u32,
+    /// The display name of the clip.
+    pub name: String,
+    /// The path at which the clip is stored on the device, if reported.
+    pub location: Option<String>,
+    /// The type of media, typically `"audio"`.
+    pub media_type: Option<String>,
+}
+
+/// Options controlling audio clip playback.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize)]
+pub struct PlayOptions {
+    /// The playback volume as a percentage, from 0 to 100.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume: Option<u8>,
+    /// The number of times to repeat the clip after playing it once.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<u32>,
+    /// The audio output on which to play the clip, for devices with more than one.
+    #[serde(rename = "audiooutput", skip_serializing_if = "Option::is_none")]
+    pub audio_output: Option<u32>,
+}
+
+impl<'a, T: Transport> AudioClips<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// List the audio clips stored on the device.
+    pub async fn list(&self) -> Result<Vec<AudioClip>> {
+        let params = self.0.parameters().list(Some(&["root.MediaClip"])).await?;
+        Ok(clips_from_params(&params))
+    }
+
+    /// Upload an audio clip to the device.
+    ///
+    /// `name` is the display name of the clip. `content_type` describes `clip_data`, e.g.
+    /// `"audio/basic"` or `"audio/x-wav"`.
+    ///
+    /// Dropping the future abandons the upload if the clip has not yet been sent in full, in which
+    /// case the device discards it.
+    pub async fn upload(&self, name: &str, content_type: &str, clip_data: &[u8]) -> Result<()> {
+        #[derive(Serialize)]
+        struct Req<'a> {
+            media_type: &'a str,
+            media_name: &'a str,
+        }
+
+        let mut request_body = format!(
+            "--fileboundary\r\n\
+            Content-Disposition: form-data; name=\"media_clip\"; filename=\"clip\"\r\n\
+            Content-Type: {}\r\n\
+            \r\n",
+            content_type
+        )
+        .into_bytes();
+        request_body.extend_from_slice(clip_data);
+        request_body.extend_from_slice(b"\r\n--fileboundary--\r\n\r\n");
+
+        let req = http::Request::builder()
+            .method(http::Method::POST)
+            .uri(self.0.uri_for_args(
+                "/axis-cgi/uploadmedia.cgi",
+                Req {
+                    media_type: "audio",
+                    media_name: name,
+                },
+            )?)
+            .header(
+                http::header::CONTENT_TYPE,
+                "multipart/form-data; boundary=fileboundary",
+            )
+            .header(
+                http::header::CONTENT_LENGTH,
+                format!("{}", request_body.len()),
+            )
+            .body(request_body)?;
+
+        self.call(req, "audio clip upload failed").await?;
+        self.0
+            .audit(|| format!("uploaded audio clip {}", name))
+            .await;
+        Ok(())
+    }
+
+    /// Remove an audio clip from the device.
+    pub async fn remove(&self, id: u32) -> Result<()> {
+        #[derive(Serialize)]
+        struct Req {
+            action: &'static str,
+            clip: u32,
+        }
+
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for_args(
+                "/axis-cgi/mediaclip.cgi",
+                Req {
+                    action: "remove",
+                    clip: id,
+                },
+            )?)
+            .body(Vec::new())?;
+
+        self.call(req, "audio clip removal failed").await?;
+        self.0.audit(|| format!("removed audio clip {}", id)).await;
+        Ok(())
+    }
+
+    /// Play an audio clip, replacing any clip which is currently playing.
+    pub async fn play(&self, id: u32, options: PlayOptions) -> Result<()> {
+        #[derive(Serialize)]
+        struct Req {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            action: Option<&'static str>,
+            clip: u32,
+            #[serde(flatten)]
+            options: PlayOptions,
+        }
+
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for_args(
+                "/axis-cgi/mediaclip.cgi",
+                Req {
+                    action: Some("play"),
+                    clip: id,
+                    options,
+                },
+            )?)
+            .body(Vec::new())?;
+        match self.call(req, "audio clip playback failed").await {
+            Err(Error::FeatureUnavailable) => {}
+            result => return result,
+        }
+
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for_args(
+                "/axis-cgi/playclip.cgi",
+                Req {
+                    action: None,
+                    clip: id,
+                    options,
+                },
+            )?)
+            .body(Vec::new())?;
+
+        self.call(req, "audio clip playback failed").await
+    }
+
+    /// Stop any audio clip which is currently playing.
+    pub async fn stop(&self) -> Result<()> {
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for("/axis-cgi/mediaclip.cgi?action=stop")?)
+            .body(Vec::new())?;
+        match self.call(req, "audio clip stop failed").await {
+            Err(Error::FeatureUnavailable) => {}
+            result => return result,
+        }
+
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for("/axis-cgi/stopclip.cgi")?)
+            .body(Vec::new())?;
+
+        self.call(req, "audio clip stop failed").await
+    }
+
+    async fn call(&self, req: http::Request<Vec<u8>>, failure: &'static str) -> Result<()> {
+        self.0.check_writable()?;
+        let (_resp, resp_body) = self
+            .0
+            .roundtrip(req, "text/plain")
+            .await
+            .map_404_to_feature_unavailable()?;
+
+        if resp_body.as_slice().starts_with(b"OK") {
+            Ok(())
+        } else {
+            Err(Error::Other(failure))
+        }
+    }
+}
+
+fn clips_from_params(params: &BTreeMap<String, String>) -> Vec<AudioClip> {
+    let mut clips: BTreeMap<u32, AudioClip> = BTreeMap::new();
+
+    for (key, value) in params {
+        // root.MediaClip.M0.Name=…
+        let mut parts = key.trim_start_matches("root.").splitn(3, '.');
+        let (group, id, name) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(group), Some(id), Some(name)) => (group, id, name),
+            _ => continue,
+        };
+        let id = match (group, id.strip_prefix('M').map(u32::from_str)) {
+            ("MediaClip", Some(Ok(id))) => id,
+            _ => continue,
+        };
+
+        let clip = clips.entry(id).or_insert_with(|| AudioClip {
+            id,
+            name: String::new(),
+            location: None,
+            media_type: None,
+        });
+        match name {
+            "Name" => clip.name = value.clone(),
+            "Location" => clip.location = Some(value.clone()),
+            "Type" => clip.media_type = Some(value.clone()),
+            _ => {}
+        }
+    }
+
+    clips.into_iter().map(|(_, clip)| clip).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn list() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?action=list&group=root.MediaClip")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![
+                    b"root.MediaClip.M0.Location=/etc/audioclips/camera_clicks16k.au\n\
+root.MediaClip.M0.Name=Camera clicks\n\
+root.MediaClip.M0.Type=audio\n\
+root.MediaClip.M6.Location=/etc/audioclips/pir_alarm16k.au\n\
+root.MediaClip.M6.Name=Alarm\n\
+root.MediaClip.M6.Type=audio\n"
+                        .to_vec(),
+                ])
+        });
+
+        let clips = client.audio_clips().list().await.unwrap();
+        assert_eq!(
+            clips,
+            vec![
+                AudioClip {
+                    id: 0,
+                    name: "Camera clicks".into(),
+                    location: Some("/etc/audioclips/camera_clicks16k.au".into()),
+                    media_type: Some("audio".into()),
+                },
+                AudioClip {
+                    id: 6,
+                    name: "Alarm".into(),
+                    location: Some("/etc/audioclips/pir_alarm16k.au".into()),
+                    media_type: Some("audio".into()),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn play() {
+        let client = crate::mock_client(|req| {
+            // Older firmware can only play clips through playclip.cgi
+            if req.uri().path() == "/axis-cgi/mediaclip.cgi" {
+                return http::Response::builder()
+                    .status(http::StatusCode::NOT_FOUND)
+                    .body(vec![]);
+            }
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/playclip.cgi?clip=6&volume=80&repeat=2")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"OK".to_vec()])
+        });
+
+        client
+            .audio_clips()
+            .play(
+                6,
+                PlayOptions {
+                    volume: Some(80),
+                    repeat: Some(2),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn mediaclip() {
+        let client = crate::mock_client(|req| {
+            let body: &[u8] = match req.uri().path_and_query().unwrap().as_str() {
+                "/axis-cgi/mediaclip.cgi?action=play&clip=0&volume=50&repeat=1&audiooutput=1" => {
+                    b"OK"
+                }
+                "/axis-cgi/mediaclip.cgi?action=stop" => b"OK",
+                other => panic!("unexpected request {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![body.to_vec()])
+        });
+
+        let clips = client.audio_clips();
+        clips
+            .play(
+                0,
+                PlayOptions {
+                    volume: Some(50),
+                    repeat: Some(1),
+                    audio_output: Some(1),
+                },
+            )
+            .await
+            .unwrap();
+        clips.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn upload() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(req.method(), http::Method::POST);
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/uploadmedia.cgi?media_type=audio&media_name=Door+chime")
+            );
+            let body = String::from_utf8_lossy(req.body());
+            assert!(body.contains("Content-Type: audio/basic\r\n\r\nchime\r\n--fileboundary--"));
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"# Error: unsupported file format".to_vec()])
+        });
+
+        match client
+            .audio_clips()
+            .upload("Door chime", "audio/basic", b"chime")
+            .await
+        {
+            Err(Error::Other(_)) => {}
+            other => panic!("upload should fail, got {:?}", other),
+        }
+    }
+}
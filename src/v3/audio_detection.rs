@@ -0,0 +1,156 @@
+//! Audio detection, as exposed by the VAPIX parameters interface.
+//!
+//! Devices with audio inputs can raise an event when the input level crosses a threshold, like a
+//! loud noise near a camera. Thresholds live in the `AudioSource.A{index}` parameter groups. The
+//! event itself is published on `AUDIO_DETECTION_TOPIC`, where action rules and event
+//! subscribers can react to it, e.g. by starting a recording.
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The event topic on which devices report audio detection, with a `channel` source and a
+/// boolean `triggered` data item.
+pub const AUDIO_DETECTION_TOPIC: &str = "tns1:AudioSource/tnsaxis:TriggerLevel";
+
+/// A device's audio detection controls.
+pub struct AudioDetection<'a, T: Transport>(&'a Client<T>);
+
+/// The audio detection configuration of an audio source.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioDetectionSource {
+    /// The index of the source's `AudioSource.A{index}` parameter group.
+    pub index: u32,
+    /// The name of the source.
+    pub name: String,
+    /// The input level which triggers detection, from 0 (the quietest) to 100 (the loudest).
+    pub alarm_level: u8,
+}
+
+impl<'a, T: Transport> AudioDetection<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// List the device's audio sources and their detection levels.
+    ///
+    /// Devices without audio inputs have no sources.
+    pub async fn list(&self) -> Result<Vec<AudioDetectionSource>> {
+        let params = self
+            .0
+            .parameters()
+            .list(Some(&["root.AudioSource"][..]))
+            .await
+            .map_404_to_feature_unavailable()?;
+        let params: BTreeMap<&str, &str> = params
+            .iter()
+            .map(|(k, v)| (k.trim_start_matches("root."), v.as_str()))
+            .collect();
+
+        Ok(sources_from_params(&params))
+    }
+
+    /// Change the input level at which an audio source triggers detection, from 0 to 100.
+    pub async fn set_alarm_level(&self, index: u32, alarm_level: u8) -> Result<()> {
+        if alarm_level > 100 {
+            return Err(Error::Other("alarm level must be between 0 and 100"));
+        }
+
+        self.0
+            .parameters()
+            .update(vec![(
+                format!("AudioSource.A{}.AlarmLevel", index),
+                alarm_level.to_string(),
+            )])
+            .await
+    }
+}
+
+fn sources_from_params(params: &BTreeMap<&str, &str>) -> Vec<AudioDetectionSource> {
+    let indices: BTreeSet<u32> = params
+        .keys()
+        .filter_map(|k| k.strip_prefix("AudioSource.A"))
+        .filter_map(|k| k.splitn(2, '.').next())
+        .filter_map(|index| index.parse().ok())
+        .collect();
+
+    indices
+        .into_iter()
+        .filter_map(|index| {
+            let get = |name: &str| {
+                params
+                    .get(format!("AudioSource.A{}.{}", index, name).as_str())
+                    .copied()
+            };
+
+            Some(AudioDetectionSource {
+                index,
+                name: get("Name").unwrap_or("").to_string(),
+                alarm_level: get("AlarmLevel")?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn list() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?action=list&group=root.AudioSource")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.AudioSource.NbrOfSources=2\n\
+root.AudioSource.A0.AlarmLevel=100\n\
+root.AudioSource.A0.AudioSupport=yes\n\
+root.AudioSource.A0.InputType=mic\n\
+root.AudioSource.A0.Name=Audio\n\
+root.AudioSource.A1.AudioSupport=yes\n\
+root.AudioSource.A1.Name=Line in\n"
+                    .to_vec()])
+        });
+
+        assert_eq!(
+            client.audio_detection().list().await.unwrap(),
+            vec![AudioDetectionSource {
+                index: 0,
+                name: "Audio".into(),
+                alarm_level: 100,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn set_alarm_level() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?AudioSource.A0.AlarmLevel=60&action=update")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"OK".to_vec()])
+        });
+
+        client
+            .audio_detection()
+            .set_alarm_level(0, 60)
+            .await
+            .unwrap();
+        assert!(client
+            .audio_detection()
+            .set_alarm_level(0, 101)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! The legacy date and time interface at `/axis-cgi/date.cgi`.
+//!
+//! Devices which predate the v4 time API report and set their clock through `date.cgi`, always in
+//! local time, and keep their time zone in the `Time` parameter group. `Client::time_info()`
+//! combines the two into the same `TimeInfo` the v4 API provides.
+
+use crate::v4::time_service::{PosixTimeZone, TimeInfo};
+use crate::*;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A device's legacy date and time interface.
+pub struct DateTime<'a, T: Transport>(&'a Client<T>);
+
+impl<'a, T: Transport> DateTime<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// Read the device's clock, expressed in its local time zone.
+    pub async fn now(&self) -> Result<NaiveDateTime> {
+        let body = self.call(Req::Get).await?;
+        let body = std::str::from_utf8(&body).map_err(|_| Error::Other("invalid UTF-8"))?;
+        NaiveDateTime::parse_from_str(body.trim(), "%b %d, %Y %H:%M:%S")
+            .map_err(|_| Error::Other("unparseable date"))
+    }
+
+    /// Set the device's clock, expressed in its local time zone.
+    ///
+    /// Devices which synchronize their clock with NTP may promptly set it again.
+    pub async fn set(&self, local: &NaiveDateTime) -> Result<()> {
+        self.0.check_writable()?;
+        use chrono::{Datelike, Timelike};
+
+        let body = self
+            .call(Req::Set {
+                year: local.year(),
+                month: local.month(),
+                day: local.day(),
+                hour: local.hour(),
+                minute: local.minute(),
+                second: local.second(),
+            })
+            .await?;
+        if String::from_utf8_lossy(&body).trim() != "OK" {
+            return Err(Error::Other("date.cgi did not confirm the new time"));
+        }
+
+        self.0.audit(|| format!("set the clock to {}", local)).await;
+        Ok(())
+    }
+
+    /// Retrieve the device's time zone rules from the `Time` parameter group.
+    ///
+    /// Daylight saving time rules are discarded if the device has DST disabled.
+    pub async fn time_zone(&self) -> Result<PosixTimeZone> {
+        let (tz, dst_enabled) = self.time_params().await?;
+        Self::parse_time_zone(&tz, dst_enabled)
+    }
+
+    /// Change the device's time zone, given as a POSIX `TZ` string like
+    /// `"CET-1CEST,M3.5.0,M10.5.0/3"`.
+    ///
+    /// Daylight saving time is enabled if and only if the string includes a DST rule.
+    pub async fn set_time_zone(&self, posix_time_zone: &str) -> Result<()> {
+        let tz: PosixTimeZone = posix_time_zone
+            .parse()
+            .map_err(|_| Error::Other("unparseable POSIX time zone"))?;
+
+        self.0
+            .parameters()
+            .update(vec![
+                ("root.Time.POSIXTimeZone", posix_time_zone),
+                (
+                    "root.Time.DST.Enabled",
+                    if tz.dst_offset().is_some() {
+                        "yes"
+                    } else {
+                        "no"
+                    },
+                ),
+            ])
+            .await
+    }
+
+    /// Retrieve the device's clock and time zone settings.
+    ///
+    /// The device's clock is read in local time and converted to UTC using its time zone rules,
+    /// which is ambiguous during the hour repeated at the end of daylight saving time.
+    pub async fn info(&self) -> Result<TimeInfo> {
+        let (posix_time_zone, dst_enabled) = self.time_params().await?;
+        let tz = Self::parse_time_zone(&posix_time_zone, dst_enabled)?;
+        let local = self.now().await?;
+
+        Ok(TimeInfo {
+            date_time: tz.to_utc(&local),
+            local_date_time: Some(tz.from_local(&local)),
+            time_zone: None,
+            posix_time_zone,
+            dst_enabled,
+            max_supported_year: None,
+            extra: Default::default(),
+        })
+    }
+
+    async fn time_params(&self) -> Result<(String, bool)> {
+        let params = self.0.parameters().list(Some(&["root.Time"][..])).await?;
+        let params: BTreeMap<&str, &str> = params
+            .iter()
+            .map(|(k, v)| (k.trim_start_matches("root."), v.as_str()))
+            .collect();
+
+        let tz = params
+            .get("Time.POSIXTimeZone")
+            .ok_or(Error::Other("device did not report a POSIX time zone"))?;
+        let dst_enabled = params.get("Time.DST.Enabled") == Some(&"yes");
+        Ok((tz.to_string(), dst_enabled))
+    }
+
+    fn parse_time_zone(posix_time_zone: &str, dst_enabled: bool) -> Result<PosixTimeZone> {
+        posix_time_zone
+            .parse::<PosixTimeZone>()
+            .map(|tz| tz.with_dst_enabled(dst_enabled))
+            .map_err(|_| Error::Other("device reported an unparseable POSIX time zone"))
+    }
+
+    async fn call(&self, req: Req) -> Result<Vec<u8>> {
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for_args("/axis-cgi/date.cgi", req)?)
+            .body(Vec::new())?;
+
+        let (_resp, body) = self
+            .0
+            .roundtrip(req, "text/plain")
+            .await
+            .map_404_to_feature_unavailable()?;
+        Ok(body)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+enum Req {
+    Get,
+    Set {
+        year: i32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        second: u32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    #[tokio::test]
+    async fn legacy_time() {
+        let client = crate::mock_client(|req| {
+            if req.uri().path() == "/axis-cgi/time.cgi" {
+                return http::Response::builder()
+                    .status(http::StatusCode::NOT_FOUND)
+                    .body(vec![]);
+            }
+            let body: &[u8] = match req.uri().path_and_query().unwrap().as_str() {
+                "/axis-cgi/date.cgi?action=get" => b"Jul 04, 2021 13:30:00\r\n",
+                "/axis-cgi/date.cgi?action=set&year=2021&month=12&day=24&hour=18&minute=5&second=9" => {
+                    b"OK"
+                }
+                "/axis-cgi/param.cgi?action=list&group=root.Time" => {
+                    b"root.Time.DST.Enabled=yes\n\
+root.Time.NTP.Server=0.0.0.0\n\
+root.Time.POSIXTimeZone=CST6CDT,M3.2.0,M11.1.0\n\
+root.Time.SyncSource=NTP\n"
+                }
+                "/axis-cgi/param.cgi?action=update&root.Time.DST.Enabled=no&root.Time.POSIXTimeZone=UTC0" => {
+                    b"OK"
+                }
+                other => panic!("unexpected request {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![body.to_vec()])
+        });
+        let date_time = client.date_time();
+
+        let info = date_time.info().await.unwrap();
+        assert_eq!(info.date_time, Utc.ymd(2021, 7, 4).and_hms(18, 30, 0));
+        assert_eq!(
+            info.local_date_time.unwrap().naive_local(),
+            NaiveDate::from_ymd(2021, 7, 4).and_hms(13, 30, 0)
+        );
+        assert_eq!(info.posix_time_zone, "CST6CDT,M3.2.0,M11.1.0");
+        assert!(info.dst_enabled);
+        assert_eq!(client.time_info().await.unwrap(), info);
+
+        date_time
+            .set(&NaiveDate::from_ymd(2021, 12, 24).and_hms(18, 5, 9))
+            .await
+            .unwrap();
+        date_time.set_time_zone("UTC0").await.unwrap();
+        assert!(date_time.set_time_zone("not a zone").await.is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! Day/night switching, as exposed by the VAPIX parameters interface.
+//!
+//! Day/night cameras have an infrared-cut filter which is moved in front of the sensor during the
+//! day, producing accurate colors, and removed at night, letting the sensor use infrared light at
+//! the cost of a monochrome image. Settings live in the `ImageSource.I{index}.DayNight` parameter
+//! groups. Devices without an IR-cut filter omit these parameters.
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// A device's day/night controls.
+pub struct DayNight<'a, T: Transport>(&'a Client<T>);
+
+/// The day/night configuration of a video channel.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayNightConfiguration {
+    /// How the IR-cut filter is controlled.
+    pub mode: DayNightMode,
+    /// The light level at which an automatic switch occurs, from 0 (switch at the darkest) to 100
+    /// (switch at the brightest). `None` if the device does not report it.
+    pub shift_level: Option<u8>,
+}
+
+/// How the IR-cut filter is controlled.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DayNightMode {
+    /// Switch automatically based on the light level.
+    Auto,
+    /// Always day mode: the IR-cut filter is on, producing a color image.
+    Day,
+    /// Always night mode: the IR-cut filter is off, producing a monochrome image.
+    Night,
+}
+
+impl DayNightMode {
+    /// Is the IR-cut filter fixed in place by this mode? `None` for `DayNightMode::Auto`, where
+    /// the device decides.
+    pub fn ir_cut_filter(self) -> Option<bool> {
+        match self {
+            DayNightMode::Auto => None,
+            DayNightMode::Day => Some(true),
+            DayNightMode::Night => Some(false),
+        }
+    }
+
+    fn as_param(self) -> &'static str {
+        match self {
+            DayNightMode::Auto => "auto",
+            DayNightMode::Day => "yes",
+            DayNightMode::Night => "no",
+        }
+    }
+
+    fn from_param(value: &str) -> Option<Self> {
+        match value {
+            "auto" => Some(DayNightMode::Auto),
+            "yes" => Some(DayNightMode::Day),
+            "no" => Some(DayNightMode::Night),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, T: Transport> DayNight<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// Retrieve the day/night configuration of a video channel.
+    ///
+    /// Returns `Error::FeatureUnavailable` if the channel has no IR-cut filter.
+    pub async fn configuration(&self, channel: Channel) -> Result<DayNightConfiguration> {
+        let group = format!("root.ImageSource.I{}.DayNight", channel.index());
+        let params = self
+            .0
+            .parameters()
+            .list(Some(&[group.as_str()][..]))
+            .await?;
+        let params: BTreeMap<&str, &str> = params
+            .iter()
+            .map(|(k, v)| (k.trim_start_matches("root."), v.as_str()))
+            .collect();
+
+        configuration_from_params(&params, channel).ok_or(Error::FeatureUnavailable)
+    }
+
+    /// Change the day/night mode of a video channel.
+    pub async fn set_mode(&self, channel: Channel, mode: DayNightMode) -> Result<()> {
+        self.0
+            .parameters()
+            .update(vec![(ir_cut_filter_param(channel), mode.as_param())])
+            .await
+    }
+
+    /// Change the light level at which a video channel switches automatically, from 0 to 100.
+    pub async fn set_shift_level(&self, channel: Channel, shift_level: u8) -> Result<()> {
+        if shift_level > 100 {
+            return Err(Error::Other("shift level must be between 0 and 100"));
+        }
+
+        self.0
+            .parameters()
+            .update(vec![(shift_level_param(channel), shift_level.to_string())])
+            .await
+    }
+}
+
+fn ir_cut_filter_param(channel: Channel) -> String {
+    format!("ImageSource.I{}.DayNight.IrCutFilter", channel.index())
+}
+
+fn shift_level_param(channel: Channel) -> String {
+    format!("ImageSource.I{}.DayNight.ShiftLevel", channel.index())
+}
+
+fn configuration_from_params(
+    params: &BTreeMap<&str, &str>,
+    channel: Channel,
+) -> Option<DayNightConfiguration> {
+    let mode = DayNightMode::from_param(params.get(ir_cut_filter_param(channel).as_str())?)?;
+    let shift_level = params
+        .get(shift_level_param(channel).as_str())
+        .and_then(|v| u8::from_str(v).ok());
+
+    Some(DayNightConfiguration { mode, shift_level })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn configuration() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?action=list&group=root.ImageSource.I1.DayNight")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.ImageSource.I1.DayNight.IrCutFilter=auto\n\
+root.ImageSource.I1.DayNight.ShiftLevel=50\n"
+                    .to_vec()])
+        });
+
+        assert_eq!(
+            client
+                .day_night()
+                .configuration(Channel::new(2).unwrap())
+                .await
+                .unwrap(),
+            DayNightConfiguration {
+                mode: DayNightMode::Auto,
+                shift_level: Some(50),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn set_mode() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?ImageSource.I0.DayNight.IrCutFilter=no&action=update")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"OK".to_vec()])
+        });
+
+        client
+            .day_night()
+            .set_mode(Channel::first(), DayNightMode::Night)
+            .await
+            .unwrap();
+    }
+}
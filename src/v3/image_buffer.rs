@@ -0,0 +1,265 @@
+//! The VAPIX image buffer interface at `/axis-cgi/buffer/command.cgi`.
+//!
+//! An image buffer continuously retains recent JPEG frames on the device. Starting a buffer marks
+//! a trigger point: the device keeps the frames captured during the pre-trigger period and
+//! continues capturing for the post-trigger period, after which the series can be listed and
+//! downloaded. This is much lighter than exporting video when a verification workflow only needs
+//! a handful of stills around an event.
+
+use crate::*;
+use chrono::prelude::*;
+use serde::Serialize;
+use std::time::Duration;
+
+/// A device's image buffer interface.
+pub struct ImageBuffer<'a, T: Transport>(&'a Client<T>);
+
+/// Options for capturing a series of images around a trigger point.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ImageSeriesOptions {
+    /// The video channel to capture.
+    pub channel: Channel,
+    /// How far before the trigger point to retain images. Rounded down to whole seconds.
+    pub pre_trigger: Duration,
+    /// How long after the trigger point to keep capturing. Rounded down to whole seconds.
+    pub post_trigger: Duration,
+    /// The number of images to capture per second, if not the device's default.
+    pub fps: Option<u32>,
+}
+
+impl Default for ImageSeriesOptions {
+    fn default() -> Self {
+        Self {
+            channel: Channel::first(),
+            pre_trigger: Duration::from_secs(2),
+            post_trigger: Duration::from_secs(2),
+            fps: None,
+        }
+    }
+}
+
+/// A JPEG image retrieved from an image buffer.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BufferedImage {
+    /// The path and query at which the device served the image.
+    pub path: String,
+    /// The time at which the image was captured, if reported by the device.
+    pub timestamp: Option<DateTime<FixedOffset>>,
+    /// The raw JPEG data.
+    pub jpeg: Vec<u8>,
+}
+
+impl<'a, T: Transport> ImageBuffer<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// Capture a series of images around the present moment.
+    ///
+    /// Starts the buffer named `name`, waits for the post-trigger period to elapse, and returns
+    /// every buffered image in order. The buffer is removed from the device afterwards.
+    pub async fn capture_series(
+        &self,
+        name: &str,
+        options: &ImageSeriesOptions,
+    ) -> Result<Vec<BufferedImage>> {
+        self.start(name, options).await?;
+        futures_timer::Delay::new(options.post_trigger).await;
+
+        let result = self.download(name).await;
+        let removed = self.remove(name).await;
+
+        let images = result?;
+        removed?;
+        Ok(images)
+    }
+
+    /// Start capturing into the buffer named `name`, marking the trigger point.
+    pub async fn start(&self, name: &str, options: &ImageSeriesOptions) -> Result<()> {
+        self.0.check_writable()?;
+        #[derive(Serialize)]
+        struct Req<'a> {
+            #[serde(rename = "do")]
+            command: &'a str,
+            buffername: &'a str,
+            camera: Channel,
+            pretrigger: u64,
+            posttrigger: u64,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            fps: Option<u32>,
+        }
+
+        self.command(Req {
+            command: "start",
+            buffername: name,
+            camera: options.channel,
+            pretrigger: options.pre_trigger.as_secs(),
+            posttrigger: options.post_trigger.as_secs(),
+            fps: options.fps,
+        })
+        .await
+        .map(|_| ())
+    }
+
+    /// Stop capturing into the buffer named `name`, retaining the images captured so far.
+    pub async fn stop(&self, name: &str) -> Result<()> {
+        self.0.check_writable()?;
+        self.simple_command("stop", name).await.map(|_| ())
+    }
+
+    /// List the paths of the images in the buffer named `name`, in capture order.
+    pub async fn list(&self, name: &str) -> Result<Vec<String>> {
+        let body = self.simple_command("list", name).await?;
+        Ok(String::from_utf8_lossy(&body)
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with('/'))
+            .map(String::from)
+            .collect())
+    }
+
+    /// Download an image, given a path returned by `list()`.
+    pub async fn image(&self, path: &str) -> Result<BufferedImage> {
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(
+                self.0
+                    .uri_for(path)
+                    .map_err(|_| Error::Other("invalid image path"))?,
+            )
+            .body(Vec::new())?;
+
+        let (parts, jpeg) = self.0.roundtrip(req, "image/jpeg").await?;
+        let timestamp = parts
+            .headers
+            .get(http::header::LAST_MODIFIED)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| DateTime::parse_from_rfc2822(v).ok());
+
+        Ok(BufferedImage {
+            path: path.to_string(),
+            timestamp,
+            jpeg,
+        })
+    }
+
+    /// Remove the buffer named `name` and its images from the device.
+    pub async fn remove(&self, name: &str) -> Result<()> {
+        self.0.check_writable()?;
+        self.simple_command("remove", name).await.map(|_| ())
+    }
+
+    async fn download(&self, name: &str) -> Result<Vec<BufferedImage>> {
+        self.stop(name).await?;
+
+        let mut images = Vec::new();
+        for path in self.list(name).await? {
+            images.push(self.image(&path).await?);
+        }
+        Ok(images)
+    }
+
+    async fn simple_command(&self, command: &str, name: &str) -> Result<Vec<u8>> {
+        #[derive(Serialize)]
+        struct Req<'a> {
+            #[serde(rename = "do")]
+            command: &'a str,
+            buffername: &'a str,
+        }
+
+        self.command(Req {
+            command,
+            buffername: name,
+        })
+        .await
+    }
+
+    async fn command<R: Serialize>(&self, req: R) -> Result<Vec<u8>> {
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for_args("/axis-cgi/buffer/command.cgi", req)?)
+            .body(Vec::new())?;
+
+        let (_, body) = self
+            .0
+            .roundtrip(req, "text/plain")
+            .await
+            .map_404_to_feature_unavailable()?;
+
+        if body.starts_with(b"Error") || body.starts_with(b"# Error") {
+            return Err(Error::Other("image buffer command failed"));
+        }
+        Ok(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn capture_series() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let client = crate::mock_client({
+            let requests = requests.clone();
+            move |req| {
+                let pq = req.uri().path_and_query().unwrap().as_str().to_string();
+                requests.lock().unwrap().push(pq.clone());
+
+                let (content_type, body): (_, &[u8]) = match pq.as_str() {
+                    "/axis-cgi/buffer/command.cgi?do=list&buffername=alarm" => (
+                        "text/plain",
+                        b"/axis-cgi/buffer/image.cgi?buffername=alarm&imagenumber=1\r\n\
+/axis-cgi/buffer/image.cgi?buffername=alarm&imagenumber=2\r\n",
+                    ),
+                    p if p.starts_with("/axis-cgi/buffer/command.cgi") => ("text/plain", b"OK"),
+                    p if p.starts_with("/axis-cgi/buffer/image.cgi") => {
+                        ("image/jpeg", b"\xff\xd8\xff\xd9")
+                    }
+                    other => panic!("unexpected request {:?}", other),
+                };
+
+                http::Response::builder()
+                    .status(http::StatusCode::OK)
+                    .header(http::header::CONTENT_TYPE, content_type)
+                    .header(http::header::LAST_MODIFIED, "Fri, 09 Oct 2020 15:30:02 GMT")
+                    .body(vec![body.to_vec()])
+            }
+        });
+
+        let images = client
+            .image_buffer()
+            .capture_series(
+                "alarm",
+                &ImageSeriesOptions {
+                    pre_trigger: Duration::from_secs(3),
+                    post_trigger: Duration::from_millis(0),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(images.len(), 2);
+        assert_eq!(
+            images[1].path,
+            "/axis-cgi/buffer/image.cgi?buffername=alarm&imagenumber=2"
+        );
+        assert_eq!(
+            images[0].timestamp,
+            Some(FixedOffset::east(0).ymd(2020, 10, 9).and_hms(15, 30, 2))
+        );
+        assert_eq!(
+            requests.lock().unwrap().as_slice(),
+            &[
+                "/axis-cgi/buffer/command.cgi?do=start&buffername=alarm&camera=1&pretrigger=3&posttrigger=0",
+                "/axis-cgi/buffer/command.cgi?do=stop&buffername=alarm",
+                "/axis-cgi/buffer/command.cgi?do=list&buffername=alarm",
+                "/axis-cgi/buffer/image.cgi?buffername=alarm&imagenumber=1",
+                "/axis-cgi/buffer/image.cgi?buffername=alarm&imagenumber=2",
+                "/axis-cgi/buffer/command.cgi?do=remove&buffername=alarm",
+            ]
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! The legacy I/O port interface at `/axis-cgi/io/port.cgi`.
+//!
+//! Devices with physical inputs and outputs, like door controllers or cameras with terminal
+//! blocks, describe their ports in the `IOPort.I{index}` parameter groups and report or change
+//! their states through `port.cgi`. Newer devices offer the same through the v4 I/O port
+//! management API; `Client::list_io_ports()` and `Client::set_io_port_active()` use whichever is
+//! available.
+//!
+//! Ports are numbered from 0, matching `IOPort.I{index}`, even though `port.cgi` numbers them
+//! from 1.
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+/// A device's legacy I/O port interface.
+pub struct IoPorts<'a, T: Transport>(&'a Client<T>);
+
+/// An I/O port and its current state.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IoPort {
+    /// The index of the port, starting from 0.
+    pub index: u32,
+    /// The name of the port.
+    pub name: String,
+    /// Whether the port is an input or an output.
+    pub direction: IoPortDirection,
+    /// Can the port's direction be changed?
+    pub configurable: bool,
+    /// Is the port in its active state, as opposed to its normal state?
+    pub active: bool,
+}
+
+/// Whether an I/O port is an input or an output.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IoPortDirection {
+    /// The device monitors the port, e.g. for a door sensor.
+    Input,
+    /// The device drives the port, e.g. to unlock a door.
+    Output,
+}
+
+impl<'a, T: Transport> IoPorts<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// List the device's I/O ports and their current states.
+    ///
+    /// Devices without I/O ports have no ports.
+    pub async fn list(&self) -> Result<Vec<IoPort>> {
+        let params = self.0.parameters().list(Some(&["root.IOPort"][..])).await?;
+        let params: BTreeMap<&str, &str> = params
+            .iter()
+            .map(|(k, v)| (k.trim_start_matches("root."), v.as_str()))
+            .collect();
+
+        let mut ports = ports_from_params(&params);
+        if ports.is_empty() {
+            return Ok(ports);
+        }
+
+        let indices: Vec<u32> = ports.iter().map(|port| port.index).collect();
+        let states = self.states(&indices).await?;
+        for port in &mut ports {
+            port.active = states.get(&port.index).copied().unwrap_or(false);
+        }
+        Ok(ports)
+    }
+
+    /// Retrieve whether each of the given ports is active.
+    pub async fn states(&self, indices: &[u32]) -> Result<BTreeMap<u32, bool>> {
+        let check = indices
+            .iter()
+            .map(|index| (index + 1).to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let body = self.call(&[("check", check)]).await?;
+        let body = std::str::from_utf8(&body).map_err(|_| Error::Other("invalid UTF-8"))?;
+
+        body.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let (port, state) = line
+                    .trim()
+                    .strip_prefix("port")
+                    .and_then(|line| {
+                        let mut parts = line.splitn(2, '=');
+                        Some((parts.next()?.parse::<u32>().ok()?, parts.next()?))
+                    })
+                    .filter(|(port, _)| *port > 0)
+                    .ok_or(Error::Other("unparseable port state"))?;
+                Ok((port - 1, state == "1"))
+            })
+            .collect()
+    }
+
+    /// Activate or deactivate an output port.
+    pub async fn set_active(&self, index: u32, active: bool) -> Result<()> {
+        self.0.check_writable()?;
+        let action = format!("{}:{}", index + 1, if active { "/" } else { "\\" });
+        self.call(&[("action", action)]).await?;
+        self.0
+            .audit(|| {
+                format!(
+                    "{} I/O port {}",
+                    if active { "activated" } else { "deactivated" },
+                    index
+                )
+            })
+            .await;
+        Ok(())
+    }
+
+    /// Activate an output port for a duration, to the millisecond, then deactivate it.
+    pub async fn pulse(&self, index: u32, duration: Duration) -> Result<()> {
+        self.0.check_writable()?;
+        let action = format!("{}:/{}\\", index + 1, duration.as_millis());
+        self.call(&[("action", action)]).await?;
+        self.0
+            .audit(|| format!("pulsed I/O port {} for {:?}", index, duration))
+            .await;
+        Ok(())
+    }
+
+    async fn call(&self, args: &[(&str, String)]) -> Result<Vec<u8>> {
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for_args("/axis-cgi/io/port.cgi", args)?)
+            .body(Vec::new())?;
+
+        let (_resp, body) = self
+            .0
+            .roundtrip(req, "text/plain")
+            .await
+            .map_404_to_feature_unavailable()?;
+        Ok(body)
+    }
+}
+
+fn ports_from_params(params: &BTreeMap<&str, &str>) -> Vec<IoPort> {
+    let indices: BTreeSet<u32> = params
+        .keys()
+        .filter_map(|k| k.strip_prefix("IOPort.I"))
+        .filter_map(|k| k.splitn(2, '.').next())
+        .filter_map(|index| index.parse().ok())
+        .collect();
+
+    indices
+        .into_iter()
+        .filter_map(|index| {
+            let get = |name: &str| {
+                params
+                    .get(format!("IOPort.I{}.{}", index, name).as_str())
+                    .copied()
+            };
+
+            let direction = match get("Direction")? {
+                "input" => IoPortDirection::Input,
+                "output" => IoPortDirection::Output,
+                _ => return None,
+            };
+            let name = match direction {
+                IoPortDirection::Input => get("Input.Name"),
+                IoPortDirection::Output => get("Output.Name"),
+            };
+
+            Some(IoPort {
+                index,
+                name: name.unwrap_or("").to_string(),
+                direction,
+                configurable: get("Configurable") == Some("yes"),
+                active: false,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn list_and_set() {
+        let client = crate::mock_client(|req| {
+            let body: &[u8] = match req.uri().path_and_query().unwrap().as_str() {
+                "/axis-cgi/param.cgi?action=list&group=root.IOPort" => {
+                    b"root.IOPort.I0.Configurable=yes\n\
+root.IOPort.I0.Direction=input\n\
+root.IOPort.I0.Input.Name=Door\n\
+root.IOPort.I0.Input.Trig=closed\n\
+root.IOPort.I0.Output.Name=Output 1\n\
+root.IOPort.I1.Configurable=no\n\
+root.IOPort.I1.Direction=output\n\
+root.IOPort.I1.Input.Name=Input 2\n\
+root.IOPort.I1.Output.Name=Lock\n"
+                }
+                "/axis-cgi/io/port.cgi?check=1%2C2" => b"port1=1\r\nport2=0\r\n",
+                "/axis-cgi/io/port.cgi?action=2%3A%2F" => b"OK",
+                "/axis-cgi/io/port.cgi?action=2%3A%2F1500%5C" => b"OK",
+                other => panic!("unexpected request {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![body.to_vec()])
+        });
+
+        assert_eq!(
+            client.io_ports().list().await.unwrap(),
+            vec![
+                IoPort {
+                    index: 0,
+                    name: "Door".into(),
+                    direction: IoPortDirection::Input,
+                    configurable: true,
+                    active: true,
+                },
+                IoPort {
+                    index: 1,
+                    name: "Lock".into(),
+                    direction: IoPortDirection::Output,
+                    configurable: false,
+                    active: false,
+                },
+            ]
+        );
+
+        client.io_ports().set_active(1, true).await.unwrap();
+        client
+            .io_ports()
+            .pulse(1, Duration::from_millis(1500))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn fallback() {
+        let client = crate::mock_client(|req| {
+            if req.uri().path() == "/axis-cgi/io/portmanagement.cgi" {
+                return http::Response::builder()
+                    .status(http::StatusCode::NOT_FOUND)
+                    .body(vec![]);
+            }
+            assert_eq!(
+                req.uri().path_and_query().unwrap().as_str(),
+                "/axis-cgi/io/port.cgi?action=1%3A%5C"
+            );
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"OK".to_vec()])
+        });
+
+        client.set_io_port_active(0, false).await.unwrap();
+    }
+}
@@ -0,0 +1,356 @@
+//! The VAPIX MJPEG video interface at `/axis-cgi/mjpg/video.cgi`.
+//!
+//! The device sends a `multipart/x-mixed-replace` response which never ends, with one JPEG image
+//! per part. Frames are parsed as the response arrives, subject to the client's streaming
+//! timeouts.
+
+use super::snapshot::Resolution;
+use crate::*;
+use futures::stream::{self, Stream};
+use serde::Serialize;
+
+/// The largest frame to buffer before giving up on finding the next boundary.
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// A device's MJPEG video interface.
+pub struct Mjpeg<'a, T: Transport>(&'a Client<T>);
+
+/// Options for requesting an MJPEG stream.
+///
+/// Options left as `None` use the device's configured defaults for the channel.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MjpegOptions {
+    /// The video channel to stream.
+    pub channel: Channel,
+    /// The resolution of each frame. The device rejects resolutions it does not support.
+    pub resolution: Option<Resolution>,
+    /// The JPEG compression level, from 0 (best quality) to 100 (smallest frames).
+    pub compression: Option<u8>,
+    /// The maximum number of frames per second.
+    pub fps: Option<u32>,
+}
+
+impl MjpegOptions {
+    /// Default options for streaming `channel`.
+    pub fn new(channel: Channel) -> Self {
+        Self {
+            channel,
+            resolution: None,
+            compression: None,
+            fps: None,
+        }
+    }
+}
+
+impl Default for MjpegOptions {
+    fn default() -> Self {
+        Self::new(Channel::first())
+    }
+}
+
+/// A single frame of an MJPEG stream.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct JpegFrame {
+    /// The raw JPEG data.
+    pub jpeg: Vec<u8>,
+}
+
+impl StreamItem for JpegFrame {
+    type Resume = ();
+
+    fn byte_len(&self) -> usize {
+        self.jpeg.len()
+    }
+}
+
+impl<'a, T: Transport> Mjpeg<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// Start an MJPEG stream, returning each frame as it arrives.
+    ///
+    /// The stream ends if the device closes the connection. Use `supervise()` to reconnect
+    /// automatically instead.
+    pub async fn stream(
+        &self,
+        options: &MjpegOptions,
+    ) -> Result<impl Stream<Item = Result<JpegFrame>>> {
+        #[derive(Serialize)]
+        struct Req {
+            camera: Channel,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            resolution: Option<Resolution>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            compression: Option<u8>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            fps: Option<u32>,
+        }
+
+        if matches!(options.compression, Some(c) if c > 100) {
+            return Err(Error::Other("JPEG compression must be between 0 and 100"));
+        }
+
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for_args(
+                "/axis-cgi/mjpg/video.cgi",
+                Req {
+                    camera: options.channel,
+                    resolution: options.resolution,
+                    compression: options.compression,
+                    fps: options.fps,
+                },
+            )?)
+            .body(Vec::new())?;
+
+        let (parts, body) = self
+            .0
+            .roundtrip_streaming(req, "multipart/x-mixed-replace")
+            .await
+            .map_404_to_feature_unavailable()?;
+
+        let boundary = parts
+            .headers
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(boundary_of)
+            .ok_or(Error::Other("MJPEG response has no multipart boundary"))?;
+
+        Ok(frames(body, FrameParser::new(&boundary)))
+    }
+
+    /// Start an MJPEG stream which reconnects according to `policy` whenever the connection
+    /// drops or stalls.
+    pub fn supervise(
+        &self,
+        options: MjpegOptions,
+        policy: Reconnect,
+    ) -> impl Stream<Item = Result<JpegFrame>> + '_ {
+        let cancel = policy.cancel_handle();
+        policy.supervise(move |_| {
+            let cancel = cancel.clone();
+            async move { Ok(cancel.run_stream(self.stream(&options).await?)) }
+        })
+    }
+}
+
+/// Extract the `boundary` parameter from a `multipart/x-mixed-replace` content type.
+fn boundary_of(content_type: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let mut kv = param.trim().splitn(2, '=');
+        match (kv.next(), kv.next()) {
+            (Some(key), Some(value)) if key.eq_ignore_ascii_case("boundary") => {
+                let value = value.trim().trim_matches('"');
+                // Some firmware repeats the delimiter's leading dashes in the parameter
+                Some(value.trim_start_matches("--").to_string())
+            }
+            _ => None,
+        }
+    })
+}
+
+fn frames<B, C>(body: B, parser: FrameParser) -> impl Stream<Item = Result<JpegFrame>>
+where
+    B: Stream<Item = std::result::Result<C, crate::transport::Error>>,
+    C: AsRef<[u8]>,
+{
+    stream::unfold(
+        (Box::pin(body), parser, false),
+        |(mut body, mut parser, done)| async move {
+            use futures::StreamExt;
+
+            if done {
+                return None;
+            }
+            loop {
+                match parser.next_frame() {
+                    Ok(Some(frame)) => return Some((Ok(frame), (body, parser, false))),
+                    Ok(None) => {}
+                    Err(e) => return Some((Err(e), (body, parser, true))),
+                }
+                match body.next().await {
+                    Some(Ok(chunk)) => parser.push(chunk.as_ref()),
+                    Some(Err(e)) => return Some((Err(e.into()), (body, parser, true))),
+                    None => return None,
+                }
+            }
+        },
+    )
+}
+
+/// An incremental parser for the parts of a `multipart/x-mixed-replace` body.
+struct FrameParser {
+    delimiter: Vec<u8>,
+    buffer: Vec<u8>,
+}
+
+impl FrameParser {
+    fn new(boundary: &str) -> Self {
+        Self {
+            delimiter: format!("--{}", boundary).into_bytes(),
+            buffer: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Remove and return the next complete frame from the buffer, if there is one.
+    fn next_frame(&mut self) -> Result<Option<JpegFrame>> {
+        if self.buffer.len() > MAX_FRAME_SIZE {
+            return Err(Error::Other("MJPEG frame too large"));
+        }
+
+        let start = match find(&self.buffer, &self.delimiter, 0) {
+            Some(start) => start,
+            None => return Ok(None),
+        };
+        let headers_start = start + self.delimiter.len();
+        let body_start = match find(&self.buffer, b"\r\n\r\n", headers_start) {
+            Some(end) => end + 4,
+            None => return Ok(None),
+        };
+
+        let content_length = String::from_utf8_lossy(&self.buffer[headers_start..body_start])
+            .lines()
+            .find_map(|line| {
+                let mut kv = line.splitn(2, ':');
+                match (kv.next(), kv.next()) {
+                    (Some(name), Some(value))
+                        if name.trim().eq_ignore_ascii_case("content-length") =>
+                    {
+                        value.trim().parse::<usize>().ok()
+                    }
+                    _ => None,
+                }
+            });
+
+        let (body_end, next) = match content_length {
+            Some(length) if self.buffer.len() >= body_start + length => {
+                (body_start + length, body_start + length)
+            }
+            Some(_) => return Ok(None),
+            None => match find(&self.buffer, &self.delimiter, body_start) {
+                Some(next) => {
+                    let mut end = next;
+                    if self.buffer[body_start..end].ends_with(b"\r\n") {
+                        end -= 2;
+                    }
+                    (end, next)
+                }
+                None => return Ok(None),
+            },
+        };
+
+        let jpeg = self.buffer[body_start..body_end].to_vec();
+        self.buffer.drain(..next);
+        Ok(Some(JpegFrame { jpeg }))
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|position| from + position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[test]
+    fn boundary() {
+        assert_eq!(
+            boundary_of("multipart/x-mixed-replace; boundary=myboundary").as_deref(),
+            Some("myboundary")
+        );
+        assert_eq!(
+            boundary_of("multipart/x-mixed-replace;boundary=\"--abc\"").as_deref(),
+            Some("abc")
+        );
+        assert_eq!(boundary_of("multipart/x-mixed-replace"), None);
+    }
+
+    #[tokio::test]
+    async fn stream() {
+        let device = crate::mock_client(|req| {
+            assert_eq!(req.uri().path(), "/axis-cgi/mjpg/video.cgi");
+            assert_eq!(req.uri().query(), Some("camera=1&resolution=320x240&fps=5"));
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(
+                    http::header::CONTENT_TYPE,
+                    "multipart/x-mixed-replace; boundary=myboundary",
+                )
+                .body(vec![
+                    b"--myboundary\r\nContent-Type: image/jpeg\r\nContent-Le".to_vec(),
+                    b"ngth: 4\r\n\r\n\xff\xd8\xff".to_vec(),
+                    b"\xd9\r\n--myboundary\r\nContent-Type: image/jpeg\r\n\r\n\xff\xd8\x00\xff\xd9"
+                        .to_vec(),
+                    b"\r\n--myboundary\r\n".to_vec(),
+                ])
+        });
+
+        let frames: Vec<JpegFrame> = device
+            .mjpeg()
+            .stream(&MjpegOptions {
+                resolution: Some(Resolution::new(320, 240)),
+                fps: Some(5),
+                ..MjpegOptions::default()
+            })
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(
+            frames,
+            vec![
+                JpegFrame {
+                    jpeg: b"\xff\xd8\xff\xd9".to_vec()
+                },
+                JpegFrame {
+                    jpeg: b"\xff\xd8\x00\xff\xd9".to_vec()
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn supervise() {
+        let mut connects = 0;
+        let device = crate::mock_client(move |_| {
+            connects += 1;
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(
+                    http::header::CONTENT_TYPE,
+                    "multipart/x-mixed-replace; boundary=b",
+                )
+                .body(vec![format!(
+                    "--b\r\nContent-Length: 1\r\n\r\n{}\r\n",
+                    connects
+                )
+                .into_bytes()])
+        });
+
+        // Each connection delivers one frame and then ends, so the stream reconnects each time
+        let frames: Vec<JpegFrame> = device
+            .mjpeg()
+            .supervise(
+                MjpegOptions::default(),
+                Reconnect::new(1).with_delay(std::time::Duration::from_millis(1)),
+            )
+            .take(3)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        let frames: Vec<&[u8]> = frames.iter().map(|f| f.jpeg.as_slice()).collect();
+        assert_eq!(frames, vec![&b"1"[..], &b"2"[..], &b"3"[..]]);
+    }
+}
@@ -0,0 +1,201 @@
+//! Multicast streaming configuration, as exposed by the VAPIX parameters interface.
+//!
+//! Each video channel has a `Network.RTP.R{index}` parameter group describing the multicast
+//! groups to which its RTP streams are sent. Clients normally request multicast per RTSP session,
+//! but a channel can also be set to always multicast, independent of any RTSP client, which is
+//! how large deployments distribute a single stream to many receivers.
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// A device's multicast configuration interface.
+pub struct Multicast<'a, T: Transport>(&'a Client<T>);
+
+/// The multicast configuration of a video channel.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MulticastConfiguration {
+    /// The multicast group to which video is sent.
+    pub video_address: IpAddr,
+    /// The UDP port to which video is sent, or `0` to let the device choose.
+    pub video_port: u16,
+    /// The multicast group to which audio is sent.
+    pub audio_address: IpAddr,
+    /// The UDP port to which audio is sent, or `0` to let the device choose.
+    pub audio_port: u16,
+    /// The time-to-live of multicast packets, limiting how many routers they may cross.
+    pub ttl: u8,
+    /// Is video always multicast, whether or not an RTSP client has requested it?
+    pub always_multicast_video: bool,
+    /// Is audio always multicast, whether or not an RTSP client has requested it?
+    pub always_multicast_audio: bool,
+}
+
+impl<'a, T: Transport> Multicast<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// Retrieve the multicast configuration of a video channel.
+    ///
+    /// Returns `Error::FeatureUnavailable` if the device does not support multicast for this
+    /// channel.
+    pub async fn configuration(&self, channel: Channel) -> Result<MulticastConfiguration> {
+        let group = format!("root.{}", group(channel));
+        let params = self
+            .0
+            .parameters()
+            .list(Some(&[group.as_str()][..]))
+            .await?;
+        let params: BTreeMap<&str, &str> = params
+            .iter()
+            .map(|(k, v)| (k.trim_start_matches("root."), v.as_str()))
+            .collect();
+
+        configuration_from_params(&params, channel).ok_or(Error::FeatureUnavailable)
+    }
+
+    /// Change the multicast configuration of a video channel.
+    pub async fn set_configuration(
+        &self,
+        channel: Channel,
+        configuration: &MulticastConfiguration,
+    ) -> Result<()> {
+        self.0
+            .parameters()
+            .update(params_from_configuration(channel, configuration))
+            .await
+    }
+}
+
+fn group(channel: Channel) -> String {
+    format!("Network.RTP.R{}", channel.index())
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "yes" } else { "no" }.to_string()
+}
+
+fn configuration_from_params(
+    params: &BTreeMap<&str, &str>,
+    channel: Channel,
+) -> Option<MulticastConfiguration> {
+    let group = group(channel);
+    let get = |name: &str| params.get(format!("{}.{}", group, name).as_str()).copied();
+
+    Some(MulticastConfiguration {
+        video_address: IpAddr::from_str(get("VideoAddress")?).ok()?,
+        video_port: get("VideoPort")
+            .and_then(|v| u16::from_str(v).ok())
+            .unwrap_or(0),
+        audio_address: IpAddr::from_str(get("AudioAddress")?).ok()?,
+        audio_port: get("AudioPort")
+            .and_then(|v| u16::from_str(v).ok())
+            .unwrap_or(0),
+        ttl: get("TTL").and_then(|v| u8::from_str(v).ok()).unwrap_or(5),
+        always_multicast_video: get("AlwaysMulticastVideo") == Some("yes"),
+        always_multicast_audio: get("AlwaysMulticastAudio") == Some("yes"),
+    })
+}
+
+fn params_from_configuration(
+    channel: Channel,
+    configuration: &MulticastConfiguration,
+) -> Vec<(String, String)> {
+    let group = group(channel);
+    vec![
+        ("VideoAddress", configuration.video_address.to_string()),
+        ("VideoPort", configuration.video_port.to_string()),
+        ("AudioAddress", configuration.audio_address.to_string()),
+        ("AudioPort", configuration.audio_port.to_string()),
+        ("TTL", configuration.ttl.to_string()),
+        (
+            "AlwaysMulticastVideo",
+            yes_no(configuration.always_multicast_video),
+        ),
+        (
+            "AlwaysMulticastAudio",
+            yes_no(configuration.always_multicast_audio),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, value)| (format!("{}.{}", group, name), value))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn configuration() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?action=list&group=root.Network.RTP.R0")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.Network.RTP.R0.VideoAddress=239.216.18.60\n\
+root.Network.RTP.R0.VideoPort=0\n\
+root.Network.RTP.R0.AudioAddress=239.216.18.61\n\
+root.Network.RTP.R0.AudioPort=0\n\
+root.Network.RTP.R0.TTL=5\n\
+root.Network.RTP.R0.AlwaysMulticastVideo=yes\n\
+root.Network.RTP.R0.AlwaysMulticastAudio=no\n"
+                    .to_vec()])
+        });
+
+        assert_eq!(
+            client
+                .multicast()
+                .configuration(Channel::first())
+                .await
+                .unwrap(),
+            MulticastConfiguration {
+                video_address: "239.216.18.60".parse().unwrap(),
+                video_port: 0,
+                audio_address: "239.216.18.61".parse().unwrap(),
+                audio_port: 0,
+                ttl: 5,
+                always_multicast_video: true,
+                always_multicast_audio: false,
+            }
+        );
+    }
+
+    #[test]
+    fn params() {
+        let configuration = MulticastConfiguration {
+            video_address: "239.1.1.1".parse().unwrap(),
+            video_port: 50000,
+            audio_address: "239.1.1.2".parse().unwrap(),
+            audio_port: 50002,
+            ttl: 16,
+            always_multicast_video: false,
+            always_multicast_audio: true,
+        };
+        let params = params_from_configuration(Channel::new(2).unwrap(), &configuration);
+        assert_eq!(
+            params[0],
+            (
+                "Network.RTP.R1.VideoAddress".to_string(),
+                "239.1.1.1".to_string()
+            )
+        );
+
+        let params: BTreeMap<&str, &str> = params
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        assert_eq!(
+            configuration_from_params(&params, Channel::new(2).unwrap()),
+            Some(configuration)
+        );
+    }
+}
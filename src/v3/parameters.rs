@@ -7,12 +7,93 @@ use std::convert::TryFrom;
 use std::fmt;
 use std::str::FromStr;
 
+mod backup;
+mod diff;
+mod dynamic_group;
+mod tree;
+mod update_error;
+pub use backup::{
+    format_parameter_list, parse_parameter_list, ParameterExport, ParameterImport,
+    VALUES_ONLY_HEADER,
+};
+pub use diff::{ConfigDiff, ValueChange};
+pub use dynamic_group::{DynamicGroup, DynamicGroupParseError};
+pub use tree::{Parameter, ParameterGroup, ParameterTree, ParameterValueError};
+pub use update_error::ParameterUpdateError;
+
 /// A device's legacy parameters API.
-pub struct Parameters<'a, T: Transport>(&'a Client<T>, String);
+pub struct Parameters<'a, T: Transport>(&'a Client<T>, String, RootPrefix, ResponseFormat);
+
+/// How to treat the `root.` prefix of parameter names.
+///
+/// Devices include the prefix when listing groups requested with it, e.g. `root.Brand`, and omit
+/// it otherwise. Choosing `Include` or `Strip` makes lookups consistent regardless of how groups
+/// were requested.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RootPrefix {
+    /// Return names exactly as the device does.
+    AsReturned,
+    /// Ensure every name starts with `root.`.
+    Include,
+    /// Ensure no name starts with `root.`.
+    Strip,
+}
+
+impl Default for RootPrefix {
+    fn default() -> Self {
+        RootPrefix::AsReturned
+    }
+}
+
+impl RootPrefix {
+    fn apply(self, name: &str) -> String {
+        match self {
+            RootPrefix::AsReturned => name.to_string(),
+            RootPrefix::Include if name.starts_with("root.") || name == "root" => name.to_string(),
+            RootPrefix::Include => format!("root.{}", name),
+            RootPrefix::Strip => name.trim_start_matches("root.").to_string(),
+        }
+    }
+}
+
+/// The format in which the device lists parameter values.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ResponseFormat {
+    /// Plain `name=value` lines, which are ambiguous if a value contains a newline.
+    Plain,
+    /// `name=value` lines with percent-encoded values, as requested by `responseformat=rfc`.
+    ///
+    /// Devices which don't support this format are asked again for `Plain`.
+    Rfc,
+}
+
+impl Default for ResponseFormat {
+    fn default() -> Self {
+        ResponseFormat::Plain
+    }
+}
 
 impl<'a, T: Transport> Parameters<'a, T> {
     pub(crate) fn new(device: &'a Client<T>, api_version: String) -> Self {
-        Self(device, api_version)
+        Self(
+            device,
+            api_version,
+            RootPrefix::default(),
+            ResponseFormat::default(),
+        )
+    }
+
+    /// Normalize the `root.` prefix of parameter names returned by `list()` and groups returned
+    /// by `list_definitions()`.
+    pub fn with_root_prefix(mut self, root_prefix: RootPrefix) -> Self {
+        self.2 = root_prefix;
+        self
+    }
+
+    /// Request parameter values from `list()` in the given format.
+    pub fn with_response_format(mut self, response_format: ResponseFormat) -> Self {
+        self.3 = response_format;
+        self
     }
 
     /// List parameters, including their definitions and current values.
@@ -21,115 +102,447 @@ impl<'a, T: Transport> Parameters<'a, T> {
     pub async fn list_definitions(&self, groups: Option<&[&str]>) -> Result<ParameterDefinitions> {
         let req = http::Request::builder()
             .method(http::Method::GET)
-            .uri(
-                self.0
-                    .uri_for_args(
-                        "/axis-cgi/param.cgi",
-                        ListParams {
-                            action: "listdefinitions",
-                            list_format: Some("xmlschema"),
-                            groups,
-                        },
-                    )
-                    .unwrap(),
-            )
-            .body(Vec::new())
-            .unwrap();
+            .uri(self.0.uri_for_args(
+                "/axis-cgi/param.cgi",
+                ListParams {
+                    action: "listdefinitions",
+                    list_format: Some("xmlschema"),
+                    response_format: None,
+                    groups,
+                },
+            )?)
+            .body(Vec::new())?;
 
         let (_resp, resp_body) = self.0.roundtrip(req, "text/xml").await?;
 
-        let resp_body =
-            std::str::from_utf8(resp_body.as_slice()).map_err(|_| Error::Other("invalid UTF-8"))?;
-
-        let params: ParameterDefinitions = quick_xml::de::from_str(resp_body)?;
+        let mut params: ParameterDefinitions = crate::xml::from_slice(&resp_body)?;
+
+        match self.2 {
+            RootPrefix::AsReturned => {}
+            RootPrefix::Include => {
+                if !params.groups.iter().all(|g| g.name == "root") {
+                    params.groups = vec![ParameterGroupDefinition {
+                        name: "root".into(),
+                        max_groups: None,
+                        groups: std::mem::take(&mut params.groups),
+                        parameters: Vec::new(),
+                    }];
+                }
+            }
+            RootPrefix::Strip => {
+                params.groups = std::mem::take(&mut params.groups)
+                    .into_iter()
+                    .flat_map(|g| if g.name == "root" { g.groups } else { vec![g] })
+                    .collect();
+            }
+        }
 
         Ok(params)
     }
 
     /// List parameters, including their current values.
     ///
-    /// If `groups` is provided, return a subset of the parameter tree.
+    /// If `groups` is provided, return a subset of the parameter tree. Values are requested in
+    /// the format chosen by `with_response_format()`.
     pub async fn list(&self, groups: Option<&[&str]>) -> Result<BTreeMap<String, String>> {
+        if self.3 == ResponseFormat::Rfc {
+            match self.list_body(groups, Some("rfc")).await {
+                Ok(body) if !body.starts_with(b"# Error") => {
+                    return self.parse_list(&body, ResponseFormat::Rfc)
+                }
+                Ok(_) => {}
+                Err(Error::HttpRequestFailed(e))
+                    if e.downcast_ref()
+                        == Some(&crate::error::HttpStatusCodeError(
+                            http::StatusCode::BAD_REQUEST,
+                        )) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let body = self.list_body(groups, None).await?;
+        self.parse_list(&body, ResponseFormat::Plain)
+    }
+
+    async fn list_body(
+        &self,
+        groups: Option<&[&str]>,
+        response_format: Option<&str>,
+    ) -> Result<Vec<u8>> {
         let req = http::request::Builder::new()
             .method(http::Method::GET)
-            .uri(
-                self.0
-                    .uri_for_args(
-                        "/axis-cgi/param.cgi",
-                        ListParams {
-                            action: "list",
-                            list_format: None,
-                            groups,
-                        },
-                    )
-                    .unwrap(),
-            )
-            .body(Vec::new())
-            .unwrap();
+            .uri(self.0.uri_for_args(
+                "/axis-cgi/param.cgi",
+                ListParams {
+                    action: "list",
+                    list_format: None,
+                    response_format,
+                    groups,
+                },
+            )?)
+            .body(Vec::new())?;
 
         let (_, body) = self.0.roundtrip(req, "text/plain").await?;
-        Ok(body
-            .as_slice()
-            .split(|byte| *byte == b'\n')
-            .filter_map(|line| {
-                let line = std::str::from_utf8(line).unwrap_or("");
-                let mut parts = line.splitn(2, '=');
-                match (parts.next(), parts.next()) {
-                    (Some(key), Some(value)) => Some((key.to_string(), value.to_string())),
-                    _ => None,
-                }
+        Ok(body)
+    }
+
+    fn parse_list(&self, body: &[u8], format: ResponseFormat) -> Result<BTreeMap<String, String>> {
+        let body = std::str::from_utf8(body).map_err(|_| Error::Other("invalid UTF-8"))?;
+        Ok(split_assignments(body)
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match format {
+                    ResponseFormat::Plain => value,
+                    ResponseFormat::Rfc => crate::client::percent_decode(&value),
+                };
+                (self.2.apply(key), value)
             })
             .collect())
     }
 
-    // todo: ?action=add, optional force=yes
-    // The force parameter can be used to exceed limits set for adding dynamic parameter groups.
-    // Example: Axis products can be configured for up to 10 event types. The force parameter can be used to exceed this maximum number of events.
+    /// Retrieve parameters as a `ParameterTree`, including their definitions.
+    ///
+    /// If `groups` is provided, return a subset of the parameter tree. Devices which can't
+    /// describe their parameters are asked for values alone, in which case the tree has no
+    /// definitions.
+    pub async fn tree(&self, groups: Option<&[&str]>) -> Result<ParameterTree> {
+        match self.list_definitions(groups).await {
+            Ok(definitions) => Ok(ParameterTree::from_definitions(definitions)),
+            Err(e) if definitions_unsupported(&e) => {
+                Ok(ParameterTree::from_values(&self.list(groups).await?))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Export every parameter in the format produced by the device's web interface.
+    pub async fn export(&self) -> Result<String> {
+        Ok(format_parameter_list(&self.list(None).await?))
+    }
+
+    /// Export every parameter's value, noting whether the device can also describe its
+    /// parameters.
+    ///
+    /// Some older firmwares answer `list()` but not `list_definitions()`. Their exports are marked
+    /// as values-only, and can still be restored with `restore()`.
+    pub async fn backup(&self) -> Result<ParameterExport> {
+        let parameters = self
+            .with_root_prefix_of(RootPrefix::Include)
+            .list(None)
+            .await?;
+        let values_only = match self
+            .with_root_prefix_of(RootPrefix::Include)
+            .list_definitions(Some(&["root.Brand"][..]))
+            .await
+        {
+            Ok(_) => false,
+            Err(e) if definitions_unsupported(&e) => true,
+            Err(e) => return Err(e),
+        };
+        Ok(ParameterExport {
+            parameters,
+            values_only,
+        })
+    }
+
+    /// Import parameters in the format produced by the device's web interface.
+    ///
+    /// Parameters which are read-only or which do not exist on the device are skipped, so that a
+    /// complete export can be imported as-is.
+    ///
+    /// If the device cannot list its parameter definitions, every parameter it lists is taken to
+    /// be writable except for the `root.Brand` and `root.Properties` groups, and the result is
+    /// marked as values-only.
+    ///
+    /// Parameters are written a few at a time. If a write fails, the import stops and returns
+    /// `Error::PartialImport`, listing which parameters were applied and which weren't attempted.
+    pub async fn import(&self, contents: &str) -> Result<ParameterImport> {
+        self.import_parameters(parse_parameter_list(contents)).await
+    }
+
+    /// Restore parameters from an earlier `backup()`, as with `import()`.
+    ///
+    /// If a write fails part way through, this returns `Error::PartialImport` like `import()`.
+    pub async fn restore(&self, export: &ParameterExport) -> Result<ParameterImport> {
+        self.import_parameters(
+            export
+                .parameters
+                .iter()
+                .map(|(key, value)| (backup::qualified(key), value.clone()))
+                .collect(),
+        )
+        .await
+    }
+
+    async fn import_parameters(
+        &self,
+        parameters: BTreeMap<String, String>,
+    ) -> Result<ParameterImport> {
+        let (writable, values_only) = match self.list_definitions(None).await {
+            Ok(definitions) => (backup::writable_parameters(&definitions), false),
+            Err(e) if definitions_unsupported(&e) => (
+                backup::listed_parameters(
+                    &self
+                        .with_root_prefix_of(RootPrefix::Include)
+                        .list(None)
+                        .await?,
+                ),
+                true,
+            ),
+            Err(e) => return Err(e),
+        };
+
+        let mut import = ParameterImport {
+            values_only,
+            ..Default::default()
+        };
+        let mut updates = Vec::new();
+        let mut previous = BTreeMap::new();
+        for (key, value) in parameters {
+            match writable.get(&key) {
+                Some(current) => {
+                    if let Some(current) = current {
+                        previous.insert(key.clone(), current.clone());
+                    }
+                    updates.push((key.clone(), value));
+                    import.updated.push(key);
+                }
+                None => import.skipped.push(key),
+            }
+        }
+
+        // Keep each request's URL to a reasonable length
+        const CHUNK_SIZE: usize = 20;
+        for (i, chunk) in updates.chunks(CHUNK_SIZE).enumerate() {
+            if let Err(error) = self.update(chunk.iter().map(|(k, v)| (k, v))).await {
+                let names = |updates: &[(String, String)]| -> Vec<String> {
+                    updates.iter().map(|(k, _)| k.clone()).collect()
+                };
+                let mut applied = names(&updates[..i * CHUNK_SIZE]);
+                if let Error::ParameterUpdate { succeeded, .. } = &error {
+                    applied.extend(succeeded.iter().cloned());
+                }
+                return Err(Error::PartialImport {
+                    applied,
+                    error: Box::new(error),
+                    unattempted: names(&updates[(i * CHUNK_SIZE + chunk.len())..]),
+                });
+            }
+        }
+
+        // Parameters which the device listed without a value are reported as added
+        import.changes = ConfigDiff::between(&previous, &updates.into_iter().collect());
+
+        Ok(import)
+    }
+
+    /// A copy of this interface which normalizes names with `root_prefix`.
+    fn with_root_prefix_of(&self, root_prefix: RootPrefix) -> Parameters<'a, T> {
+        Parameters(self.0, self.1.clone(), root_prefix, self.3)
+    }
+
+    /// Compare the current values of the parameters in `groups` against an earlier snapshot, such
+    /// as one returned by `list()` with the same `groups`.
+    ///
+    /// Parameters in the snapshot which the device no longer lists are reported as removed.
+    pub async fn changes_since(
+        &self,
+        previous: &BTreeMap<String, String>,
+        groups: Option<&[&str]>,
+    ) -> Result<ConfigDiff> {
+        Ok(ConfigDiff::between(previous, &self.list(groups).await?))
+    }
+
+    /// Create an instance of a dynamic parameter group, like an event or a stream profile, from
+    /// one of the device's templates, returning a handle to the new group.
+    ///
+    /// `group` is the parent group, like `Motion`, and `template` names the template, like
+    /// `motion`. Parameters of the new group may be set as it is created by naming them with the
+    /// group's prefix but no index, like `Motion.M.Name`. Devices limit how many instances some
+    /// groups may have; `force` asks the device to exceed that limit.
+    pub async fn add_group<I, K, V>(
+        &self,
+        template: &str,
+        group: &str,
+        initial_values: I,
+        force: bool,
+    ) -> Result<DynamicGroup>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.0.check_writable()?;
+        let mut args = vec![
+            ("action".to_string(), "add".to_string()),
+            ("template".to_string(), template.to_string()),
+            ("group".to_string(), group.to_string()),
+        ];
+        if force {
+            args.push(("force".to_string(), "yes".to_string()));
+        }
+        args.extend(
+            initial_values
+                .into_iter()
+                .map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string())),
+        );
+
+        let req = http::request::Builder::new()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for_args("/axis-cgi/param.cgi", args)?)
+            .body(Vec::new())?;
+
+        let (_, body) = self.0.roundtrip(req, "text/plain").await?;
+        let created = DynamicGroup::from_add_response(group, &body)?;
+        self.0
+            .audit(|| format!("added parameter group {}", created))
+            .await;
+        Ok(created)
+    }
+
+    /// Remove instances of dynamic parameter groups by name, like `Motion.M3`.
+    pub async fn remove_group<I, S>(&self, names: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.0.check_writable()?;
+        let names: Vec<String> = names.into_iter().map(|n| n.as_ref().to_string()).collect();
+        if names.is_empty() {
+            return Ok(());
+        }
+
+        let groups = names.join(",");
+        let req = http::request::Builder::new()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for_args(
+                "/axis-cgi/param.cgi",
+                [("action", "remove"), ("group", groups.as_str())],
+            )?)
+            .body(Vec::new())?;
 
-    // todo action=remove
+        let (_, body) = self.0.roundtrip(req, "text/plain").await?;
+        if String::from_utf8_lossy(&body).trim() != "OK" {
+            return Err(Error::Other("parameter group removal failed"));
+        }
+        self.0
+            .audit(|| format!("removed parameter groups {}", names.join(", ")))
+            .await;
+        Ok(())
+    }
 
     /// Attempt to update one or more parameters.
     ///
-    /// TODO: what happens with partial failure?
+    /// If the device refuses any of them, this returns `Error::ParameterUpdate` listing which
+    /// parameters failed and why.
     pub async fn update<I: IntoIterator<Item = (K, V)>, K: AsRef<str>, V: AsRef<str>>(
         &self,
         parameters: I,
     ) -> Result<()> {
+        self.0.check_writable()?;
         let mut query_params: BTreeMap<String, String> = parameters
             .into_iter()
             .map(move |(k, v)| (k.as_ref().to_string(), v.as_ref().to_string()))
             .collect();
+        let names: Vec<String> = query_params.keys().cloned().collect();
         query_params.insert("action".into(), "update".into());
 
-        assert!(!query_params.is_empty());
-
         let req = http::request::Builder::new()
             .method(http::Method::GET)
-            .uri(
-                self.0
-                    .uri_for_args("/axis-cgi/param.cgi", query_params)
-                    .unwrap(),
-            )
-            .body(Vec::new())
-            .unwrap();
+            .uri(self.0.uri_for_args("/axis-cgi/param.cgi", query_params)?)
+            .body(Vec::new())?;
 
         let (_, body) = self.0.roundtrip(req, "text/plain").await?;
         if body.as_slice() == b"OK" {
+            self.0
+                .audit(|| format!("updated parameters {}", names.join(", ")))
+                .await;
             Ok(())
-        } else if body.as_slice().starts_with(b"# ") {
-            // xxx: body contains error message
-            Err(Error::Other("call failed for specific reason"))
+        } else if let Some(e) = update_error::parse(&body, &names) {
+            Err(e)
         } else {
             Err(Error::Other("call failed for unknown reason"))
         }
     }
 }
 
+/// Did `list_definitions()` fail because the device can't describe its parameters, rather than
+/// because the device couldn't be reached?
+///
+/// Older firmwares reject `action=listdefinitions` outright, answer it with an error message in
+/// place of XML, or return XML which doesn't follow the schema.
+pub(crate) fn definitions_unsupported(error: &Error) -> bool {
+    match error.without_context() {
+        Error::FeatureUnavailable | Error::UnparseableResponseError(_) | Error::Other(_) => true,
+        e @ Error::HttpRequestFailed(_) => {
+            !e.is_transient()
+                && !e.is_authentication_failure()
+                && e.connection_error_kind().is_none()
+        }
+        _ => false,
+    }
+}
+
+/// Split a parameter list into `name=value` assignments.
+///
+/// Devices emit values verbatim, so values like custom HTTP headers and text overlays may span
+/// several lines. A line begins a new assignment only if it starts with a plausible parameter name;
+/// any other line continues the previous value. Blank lines are kept only within a value.
+///
+/// Lines starting with `#` are comments or errors, like `# Error: Error -1 getting param in group
+/// 'root.Foo'`, and are skipped.
+pub(crate) fn split_assignments(contents: &str) -> Vec<(&str, String)> {
+    let mut assignments: Vec<(&str, String)> = Vec::new();
+    let mut blank_lines = 0;
+
+    for line in contents.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) if is_parameter_name(key.trim()) => {
+                assignments.push((key.trim(), value.to_string()));
+            }
+            _ => {
+                if let Some((_, value)) = assignments.last_mut() {
+                    for _ in 0..=blank_lines {
+                        value.push('\n');
+                    }
+                    value.push_str(line);
+                }
+            }
+        }
+        blank_lines = 0;
+    }
+
+    assignments
+}
+
+/// Is `name` a dotted parameter name, like `root.Brand.ProdNbr` or `Image.I0.Text.String`?
+fn is_parameter_name(name: &str) -> bool {
+    name.contains('.')
+        && name.split('.').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'_')
+        })
+}
+
 #[derive(Serialize)]
 struct ListParams<'a> {
     action: &'a str,
     #[serde(skip_serializing_if = "Option::is_none", rename = "listformat")]
     list_format: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "responseformat")]
+    response_format: Option<&'a str>,
     #[serde(
         rename = "group",
         skip_serializing_if = "Option::is_none",
@@ -152,7 +565,7 @@ where
 }
 
 /// A set of parameter definitions.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ParameterDefinitions {
     /// The version of the data structures used to describe the parameter definitions.
@@ -175,7 +588,7 @@ pub struct ParameterDefinitions {
 /// A group of parameter definitions.
 ///
 /// May contain parameters or additional groups.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ParameterGroupDefinition {
     /// The name of the parameter group.
@@ -206,7 +619,7 @@ impl ParameterGroupDefinition {
 }
 
 /// A parameter definition.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ParameterDefinition {
     /// The name of the parameter.
@@ -351,6 +764,35 @@ pub enum AccessLevel {
     RootAccess,
 }
 
+impl AccessLevel {
+    /// Does this access level grant everything `required` grants?
+    pub fn satisfies(self, required: AccessLevel) -> bool {
+        self >= required
+    }
+
+    /// Map a VAPIX user group name to the access level it grants.
+    pub fn from_group(group: &str) -> Option<AccessLevel> {
+        match group {
+            "viewer" => Some(AccessLevel::ViewerAccess),
+            "operator" => Some(AccessLevel::OperatorAccess),
+            "admin" => Some(AccessLevel::AdministratorAccess),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AccessLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            AccessLevel::Unprotected => "unprotected",
+            AccessLevel::ViewerAccess => "viewer",
+            AccessLevel::OperatorAccess => "operator",
+            AccessLevel::AdministratorAccess => "administrator",
+            AccessLevel::RootAccess => "root",
+        })
+    }
+}
+
 impl From<AccessLevel> for char {
     fn from(al: AccessLevel) -> Self {
         match al {
@@ -382,7 +824,7 @@ impl TryFrom<char> for AccessLevel {
 }
 
 /// A parameter type definition, describing flags and type information.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ParameterTypeDefinition {
     /// Is this parameter read-only?
@@ -415,7 +857,7 @@ pub struct ParameterTypeDefinition {
 }
 
 /// A type definition, describing a parameter's domain and encoding.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum TypeDefinition {
     /// A string, to be displayed as a text box.
@@ -445,7 +887,7 @@ pub enum TypeDefinition {
 }
 
 /// String parameter definition details.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StringParameterDefinition {
     /// The maximum length of the string.
@@ -454,7 +896,7 @@ pub struct StringParameterDefinition {
 }
 
 /// Password parameter definition details.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PasswordParameterDefinition {
     /// The maximum length of the string.
@@ -463,7 +905,7 @@ pub struct PasswordParameterDefinition {
 }
 
 /// Integer parameter definition details.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct IntParameterDefinition {
     /// The minimum value of the integer.
@@ -478,7 +920,7 @@ pub struct IntParameterDefinition {
 }
 
 /// Integer parameter range definiton details.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct IntParameterRangeDefinition {
     /// TODO: parse "0" and "1024-65534" into something more appropriate
@@ -486,7 +928,7 @@ pub struct IntParameterRangeDefinition {
 }
 
 /// Enumeration parameter definition details.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EnumParameterDefinition {
     /// A list of entries from which the parameter value must be selected.
@@ -495,7 +937,7 @@ pub struct EnumParameterDefinition {
 }
 
 /// An enumeration entry.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EnumEntryDefinition {
     /// The value of the parameter.
@@ -505,7 +947,7 @@ pub struct EnumEntryDefinition {
 }
 
 /// Boolean parameter definition details.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BoolParameterDefinition {
     /// The string value used to represent `true`.
@@ -518,6 +960,343 @@ pub struct BoolParameterDefinition {
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn split_arbitrary_lists(contents in "(\\PC{0,40}[\r\n]{0,2}){0,8}") {
+            let _ = super::split_assignments(&contents);
+        }
+
+        #[test]
+        fn split_formatted_lists(
+            parameters in prop::collection::btree_map(
+                "root(\\.[A-Za-z0-9_]{1,8}){2,4}",
+                "[^\r\n]{0,16}(\n[^\r\n=#]{1,16}){0,3}",
+                0..8,
+            )
+        ) {
+            let formatted = super::format_parameter_list(&parameters);
+            prop_assert_eq!(super::parse_parameter_list(&formatted), parameters);
+        }
+    }
+
+    #[tokio::test]
+    async fn root_prefix() {
+        let client = crate::mock_client(|_| {
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![
+                    b"root.Brand.ProdNbr=P5512\nBrand.ProdShortName=AXIS P5512\n".to_vec(),
+                ])
+        });
+
+        let params = client
+            .parameters()
+            .with_root_prefix(super::RootPrefix::Strip)
+            .list(None)
+            .await
+            .unwrap();
+        assert_eq!(
+            params.keys().collect::<Vec<_>>(),
+            vec!["Brand.ProdNbr", "Brand.ProdShortName"]
+        );
+
+        let params = client
+            .parameters()
+            .with_root_prefix(super::RootPrefix::Include)
+            .list(None)
+            .await
+            .unwrap();
+        assert_eq!(
+            params.keys().collect::<Vec<_>>(),
+            vec!["root.Brand.ProdNbr", "root.Brand.ProdShortName"]
+        );
+    }
+
+    #[tokio::test]
+    async fn response_format() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().query(),
+                Some("action=list&responseformat=rfc&group=root.Image.I0.Text")
+            );
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![
+                    b"root.Image.I0.Text.String=a%3Db%0Asecond line\r\n".to_vec(),
+                    b"root.Image.I0.Text.TextEnabled=yes\r\n".to_vec(),
+                ])
+        });
+
+        let params = client
+            .parameters()
+            .with_response_format(super::ResponseFormat::Rfc)
+            .list(Some(&["root.Image.I0.Text"][..]))
+            .await
+            .unwrap();
+        assert_eq!(
+            params.get("root.Image.I0.Text.String").map(String::as_str),
+            Some("a=b\nsecond line")
+        );
+        assert_eq!(
+            params
+                .get("root.Image.I0.Text.TextEnabled")
+                .map(String::as_str),
+            Some("yes")
+        );
+
+        // Devices which reject the format are asked again for plain text
+        let client = crate::mock_client(|req| match req.uri().query() {
+            Some("action=list&responseformat=rfc&group=root.Brand") => http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"# Error: Unknown argument responseformat\n".to_vec()]),
+            Some("action=list&group=root.Brand") => http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.Brand.ProdNbr=P5512%\n".to_vec()]),
+            other => panic!("unexpected query {:?}", other),
+        });
+
+        let params = client
+            .parameters()
+            .with_response_format(super::ResponseFormat::Rfc)
+            .list(Some(&["root.Brand"][..]))
+            .await
+            .unwrap();
+        assert_eq!(
+            params.get("root.Brand.ProdNbr").map(String::as_str),
+            Some("P5512%")
+        );
+    }
+
+    #[tokio::test]
+    async fn multiline_values() {
+        let client = crate::mock_client(|_| {
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![
+                    include_bytes!("../../fixtures/params/multiline.txt").to_vec()
+                ])
+        });
+
+        let params = client.parameters().list(None).await.unwrap();
+        assert_eq!(
+            params.keys().collect::<Vec<_>>(),
+            vec![
+                "root.Brand.ProdNbr",
+                "root.HTTP.CustomHeaders.H0.Name",
+                "root.HTTP.CustomHeaders.H0.Value",
+                "root.Image.I0.Text.String",
+                "root.Image.I0.Text.TextEnabled",
+                "root.Network.HostName",
+            ]
+        );
+        assert_eq!(
+            params["root.HTTP.CustomHeaders.H0.Value"],
+            "default-src 'self';\n  img-src 'self' data:"
+        );
+        assert_eq!(
+            params["root.Image.I0.Text.String"],
+            "Loading dock\n\nCamera=2 of 4"
+        );
+        assert_eq!(params["root.Network.HostName"], "axis-accc8e000000");
+    }
+
+    #[tokio::test]
+    async fn list_definitions_latin1() {
+        let client = crate::mock_client(|_| {
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/xml")
+                .body(vec![include_bytes!(
+                    "../../fixtures/xml/listdefinitions-latin1.xml"
+                )
+                .to_vec()])
+        });
+
+        let definitions = client.parameters().list_definitions(None).await.unwrap();
+        let text = &definitions.groups[0].groups[0].groups[0].groups[0].parameters[0];
+        assert_eq!(text.current_value.as_deref(), Some("Caf\u{e9} entr\u{e9}e"));
+    }
+
+    #[tokio::test]
+    async fn import() {
+        let client = crate::mock_client(|req| {
+            let body = match req.uri().query() {
+                Some("action=listdefinitions&listformat=xmlschema") => {
+                    r#"<?xml version="1.0"?>
+<parameterDefinitions version="1.0">
+  <group name="root">
+    <group name="Brand">
+      <parameter name="Brand" value="AXIS">
+        <type readonly="true"><string/></type>
+      </parameter>
+    </group>
+    <group name="Network">
+      <parameter name="HostName" value="axis-accc8e000000">
+        <type><string maxlen="64"/></type>
+      </parameter>
+    </group>
+  </group>
+</parameterDefinitions>"#
+                }
+                Some("action=update&root.Network.HostName=lobby") => "OK",
+                other => panic!("unexpected query {:?}", other),
+            };
+            let content_type = if body == "OK" {
+                "text/plain"
+            } else {
+                "text/xml"
+            };
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, content_type)
+                .body(vec![body.as_bytes().to_vec()])
+        });
+
+        let import = client
+            .parameters()
+            .import("root.Brand.Brand=ACME\nroot.Network.HostName=lobby\nroot.Missing.P=1\n")
+            .await
+            .unwrap();
+        assert_eq!(import.updated, vec!["root.Network.HostName".to_string()]);
+        assert_eq!(
+            import.skipped,
+            vec!["root.Brand.Brand".to_string(), "root.Missing.P".to_string()]
+        );
+        assert_eq!(
+            import.changes.changed.get("root.Network.HostName"),
+            Some(&super::ValueChange {
+                old: "axis-accc8e000000".into(),
+                new: "lobby".into(),
+            })
+        );
+        assert_eq!(import.changes.len(), 1);
+    }
+
+    /// A device without parameter definitions which lists `root.G.P00` through `root.G.P44`, and
+    /// which refuses to update `root.G.P25`.
+    fn chunked_import_client() -> crate::Client<impl crate::Transport> {
+        crate::mock_client(|req| {
+            let query = req.uri().query().unwrap_or_default();
+            let body = match query {
+                _ if query.starts_with("action=listdefinitions") => {
+                    "# Error: Error -1 getting param in group 'root'\r\n".to_string()
+                }
+                "action=list" => (0..45)
+                    .map(|i| format!("root.G.P{:02}=old\r\n", i))
+                    .collect(),
+                _ if query.starts_with("action=update&") => {
+                    assert!(
+                        !query.contains("root.G.P4"),
+                        "unexpected update {:?}",
+                        query
+                    );
+                    if query.contains("root.G.P25=") {
+                        "# Error: Error setting 'root.G.P25' to 'new'!\r\n".to_string()
+                    } else {
+                        "OK".to_string()
+                    }
+                }
+                other => panic!("unexpected query {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![body.into_bytes()])
+        })
+    }
+
+    /// Check the error from importing every parameter of `chunked_import_client()`.
+    fn assert_partial_import<T: std::fmt::Debug>(result: crate::Result<T>) {
+        let names = |range: std::ops::Range<usize>| -> Vec<String> {
+            range.map(|i| format!("root.G.P{:02}", i)).collect()
+        };
+        match result {
+            Err(crate::Error::PartialImport {
+                applied,
+                error,
+                unattempted,
+            }) => {
+                let mut expected = names(0..25);
+                expected.extend(names(26..40));
+                assert_eq!(applied, expected);
+                assert_eq!(unattempted, names(40..45));
+                match *error {
+                    crate::Error::ParameterUpdate { failed, .. } => {
+                        assert_eq!(failed.len(), 1);
+                        assert_eq!(failed[0].parameter.as_deref(), Some("root.G.P25"));
+                    }
+                    other => panic!("unexpected error {:?}", other),
+                }
+            }
+            other => panic!("expected PartialImport, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn import_stops_at_failed_chunk() {
+        let client = chunked_import_client();
+        let contents: String = (0..45).map(|i| format!("root.G.P{:02}=new\n", i)).collect();
+        assert_partial_import(client.parameters().import(&contents).await);
+    }
+
+    #[tokio::test]
+    async fn restore_stops_at_failed_chunk() {
+        let client = chunked_import_client();
+        let export = super::ParameterExport {
+            parameters: (0..45)
+                .map(|i| (format!("G.P{:02}", i), "new".to_string()))
+                .collect(),
+            values_only: true,
+        };
+        assert_partial_import(client.parameters().restore(&export).await);
+    }
+
+    #[tokio::test]
+    async fn values_only() {
+        let client = crate::mock_client(|req| {
+            let (content_type, body) = match req.uri().query() {
+                Some(q) if q.starts_with("action=listdefinitions") => (
+                    "text/plain",
+                    "# Error: Error -1 getting param in group 'root'\r\n",
+                ),
+                Some("action=list") => (
+                    "text/plain",
+                    "root.Brand.ProdNbr=M3004\r\nroot.Network.HostName=axis-accc8e000000\r\n",
+                ),
+                Some("action=update&root.Network.HostName=lobby") => ("text/plain", "OK"),
+                other => panic!("unexpected query {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, content_type)
+                .body(vec![body.as_bytes().to_vec()])
+        });
+
+        let export = client.parameters().backup().await.unwrap();
+        assert!(export.values_only);
+        assert_eq!(export.parameters.len(), 2);
+        let contents = export.to_parameter_list();
+        assert!(contents.starts_with(super::VALUES_ONLY_HEADER));
+
+        let import = client
+            .parameters()
+            .import(&contents.replace("axis-accc8e000000", "lobby"))
+            .await
+            .unwrap();
+        assert!(import.values_only);
+        assert_eq!(import.updated, vec!["root.Network.HostName".to_string()]);
+        assert_eq!(import.skipped, vec!["root.Brand.ProdNbr".to_string()]);
+        assert_eq!(import.changes.len(), 1);
+    }
+
     #[test]
     fn list() {
         crate::test_with_devices(|test_device| async move {
@@ -555,6 +1334,39 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn list_skips_error_lines() {
+        let device = crate::mock_client(|req| {
+            let query = req.uri().query().unwrap_or_default();
+            let body: &[u8] = match query {
+                _ if query.contains("group=root.Brand") => {
+                    b"root.Brand.Brand=AXIS\n\
+                    # Error: Error -1 getting param in group 'root.Brand.Missing'\n\
+                    root.Brand.ProdNbr=P5512\n"
+                }
+                _ => b"root.Brand.Brand=AXIS\xff\n",
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![body.to_vec()])
+        });
+
+        let params = device
+            .parameters()
+            .list(Some(&["root.Brand"]))
+            .await
+            .unwrap();
+        assert_eq!(params.len(), 2);
+        assert_eq!(params["root.Brand.Brand"], "AXIS");
+        assert_eq!(params["root.Brand.ProdNbr"], "P5512");
+
+        assert!(matches!(
+            device.parameters().list(None).await,
+            Err(crate::Error::Other("invalid UTF-8"))
+        ));
+    }
+
     #[test]
     fn list_definitions() {
         crate::test_with_devices(|test_device| async move {
@@ -584,6 +1396,11 @@ mod tests {
             assert_eq!(brand_params.groups[0].groups.len(), 1);
             assert_eq!(brand_and_firmware_params.groups[0].groups.len(), 2);
 
+            // definitions round-trip through JSON
+            let json = serde_json::to_value(&all_params)?;
+            let round_tripped: super::ParameterDefinitions = serde_json::from_value(json.clone())?;
+            assert_eq!(serde_json::to_value(&round_tripped)?, json);
+
             Ok(())
         });
     }
@@ -632,9 +1449,60 @@ mod tests {
             .update(vec![("foo.bar", "baz quxx")])
             .await;
         match response {
-            Err(crate::Error::Other(_)) => {}
+            Err(crate::Error::ParameterUpdate { failed, succeeded }) => {
+                assert_eq!(
+                    failed,
+                    vec![crate::v3::parameters::ParameterUpdateError {
+                        parameter: Some("foo.bar".into()),
+                        message: "Error setting 'foo.bar' to 'baz quxx'!".into(),
+                    }]
+                );
+                assert!(succeeded.is_empty());
+            }
             Ok(()) => panic!("update should fail"),
             Err(e) => panic!("update should fail with a different error: {}", e),
         };
     }
+
+    #[tokio::test]
+    async fn dynamic_groups() {
+        let client = crate::mock_client(|req| {
+            let body: &[u8] = match req.uri().query() {
+                Some("action=add&template=motion&group=Motion&force=yes&Motion.M.Name=Door") => {
+                    b"M3 OK\r\n"
+                }
+                Some("action=add&template=event&group=Event") => b"# Error: Maximum reached\r\n",
+                Some("action=remove&group=Motion.M3%2Croot.Motion.M4") => b"OK",
+                other => panic!("unexpected query {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![body.to_vec()])
+        });
+        let parameters = client.parameters();
+
+        let group = parameters
+            .add_group("motion", "Motion", vec![("Motion.M.Name", "Door")], true)
+            .await
+            .unwrap();
+        assert_eq!(group, super::DynamicGroup::new("Motion", "M", 3));
+
+        let empty: Vec<(&str, &str)> = Vec::new();
+        assert!(parameters
+            .add_group("event", "Event", empty, false)
+            .await
+            .is_err());
+
+        parameters
+            .remove_group(vec!["Motion.M3".to_string(), "root.Motion.M4".to_string()])
+            .await
+            .unwrap();
+
+        let client = client.with_read_only(true);
+        match client.parameters().remove_group(vec!["Motion.M3"]).await {
+            Err(crate::Error::ReadOnlyMode) => {}
+            other => panic!("expected ReadOnlyMode, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,193 @@
+//! The plain-text parameter list format produced by the device's web interface.
+//!
+//! Each line contains a single `root.Group.Parameter=value` assignment, exactly as returned by
+//! `param.cgi?action=list`. Values containing newlines continue onto the following lines. Blank
+//! lines outside of values and lines starting with `#` are ignored.
+//!
+//! Exports from devices which cannot list their parameter definitions begin with
+//! `VALUES_ONLY_HEADER`. Like any other comment, it is ignored on import.
+
+use super::*;
+
+/// The comment which begins the parameter list of an export lacking parameter definitions.
+pub const VALUES_ONLY_HEADER: &str =
+    "# values only: this device did not provide parameter definitions";
+
+/// Every parameter's value, as exported by `Parameters::backup()`.
+#[derive(Debug, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterExport {
+    /// The parameters, with values exactly as listed by the device.
+    pub parameters: BTreeMap<String, String>,
+    /// Was the device unable to list its parameter definitions? If so, nothing is known about
+    /// the parameters beyond their values: not their types, nor which are writable.
+    pub values_only: bool,
+}
+
+impl ParameterExport {
+    /// Format the export in the format produced by the device's web interface, beginning with
+    /// `VALUES_ONLY_HEADER` if the export lacks parameter definitions.
+    pub fn to_parameter_list(&self) -> String {
+        let list = format_parameter_list(&self.parameters);
+        if self.values_only {
+            format!("{}\n{}", VALUES_ONLY_HEADER, list)
+        } else {
+            list
+        }
+    }
+}
+
+/// The outcome of importing a parameter list.
+#[derive(Debug, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterImport {
+    /// The parameters which were written to the device.
+    pub updated: Vec<String>,
+    /// The parameters which were not written, because they are read-only or do not exist on the
+    /// device.
+    pub skipped: Vec<String>,
+    /// How the written parameters differ from their values before the import. Parameters which
+    /// were written with their existing values are not included.
+    pub changes: ConfigDiff,
+    /// Was the device unable to list its parameter definitions? If so, parameters were judged
+    /// writable by name alone, and the device may have rejected some of them.
+    pub values_only: bool,
+}
+
+/// Parse a parameter list in the format produced by the device's web interface.
+///
+/// Values spanning several lines are supported, but lines within a value which start with `#`
+/// are taken to be comments.
+pub fn parse_parameter_list(contents: &str) -> BTreeMap<String, String> {
+    split_assignments(contents)
+        .into_iter()
+        .map(|(key, value)| (qualified(key), value))
+        .collect()
+}
+
+/// Format parameters in the format produced by the device's web interface.
+pub fn format_parameter_list(parameters: &BTreeMap<String, String>) -> String {
+    let mut output = String::new();
+    for (key, value) in parameters {
+        output.push_str(&qualified(key));
+        output.push('=');
+        output.push_str(value);
+        output.push('\n');
+    }
+    output
+}
+
+pub(super) fn qualified(key: &str) -> String {
+    if key.starts_with("root.") {
+        key.to_string()
+    } else {
+        format!("root.{}", key)
+    }
+}
+
+/// Return the fully-qualified names of every writable parameter in `definitions`, along with its
+/// current value, if any.
+pub(super) fn writable_parameters(
+    definitions: &ParameterDefinitions,
+) -> BTreeMap<String, Option<String>> {
+    fn walk(
+        prefix: &str,
+        group: &ParameterGroupDefinition,
+        into: &mut BTreeMap<String, Option<String>>,
+    ) {
+        let prefix = if prefix.is_empty() {
+            group.name.clone()
+        } else {
+            format!("{}.{}", prefix, group.name)
+        };
+
+        for parameter in &group.parameters {
+            let read_only = parameter
+                .parameter_type
+                .as_ref()
+                .map(|t| t.read_only == Some(true) || t.constant == Some(true))
+                .unwrap_or(false);
+            if !read_only {
+                into.insert(
+                    qualified(&format!("{}.{}", prefix, parameter.name)),
+                    parameter.current_value.clone(),
+                );
+            }
+        }
+
+        for nested in &group.groups {
+            walk(&prefix, nested, into);
+        }
+    }
+
+    let mut writable = BTreeMap::new();
+    for group in &definitions.groups {
+        walk("", group, &mut writable);
+    }
+    writable
+}
+
+/// Return the fully-qualified names of every parameter in a `list()` which is presumed writable,
+/// along with its current value, for devices which cannot list their parameter definitions.
+///
+/// The `root.Brand` and `root.Properties` groups describe the device itself and are never
+/// writable.
+pub(super) fn listed_parameters(
+    parameters: &BTreeMap<String, String>,
+) -> BTreeMap<String, Option<String>> {
+    parameters
+        .iter()
+        .map(|(key, value)| (qualified(key), value))
+        .filter(|(key, _)| !key.starts_with("root.Brand.") && !key.starts_with("root.Properties."))
+        .map(|(key, value)| (key, Some(value.clone())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let contents = "# exported from the web interface\r\n\
+root.Brand.Brand=AXIS\r\n\
+\r\n\
+root.Network.HostName=axis-accc8e000000\r\n\
+Image.I0.Text.String=a=b\r\n";
+
+        let parameters = parse_parameter_list(contents);
+        assert_eq!(
+            parameters
+                .get("root.Image.I0.Text.String")
+                .map(String::as_str),
+            Some("a=b")
+        );
+        assert_eq!(parameters.len(), 3);
+
+        assert_eq!(
+            format_parameter_list(&parameters),
+            "root.Brand.Brand=AXIS\n\
+root.Image.I0.Text.String=a=b\n\
+root.Network.HostName=axis-accc8e000000\n"
+        );
+        assert_eq!(
+            parse_parameter_list(&format_parameter_list(&parameters)),
+            parameters
+        );
+    }
+
+    #[test]
+    fn multiline_round_trip() {
+        let parameters =
+            parse_parameter_list(include_str!("../../../fixtures/params/multiline.txt"));
+        assert_eq!(parameters.len(), 6);
+        assert_eq!(
+            parameters["root.Image.I0.Text.String"],
+            "Loading dock\n\nCamera=2 of 4"
+        );
+        assert_eq!(
+            parse_parameter_list(&format_parameter_list(&parameters)),
+            parameters
+        );
+    }
+}
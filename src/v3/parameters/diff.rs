@@ -0,0 +1,174 @@
+//! Structured differences between two sets of parameter values.
+//!
+//! `ConfigDiff` is the common change representation for configuration features: importing a
+//! backup reports the changes it made as a `ConfigDiff`, and `Parameters::changes_since()`
+//! compares the device's current values against an earlier snapshot.
+
+use super::*;
+
+/// The differences between two sets of parameter values, keyed by parameter name.
+#[derive(Debug, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigDiff {
+    /// Parameters which are present only in the newer set, with their values.
+    pub added: BTreeMap<String, String>,
+    /// Parameters which are present only in the older set, with their last values.
+    pub removed: BTreeMap<String, String>,
+    /// Parameters whose values differ between the two sets.
+    pub changed: BTreeMap<String, ValueChange>,
+}
+
+/// The old and new values of a changed parameter.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ValueChange {
+    pub old: String,
+    pub new: String,
+}
+
+impl ConfigDiff {
+    /// Compare two sets of parameter values.
+    ///
+    /// Names are compared exactly, so both sets should use the same `RootPrefix` convention.
+    pub fn between(old: &BTreeMap<String, String>, new: &BTreeMap<String, String>) -> Self {
+        let mut diff = ConfigDiff::default();
+        for (name, old_value) in old {
+            match new.get(name) {
+                None => {
+                    diff.removed.insert(name.clone(), old_value.clone());
+                }
+                Some(new_value) if new_value != old_value => {
+                    diff.changed.insert(
+                        name.clone(),
+                        ValueChange {
+                            old: old_value.clone(),
+                            new: new_value.clone(),
+                        },
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+        for (name, new_value) in new {
+            if !old.contains_key(name) {
+                diff.added.insert(name.clone(), new_value.clone());
+            }
+        }
+        diff
+    }
+
+    /// Are the two sets of values identical?
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// The number of parameters which differ.
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.changed.len()
+    }
+
+    /// The names of every parameter which differs, in order.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .added
+            .keys()
+            .chain(self.removed.keys())
+            .chain(self.changed.keys())
+            .map(String::as_str)
+            .collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// Summarizes the diff by parameter name, e.g. `changed Network.HostName; added Motion.M1.Name`.
+///
+/// Values are omitted since they may contain secrets, so the summary is suitable for audit logs.
+impl fmt::Display for ConfigDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no changes");
+        }
+
+        let mut first = true;
+        for (verb, names) in [
+            ("changed", self.changed.keys().collect::<Vec<_>>()),
+            ("added", self.added.keys().collect()),
+            ("removed", self.removed.keys().collect()),
+        ]
+        .iter()
+        {
+            if names.is_empty() {
+                continue;
+            }
+            if !first {
+                write!(f, "; ")?;
+            }
+            first = false;
+            write!(f, "{} ", verb)?;
+            for (i, name) in names.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn between() {
+        let old = params(&[
+            ("Brand.Brand", "AXIS"),
+            ("Network.HostName", "axis-accc8e000000"),
+            ("Motion.M0.Name", "Door"),
+        ]);
+        let new = params(&[
+            ("Brand.Brand", "AXIS"),
+            ("Network.HostName", "lobby"),
+            ("Motion.M1.Name", "Window"),
+        ]);
+
+        let diff = ConfigDiff::between(&old, &new);
+        assert_eq!(diff.added, params(&[("Motion.M1.Name", "Window")]));
+        assert_eq!(diff.removed, params(&[("Motion.M0.Name", "Door")]));
+        assert_eq!(
+            diff.changed.get("Network.HostName"),
+            Some(&ValueChange {
+                old: "axis-accc8e000000".into(),
+                new: "lobby".into(),
+            })
+        );
+        assert_eq!(diff.len(), 3);
+        assert_eq!(
+            diff.names(),
+            vec!["Motion.M0.Name", "Motion.M1.Name", "Network.HostName"]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "changed Network.HostName; added Motion.M1.Name; removed Motion.M0.Name"
+        );
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(
+            json["changed"]["Network.HostName"],
+            serde_json::json!({"old": "axis-accc8e000000", "new": "lobby"})
+        );
+        assert_eq!(serde_json::from_value::<ConfigDiff>(json).unwrap(), diff);
+
+        let unchanged = ConfigDiff::between(&old, &old);
+        assert!(unchanged.is_empty());
+        assert_eq!(unchanged.to_string(), "no changes");
+    }
+}
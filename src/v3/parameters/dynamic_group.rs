@@ -0,0 +1,225 @@
+//! Handles to instances of dynamic parameter groups.
+//!
+//! Some parameter groups, like `root.Motion` or `root.Event`, contain a variable number of
+//! numbered subgroups created from a template by `param.cgi?action=add`. The device chooses the
+//! number and reports the name of the new subgroup in its response.
+
+use super::*;
+
+/// A handle to an instance of a dynamic parameter group, like `root.Motion.M3`.
+///
+/// A `DynamicGroup` formats the names of the group and its parameters, so callers never need to
+/// assemble them by hand.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DynamicGroup {
+    parent: String,
+    prefix: String,
+    index: u32,
+}
+
+impl DynamicGroup {
+    /// Construct a handle from its parts, e.g. `("Motion", "M", 3)` for `root.Motion.M3`.
+    ///
+    /// The `root.` prefix is optional in `parent`.
+    pub fn new(parent: &str, prefix: &str, index: u32) -> Self {
+        Self {
+            parent: parent.trim_start_matches("root.").to_string(),
+            prefix: prefix.to_string(),
+            index,
+        }
+    }
+
+    /// Parse the response to `param.cgi?action=add&group=<parent>`, which names the new group
+    /// either relative to `parent`, like `M3 OK`, or in full, like `root.Motion.M3 OK`.
+    pub fn from_add_response(parent: &str, body: &[u8]) -> Result<Self> {
+        let body = std::str::from_utf8(body)
+            .map_err(|_| Error::Other("dynamic group creation returned invalid UTF-8"))?
+            .trim();
+        if body.starts_with('#') {
+            return Err(Error::Other("dynamic group creation failed"));
+        }
+
+        let name = match body.strip_suffix("OK") {
+            Some(name) => name.trim(),
+            None => {
+                return Err(Error::Other(
+                    "dynamic group creation returned no group name",
+                ))
+            }
+        };
+
+        let parent = parent.trim_start_matches("root.");
+        let relative = name
+            .trim_start_matches("root.")
+            .strip_prefix(parent)
+            .and_then(|rest| rest.strip_prefix('.'))
+            .unwrap_or(name);
+
+        Self::parse_child(parent, relative).ok_or(Error::Other(
+            "dynamic group creation returned an unparseable group name",
+        ))
+    }
+
+    /// Parse a subgroup name like `M3` relative to `parent`.
+    fn parse_child(parent: &str, child: &str) -> Option<Self> {
+        let digits = child.len() - child.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+        let (prefix, index) = child.split_at(child.len() - digits);
+        if parent.is_empty()
+            || prefix.is_empty()
+            || !prefix
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return None;
+        }
+        Some(Self::new(parent, prefix, index.parse().ok()?))
+    }
+
+    /// The parent group, without the `root.` prefix, e.g. `Motion`.
+    pub fn parent(&self) -> &str {
+        &self.parent
+    }
+
+    /// The subgroup name's prefix, e.g. `M`.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// The device-assigned index of this instance, e.g. `3`.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The name of this instance relative to its parent, e.g. `M3`, as expected by
+    /// `param.cgi?action=remove`.
+    pub fn child_name(&self) -> String {
+        format!("{}{}", self.prefix, self.index)
+    }
+
+    /// The fully-qualified name of this group, e.g. `root.Motion.M3`.
+    pub fn name(&self) -> String {
+        self.to_string()
+    }
+
+    /// The fully-qualified name of a parameter in this group, e.g. `root.Motion.M3.Name`.
+    pub fn parameter(&self, name: &str) -> String {
+        format!("{}.{}", self, name)
+    }
+}
+
+impl fmt::Display for DynamicGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "root.{}.{}{}", self.parent, self.prefix, self.index)
+    }
+}
+
+/// An error returned when parsing a `DynamicGroup` name fails.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DynamicGroupParseError;
+
+impl fmt::Display for DynamicGroupParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid dynamic parameter group name")
+    }
+}
+
+impl std::error::Error for DynamicGroupParseError {}
+
+impl FromStr for DynamicGroup {
+    type Err = DynamicGroupParseError;
+
+    /// Parse a fully-qualified group name, with or without the `root.` prefix.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim_start_matches("root.");
+        let (parent, child) = match s.rfind('.') {
+            Some(i) => (&s[..i], &s[i + 1..]),
+            None => return Err(DynamicGroupParseError),
+        };
+        Self::parse_child(parent, child).ok_or(DynamicGroupParseError)
+    }
+}
+
+impl TryFrom<String> for DynamicGroup {
+    type Error = DynamicGroupParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<DynamicGroup> for String {
+    fn from(group: DynamicGroup) -> Self {
+        group.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        let group = DynamicGroup::new("root.Motion", "M", 3);
+        assert_eq!(group.name(), "root.Motion.M3");
+        assert_eq!(group.child_name(), "M3");
+        assert_eq!(group.parameter("Name"), "root.Motion.M3.Name");
+        assert_eq!("Motion.M3".parse::<DynamicGroup>(), Ok(group.clone()));
+        assert_eq!("root.Motion.M3".parse::<DynamicGroup>(), Ok(group));
+        assert_eq!(
+            "root.StreamProfile.S12"
+                .parse::<DynamicGroup>()
+                .unwrap()
+                .index(),
+            12
+        );
+        assert_eq!(
+            "root.Image.I0.Overlay.Text.T1"
+                .parse::<DynamicGroup>()
+                .unwrap()
+                .parent(),
+            "Image.I0.Overlay.Text"
+        );
+
+        for bad in &[
+            "",
+            "M3",
+            "root.Motion",
+            "root.Motion.3",
+            "root.Motion.M",
+            "root.Motion.M-3",
+        ] {
+            assert_eq!(
+                bad.parse::<DynamicGroup>(),
+                Err(DynamicGroupParseError),
+                "{:?}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn add_response() {
+        let expected = DynamicGroup::new("Motion", "M", 3);
+        for body in &["M3 OK", "M3 OK\r\n", "Motion.M3 OK", "root.Motion.M3 OK\n"] {
+            assert_eq!(
+                DynamicGroup::from_add_response("root.Motion", body.as_bytes()).unwrap(),
+                expected,
+                "{:?}",
+                body
+            );
+        }
+
+        for body in &["", "OK", "# Error: Maximum number of groups reached", "M3"] {
+            assert!(DynamicGroup::from_add_response("Motion", body.as_bytes()).is_err());
+        }
+    }
+
+    #[test]
+    fn serde() {
+        let group = DynamicGroup::new("Event", "E", 0);
+        let json = serde_json::to_string(&group).unwrap();
+        assert_eq!(json, r#""root.Event.E0""#);
+        assert_eq!(serde_json::from_str::<DynamicGroup>(&json).unwrap(), group);
+    }
+}
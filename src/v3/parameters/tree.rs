@@ -0,0 +1,521 @@
+//! A hierarchical view of parameters, with values checked against their definitions.
+//!
+//! `Parameters::list()` returns a flat map of names to strings. A `ParameterTree` arranges the
+//! same parameters into their groups, keeps each parameter's definition when one is available,
+//! and interprets values as the types the definitions describe.
+
+use super::*;
+
+/// A snapshot of a device's parameters, arranged into groups.
+///
+/// Names within a tree omit the `root.` prefix, and lookups accept names with or without it.
+#[derive(Debug, Default)]
+pub struct ParameterTree {
+    root: ParameterGroup,
+}
+
+/// A group of parameters within a `ParameterTree`.
+#[derive(Debug, Default)]
+pub struct ParameterGroup {
+    name: String,
+    groups: BTreeMap<String, ParameterGroup>,
+    parameters: BTreeMap<String, Parameter>,
+}
+
+/// A parameter within a `ParameterTree`.
+#[derive(Debug)]
+pub struct Parameter {
+    name: String,
+    value: Option<String>,
+    definition: Option<ParameterDefinition>,
+}
+
+/// An error returned when a parameter's value can't be interpreted as the requested type.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParameterValueError {
+    /// The parameter has no value, e.g. because it is write-only.
+    NoValue,
+    /// The parameter's definition gives it a different type.
+    WrongType,
+    /// The value is not valid for the requested type, or is not one of the values permitted by
+    /// the parameter's definition.
+    Invalid(String),
+    /// The value is an integer outside the range permitted by the parameter's definition.
+    OutOfRange(i64),
+}
+
+impl fmt::Display for ParameterValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParameterValueError::NoValue => f.write_str("parameter has no value"),
+            ParameterValueError::WrongType => {
+                f.write_str("parameter is defined with a different type")
+            }
+            ParameterValueError::Invalid(value) => write!(f, "invalid parameter value {:?}", value),
+            ParameterValueError::OutOfRange(value) => {
+                write!(f, "parameter value {} is out of range", value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParameterValueError {}
+
+fn strip_root(name: &str) -> &str {
+    if name == "root" {
+        ""
+    } else {
+        name.trim_start_matches("root.")
+    }
+}
+
+impl ParameterTree {
+    /// Arrange parameter values, as returned by `Parameters::list()`, into a tree.
+    ///
+    /// The parameters have no definitions, so their values are interpreted by convention.
+    pub fn from_values(values: &BTreeMap<String, String>) -> Self {
+        let mut tree = ParameterTree::default();
+        for (name, value) in values {
+            tree.insert(Parameter {
+                name: strip_root(name).to_string(),
+                value: Some(value.clone()),
+                definition: None,
+            });
+        }
+        tree
+    }
+
+    /// Arrange parameter definitions, as returned by `Parameters::list_definitions()`, into a
+    /// tree.
+    pub fn from_definitions(definitions: ParameterDefinitions) -> Self {
+        fn walk(prefix: &str, group: ParameterGroupDefinition, tree: &mut ParameterTree) {
+            let prefix = match (prefix, strip_root(&group.name)) {
+                ("", name) => name.to_string(),
+                (prefix, name) => format!("{}.{}", prefix, name),
+            };
+            tree.group_mut(&prefix);
+            for mut definition in group.parameters {
+                let name = match prefix.as_str() {
+                    "" => definition.name.clone(),
+                    prefix => format!("{}.{}", prefix, definition.name),
+                };
+                tree.insert(Parameter {
+                    name,
+                    value: definition.current_value.take(),
+                    definition: Some(definition),
+                });
+            }
+            for nested in group.groups {
+                walk(&prefix, nested, tree);
+            }
+        }
+
+        let mut tree = ParameterTree::default();
+        for group in definitions.groups {
+            walk("", group, &mut tree);
+        }
+        tree
+    }
+
+    /// The top-level group, which contains every other group.
+    pub fn root(&self) -> &ParameterGroup {
+        &self.root
+    }
+
+    /// Find a group by name, like `Network` or `Network.Bonjour`.
+    pub fn group(&self, name: &str) -> Option<&ParameterGroup> {
+        self.root.group(strip_root(name))
+    }
+
+    /// Find a parameter by name, like `Network.Bonjour.Enabled`.
+    pub fn parameter(&self, name: &str) -> Option<&Parameter> {
+        self.root.parameter(strip_root(name))
+    }
+
+    /// Every parameter with a value, keyed by name without the `root.` prefix.
+    pub fn values(&self) -> BTreeMap<String, String> {
+        fn walk(group: &ParameterGroup, into: &mut BTreeMap<String, String>) {
+            for parameter in group.parameters.values() {
+                if let Some(value) = &parameter.value {
+                    into.insert(parameter.name.clone(), value.clone());
+                }
+            }
+            for nested in group.groups.values() {
+                walk(nested, into);
+            }
+        }
+
+        let mut values = BTreeMap::new();
+        walk(&self.root, &mut values);
+        values
+    }
+
+    /// Compare this snapshot against a newer one.
+    pub fn diff(&self, newer: &ParameterTree) -> ConfigDiff {
+        ConfigDiff::between(&self.values(), &newer.values())
+    }
+
+    fn group_mut(&mut self, name: &str) -> &mut ParameterGroup {
+        let mut group = &mut self.root;
+        if name.is_empty() {
+            return group;
+        }
+        let mut prefix = String::new();
+        for child in name.split('.') {
+            if !prefix.is_empty() {
+                prefix.push('.');
+            }
+            prefix.push_str(child);
+            group = group
+                .groups
+                .entry(child.to_string())
+                .or_insert_with(|| ParameterGroup {
+                    name: prefix.clone(),
+                    ..ParameterGroup::default()
+                });
+        }
+        group
+    }
+
+    fn insert(&mut self, parameter: Parameter) {
+        let (group, name) = match parameter.name.rfind('.') {
+            Some(i) => (&parameter.name[..i], &parameter.name[i + 1..]),
+            None => ("", parameter.name.as_str()),
+        };
+        let name = name.to_string();
+        let group = group.to_string();
+        self.group_mut(&group).parameters.insert(name, parameter);
+    }
+}
+
+impl ParameterGroup {
+    /// The name of this group, like `Network.Bonjour`, or an empty string for the root.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Find a nested group by name, which may span several levels, like `Bonjour` or
+    /// `I0.Text`.
+    pub fn group(&self, name: &str) -> Option<&ParameterGroup> {
+        if name.is_empty() {
+            return Some(self);
+        }
+        name.split('.')
+            .try_fold(self, |group, child| group.groups.get(child))
+    }
+
+    /// Find a parameter in this group or a nested group by name, like `Enabled` or
+    /// `Bonjour.Enabled`.
+    pub fn parameter(&self, name: &str) -> Option<&Parameter> {
+        match name.rfind('.') {
+            Some(i) => self.group(&name[..i])?.parameters.get(&name[i + 1..]),
+            None => self.parameters.get(name),
+        }
+    }
+
+    /// The groups nested directly within this group, in order.
+    pub fn groups(&self) -> impl Iterator<Item = &ParameterGroup> {
+        self.groups.values()
+    }
+
+    /// The parameters directly within this group, in order.
+    pub fn parameters(&self) -> impl Iterator<Item = &Parameter> {
+        self.parameters.values()
+    }
+}
+
+impl Parameter {
+    /// The name of this parameter, like `Network.Bonjour.Enabled`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The parameter's value, expressed as a string.
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+
+    /// The parameter's definition, if the tree was built from definitions.
+    pub fn definition(&self) -> Option<&ParameterDefinition> {
+        self.definition.as_ref()
+    }
+
+    fn type_definition(&self) -> Option<&TypeDefinition> {
+        self.definition
+            .as_ref()
+            .and_then(|d| d.parameter_type.as_ref())
+            .map(|t| &t.type_definition)
+    }
+
+    fn value_or_err(&self) -> Result<&str, ParameterValueError> {
+        self.value().ok_or(ParameterValueError::NoValue)
+    }
+
+    /// Interpret this parameter as a `bool`.
+    ///
+    /// Parameters defined as booleans must have one of the two values in their definition.
+    /// Parameters without a definition may be `yes`, `no`, `true`, or `false`.
+    pub fn as_bool(&self) -> Result<bool, ParameterValueError> {
+        let value = self.value_or_err()?;
+        match self.type_definition() {
+            Some(TypeDefinition::Bool(td)) if value == td.true_value => Ok(true),
+            Some(TypeDefinition::Bool(td)) if value == td.false_value => Ok(false),
+            Some(TypeDefinition::Bool(_)) => Err(ParameterValueError::Invalid(value.into())),
+            Some(_) => Err(ParameterValueError::WrongType),
+            None => match value {
+                "yes" | "true" => Ok(true),
+                "no" | "false" => Ok(false),
+                other => Err(ParameterValueError::Invalid(other.into())),
+            },
+        }
+    }
+
+    /// Interpret this parameter as an integer.
+    ///
+    /// Parameters defined as integers must lie within the minimum, maximum, and ranges in their
+    /// definition.
+    pub fn as_int(&self) -> Result<i64, ParameterValueError> {
+        let value = self.value_or_err()?;
+        let td = match self.type_definition() {
+            Some(TypeDefinition::Int(td)) => Some(td),
+            Some(_) => return Err(ParameterValueError::WrongType),
+            None => None,
+        };
+        let n: i64 = value
+            .trim()
+            .parse()
+            .map_err(|_| ParameterValueError::Invalid(value.into()))?;
+
+        if let Some(td) = td {
+            let in_bounds =
+                !matches!(td.min, Some(min) if n < min) && !matches!(td.max, Some(max) if n > max);
+            let in_ranges = match &td.range_entries {
+                Some(entries) if !entries.is_empty() => entries
+                    .iter()
+                    .any(|entry| range_contains(&entry.value, n).unwrap_or(true)),
+                _ => true,
+            };
+            if !in_bounds || !in_ranges {
+                return Err(ParameterValueError::OutOfRange(n));
+            }
+        }
+        Ok(n)
+    }
+
+    /// Interpret this parameter as one of a set of values, parsed with `FromStr`.
+    ///
+    /// Parameters defined as enumerations must have one of the values in their definition. Use
+    /// `as_enum::<String>()` to check the value without converting it.
+    pub fn as_enum<E: FromStr>(&self) -> Result<E, ParameterValueError> {
+        let value = self.value_or_err()?;
+        match self.type_definition() {
+            Some(TypeDefinition::Enum(td)) if !td.values.iter().any(|e| e.value == value) => {
+                return Err(ParameterValueError::Invalid(value.into()))
+            }
+            Some(TypeDefinition::Enum(_)) | None => {}
+            Some(_) => return Err(ParameterValueError::WrongType),
+        }
+        value
+            .parse()
+            .map_err(|_| ParameterValueError::Invalid(value.into()))
+    }
+}
+
+/// Does an integer range entry like `1024-65534` or `0` contain `n`?
+///
+/// Returns `None` if the entry can't be parsed.
+fn range_contains(entry: &str, n: i64) -> Option<bool> {
+    let entry = entry.trim();
+    // Skip the first character so that a leading minus sign isn't taken as the separator
+    match entry.char_indices().skip(1).find(|(_, c)| *c == '-') {
+        Some((i, _)) => {
+            let min: i64 = entry[..i].trim().parse().ok()?;
+            let max: i64 = entry[i + 1..].trim().parse().ok()?;
+            Some(n >= min && n <= max)
+        }
+        None => Some(entry.parse::<i64>().ok()? == n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFINITIONS: &str = r#"<?xml version="1.0"?>
+<parameterDefinitions version="1.0">
+  <group name="root">
+    <group name="Network">
+      <parameter name="HostName" value="axis-accc8e000000">
+        <type><string maxlen="64"/></type>
+      </parameter>
+      <group name="Bonjour">
+        <parameter name="Enabled" value="yes">
+          <type><bool true="yes" false="no"/></type>
+        </parameter>
+        <parameter name="FriendlyName" value="Lobby">
+          <type><string/></type>
+        </parameter>
+      </group>
+      <group name="HTTP">
+        <parameter name="Port" value="8080">
+          <type><int min="1" max="65535"/></type>
+        </parameter>
+        <parameter name="AuthenticationPolicy" value="digest">
+          <type><enum><entry value="basic"/><entry value="digest"/></enum></type>
+        </parameter>
+      </group>
+    </group>
+  </group>
+</parameterDefinitions>"#;
+
+    fn tree() -> ParameterTree {
+        ParameterTree::from_definitions(crate::xml::from_slice(DEFINITIONS.as_bytes()).unwrap())
+    }
+
+    #[test]
+    fn navigation() {
+        let tree = tree();
+        let network = tree.group("Network").unwrap();
+        assert_eq!(network.name(), "Network");
+        assert_eq!(
+            network
+                .groups()
+                .map(ParameterGroup::name)
+                .collect::<Vec<_>>(),
+            vec!["Network.Bonjour", "Network.HTTP"]
+        );
+        assert_eq!(
+            network
+                .group("Bonjour")
+                .unwrap()
+                .parameter("Enabled")
+                .unwrap()
+                .name(),
+            "Network.Bonjour.Enabled"
+        );
+        assert_eq!(
+            tree.parameter("root.Network.HostName").unwrap().value(),
+            Some("axis-accc8e000000")
+        );
+        assert!(tree
+            .parameter("Network.Bonjour.Enabled")
+            .unwrap()
+            .definition()
+            .is_some());
+        assert!(tree.group("Network.SSH").is_none());
+        assert!(tree.parameter("Network.Bonjour").is_none());
+        assert_eq!(tree.root().groups().count(), 1);
+    }
+
+    #[test]
+    fn typed_getters() {
+        let tree = tree();
+        let p = |name| tree.parameter(name).unwrap();
+
+        assert_eq!(p("Network.Bonjour.Enabled").as_bool(), Ok(true));
+        assert_eq!(p("Network.HTTP.Port").as_int(), Ok(8080));
+        assert_eq!(
+            p("Network.HTTP.AuthenticationPolicy").as_enum::<String>(),
+            Ok("digest".to_string())
+        );
+
+        // the definitions decide which interpretations are valid
+        assert_eq!(
+            p("Network.Bonjour.FriendlyName").as_bool(),
+            Err(ParameterValueError::WrongType)
+        );
+        assert_eq!(
+            p("Network.HTTP.Port").as_enum::<String>(),
+            Err(ParameterValueError::WrongType)
+        );
+
+        let mut tree = tree;
+        let mut set = |name: &str, value: &str| {
+            let (group, name) = name.split_at(name.rfind('.').unwrap());
+            tree.group_mut(group)
+                .parameters
+                .get_mut(&name[1..])
+                .unwrap()
+                .value = Some(value.to_string());
+        };
+        set("Network.HTTP.Port", "70000");
+        set("Network.Bonjour.Enabled", "true");
+        set("Network.HTTP.AuthenticationPolicy", "none");
+        let p = |name| tree.parameter(name).unwrap();
+        assert_eq!(
+            p("Network.HTTP.Port").as_int(),
+            Err(ParameterValueError::OutOfRange(70000))
+        );
+        assert_eq!(
+            p("Network.Bonjour.Enabled").as_bool(),
+            Err(ParameterValueError::Invalid("true".into()))
+        );
+        assert_eq!(
+            p("Network.HTTP.AuthenticationPolicy").as_enum::<String>(),
+            Err(ParameterValueError::Invalid("none".into()))
+        );
+
+        // parameters without definitions are interpreted by convention
+        let mut values = BTreeMap::new();
+        values.insert("root.Network.Bonjour.Enabled".to_string(), "no".to_string());
+        values.insert("root.Network.HTTP.Port".to_string(), "-1".to_string());
+        let tree = ParameterTree::from_values(&values);
+        let p = |name| tree.parameter(name).unwrap();
+        assert_eq!(p("Network.Bonjour.Enabled").as_bool(), Ok(false));
+        assert_eq!(p("Network.HTTP.Port").as_int(), Ok(-1));
+        assert!(p("Network.HTTP.Port").as_enum::<u16>().is_err());
+    }
+
+    #[tokio::test]
+    async fn values_only() {
+        let client = crate::mock_client(|req| {
+            let body = match req.uri().query() {
+                Some("action=listdefinitions&listformat=xmlschema&group=root.Network") => {
+                    "# Error: Error -1 getting param in group 'root.Network'\r\n"
+                }
+                Some("action=list&group=root.Network") => "root.Network.Bonjour.Enabled=yes\r\n",
+                other => panic!("unexpected query {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![body.as_bytes().to_vec()])
+        });
+
+        let tree = client
+            .parameters()
+            .tree(Some(&["root.Network"][..]))
+            .await
+            .unwrap();
+        let enabled = tree.parameter("Network.Bonjour.Enabled").unwrap();
+        assert!(enabled.definition().is_none());
+        assert_eq!(enabled.as_bool(), Ok(true));
+        assert_eq!(tree.group("root").unwrap().name(), "");
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(range_contains("0", 0), Some(true));
+        assert_eq!(range_contains("1024-65534", 8080), Some(true));
+        assert_eq!(range_contains("1024-65534", 80), Some(false));
+        assert_eq!(range_contains("-10--1", -5), Some(true));
+        assert_eq!(range_contains("any", 1), None);
+    }
+
+    #[test]
+    fn diff() {
+        let old = tree();
+        let mut values = old.values();
+        assert_eq!(values["Network.HTTP.Port"], "8080");
+        values.insert("Network.HTTP.Port".into(), "80".into());
+        values.remove("Network.Bonjour.FriendlyName");
+        let new = ParameterTree::from_values(&values);
+
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.names(),
+            vec!["Network.Bonjour.FriendlyName", "Network.HTTP.Port"]
+        );
+        assert_eq!(diff.changed["Network.HTTP.Port"].new, "80");
+    }
+}
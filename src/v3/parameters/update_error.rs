@@ -0,0 +1,131 @@
+//! Failures reported by `param.cgi?action=update`.
+//!
+//! Devices answer a successful update with `OK`, and a failed one with a line per problem, like
+//! `# Error: Error setting 'root.Network.HostName' to 'lobby!'!`.
+
+use super::*;
+
+/// A device's report that it could not update a parameter.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ParameterUpdateError {
+    /// The parameter which failed, as named in the update, if the device identified it.
+    pub parameter: Option<String>,
+    /// The device's message, e.g. `Error setting 'root.Network.HostName' to 'lobby!'!`.
+    pub message: String,
+}
+
+impl fmt::Display for ParameterUpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.parameter {
+            Some(parameter) => write!(f, "{}: {}", parameter, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Parse the `# ` lines of a failed update of `names` into an `Error::ParameterUpdate`.
+///
+/// Returns `None` if the body contains no such lines.
+pub(crate) fn parse(body: &[u8], names: &[String]) -> Option<Error> {
+    let body = String::from_utf8_lossy(body);
+    let failed: Vec<ParameterUpdateError> = body
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix('#'))
+        .map(|line| {
+            let line = line.trim();
+            let message = line.strip_prefix("Error:").unwrap_or(line).trim();
+            ParameterUpdateError {
+                parameter: quoted_name(message, names),
+                message: message.to_string(),
+            }
+        })
+        .collect();
+    if failed.is_empty() {
+        return None;
+    }
+
+    // An unattributed failure could concern any parameter, so none are known to have succeeded
+    let succeeded = if failed.iter().all(|e| e.parameter.is_some()) {
+        names
+            .iter()
+            .filter(|name| !failed.iter().any(|e| e.parameter.as_ref() == Some(name)))
+            .cloned()
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Some(Error::ParameterUpdate { failed, succeeded })
+}
+
+/// Find the first single-quoted string in `message` naming one of `names`, with or without the
+/// `root.` prefix, and return that name as given in `names`.
+fn quoted_name(message: &str, names: &[String]) -> Option<String> {
+    message
+        .split('\'')
+        .skip(1)
+        .step_by(2)
+        .find_map(|quoted| {
+            let quoted = quoted.trim_start_matches("root.");
+            names
+                .iter()
+                .find(|name| name.trim_start_matches("root.") == quoted)
+        })
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_failures() {
+        let names = vec![
+            "root.Network.HostName".to_string(),
+            "Image.I0.Appearance.Resolution".to_string(),
+            "Brand.Brand".to_string(),
+        ];
+
+        assert!(parse(b"OK", &names).is_none());
+        assert!(parse(b"", &names).is_none());
+
+        let body = b"# Error: Error setting 'Network.HostName' to 'lobby!'!\r\n\
+            # Error: Error setting 'root.Image.I0.Appearance.Resolution' to '1x1'!\r\n";
+        match parse(body, &names) {
+            Some(Error::ParameterUpdate { failed, succeeded }) => {
+                assert_eq!(
+                    failed,
+                    vec![
+                        ParameterUpdateError {
+                            parameter: Some("root.Network.HostName".into()),
+                            message: "Error setting 'Network.HostName' to 'lobby!'!".into(),
+                        },
+                        ParameterUpdateError {
+                            parameter: Some("Image.I0.Appearance.Resolution".into()),
+                            message:
+                                "Error setting 'root.Image.I0.Appearance.Resolution' to '1x1'!"
+                                    .into(),
+                        },
+                    ]
+                );
+                assert_eq!(succeeded, vec!["Brand.Brand".to_string()]);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        // failures which don't name a parameter leave the outcome of the others unknown
+        match parse(b"# Request failed: Parameter update not allowed\n", &names) {
+            Some(Error::ParameterUpdate { failed, succeeded }) => {
+                assert_eq!(
+                    failed,
+                    vec![ParameterUpdateError {
+                        parameter: None,
+                        message: "Request failed: Parameter update not allowed".into(),
+                    }]
+                );
+                assert!(succeeded.is_empty());
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! Privacy-related video behaviors, as exposed by the VAPIX parameters interface.
+//!
+//! Privacy masks are described by the `Image.I{channel}.Overlay.MaskWindows` parameter groups.
+//! PTZ devices can freeze the image while moving, and some devices have a privacy shutter which
+//! blanks the video entirely. Devices lacking a feature omit the corresponding parameters.
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+const FREEZE_ON_PTZ: &str = "PTZ.Various.V1.ImageFreeze";
+
+/// A device's privacy controls.
+pub struct Privacy<'a, T: Transport>(&'a Client<T>);
+
+/// The privacy state of a device.
+#[derive(Debug, Clone, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivacyState {
+    /// Is the image frozen while the PTZ head moves? `None` if the device does not support
+    /// freezing.
+    pub freeze_on_ptz: Option<bool>,
+    /// The privacy shutters of the device's channels. Empty if the device has no privacy shutter.
+    pub privacy_shutters: Vec<PrivacyShutter>,
+    /// The privacy mask windows defined on the device.
+    pub masks: Vec<PrivacyMaskWindow>,
+}
+
+impl PrivacyState {
+    /// Is any privacy feature currently hiding part or all of the video?
+    pub fn is_active(&self) -> bool {
+        self.privacy_shutters.iter().any(|s| s.engaged) || self.masks.iter().any(|m| m.enabled)
+    }
+}
+
+/// The privacy shutter of a video channel.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivacyShutter {
+    /// The video channel which this shutter blanks.
+    pub channel: Channel,
+    /// Is the shutter engaged?
+    pub engaged: bool,
+}
+
+/// A privacy mask window.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivacyMaskWindow {
+    /// The video channel to which this mask applies.
+    pub channel: Channel,
+    /// The index of this mask within the channel.
+    pub index: u32,
+    /// Is this mask enabled?
+    pub enabled: bool,
+    /// The mask's display name, if any.
+    pub name: Option<String>,
+}
+
+impl<'a, T: Transport> Privacy<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// Query the device's current privacy state.
+    pub async fn state(&self) -> Result<PrivacyState> {
+        let params = self
+            .0
+            .parameters()
+            .list(Some(
+                &["root.PTZ.Various", "root.ImageSource", "root.Image"][..],
+            ))
+            .await?;
+
+        Ok(state_from_params(&params))
+    }
+
+    /// Enable or disable freezing the image while the PTZ head moves.
+    pub async fn set_freeze_on_ptz(&self, freeze: bool) -> Result<()> {
+        self.0
+            .parameters()
+            .update(vec![(FREEZE_ON_PTZ, if freeze { "yes" } else { "no" })])
+            .await
+    }
+
+    /// Engage or release the privacy shutter of `channel`.
+    pub async fn set_privacy_shutter(&self, channel: Channel, engaged: bool) -> Result<()> {
+        let name = format!("ImageSource.I{}.PrivacyShutter", channel.index());
+        self.0
+            .parameters()
+            .update(vec![(name.as_str(), if engaged { "yes" } else { "no" })])
+            .await
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "yes" | "true" | "on" => Some(true),
+        "no" | "false" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn state_from_params(params: &BTreeMap<String, String>) -> PrivacyState {
+    let mut state = PrivacyState::default();
+    let mut masks: BTreeMap<(Channel, u32), PrivacyMaskWindow> = BTreeMap::new();
+
+    for (key, value) in params {
+        let key = key.trim_start_matches("root.");
+        if key == FREEZE_ON_PTZ {
+            state.freeze_on_ptz = parse_bool(value);
+        }
+
+        // ImageSource.I0.PrivacyShutter=yes
+        let parts: Vec<&str> = key.split('.').collect();
+        if let ["ImageSource", channel, "PrivacyShutter"] = parts.as_slice() {
+            let channel = channel
+                .strip_prefix('I')
+                .and_then(|n| u32::from_str(n).ok());
+            if let (Some(channel), Some(engaged)) = (channel, parse_bool(value)) {
+                state.privacy_shutters.push(PrivacyShutter {
+                    channel: Channel::from_index(channel),
+                    engaged,
+                });
+            }
+        }
+
+        // Image.I0.Overlay.MaskWindows.M0.Enabled=yes
+        if let ["Image", channel, "Overlay", "MaskWindows", index, name] = parts.as_slice() {
+            let index_of =
+                |s: &str, prefix: char| s.strip_prefix(prefix).and_then(|n| u32::from_str(n).ok());
+            let (channel, index) = match (index_of(channel, 'I'), index_of(index, 'M')) {
+                (Some(channel), Some(index)) => (Channel::from_index(channel), index),
+                _ => continue,
+            };
+
+            let mask = masks
+                .entry((channel, index))
+                .or_insert_with(|| PrivacyMaskWindow {
+                    channel,
+                    index,
+                    enabled: false,
+                    name: None,
+                });
+            match *name {
+                "Enabled" => mask.enabled = parse_bool(value).unwrap_or(false),
+                "Name" if !value.is_empty() => mask.name = Some(value.clone()),
+                _ => {}
+            }
+        }
+    }
+
+    state.masks = masks.into_iter().map(|(_, mask)| mask).collect();
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn state() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?action=list&group=root.PTZ.Various%2Croot.ImageSource%2Croot.Image")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.Image.I0.Appearance.Resolution=1920x1080\n\
+root.Image.I0.Overlay.MaskWindows.M0.Enabled=yes\n\
+root.Image.I0.Overlay.MaskWindows.M0.Name=Neighbor\n\
+root.Image.I0.Overlay.MaskWindows.M1.Enabled=no\n\
+root.ImageSource.I0.PrivacyShutter=no\n\
+root.ImageSource.I1.PrivacyShutter=yes\n\
+root.PTZ.Various.V1.ImageFreeze=yes\n"
+                    .to_vec()])
+        });
+
+        let state = client.privacy().state().await.unwrap();
+        assert_eq!(
+            state,
+            PrivacyState {
+                freeze_on_ptz: Some(true),
+                privacy_shutters: vec![
+                    PrivacyShutter {
+                        channel: Channel::first(),
+                        engaged: false,
+                    },
+                    PrivacyShutter {
+                        channel: Channel::from_index(1),
+                        engaged: true,
+                    },
+                ],
+                masks: vec![
+                    PrivacyMaskWindow {
+                        channel: Channel::first(),
+                        index: 0,
+                        enabled: true,
+                        name: Some("Neighbor".into()),
+                    },
+                    PrivacyMaskWindow {
+                        channel: Channel::first(),
+                        index: 1,
+                        enabled: false,
+                        name: None,
+                    },
+                ],
+            }
+        );
+        assert!(state.is_active());
+    }
+
+    #[tokio::test]
+    async fn set_freeze_on_ptz() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?PTZ.Various.V1.ImageFreeze=no&action=update")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"OK".to_vec()])
+        });
+
+        client.privacy().set_freeze_on_ptz(false).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn set_privacy_shutter() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?ImageSource.I1.PrivacyShutter=yes&action=update")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"OK".to_vec()])
+        });
+
+        client
+            .privacy()
+            .set_privacy_shutter(Channel::from_index(1), true)
+            .await
+            .unwrap();
+    }
+}
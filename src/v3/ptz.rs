@@ -0,0 +1,416 @@
+//! The VAPIX PTZ interface at `/axis-cgi/com/ptz.cgi`.
+//!
+//! Pan and tilt are expressed in degrees, while zoom, focus, and iris are expressed in the
+//! device's own units, from 1 to 9999 on most devices. `Ptz::limits()` reports the ranges a
+//! particular device accepts.
+//!
+//! Commands which move the camera or change its presets are refused in read-only mode. Devices
+//! acknowledge commands with an empty response, and report failures in plain text.
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A device's PTZ interface, controlling a single video channel.
+pub struct Ptz<'a, T: Transport>(&'a Client<T>, Channel);
+
+/// The position of a PTZ camera, as reported by `Ptz::position()`.
+///
+/// Cameras omit the values they cannot report, like pan and tilt on a zoom-only camera.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PtzPosition {
+    /// The pan angle, in degrees.
+    pub pan: Option<f64>,
+    /// The tilt angle, in degrees.
+    pub tilt: Option<f64>,
+    /// The zoom level.
+    pub zoom: Option<f64>,
+    /// The focus position.
+    pub focus: Option<f64>,
+    /// The iris position.
+    pub iris: Option<f64>,
+    /// Is autofocus enabled?
+    pub autofocus: Option<bool>,
+    /// Is automatic iris control enabled?
+    pub autoiris: Option<bool>,
+}
+
+/// The range of each PTZ axis, as reported by `Ptz::limits()`.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PtzLimits {
+    /// The range of pan angles, in degrees.
+    pub pan: Option<PtzRange>,
+    /// The range of tilt angles, in degrees.
+    pub tilt: Option<PtzRange>,
+    /// The range of zoom levels.
+    pub zoom: Option<PtzRange>,
+    /// The range of focus positions.
+    pub focus: Option<PtzRange>,
+    /// The range of iris positions.
+    pub iris: Option<PtzRange>,
+    /// The range of field angles, in tenths of a degree.
+    pub field_angle: Option<PtzRange>,
+}
+
+/// The inclusive range of a PTZ axis.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PtzRange {
+    /// The lowest value.
+    pub min: f64,
+    /// The highest value.
+    pub max: f64,
+}
+
+/// A move to an absolute position with `Ptz::move_to()`, or by a relative amount with
+/// `Ptz::move_by()`.
+///
+/// Axes left as `None` do not move.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PtzMove {
+    /// The pan angle, in degrees.
+    pub pan: Option<f64>,
+    /// The tilt angle, in degrees.
+    pub tilt: Option<f64>,
+    /// The zoom level.
+    pub zoom: Option<f64>,
+    /// The speed of the move, from 1 to 100, or the device's default speed if `None`.
+    pub speed: Option<u32>,
+}
+
+/// A preset position stored on the device.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PtzPreset {
+    /// The number of the preset, starting from 1.
+    pub number: u32,
+    /// The name of the preset.
+    pub name: String,
+}
+
+impl<'a, T: Transport> Ptz<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device, Channel::first())
+    }
+
+    /// Control the given video channel rather than the first.
+    ///
+    /// `Client::channel()` constructs a `Channel` which is known to exist on the device.
+    pub fn with_camera(mut self, camera: Channel) -> Self {
+        self.1 = camera;
+        self
+    }
+
+    /// Retrieve the camera's current position.
+    pub async fn position(&self) -> Result<PtzPosition> {
+        let values = self.query("position").await?;
+        let number = |key: &str| values.get(key).and_then(|v| v.parse().ok());
+        let on = |key: &str| values.get(key).map(|v| v == "on");
+        Ok(PtzPosition {
+            pan: number("pan"),
+            tilt: number("tilt"),
+            zoom: number("zoom"),
+            focus: number("focus"),
+            iris: number("iris"),
+            autofocus: on("autofocus"),
+            autoiris: on("autoiris"),
+        })
+    }
+
+    /// Retrieve the range of each of the camera's axes.
+    pub async fn limits(&self) -> Result<PtzLimits> {
+        let values = self.query("limits").await?;
+        let range = |axis: &str| {
+            let get = |bound: &str| {
+                values
+                    .get(&format!("{}{}", bound, axis))
+                    .and_then(|v| v.parse().ok())
+            };
+            Some(PtzRange {
+                min: get("min")?,
+                max: get("max")?,
+            })
+        };
+        Ok(PtzLimits {
+            pan: range("pan"),
+            tilt: range("tilt"),
+            zoom: range("zoom"),
+            focus: range("focus"),
+            iris: range("iris"),
+            field_angle: range("fieldangle"),
+        })
+    }
+
+    /// List the preset positions stored on the device.
+    pub async fn presets(&self) -> Result<Vec<PtzPreset>> {
+        let values = self.query("presetposall").await?;
+        let mut presets: Vec<PtzPreset> = values
+            .into_iter()
+            .filter_map(|(key, name)| {
+                let number = key.strip_prefix("presetposno")?.parse().ok()?;
+                Some(PtzPreset { number, name })
+            })
+            .collect();
+        presets.sort_by_key(|preset| preset.number);
+        Ok(presets)
+    }
+
+    /// Move to an absolute position.
+    pub async fn move_to(&self, position: &PtzMove) -> Result<()> {
+        let args = move_args(position, false);
+        self.command(args, || format!("moved to {:?}", position))
+            .await
+    }
+
+    /// Move by an amount relative to the current position.
+    pub async fn move_by(&self, offset: &PtzMove) -> Result<()> {
+        let args = move_args(offset, true);
+        self.command(args, || format!("moved by {:?}", offset))
+            .await
+    }
+
+    /// Start panning and tilting continuously, at speeds from -100 to 100, until stopped.
+    pub async fn continuous_pan_tilt(&self, pan_speed: i32, tilt_speed: i32) -> Result<()> {
+        let args = vec![(
+            "continuouspantiltmove",
+            format!("{},{}", pan_speed, tilt_speed),
+        )];
+        self.command(args, || {
+            format!("started moving at pan {}, tilt {}", pan_speed, tilt_speed)
+        })
+        .await
+    }
+
+    /// Start zooming continuously, at a speed from -100 to 100, until stopped.
+    pub async fn continuous_zoom(&self, speed: i32) -> Result<()> {
+        let args = vec![("continuouszoommove", speed.to_string())];
+        self.command(args, || format!("started zooming at {}", speed))
+            .await
+    }
+
+    /// Stop any ongoing movement.
+    pub async fn stop(&self) -> Result<()> {
+        let args = vec![("move", "stop".to_string())];
+        self.command(args, || "stopped moving".to_string()).await
+    }
+
+    /// Move to a preset position by name.
+    pub async fn goto_preset(&self, name: &str) -> Result<()> {
+        let args = vec![("gotoserverpresetname", name.to_string())];
+        self.command(args, || format!("moved to preset {:?}", name))
+            .await
+    }
+
+    /// Store the current position as a preset, replacing any preset with the same name.
+    pub async fn set_preset(&self, name: &str) -> Result<()> {
+        let args = vec![("setserverpresetname", name.to_string())];
+        self.command(args, || format!("set preset {:?}", name))
+            .await
+    }
+
+    /// Remove a preset by name.
+    pub async fn remove_preset(&self, name: &str) -> Result<()> {
+        let args = vec![("removeserverpresetname", name.to_string())];
+        self.command(args, || format!("removed preset {:?}", name))
+            .await
+    }
+
+    async fn query(&self, query: &str) -> Result<BTreeMap<String, String>> {
+        let body = self.call(vec![("query", query.to_string())]).await?;
+        let body = String::from_utf8_lossy(&body);
+        check_error(&body)?;
+        Ok(parse_values(&body))
+    }
+
+    async fn command<F>(&self, args: Vec<(&str, String)>, description: F) -> Result<()>
+    where
+        F: FnOnce() -> String,
+    {
+        self.0.check_writable()?;
+        let body = self.call(args).await?;
+        check_error(&String::from_utf8_lossy(&body))?;
+        let camera = self.1;
+        self.0
+            .audit(|| format!("PTZ camera {} {}", camera, description()))
+            .await;
+        Ok(())
+    }
+
+    async fn call(&self, mut args: Vec<(&str, String)>) -> Result<Vec<u8>> {
+        args.push(("camera", self.1.to_string()));
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for_args("/axis-cgi/com/ptz.cgi", &args)?)
+            .body(Vec::new())?;
+
+        match self.0.roundtrip(req, "text/plain").await {
+            Ok((_, body)) => Ok(body),
+            // Commands are acknowledged with an empty response
+            Err(Error::HttpRequestFailed(e))
+                if e.downcast_ref()
+                    == Some(&crate::error::HttpStatusCodeError(
+                        http::StatusCode::NO_CONTENT,
+                    )) =>
+            {
+                Ok(Vec::new())
+            }
+            Err(e) => Err(e).map_404_to_feature_unavailable(),
+        }
+    }
+}
+
+fn move_args(position: &PtzMove, relative: bool) -> Vec<(&'static str, String)> {
+    let axes: [(&'static str, &'static str, Option<f64>); 3] = [
+        ("pan", "rpan", position.pan),
+        ("tilt", "rtilt", position.tilt),
+        ("zoom", "rzoom", position.zoom),
+    ];
+    let mut args: Vec<(&'static str, String)> = axes
+        .iter()
+        .filter_map(|(absolute_name, relative_name, value)| {
+            let name = if relative {
+                relative_name
+            } else {
+                absolute_name
+            };
+            value.map(|value| (*name, value.to_string()))
+        })
+        .collect();
+    if let Some(speed) = position.speed {
+        args.push(("speed", speed.to_string()));
+    }
+    args
+}
+
+/// Devices report failures as text, like `Error: No such preset`.
+fn check_error(body: &str) -> Result<()> {
+    if body.trim_start().to_ascii_lowercase().starts_with("error") {
+        Err(Error::Other("PTZ request failed"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Parse `name=value` lines, ignoring any others, with names in lowercase.
+fn parse_values(body: &str) -> BTreeMap<String, String> {
+    body.lines()
+        .filter_map(|line| {
+            let mut parts = line.trim().splitn(2, '=');
+            let key = parts.next()?.trim().to_ascii_lowercase();
+            let value = parts.next()?.trim().to_string();
+            Some((key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_ptz_client() -> Client<impl Transport> {
+        crate::mock_client(|req| {
+            let (status, body): (_, &[u8]) =
+                match req.uri().query().unwrap() {
+                    "query=position&camera=1" => (
+                        http::StatusCode::OK,
+                        b"pan=-45.37\r\ntilt=-12.5\r\nzoom=1\r\niris=2500\r\nfocus=6000\r\nautofocus=on\r\nautoiris=off\r\n",
+                    ),
+                    "query=limits&camera=1" => (
+                        http::StatusCode::OK,
+                        b"MinPan=-180\r\nMaxPan=180\r\nMinTilt=-90\r\nMaxTilt=0\r\nMinZoom=1\r\nMaxZoom=9999\r\nMinFieldAngle=22\r\nMaxFieldAngle=623\r\n",
+                    ),
+                    "query=presetposall&camera=2" => (
+                        http::StatusCode::OK,
+                        b"Preset Positions for camera 2\r\npresetposno10=Gate\r\npresetposno2=Door\r\npresetposno1=Home\r\n",
+                    ),
+                    "pan=10.5&zoom=500&speed=50&camera=1" => (http::StatusCode::NO_CONTENT, b""),
+                    "rtilt=-5&camera=1" => (http::StatusCode::NO_CONTENT, b""),
+                    "continuouspantiltmove=-20%2C0&camera=1" => (http::StatusCode::NO_CONTENT, b""),
+                    "move=stop&camera=1" => (http::StatusCode::NO_CONTENT, b""),
+                    "gotoserverpresetname=Missing&camera=1" => {
+                        (http::StatusCode::OK, b"Error: No such preset\r\n")
+                    }
+                    other => panic!("unexpected query {:?}", other),
+                };
+            http::Response::builder()
+                .status(status)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![body.to_vec()])
+        })
+    }
+
+    #[tokio::test]
+    async fn queries() {
+        let client = mock_ptz_client();
+
+        let position = client.ptz().position().await.unwrap();
+        assert_eq!(
+            position,
+            PtzPosition {
+                pan: Some(-45.37),
+                tilt: Some(-12.5),
+                zoom: Some(1.0),
+                focus: Some(6000.0),
+                iris: Some(2500.0),
+                autofocus: Some(true),
+                autoiris: Some(false),
+            }
+        );
+
+        let limits = client.ptz().limits().await.unwrap();
+        assert_eq!(
+            limits.pan,
+            Some(PtzRange {
+                min: -180.0,
+                max: 180.0
+            })
+        );
+        assert_eq!(limits.zoom.map(|z| z.max), Some(9999.0));
+        assert_eq!(limits.focus, None);
+        assert_eq!(limits.field_angle.map(|f| f.min), Some(22.0));
+
+        let presets = client
+            .ptz()
+            .with_camera(Channel::new(2).unwrap())
+            .presets()
+            .await
+            .unwrap();
+        let names: Vec<(u32, &str)> = presets
+            .iter()
+            .map(|preset| (preset.number, preset.name.as_str()))
+            .collect();
+        assert_eq!(names, vec![(1, "Home"), (2, "Door"), (10, "Gate")]);
+    }
+
+    #[tokio::test]
+    async fn commands() {
+        let client = mock_ptz_client();
+        let ptz = client.ptz();
+
+        ptz.move_to(&PtzMove {
+            pan: Some(10.5),
+            zoom: Some(500.0),
+            speed: Some(50),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        ptz.move_by(&PtzMove {
+            tilt: Some(-5.0),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        ptz.continuous_pan_tilt(-20, 0).await.unwrap();
+        ptz.stop().await.unwrap();
+        assert!(ptz.goto_preset("Missing").await.is_err());
+
+        let client = client.with_read_only(true);
+        match client.ptz().stop().await {
+            Err(Error::ReadOnlyMode) => {}
+            other => panic!("expected ReadOnlyMode, got {:?}", other),
+        }
+    }
+}
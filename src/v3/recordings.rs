@@ -1,9 +1,12 @@
 //! The VAPIX recording API at `/axis-cgi/record/*`.
 
+use crate::v3::parameters::RootPrefix;
 use crate::v4::disk_management::DiskId;
 use crate::*;
 use chrono::prelude::*;
+use futures::prelude::*;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
 use std::num::NonZeroU32;
 use std::str::FromStr;
 
@@ -23,6 +26,7 @@ impl<'a, T: Transport> Recordings<'a, T> {
     pub(crate) async fn new(device: &'a Client<T>) -> Result<Option<Recordings<'a, T>>> {
         let params = device
             .parameters()
+            .with_root_prefix(RootPrefix::Strip)
             .list(Some(
                 &[
                     "Properties.API.HTTP.Version",
@@ -32,12 +36,16 @@ impl<'a, T: Transport> Recordings<'a, T> {
             ))
             .await?;
 
+        let api_versions = ApiVersions::from_params(
+            &params
+                .iter()
+                .map(|(k, v)| (k.trim_start_matches("Properties.API."), v.as_str()))
+                .collect(),
+        );
+
         // We require Properties.API.HTTP.Version=3 and Properties.LocalStorage.LocalStorage=yes
         // in order to have a Recording interface
-        if params
-            .get("Properties.API.HTTP.Version")
-            .map(String::as_str)
-            != Some("3")
+        if api_versions.http.map(|version| version.major) != Some(3)
             || params
                 .get("Properties.LocalStorage.LocalStorage")
                 .map(String::as_str)
@@ -58,9 +66,9 @@ impl<'a, T: Transport> Recordings<'a, T> {
                 .unwrap_or(false);
 
         // And we may also support playback over RTSP
-        let supports_playback_over_rtsp = params
-            .get("Properties.API.RTSP.Version")
-            .map(|s| s.as_str() >= "2.01")
+        let supports_playback_over_rtsp = api_versions
+            .rtsp
+            .map(|version| version >= ApiVersion::new(2, 1))
             .unwrap_or(false);
 
         // And we may also support exporting recordings
@@ -77,11 +85,239 @@ impl<'a, T: Transport> Recordings<'a, T> {
         }))
     }
 
+    /// Does the device support `export()`?
+    pub fn supports_exporting(&self) -> bool {
+        self.supports_exporting
+    }
+
+    /// Export a recording as a Matroska file, streaming it as the device produces it.
+    ///
+    /// The device assembles the export while sending it, so there is no export job to poll:
+    /// progress is the number of bytes read so far, out of `BodyReader::content_length()` if the
+    /// device reports it. Dropping the reader abandons the export.
+    ///
+    /// Returns `Error::FeatureUnavailable` if the device does not support exporting.
+    pub async fn export(
+        &self,
+        recording: &Recording,
+        options: &ExportOptions,
+    ) -> Result<crate::BodyReader<ResponseBody<T::Body>>> {
+        self.export_by_id(&recording.id, &recording.disk_id, options)
+            .await
+    }
+
+    /// Export a recording like `export()`, along with the HTTP response headers.
+    ///
+    /// The device suggests a filename for the export, available through
+    /// `WithHeaders::content_disposition_filename()`.
+    pub async fn export_with_headers(
+        &self,
+        recording: &Recording,
+        options: &ExportOptions,
+    ) -> Result<WithHeaders<crate::BodyReader<ResponseBody<T::Body>>>> {
+        self.export_by_id_with_headers(&recording.id, &recording.disk_id, options)
+            .await
+    }
+
+    /// Export a recording by its ID, like `export()`.
+    ///
+    /// This allows salvaging recordings whose metadata is too damaged to form a `Recording`, as
+    /// reported by `browse_disk()`.
+    pub async fn export_by_id(
+        &self,
+        recording_id: &RecordingId,
+        disk_id: &DiskId,
+        options: &ExportOptions,
+    ) -> Result<crate::BodyReader<ResponseBody<T::Body>>> {
+        self.export_by_id_with_headers(recording_id, disk_id, options)
+            .await
+            .map(WithHeaders::into_inner)
+    }
+
+    /// Export a recording by its ID like `export_by_id()`, along with the HTTP response headers.
+    pub async fn export_by_id_with_headers(
+        &self,
+        recording_id: &RecordingId,
+        disk_id: &DiskId,
+        options: &ExportOptions,
+    ) -> Result<WithHeaders<crate::BodyReader<ResponseBody<T::Body>>>> {
+        let req = self.export_request(recording_id, disk_id, options)?;
+        let (parts, body) = self
+            .device
+            .roundtrip_streaming(req, EXPORT_CONTENT_TYPE)
+            .await
+            .map_404_to_feature_unavailable()?;
+        let reader = crate::BodyReader::new(&parts, body);
+        Ok(WithHeaders::new(reader, parts.headers))
+    }
+
+    /// Export a recording as a Matroska file, writing it to `file` as it arrives.
+    ///
+    /// `progress` is called after each chunk is written. Returns the number of bytes written.
+    ///
+    /// Dropping the future, for example through `CancelHandle::run()`, stops the export and
+    /// leaves a partial file in `file`, which the caller should discard.
+    #[cfg(feature = "tokio")]
+    pub async fn export_to_file<P>(
+        &self,
+        recording: &Recording,
+        options: &ExportOptions,
+        file: &mut tokio::fs::File,
+        progress: P,
+    ) -> Result<u64>
+    where
+        P: FnMut(DownloadProgress),
+    {
+        let req = self.export_request(&recording.id, &recording.disk_id, options)?;
+        self.device
+            .download_to_file(req, EXPORT_CONTENT_TYPE, file, progress)
+            .await
+            .map_404_to_feature_unavailable()
+    }
+
+    fn export_request(
+        &self,
+        recording_id: &RecordingId,
+        disk_id: &DiskId,
+        options: &ExportOptions,
+    ) -> Result<http::Request<Vec<u8>>> {
+        if !self.supports_exporting {
+            return Err(Error::FeatureUnavailable);
+        }
+
+        #[derive(Serialize)]
+        struct Req<'a> {
+            schemaversion: u32,
+            recordingid: &'a str,
+            diskid: &'a str,
+            exportformat: &'static str,
+            #[serde(with = "optional_iso8601", skip_serializing_if = "Option::is_none")]
+            starttime: Option<DateTime<FixedOffset>>,
+            #[serde(with = "optional_iso8601", skip_serializing_if = "Option::is_none")]
+            stoptime: Option<DateTime<FixedOffset>>,
+        }
+
+        let utc = |dt: DateTime<Utc>| dt.with_timezone(&FixedOffset::west(0));
+        Ok(http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.device.uri_for_args(
+                "/axis-cgi/record/export/exportrecording.cgi",
+                Req {
+                    schemaversion: 1,
+                    recordingid: recording_id.as_str(),
+                    diskid: disk_id.as_str(),
+                    exportformat: "matroska",
+                    starttime: options.start_time.map(utc),
+                    stoptime: options.stop_time.map(utc),
+                },
+            )?)
+            .body(Vec::new())?)
+    }
+
+    /// List the recordings matching `request`, returning at most one page as described by
+    /// `request.pagination`.
     pub async fn list_recordings(
         &self,
-        _request: ListRecordingsRequest,
+        request: ListRecordingsRequest,
     ) -> Result<ListRecordingsResponse> {
-        todo!()
+        let (resp, _) = self.list_page(request).await?;
+        Ok(resp)
+    }
+
+    /// List one page of recordings, also returning the overall total only if the device reported
+    /// it.
+    async fn list_page(
+        &self,
+        request: ListRecordingsRequest,
+    ) -> Result<(ListRecordingsResponse, Option<u64>)> {
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.device.uri_for_args(
+                "/axis-cgi/record/list.cgi",
+                list_cgi::Request::from(&request),
+            )?)
+            .body(Vec::new())?;
+
+        let (_resp, (recordings, count, total)) = self
+            .device
+            .roundtrip_with(req, "text/xml", |body| async move {
+                let mut reader = reader::BodyRecordingsReader::new(body);
+                let mut recordings = Vec::new();
+                while let Some(recording) = reader.next_recording().await? {
+                    recordings.push(recording);
+                }
+                let count = reader.number_of_recordings();
+                Ok((recordings, count, reader.total_number_of_recordings()))
+            })
+            .await?;
+
+        let resp = ListRecordingsResponse {
+            count: count.unwrap_or(recordings.len() as u64),
+            overall_total_recordings: total.unwrap_or(recordings.len() as u64),
+            recordings,
+        };
+        Ok((resp, total))
+    }
+
+    /// List every recording the device reports on `disk_id`, for recovery tooling which needs to
+    /// account for all of a disk's recordings.
+    ///
+    /// This asks `list.cgi` for the disk's entire listing, without any filters or sorting. A
+    /// recording whose metadata can't be parsed, like one with a missing start time, doesn't fail
+    /// the listing as it would in `list_recordings()`: it is returned as a `DamagedRecording` with
+    /// whatever the device reported, so it can still be exported with `export_by_id()`.
+    ///
+    /// VAPIX offers no file-level access to a disk, so recordings missing from the device's own
+    /// listing can't be found this way.
+    pub async fn browse_disk(&self, disk_id: &DiskId) -> Result<Vec<DiskRecordingEntry>> {
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.device.uri_for_args(
+                "/axis-cgi/record/list.cgi",
+                list_cgi::Request::for_disk(disk_id.as_str()),
+            )?)
+            .body(Vec::new())?;
+
+        let (_resp, entries) = self
+            .device
+            .roundtrip_with(req, "text/xml", |body| async move {
+                let mut reader = reader::BodyRecordingsReader::new(body);
+                let mut entries = Vec::new();
+                while let Some(entry) = reader.next_entry().await? {
+                    entries.push(entry);
+                }
+                Ok(entries)
+            })
+            .await?;
+        Ok(entries)
+    }
+
+    /// List every recording matching `request`, requesting successive pages from `list.cgi` as
+    /// the stream is consumed.
+    ///
+    /// `request.pagination` sets the page size and the offset of the first recording. The stream
+    /// ends after the first error. Devices which don't report the total number of recordings are
+    /// paged until they return a short or empty page.
+    pub fn list_all_recordings(
+        &self,
+        request: ListRecordingsRequest,
+    ) -> impl Stream<Item = Result<Recording>> + '_ {
+        Paginator::new(request.pagination.clone(), move |pagination| {
+            let request = ListRecordingsRequest {
+                pagination,
+                ..request.clone()
+            };
+            async move {
+                let offset = request.pagination.offset.unwrap_or(0);
+                let (resp, total) = self.list_page(request).await?;
+                Ok(Page {
+                    items: resp.recordings,
+                    offset,
+                    total,
+                })
+            }
+        })
+        .into_stream()
     }
 }
 
@@ -89,6 +325,65 @@ mod iso8601;
 mod optional_iso8601;
 
 mod list_cgi;
+mod reader;
+
+pub use reader::RecordingsReader;
+
+/// Deserialize a whole `list.cgi` response at once, as `RecordingsReader` avoids doing.
+///
+/// This exists only so the benchmarks can compare the two.
+#[doc(hidden)]
+pub fn parse_list_response(body: &[u8]) -> Result<Vec<Recording>> {
+    #[derive(Deserialize)]
+    struct Root {
+        recordings: list_cgi::Recordings,
+    }
+
+    let root: Root = crate::xml::from_slice(body)?;
+    Ok(root
+        .recordings
+        .recordings
+        .into_iter()
+        .map(Recording::from)
+        .collect())
+}
+
+const EXPORT_CONTENT_TYPE: &str = "video/x-matroska";
+
+/// A recording reported by `Recordings::browse_disk()`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiskRecordingEntry {
+    /// A recording whose metadata is intact.
+    Intact(Recording),
+    /// A recording whose metadata could not be parsed.
+    Damaged(DamagedRecording),
+}
+
+/// A recording whose metadata could not be parsed, as reported by `Recordings::browse_disk()`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DamagedRecording {
+    /// The recording's ID, if the device reported one.
+    pub id: Option<RecordingId>,
+    /// The disk on which the recording is stored, if the device reported it.
+    pub disk_id: Option<DiskId>,
+    /// Every attribute the device reported for the recording, verbatim.
+    pub attributes: BTreeMap<String, String>,
+    /// Why the metadata could not be parsed.
+    pub error: String,
+}
+
+/// Which part of a recording to export.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct ExportOptions {
+    /// Where to start the export, or `None` to start at the beginning of the recording.
+    pub start_time: Option<DateTime<Utc>>,
+    /// Where to stop the export, or `None` to stop at the end of the recording.
+    pub stop_time: Option<DateTime<Utc>>,
+}
+
+pub use crate::Pagination;
 
 #[derive(Debug, Clone, Eq, PartialEq, Default)]
 pub struct ListRecordingsRequest {
@@ -103,20 +398,15 @@ pub struct ListRecordingsRequest {
     pub sort: Sort,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ListRecordingsResponse {
     /// The number of recordings which match the request.
     pub count: u64,
     /// The total number of recordings
     pub overall_total_recordings: u64,
-}
-
-#[derive(Debug, Clone, Eq, PartialEq, Default)]
-pub struct Pagination {
-    /// The number of records to return.
-    pub page_size: Option<u64>,
-    /// The offset of the first record.
-    pub offset: Option<u64>,
+    /// The recordings on this page.
+    pub recordings: Vec<Recording>,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -125,14 +415,14 @@ pub enum Source {
     ///
     /// The meaning of this value is product-dependent. Channels are always numbered sequentially
     /// starting from 1.
-    ChannelNumber(NonZeroU32),
+    ChannelNumber(Channel),
     /// A synthetic quad stream, available on 4-channel devices.
     Quad,
 }
 
 impl Default for Source {
     fn default() -> Self {
-        Self::ChannelNumber(NonZeroU32::new(1).unwrap())
+        Self::ChannelNumber(Channel::first())
     }
 }
 
@@ -142,7 +432,7 @@ impl Serialize for Source {
         S: Serializer,
     {
         match self {
-            Self::ChannelNumber(num) => serializer.serialize_str(&num.get().to_string()),
+            Self::ChannelNumber(channel) => serializer.serialize_str(&channel.to_string()),
             Self::Quad => serializer.serialize_str("Quad"),
         }
     }
@@ -154,7 +444,6 @@ impl<'de> Deserialize<'de> for Source {
         D: Deserializer<'de>,
     {
         use serde::de::{self, Visitor};
-        use std::convert::TryFrom;
         use std::fmt;
 
         struct V;
@@ -173,7 +462,7 @@ impl<'de> Deserialize<'de> for Source {
                     "Quad" => Ok(Source::Quad),
                     other => u32::from_str(other)
                         .ok()
-                        .and_then(|v| NonZeroU32::try_from(v).ok())
+                        .and_then(Channel::new)
                         .map(Source::ChannelNumber)
                         .ok_or_else(|| E::custom(format!("invaid channel number: {:?}", other))),
                 }
@@ -206,13 +495,120 @@ pub struct Recording {
     pub id: RecordingId,
     pub disk_id: DiskId,
     pub start_time: DateTime<FixedOffset>,
-    pub end_time: DateTime<FixedOffset>,
+    /// The time at which this recording started, in the device's local time, as reported by the
+    /// device. Compare with `start_time` using `utc_offset()` and `check_local_time()`.
+    #[serde(
+        default,
+        with = "optional_iso8601",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub start_time_local: Option<DateTime<FixedOffset>>,
+    /// The time at which this recording stopped, or `None` if it is still in progress.
+    pub end_time: Option<DateTime<FixedOffset>>,
     pub source: Source,
     pub event_id: EventId,
     pub video: Option<Video>,
     pub audio: Option<Audio>,
+    /// The amount of storage consumed by this recording, in bytes.
+    ///
+    /// This is `None` if the device does not report recording sizes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+impl From<list_cgi::Recording> for Recording {
+    fn from(r: list_cgi::Recording) -> Self {
+        Recording {
+            id: r.id,
+            disk_id: r.disk_id,
+            start_time: r.start_time,
+            start_time_local: Some(r.start_time_local),
+            end_time: r.end_time,
+            source: r.source,
+            event_id: r.event_id,
+            video: r.video.into_iter().next().map(|v| Video {
+                video_type: v.video_type,
+                width: v.width,
+                height: v.height,
+                framerate: v.framerate,
+            }),
+            audio: r.audio.into_iter().next().map(|a| Audio {
+                audio_type: a.audio_type,
+                bitrate: a.bitrate,
+                sample_rate: a.sample_rate,
+            }),
+            size: r.size,
+        }
+    }
+}
+
+impl Recording {
+    /// The device's offset from UTC when this recording started, derived from the difference
+    /// between the wall-clock times of `start_time_local` and `start_time`.
+    ///
+    /// This is the offset the device's clock was actually using, regardless of the offset it
+    /// wrote into `start_time_local`. Returns `None` if there is no local start time, or if the
+    /// difference is not a plausible UTC offset.
+    pub fn utc_offset(&self) -> Option<FixedOffset> {
+        let local = self.start_time_local?.naive_local();
+        let utc = self.start_time.naive_utc();
+        let seconds = (local - utc).num_seconds();
+        if seconds.abs() >= 24 * 3600 {
+            return None;
+        }
+        FixedOffset::east_opt(seconds as i32)
+    }
+
+    /// Check that the device's local start time agrees with its UTC start time and with
+    /// `time_zone`, which should be the device's configured time zone as returned by
+    /// `v4::TimeService::time_zone()`.
+    ///
+    /// A mismatch indicates a device whose time zone is misconfigured, or was changed since the
+    /// recording started, which would otherwise produce exports labelled with the wrong local
+    /// time. Recordings without a local start time pass.
+    pub fn check_local_time(
+        &self,
+        time_zone: &crate::v4::time_service::PosixTimeZone,
+    ) -> Result<(), LocalTimeMismatch> {
+        let start_time_local = match self.start_time_local {
+            Some(start_time_local) => start_time_local,
+            None => return Ok(()),
+        };
+        let reported = *start_time_local.offset();
+        let actual = self.utc_offset().unwrap_or(reported);
+        let expected = time_zone.offset_at_utc(&self.start_time.naive_utc());
+
+        for &expected in &[reported, expected] {
+            if actual != expected {
+                return Err(LocalTimeMismatch { actual, expected });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A recording whose local start time disagrees with its UTC start time or the device's time
+/// zone, as found by `Recording::check_local_time()`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct LocalTimeMismatch {
+    /// The UTC offset the device's clock was using, from `Recording::utc_offset()`.
+    pub actual: FixedOffset,
+    /// The UTC offset which was expected instead.
+    pub expected: FixedOffset,
+}
+
+impl std::fmt::Display for LocalTimeMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "recording local time is offset {} from UTC, expected {}",
+            self.actual, self.expected
+        )
+    }
 }
 
+impl std::error::Error for LocalTimeMismatch {}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Video {
@@ -279,3 +675,261 @@ pub enum RecordingStatus {
 
 string_type!(pub struct EventId);
 string_type!(pub struct RecordingId);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording_xml(id: u32) -> String {
+        format!(
+            r#"<recording diskid="SD_DISK" recordingid="20201009_1530{:02}_9E3C_ACCC8E000000" starttime="2020-10-09T15:30:{:02}.000Z" starttimelocal="2020-10-09T17:30:{:02}.000+02:00" stoptime="" stoptimelocal="" recordingtype="continuous" eventid="continuous" eventtrigger="continuous" recordingstatus="recording" source="1"/>"#,
+            id, id, id
+        )
+    }
+
+    /// A device with five recordings, which reports their total only if `report_total`.
+    fn paged_client(report_total: bool) -> Client<impl Transport> {
+        crate::mock_client(move |req| {
+            let query = req.uri().query().unwrap_or_default();
+            let (content_type, body) = match req.uri().path() {
+                "/axis-cgi/param.cgi" => (
+                    "text/plain",
+                    "root.Properties.API.HTTP.Version=3
+root.Properties.API.RTSP.Version=2.01
+root.Properties.LocalStorage.LocalStorage=yes
+root.Properties.LocalStorage.ContinuousRecording=yes
+root.Properties.LocalStorage.ContinuousRecordingProfiles=1
+"
+                    .to_string(),
+                ),
+                "/axis-cgi/record/list.cgi" => {
+                    let offset = match query {
+                        "listentity=recordingid&maxnumberofresults=2&sortorder=ascending" => 0,
+                        "listentity=recordingid&maxnumberofresults=2&startatresultnumber=2&sortorder=ascending" => 2,
+                        "listentity=recordingid&maxnumberofresults=2&startatresultnumber=4&sortorder=ascending" => 4,
+                        other => panic!("unexpected query {:?}", other),
+                    };
+                    let page: Vec<String> = (offset..5).take(2).map(recording_xml).collect();
+                    let total = if report_total {
+                        r#" totalnumberofrecordings="5""#
+                    } else {
+                        ""
+                    };
+                    (
+                        "text/xml",
+                        format!(
+                            r#"<?xml version="1.0"?><root><recordings{} numberofrecordings="{}">{}</recordings></root>"#,
+                            total,
+                            page.len(),
+                            page.concat()
+                        ),
+                    )
+                }
+                other => panic!("unexpected path {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, content_type)
+                .body(vec![body.into_bytes()])
+        })
+    }
+
+    #[tokio::test]
+    async fn list_all_recordings() {
+        let client = paged_client(true);
+        let recordings = client.recordings().await.unwrap().unwrap();
+        assert!(recordings.supports_continuous_recording);
+        assert!(recordings.supports_playback_over_rtsp);
+
+        let request = ListRecordingsRequest {
+            pagination: Pagination {
+                page_size: Some(2),
+                offset: None,
+            },
+            sort: Sort::EarliestFirst,
+            ..Default::default()
+        };
+
+        let page = recordings.list_recordings(request.clone()).await.unwrap();
+        assert_eq!(page.count, 2);
+        assert_eq!(page.overall_total_recordings, 5);
+        assert_eq!(
+            page.recordings[1].id.as_str(),
+            "20201009_153001_9E3C_ACCC8E000000"
+        );
+
+        let all: Vec<Recording> = recordings
+            .list_all_recordings(request.clone())
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 5);
+        assert_eq!(all[4].id.as_str(), "20201009_153004_9E3C_ACCC8E000000");
+
+        // without a total, paging continues until a short page
+        let client = paged_client(false);
+        let recordings = client.recordings().await.unwrap().unwrap();
+        let all: Vec<Recording> = recordings
+            .list_all_recordings(request)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 5);
+        assert_eq!(all[4].id.as_str(), "20201009_153004_9E3C_ACCC8E000000");
+    }
+
+    #[tokio::test]
+    async fn browse_disk() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(req.uri().path(), "/axis-cgi/record/list.cgi");
+            assert_eq!(
+                req.uri().query(),
+                Some("listentity=recordingid&diskid=SD_DISK")
+            );
+            let body = format!(
+                r#"<?xml version="1.0"?><root><recordings totalnumberofrecordings="3" numberofrecordings="3">{}<recording diskid="SD_DISK" recordingid="20201009_153001_9E3C_ACCC8E000000" starttime="" recordingstatus="unknown"/>{}</recordings></root>"#,
+                recording_xml(0),
+                recording_xml(2),
+            );
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/xml")
+                .body(vec![body.into_bytes()])
+        });
+        let recordings = Recordings {
+            device: &client,
+            supports_continuous_recording: false,
+            supports_playback_over_rtsp: false,
+            supports_exporting: true,
+        };
+
+        let entries = recordings
+            .browse_disk(&DiskId::new("SD_DISK"))
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 3);
+        match &entries[0] {
+            DiskRecordingEntry::Intact(recording) => {
+                assert_eq!(recording.id.as_str(), "20201009_153000_9E3C_ACCC8E000000")
+            }
+            other => panic!("expected an intact recording, got {:?}", other),
+        }
+        match &entries[1] {
+            DiskRecordingEntry::Damaged(damaged) => {
+                assert_eq!(
+                    damaged.id,
+                    Some(RecordingId::new("20201009_153001_9E3C_ACCC8E000000"))
+                );
+                assert_eq!(damaged.disk_id, Some(DiskId::new("SD_DISK")));
+                assert_eq!(damaged.attributes["recordingstatus"], "unknown");
+                assert_eq!(damaged.attributes["starttime"], "");
+            }
+            other => panic!("expected a damaged recording, got {:?}", other),
+        }
+        assert!(matches!(entries[2], DiskRecordingEntry::Intact(_)));
+    }
+
+    #[tokio::test]
+    async fn export() {
+        use futures::io::AsyncReadExt;
+
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path(),
+                "/axis-cgi/record/export/exportrecording.cgi"
+            );
+            assert_eq!(
+                req.uri().query(),
+                Some("schemaversion=1&recordingid=20201009_153000_9E3C_ACCC8E000000&diskid=SD_DISK&exportformat=matroska&stoptime=2020-10-09T15%3A31%3A00%2B00%3A00")
+            );
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "video/x-matroska")
+                .header(http::header::CONTENT_LENGTH, "8")
+                .header(
+                    http::header::CONTENT_DISPOSITION,
+                    "attachment; filename=\"20201009_153000_9E3C_ACCC8E000000.mkv\"",
+                )
+                .body(vec![b"\x1a\x45\xdf\xa3".to_vec(), b"MKV!".to_vec()])
+        });
+        let recording: Recording =
+            quick_xml::de::from_str::<list_cgi::Recording>(&recording_xml(0))
+                .unwrap()
+                .into();
+        let options = ExportOptions {
+            start_time: None,
+            stop_time: Some(Utc.ymd(2020, 10, 9).and_hms(15, 31, 0)),
+        };
+
+        let mut recordings = Recordings {
+            device: &client,
+            supports_continuous_recording: false,
+            supports_playback_over_rtsp: false,
+            supports_exporting: false,
+        };
+        match recordings.export(&recording, &options).await {
+            Err(Error::FeatureUnavailable) => {}
+            Err(e) => panic!("expected FeatureUnavailable, got {:?}", e),
+            Ok(_) => panic!("expected FeatureUnavailable, got a body"),
+        }
+
+        recordings.supports_exporting = true;
+        let mut export = recordings.export(&recording, &options).await.unwrap();
+        assert_eq!(export.content_length(), Some(8));
+        let mut contents = Vec::new();
+        export.read_to_end(&mut contents).await.unwrap();
+        assert_eq!(contents, b"\x1a\x45\xdf\xa3MKV!");
+
+        let export = recordings
+            .export_with_headers(&recording, &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            export.content_disposition_filename(),
+            Some("20201009_153000_9E3C_ACCC8E000000.mkv")
+        );
+        let mut contents = Vec::new();
+        export
+            .into_inner()
+            .read_to_end(&mut contents)
+            .await
+            .unwrap();
+        assert_eq!(contents, b"\x1a\x45\xdf\xa3MKV!");
+    }
+
+    #[test]
+    fn local_time() {
+        let recording = |xml: &str| -> Recording {
+            quick_xml::de::from_str::<list_cgi::Recording>(xml)
+                .unwrap()
+                .into()
+        };
+        let cet: crate::v4::time_service::PosixTimeZone =
+            "CET-1CEST,M3.5.0,M10.5.0/3".parse().unwrap();
+
+        // 17:30 local is 15:30 UTC during CEST
+        let consistent = recording(&recording_xml(0));
+        assert_eq!(consistent.utc_offset(), Some(FixedOffset::east(7200)));
+        assert_eq!(consistent.check_local_time(&cet), Ok(()));
+        assert_eq!(
+            consistent.check_local_time(&crate::v4::time_service::PosixTimeZone::utc()),
+            Err(LocalTimeMismatch {
+                actual: FixedOffset::east(7200),
+                expected: FixedOffset::east(0),
+            })
+        );
+
+        // The wall clock says UTC, but the device labelled it +02:00
+        let mislabelled = recording(&recording_xml(0).replace(
+            r#"starttimelocal="2020-10-09T17:30:00.000+02:00""#,
+            r#"starttimelocal="2020-10-09T15:30:00.000+02:00""#,
+        ));
+        assert_eq!(mislabelled.utc_offset(), Some(FixedOffset::east(0)));
+        let mismatch = mislabelled.check_local_time(&cet).unwrap_err();
+        assert_eq!(mismatch.expected, FixedOffset::east(7200));
+        assert_eq!(
+            mismatch.to_string(),
+            "recording local time is offset +00:00 from UTC, expected +02:00"
+        );
+    }
+}
@@ -50,6 +50,17 @@ pub struct Request<'a> {
     source: Option<Source>,
 }
 
+impl<'a> Request<'a> {
+    /// A request for every recording on a disk, in the device's own order.
+    pub fn for_disk(disk_id: &'a str) -> Self {
+        Request {
+            list_entity: "recordingid",
+            disk_id: Some(disk_id),
+            ..Default::default()
+        }
+    }
+}
+
 impl<'a> From<&'a ListRecordingsRequest> for Request<'a> {
     fn from(req: &'a ListRecordingsRequest) -> Self {
         Request {
@@ -116,12 +127,16 @@ pub struct Recording {
     pub source: Source,
 
     // schema says maxoccurs="unbounded", so this is a Vec
-    #[serde(rename = "video")]
+    #[serde(default, rename = "video")]
     pub video: Vec<Video>,
 
     // schema says maxoccurs="unbounded", so this is a Vec
-    #[serde(rename = "audio")]
+    #[serde(default, rename = "audio")]
     pub audio: Vec<Audio>,
+
+    // only reported by some firmware versions
+    #[serde(default, rename = "size", skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
 }
 
 #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -184,4 +199,15 @@ mod tests {
         assert_eq!(&serde_urlencoded::to_string(&req).unwrap(), str);
         assert_eq!(&serde_urlencoded::from_str::<Request>(str).unwrap(), &req);
     }
+
+    #[test]
+    fn recording_size() {
+        let xml = r#"<recording diskid="SD_DISK" recordingid="20201009_153002_9E3C_ACCC8E000000" starttime="2020-10-09T15:30:02.123Z" starttimelocal="2020-10-09T17:30:02.123+02:00" stoptime="2020-10-09T15:31:02.456Z" stoptimelocal="2020-10-09T17:31:02.456+02:00" recordingtype="triggered" eventid="motion" eventtrigger="motion" recordingstatus="completed" source="1" size="8388608"></recording>"#;
+        let recording: Recording = quick_xml::de::from_str(xml).unwrap();
+        assert_eq!(recording.size, Some(8388608));
+
+        let xml = xml.replace(r#" size="8388608""#, "");
+        let recording: Recording = quick_xml::de::from_str(&xml).unwrap();
+        assert_eq!(recording.size, None);
+    }
 }
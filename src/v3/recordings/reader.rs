@@ -0,0 +1,435 @@
+//! An incremental parser for `list.cgi` responses.
+
+use super::*;
+use quick_xml::events::Event;
+use std::ops::Range;
+use std::pin::Pin;
+
+/// An incremental parser for `list.cgi` responses, yielding one `Recording` at a time.
+///
+/// Devices with large SD cards can report tens of thousands of recordings in a single response.
+/// Rather than deserializing the entire document into memory at once, `RecordingsReader` walks the
+/// XML and deserializes each `<recording>` element as it is reached.
+pub struct RecordingsReader<'a> {
+    input: &'a [u8],
+    offset: usize,
+    scanner: Scanner,
+    done: bool,
+}
+
+impl<'a> RecordingsReader<'a> {
+    /// Begin parsing a `list.cgi` response body.
+    ///
+    /// A leading UTF-8 byte order mark is ignored.
+    pub fn new(input: &'a [u8]) -> Self {
+        Self {
+            input: crate::xml::strip_bom(input),
+            offset: 0,
+            scanner: Scanner::default(),
+            done: false,
+        }
+    }
+
+    /// The total number of recordings on the device, once the `<recordings>` element has been
+    /// read.
+    pub fn total_number_of_recordings(&self) -> Option<u64> {
+        self.scanner.total_number_of_recordings
+    }
+
+    /// The number of recordings in this response, once the `<recordings>` element has been read.
+    pub fn number_of_recordings(&self) -> Option<u64> {
+        self.scanner.number_of_recordings
+    }
+
+    fn next_recording(&mut self) -> Result<Option<Recording>> {
+        let input = &self.input[self.offset..];
+        match self.scanner.scan(input, true)? {
+            (Scan::Element(range), consumed) => {
+                self.offset += consumed;
+                decode_recording(&input[range]).map(Some)
+            }
+            (_, consumed) => {
+                self.offset += consumed;
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// An incremental parser for a `list.cgi` response body which is still arriving.
+///
+/// Only the part of the body which hasn't been parsed yet is held in memory, which is at most a
+/// chunk of the body and one `<recording>` element.
+pub(super) struct BodyRecordingsReader<B> {
+    body: Pin<Box<B>>,
+    buf: Vec<u8>,
+    offset: usize,
+    scanner: Scanner,
+    eof: bool,
+}
+
+impl<B, C> BodyRecordingsReader<B>
+where
+    B: Stream<Item = std::result::Result<C, crate::transport::Error>>,
+    C: AsRef<[u8]>,
+{
+    pub(super) fn new(body: B) -> Self {
+        Self {
+            body: Box::pin(body),
+            buf: Vec::new(),
+            offset: 0,
+            scanner: Scanner::default(),
+            eof: false,
+        }
+    }
+
+    pub(super) fn total_number_of_recordings(&self) -> Option<u64> {
+        self.scanner.total_number_of_recordings
+    }
+
+    pub(super) fn number_of_recordings(&self) -> Option<u64> {
+        self.scanner.number_of_recordings
+    }
+
+    pub(super) async fn next_recording(&mut self) -> Result<Option<Recording>> {
+        match self.next_element().await? {
+            Some(range) => decode_recording(&self.buf[range]).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Read the next recording, reporting one which can't be parsed as `Damaged` rather than
+    /// failing.
+    pub(super) async fn next_entry(&mut self) -> Result<Option<DiskRecordingEntry>> {
+        match self.next_element().await? {
+            Some(range) => decode_entry(&self.buf[range]).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Advance to the next `<recording>` element, reading more of the body as needed, and return
+    /// its position in `buf`.
+    async fn next_element(&mut self) -> Result<Option<Range<usize>>> {
+        loop {
+            let (scan, consumed) = self.scanner.scan(&self.buf[self.offset..], self.eof)?;
+            let start = self.offset;
+            self.offset += consumed;
+            match scan {
+                Scan::Element(range) => {
+                    return Ok(Some(start + range.start..start + range.end));
+                }
+                Scan::End => return Ok(None),
+                Scan::Incomplete => {
+                    self.buf.drain(..self.offset);
+                    self.offset = 0;
+                    match self.body.next().await {
+                        Some(chunk) => self.buf.extend_from_slice(chunk?.as_ref()),
+                        None => self.eof = true,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The outcome of scanning for the next `<recording>` element.
+enum Scan {
+    /// A complete element, at this position in the input.
+    Element(Range<usize>),
+    /// The input ended before the next element did.
+    Incomplete,
+    /// There are no more elements.
+    End,
+}
+
+/// Finds `<recording>` elements in a `list.cgi` response, noting the attributes of the
+/// `<recordings>` element along the way.
+#[derive(Default)]
+struct Scanner {
+    total_number_of_recordings: Option<u64>,
+    number_of_recordings: Option<u64>,
+}
+
+impl Scanner {
+    /// Scan `input` for the next `<recording>` element, returning how much of `input` can be
+    /// discarded afterwards.
+    ///
+    /// If `complete` is false, more of the response may follow `input`, so running out of input
+    /// returns `Scan::Incomplete` rather than ending the document.
+    fn scan(&mut self, input: &[u8], complete: bool) -> Result<(Scan, usize)> {
+        // quick-xml reads a tag cut short by the end of its input as if it were complete, so only
+        // hand it input which ends at a markup boundary
+        let input = if complete {
+            input
+        } else {
+            &input[..complete_markup_len(input)]
+        };
+
+        let mut reader = quick_xml::Reader::from_reader(input);
+        reader.check_end_names(false);
+        let mut buf = Vec::new();
+        loop {
+            let start = reader.buffer_position();
+            buf.clear();
+            let is_empty = match reader
+                .read_event(&mut buf)
+                .map_err(quick_xml::DeError::Xml)?
+            {
+                Event::Start(e) if e.name() == b"recordings" => {
+                    let e = e.into_owned();
+                    self.read_recordings_attributes(&e)?;
+                    continue;
+                }
+                Event::Empty(e) if e.name() == b"recordings" => {
+                    let e = e.into_owned();
+                    self.read_recordings_attributes(&e)?;
+                    return Ok((Scan::End, reader.buffer_position()));
+                }
+                Event::Start(e) if e.name() == b"recording" => false,
+                Event::Empty(e) if e.name() == b"recording" => true,
+                Event::Eof if complete => return Ok((Scan::End, input.len())),
+                Event::Eof => return Ok((Scan::Incomplete, input.len())),
+                _ => continue,
+            };
+
+            if !is_empty {
+                buf.clear();
+                match reader.read_to_end(b"recording", &mut buf) {
+                    Ok(()) => {}
+                    Err(quick_xml::Error::UnexpectedEof(_)) if !complete => {
+                        return Ok((Scan::Incomplete, start));
+                    }
+                    Err(e) => return Err(quick_xml::DeError::Xml(e).into()),
+                }
+            }
+            let end = reader.buffer_position();
+            return Ok((Scan::Element(start..end), end));
+        }
+    }
+
+    fn read_recordings_attributes(&mut self, e: &quick_xml::events::BytesStart) -> Result<()> {
+        for attr in e.attributes() {
+            let attr = attr.map_err(quick_xml::DeError::Xml)?;
+            let value = attr.unescaped_value().map_err(quick_xml::DeError::Xml)?;
+            let value = std::str::from_utf8(&value)
+                .ok()
+                .and_then(|s| u64::from_str(s).ok());
+            match attr.key {
+                b"totalnumberofrecordings" => self.total_number_of_recordings = value,
+                b"numberofrecordings" => self.number_of_recordings = value,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The length of the longest prefix of `input` which doesn't end part-way through a tag, comment,
+/// CDATA section or processing instruction.
+fn complete_markup_len(input: &[u8]) -> usize {
+    let mut offset = 0;
+    while let Some(start) = find(&input[offset..], b"<").map(|i| offset + i) {
+        let markup = &input[start..];
+        let len = if markup.starts_with(b"<!--") {
+            find(markup, b"-->").map(|i| i + 3)
+        } else if markup.starts_with(b"<![CDATA[") {
+            find(markup, b"]]>").map(|i| i + 3)
+        } else if markup.starts_with(b"<?") {
+            find(markup, b"?>").map(|i| i + 2)
+        } else {
+            tag_len(markup)
+        };
+        match len {
+            Some(len) => offset = start + len,
+            None => return start,
+        }
+    }
+    input.len()
+}
+
+/// The length of the tag at the start of `input`, allowing for `>` in quoted attribute values.
+fn tag_len(input: &[u8]) -> Option<usize> {
+    let mut quote = None;
+    for (i, &b) in input.iter().enumerate() {
+        match (quote, b) {
+            (None, b'>') => return Some(i + 1),
+            (None, b'"') | (None, b'\'') => quote = Some(b),
+            (Some(q), b) if b == q => quote = None,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn decode_recording(element: &[u8]) -> Result<Recording> {
+    let recording: list_cgi::Recording = quick_xml::de::from_reader(element)?;
+    Ok(recording.into())
+}
+
+fn decode_entry(element: &[u8]) -> Result<DiskRecordingEntry> {
+    let recording: std::result::Result<list_cgi::Recording, _> =
+        quick_xml::de::from_reader(element);
+    Ok(match recording {
+        Ok(recording) => DiskRecordingEntry::Intact(recording.into()),
+        Err(error) => {
+            let attributes = element_attributes(element)?;
+            let get = |key| attributes.get(key).filter(|v| !v.is_empty()).cloned();
+            DiskRecordingEntry::Damaged(DamagedRecording {
+                id: get("recordingid").map(RecordingId::new),
+                disk_id: get("diskid").map(DiskId::new),
+                error: error.to_string(),
+                attributes,
+            })
+        }
+    })
+}
+
+/// The attributes of the first element in `element`.
+fn element_attributes(element: &[u8]) -> Result<BTreeMap<String, String>> {
+    let mut reader = quick_xml::Reader::from_reader(element);
+    let mut buf = Vec::new();
+    let mut attributes = BTreeMap::new();
+    loop {
+        match reader
+            .read_event(&mut buf)
+            .map_err(quick_xml::DeError::Xml)?
+        {
+            Event::Start(e) | Event::Empty(e) => {
+                for attr in e.attributes() {
+                    let attr = attr.map_err(quick_xml::DeError::Xml)?;
+                    let value = attr.unescaped_value().map_err(quick_xml::DeError::Xml)?;
+                    attributes.insert(
+                        String::from_utf8_lossy(attr.key).into_owned(),
+                        String::from_utf8_lossy(&value).into_owned(),
+                    );
+                }
+                return Ok(attributes);
+            }
+            Event::Eof => return Ok(attributes),
+            _ => {}
+        }
+    }
+}
+
+impl<'a> Iterator for RecordingsReader<'a> {
+    type Item = Result<Recording>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_recording() {
+            Ok(Some(recording)) => Some(Ok(recording)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read() {
+        let xml = r#"<?xml version="1.0"?>
+<root>
+  <recordings totalnumberofrecordings="3" numberofrecordings="2">
+    <recording diskid="SD_DISK" recordingid="20201009_153002_9E3C_ACCC8E000000" starttime="2020-10-09T15:30:02.123Z" starttimelocal="2020-10-09T17:30:02.123+02:00" stoptime="2020-10-09T15:31:02.456Z" stoptimelocal="2020-10-09T17:31:02.456+02:00" recordingtype="triggered" eventid="motion" eventtrigger="motion" recordingstatus="completed" source="1" size="8388608"></recording>
+    <recording diskid="SD_DISK" recordingid="20201009_160000_1A2B_ACCC8E000000" starttime="2020-10-09T16:00:00.000Z" starttimelocal="2020-10-09T18:00:00.000+02:00" stoptime="" stoptimelocal="" recordingtype="continuous" eventid="continuous" eventtrigger="continuous" recordingstatus="recording" source="2"/>
+  </recordings>
+</root>"#;
+
+        let mut reader = RecordingsReader::new(xml.as_bytes());
+        let recordings = (&mut reader).collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(reader.total_number_of_recordings(), Some(3));
+        assert_eq!(reader.number_of_recordings(), Some(2));
+        assert_eq!(recordings.len(), 2);
+
+        assert_eq!(
+            recordings[0].id.as_str(),
+            "20201009_153002_9E3C_ACCC8E000000"
+        );
+        assert_eq!(recordings[0].size, Some(8388608));
+        assert!(recordings[0].end_time.is_some());
+
+        assert_eq!(
+            recordings[1].source,
+            Source::ChannelNumber(Channel::new(2).unwrap())
+        );
+        assert_eq!(recordings[1].end_time, None);
+    }
+
+    fn read_body(xml: &[u8], chunk_size: usize) -> Result<(Vec<Recording>, Option<u64>)> {
+        let chunks = xml
+            .chunks(chunk_size)
+            .map(|chunk| Ok(chunk.to_vec()))
+            .collect::<Vec<std::result::Result<_, crate::transport::Error>>>();
+        let mut reader = BodyRecordingsReader::new(futures::stream::iter(chunks));
+        futures::executor::block_on(async {
+            let mut recordings = Vec::new();
+            while let Some(recording) = reader.next_recording().await? {
+                recordings.push(recording);
+            }
+            Ok((recordings, reader.total_number_of_recordings()))
+        })
+    }
+
+    #[test]
+    fn read_body_in_chunks() {
+        let xml = r#"<?xml version="1.0"?>
+<!-- a comment with a < and a " -->
+<root>
+  <recordings totalnumberofrecordings="3" numberofrecordings="2">
+    <recording diskid="SD_DISK" recordingid="20201009_153002_9E3C_ACCC8E000000" starttime="2020-10-09T15:30:02.123Z" starttimelocal="2020-10-09T17:30:02.123+02:00" stoptime="2020-10-09T15:31:02.456Z" stoptimelocal="2020-10-09T17:31:02.456+02:00" recordingtype="triggered" eventid="a>b" eventtrigger="motion" recordingstatus="completed" source="1"><note text="nested"><detail/></note></recording>
+    <recording diskid="SD_DISK" recordingid="20201009_160000_1A2B_ACCC8E000000" starttime="2020-10-09T16:00:00.000Z" starttimelocal="2020-10-09T18:00:00.000+02:00" stoptime="" stoptimelocal="" recordingtype="continuous" eventid="continuous" eventtrigger="continuous" recordingstatus="recording" source="2"/>
+  </recordings>
+</root>"#;
+
+        let expected = RecordingsReader::new(xml.as_bytes())
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(expected.len(), 2);
+        assert_eq!(expected[0].event_id.as_str(), "a>b");
+        assert_eq!(parse_list_response(xml.as_bytes()).unwrap(), expected);
+
+        for &chunk_size in &[1, 7, 64, xml.len()] {
+            let (recordings, total) = read_body(xml.as_bytes(), chunk_size).unwrap();
+            assert_eq!(recordings, expected, "chunk size {}", chunk_size);
+            assert_eq!(total, Some(3));
+        }
+    }
+
+    #[test]
+    fn read_truncated_body() {
+        let xml = r#"<root><recordings><recording diskid="SD_DISK" recordingid="20201009_160000_1A2B_ACCC8E000000" starttime="2020-10-09T16:00:00.000Z" starttimelocal="2020-10-09T18:00:00.000+02:00" stoptime="" stoptimelocal="" recordingtype="continuous" eventid="continuous" eventtrigger="continuous" recordingstatus="recording" source="2"><video"#;
+        assert!(read_body(xml.as_bytes(), 16).is_err());
+    }
+
+    #[test]
+    fn read_with_bom() {
+        let xml = b"\xef\xbb\xbf<root><recordings totalnumberofrecordings=\"0\" numberofrecordings=\"0\"/></root>";
+        let mut reader = RecordingsReader::new(xml);
+        assert!(reader.next().is_none());
+        assert_eq!(reader.total_number_of_recordings(), Some(0));
+    }
+
+    #[test]
+    fn read_error() {
+        let xml = r#"<root><recordings><recording diskid="SD_DISK"/></recordings></root>"#;
+        let mut reader = RecordingsReader::new(xml.as_bytes());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+    }
+}
@@ -0,0 +1,263 @@
+//! Configuration of the device's cloud connection, known as O3C or One-Click Connection, as
+//! exposed by the VAPIX parameters interface.
+//!
+//! When enabled, the device connects outward to a dispatcher, which in turn connects it to a
+//! hosted video service. The device is identified to the service by its Owner Authentication Key
+//! (OAK). Settings live in the `RemoteService` parameter group.
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+const ENABLED: &str = "RemoteService.Enabled";
+const SERVER_LIST: &str = "RemoteService.ServerList";
+const PROXY_SERVER: &str = "RemoteService.ProxyServer";
+const PROXY_PORT: &str = "RemoteService.ProxyPort";
+const PROXY_LOGIN: &str = "RemoteService.ProxyLogin";
+const PROXY_PASSWORD: &str = "RemoteService.ProxyPassword";
+
+/// A device's cloud connection configuration interface.
+pub struct RemoteService<'a, T: Transport>(&'a Client<T>);
+
+/// A cloud connection configuration.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteServiceConfiguration {
+    /// When the device connects to the dispatcher.
+    pub mode: RemoteServiceMode,
+    /// The dispatcher addresses, as `host:port`, in order of preference.
+    pub dispatchers: Vec<String>,
+    /// The HTTP proxy through which to reach the dispatcher, if any.
+    pub proxy: Option<RemoteServiceProxy>,
+}
+
+/// When the device connects to the dispatcher.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RemoteServiceMode {
+    /// Never connect.
+    Disabled,
+    /// Always connect.
+    Enabled,
+    /// Connect only after the control button is pressed, which is the factory default for
+    /// one-click enrollment.
+    OneClick,
+    /// A mode this crate does not recognize, as the device's `Enabled` parameter value. Writing
+    /// it back leaves the device's mode unchanged.
+    Other(String),
+}
+
+impl RemoteServiceMode {
+    fn as_param(&self) -> &str {
+        match self {
+            RemoteServiceMode::Disabled => "no",
+            RemoteServiceMode::Enabled => "yes",
+            RemoteServiceMode::OneClick => "oneclick",
+            RemoteServiceMode::Other(value) => value,
+        }
+    }
+
+    fn from_param(value: &str) -> Self {
+        match value {
+            "no" => RemoteServiceMode::Disabled,
+            "yes" => RemoteServiceMode::Enabled,
+            "oneclick" => RemoteServiceMode::OneClick,
+            other => RemoteServiceMode::Other(other.to_string()),
+        }
+    }
+}
+
+/// An HTTP proxy used to reach the dispatcher.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteServiceProxy {
+    /// The proxy's hostname or IP address.
+    pub host: String,
+    /// The proxy's port.
+    pub port: u16,
+    /// The username with which to authenticate to the proxy, if any.
+    pub username: Option<String>,
+    /// The password with which to authenticate to the proxy.
+    ///
+    /// This is `None` when read from the device. When writing, `None` leaves the device's
+    /// existing password unchanged.
+    pub password: Option<String>,
+}
+
+impl<'a, T: Transport> RemoteService<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// Retrieve the cloud connection configuration.
+    ///
+    /// Returns `Error::FeatureUnavailable` if the device does not support O3C.
+    pub async fn configuration(&self) -> Result<RemoteServiceConfiguration> {
+        let params = self
+            .0
+            .parameters()
+            .list(Some(&["root.RemoteService"][..]))
+            .await?;
+        let params: BTreeMap<&str, &str> = params
+            .iter()
+            .map(|(k, v)| (k.trim_start_matches("root."), v.as_str()))
+            .collect();
+
+        configuration_from_params(&params).ok_or(Error::FeatureUnavailable)
+    }
+
+    /// Change the cloud connection configuration.
+    pub async fn set_configuration(
+        &self,
+        configuration: &RemoteServiceConfiguration,
+    ) -> Result<()> {
+        self.0
+            .parameters()
+            .update(params_from_configuration(configuration))
+            .await
+    }
+
+    /// Change when the device connects to the dispatcher, leaving other settings unchanged.
+    pub async fn set_mode(&self, mode: RemoteServiceMode) -> Result<()> {
+        self.0
+            .parameters()
+            .update(vec![(ENABLED, mode.as_param())])
+            .await
+    }
+}
+
+fn configuration_from_params(params: &BTreeMap<&str, &str>) -> Option<RemoteServiceConfiguration> {
+    let mode = RemoteServiceMode::from_param(params.get(ENABLED)?);
+    let get = |key| params.get(key).copied().unwrap_or("");
+
+    let dispatchers = get(SERVER_LIST)
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect();
+
+    let proxy = match get(PROXY_SERVER) {
+        "" => None,
+        host => Some(RemoteServiceProxy {
+            host: host.to_string(),
+            port: u16::from_str(get(PROXY_PORT)).unwrap_or(3128),
+            username: Some(get(PROXY_LOGIN))
+                .filter(|s| !s.is_empty())
+                .map(String::from),
+            password: None,
+        }),
+    };
+
+    Some(RemoteServiceConfiguration {
+        mode,
+        dispatchers,
+        proxy,
+    })
+}
+
+fn params_from_configuration(
+    configuration: &RemoteServiceConfiguration,
+) -> Vec<(&'static str, String)> {
+    let mut params = vec![
+        (ENABLED, configuration.mode.as_param().to_string()),
+        (SERVER_LIST, configuration.dispatchers.join(",")),
+    ];
+
+    match &configuration.proxy {
+        Some(proxy) => {
+            params.push((PROXY_SERVER, proxy.host.clone()));
+            params.push((PROXY_PORT, proxy.port.to_string()));
+            params.push((PROXY_LOGIN, proxy.username.clone().unwrap_or_default()));
+            if let Some(password) = &proxy.password {
+                params.push((PROXY_PASSWORD, password.clone()));
+            }
+        }
+        None => {
+            params.push((PROXY_SERVER, String::new()));
+            params.push((PROXY_LOGIN, String::new()));
+            params.push((PROXY_PASSWORD, String::new()));
+        }
+    }
+
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn configuration() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?action=list&group=root.RemoteService")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.RemoteService.Enabled=oneclick\n\
+root.RemoteService.ServerList=dispatchse1.avhs.axis.com:3128, dispatchse2.avhs.axis.com:3128\n\
+root.RemoteService.ProxyServer=proxy.example.com\n\
+root.RemoteService.ProxyPort=8080\n\
+root.RemoteService.ProxyLogin=\n\
+root.RemoteService.ProxyPassword=\n"
+                    .to_vec()])
+        });
+
+        assert_eq!(
+            client.remote_service().configuration().await.unwrap(),
+            RemoteServiceConfiguration {
+                mode: RemoteServiceMode::OneClick,
+                dispatchers: vec![
+                    "dispatchse1.avhs.axis.com:3128".into(),
+                    "dispatchse2.avhs.axis.com:3128".into(),
+                ],
+                proxy: Some(RemoteServiceProxy {
+                    host: "proxy.example.com".into(),
+                    port: 8080,
+                    username: None,
+                    password: None,
+                }),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn set_mode() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?RemoteService.Enabled=yes&action=update")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"OK".to_vec()])
+        });
+
+        client
+            .remote_service()
+            .set_mode(RemoteServiceMode::Enabled)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn unrecognized_mode_round_trips() {
+        let params: BTreeMap<&str, &str> = vec![(ENABLED, "scheduled")].into_iter().collect();
+        let configuration = configuration_from_params(&params).unwrap();
+        assert_eq!(
+            configuration.mode,
+            RemoteServiceMode::Other("scheduled".into())
+        );
+        assert_eq!(
+            params_from_configuration(&configuration)[0],
+            (ENABLED, "scheduled".to_string())
+        );
+    }
+}
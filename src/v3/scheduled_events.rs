@@ -0,0 +1,251 @@
+//! Scheduled events, as exposed by the VAPIX parameters interface.
+//!
+//! Devices describe recurring time windows, like maintenance windows or office hours, as event
+//! types in the `Event.E{index}` parameter groups. Each event is either triggered (`Type=T`) or
+//! scheduled (`Type=S`); this module covers the scheduled kind, which recurs on certain weekdays
+//! starting at a certain time. The actions an event performs, like restarting the device, are
+//! configured separately by the device's action rules.
+
+use crate::*;
+use chrono::{NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+/// A device's scheduled events.
+pub struct ScheduledEvents<'a, T: Transport>(&'a Client<T>);
+
+/// A scheduled event.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledEvent {
+    /// The index of the event's `Event.E{index}` parameter group.
+    pub index: u32,
+    /// The name of the event.
+    pub name: String,
+    /// Is the event enabled?
+    pub enabled: bool,
+    /// When the event recurs.
+    pub window: ScheduleWindow,
+}
+
+/// A weekly recurring window of time.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleWindow {
+    /// The days of the week on which the window starts.
+    pub weekdays: Vec<Weekday>,
+    /// The device-local time at which the window starts, to the minute.
+    pub start_time: NaiveTime,
+    /// The length of the window, to the minute.
+    pub duration: Duration,
+}
+
+impl<'a, T: Transport> ScheduledEvents<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// List the device's scheduled events.
+    pub async fn list(&self) -> Result<Vec<ScheduledEvent>> {
+        let params = self
+            .0
+            .parameters()
+            .list(Some(&["root.Event"][..]))
+            .await
+            .map_404_to_feature_unavailable()?;
+        let params: BTreeMap<&str, &str> = params
+            .iter()
+            .map(|(k, v)| (k.trim_start_matches("root."), v.as_str()))
+            .collect();
+
+        Ok(events_from_params(&params))
+    }
+
+    /// Enable or disable a scheduled event.
+    pub async fn set_enabled(&self, index: u32, enabled: bool) -> Result<()> {
+        self.0
+            .parameters()
+            .update(vec![(
+                format!("Event.E{}.Enabled", index),
+                if enabled { "yes" } else { "no" }.to_string(),
+            )])
+            .await
+    }
+
+    /// Change when a scheduled event recurs.
+    pub async fn set_window(&self, index: u32, window: &ScheduleWindow) -> Result<()> {
+        let minutes = window.duration.as_secs() / 60;
+        if window.weekdays.is_empty() || minutes == 0 || minutes > 7 * 24 * 60 {
+            return Err(Error::Other(
+                "schedule windows need at least one weekday and a duration of up to a week",
+            ));
+        }
+
+        self.0
+            .parameters()
+            .update(vec![
+                (
+                    format!("Event.E{}.Weekdays", index),
+                    format_weekdays(&window.weekdays),
+                ),
+                (
+                    format!("Event.E{}.Starttime", index),
+                    window.start_time.format("%H:%M").to_string(),
+                ),
+                (
+                    format!("Event.E{}.Duration", index),
+                    format!("{:02}:{:02}", minutes / 60, minutes % 60),
+                ),
+            ])
+            .await
+    }
+}
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// Parse a weekday mask like `1111100`, which starts on Monday.
+fn parse_weekdays(value: &str) -> Option<Vec<Weekday>> {
+    if value.len() != 7 {
+        return None;
+    }
+
+    let mut weekdays = Vec::new();
+    for (c, weekday) in value.chars().zip(WEEKDAYS.iter()) {
+        match c {
+            '1' => weekdays.push(*weekday),
+            '0' => {}
+            _ => return None,
+        }
+    }
+    Some(weekdays)
+}
+
+fn format_weekdays(weekdays: &[Weekday]) -> String {
+    WEEKDAYS
+        .iter()
+        .map(|weekday| if weekdays.contains(weekday) { '1' } else { '0' })
+        .collect()
+}
+
+/// Parse a duration like `24:00` or `168:00`, in hours and minutes.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let mut parts = value.splitn(2, ':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    Some(Duration::from_secs((hours * 60 + minutes) * 60))
+}
+
+fn events_from_params(params: &BTreeMap<&str, &str>) -> Vec<ScheduledEvent> {
+    let indices: BTreeSet<u32> = params
+        .keys()
+        .filter_map(|k| k.strip_prefix("Event.E"))
+        .filter_map(|k| k.splitn(2, '.').next())
+        .filter_map(|index| index.parse().ok())
+        .collect();
+
+    indices
+        .into_iter()
+        .filter_map(|index| {
+            let get = |name: &str| {
+                params
+                    .get(format!("Event.E{}.{}", index, name).as_str())
+                    .copied()
+            };
+
+            if get("Type")? != "S" {
+                return None;
+            }
+
+            Some(ScheduledEvent {
+                index,
+                name: get("Name").unwrap_or("").to_string(),
+                enabled: get("Enabled") == Some("yes"),
+                window: ScheduleWindow {
+                    weekdays: parse_weekdays(get("Weekdays")?)?,
+                    start_time: NaiveTime::parse_from_str(get("Starttime")?, "%H:%M").ok()?,
+                    duration: parse_duration(get("Duration")?)?,
+                },
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn list() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?action=list&group=root.Event")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.Event.E0.Name=Motion\n\
+root.Event.E0.Type=T\n\
+root.Event.E0.Enabled=yes\n\
+root.Event.E1.Name=Maintenance\n\
+root.Event.E1.Type=S\n\
+root.Event.E1.Enabled=no\n\
+root.Event.E1.Weekdays=0000011\n\
+root.Event.E1.Starttime=02:30\n\
+root.Event.E1.Duration=01:15\n"
+                    .to_vec()])
+        });
+
+        assert_eq!(
+            client.scheduled_events().list().await.unwrap(),
+            vec![ScheduledEvent {
+                index: 1,
+                name: "Maintenance".into(),
+                enabled: false,
+                window: ScheduleWindow {
+                    weekdays: vec![Weekday::Sat, Weekday::Sun],
+                    start_time: NaiveTime::from_hms(2, 30, 0),
+                    duration: Duration::from_secs(75 * 60),
+                },
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn set_window() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?Event.E1.Duration=08%3A00&Event.E1.Starttime=22%3A00&Event.E1.Weekdays=1111100&action=update")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"OK".to_vec()])
+        });
+
+        client
+            .scheduled_events()
+            .set_window(
+                1,
+                &ScheduleWindow {
+                    weekdays: WEEKDAYS[..5].to_vec(),
+                    start_time: NaiveTime::from_hms(22, 0, 0),
+                    duration: Duration::from_secs(8 * 60 * 60),
+                },
+            )
+            .await
+            .unwrap();
+    }
+}
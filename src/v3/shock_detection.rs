@@ -0,0 +1,146 @@
+//! Shock detection, as exposed by the VAPIX parameters interface.
+//!
+//! Some ruggedized and vandal-resistant models include an accelerometer which detects when the
+//! device is hit, tilted, or otherwise physically disturbed. Settings live in the `ShockDetection`
+//! parameter group, which other devices omit. Detections are published on `SHOCK_DETECTION_TOPIC`,
+//! where action rules and event subscribers can react to them.
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The event topic on which devices report detected shocks, with a boolean `state` data item.
+pub const SHOCK_DETECTION_TOPIC: &str = "tns1:Device/tnsaxis:Sensor/ShockDetected";
+
+/// A device's shock detection controls.
+pub struct ShockDetection<'a, T: Transport>(&'a Client<T>);
+
+/// The shock detection configuration of a device.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShockDetectionConfiguration {
+    /// Is shock detection enabled?
+    pub enabled: bool,
+    /// How readily a shock is detected, from 0 (the hardest impacts only) to 100 (the slightest
+    /// vibration).
+    pub sensitivity: u8,
+}
+
+impl<'a, T: Transport> ShockDetection<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// Retrieve the shock detection configuration.
+    ///
+    /// Returns `Error::FeatureUnavailable` if the device has no shock detection.
+    pub async fn configuration(&self) -> Result<ShockDetectionConfiguration> {
+        let params = self
+            .0
+            .parameters()
+            .list(Some(&["root.ShockDetection"][..]))
+            .await
+            .map_404_to_feature_unavailable()?;
+        let params: BTreeMap<&str, &str> = params
+            .iter()
+            .map(|(k, v)| (k.trim_start_matches("root."), v.as_str()))
+            .collect();
+
+        configuration_from_params(&params).ok_or(Error::FeatureUnavailable)
+    }
+
+    /// Enable or disable shock detection.
+    pub async fn set_enabled(&self, enabled: bool) -> Result<()> {
+        self.0
+            .parameters()
+            .update(vec![(
+                "ShockDetection.Enabled",
+                if enabled { "yes" } else { "no" },
+            )])
+            .await
+    }
+
+    /// Change how readily a shock is detected, from 0 to 100.
+    pub async fn set_sensitivity(&self, sensitivity: u8) -> Result<()> {
+        if sensitivity > 100 {
+            return Err(Error::Other("sensitivity must be between 0 and 100"));
+        }
+
+        self.0
+            .parameters()
+            .update(vec![(
+                "ShockDetection.Sensitivity".to_string(),
+                sensitivity.to_string(),
+            )])
+            .await
+    }
+}
+
+fn configuration_from_params(params: &BTreeMap<&str, &str>) -> Option<ShockDetectionConfiguration> {
+    Some(ShockDetectionConfiguration {
+        enabled: *params.get("ShockDetection.Enabled")? == "yes",
+        sensitivity: params.get("ShockDetection.Sensitivity")?.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn configuration() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?action=list&group=root.ShockDetection")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.ShockDetection.Enabled=yes\n\
+root.ShockDetection.Sensitivity=80\n"
+                    .to_vec()])
+        });
+
+        assert_eq!(
+            client.shock_detection().configuration().await.unwrap(),
+            ShockDetectionConfiguration {
+                enabled: true,
+                sensitivity: 80,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn unsupported() {
+        let client = crate::mock_client(|_| {
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![
+                    b"# Error: Error -1 getting param in group 'ShockDetection'\n".to_vec(),
+                ])
+        });
+
+        assert!(client.shock_detection().configuration().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn set_sensitivity() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?ShockDetection.Sensitivity=25&action=update")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"OK".to_vec()])
+        });
+
+        client.shock_detection().set_sensitivity(25).await.unwrap();
+        assert!(client.shock_detection().set_sensitivity(200).await.is_err());
+    }
+}
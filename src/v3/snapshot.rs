@@ -0,0 +1,279 @@
+//! The VAPIX JPEG snapshot interface at `/axis-cgi/jpg/image.cgi`.
+
+use crate::*;
+use serde::Serialize;
+use std::fmt;
+
+/// A device's JPEG snapshot interface.
+pub struct Snapshot<'a, T: Transport>(&'a Client<T>);
+
+/// Options for capturing a JPEG snapshot.
+///
+/// Options left as `None` use the device's configured defaults for the channel.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SnapshotOptions {
+    /// The video channel to capture.
+    pub channel: Channel,
+    /// The resolution of the image. The device rejects resolutions it does not support.
+    pub resolution: Option<Resolution>,
+    /// The JPEG compression level, from 0 (best quality) to 100 (smallest image).
+    pub compression: Option<u8>,
+    /// The rotation to apply to the image.
+    pub rotation: Option<Rotation>,
+}
+
+impl SnapshotOptions {
+    /// Default options for capturing from `channel`.
+    pub fn new(channel: Channel) -> Self {
+        Self {
+            channel,
+            resolution: None,
+            compression: None,
+            rotation: None,
+        }
+    }
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        Self::new(Channel::first())
+    }
+}
+
+/// An image resolution in pixels, formatted like `1920x1080`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Resolution {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl Serialize for Resolution {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A clockwise image rotation.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Rotation {
+    Rotate0,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+impl Rotation {
+    /// The rotation in degrees.
+    pub fn degrees(self) -> u16 {
+        match self {
+            Rotation::Rotate0 => 0,
+            Rotation::Rotate90 => 90,
+            Rotation::Rotate180 => 180,
+            Rotation::Rotate270 => 270,
+        }
+    }
+}
+
+impl Serialize for Rotation {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(self.degrees())
+    }
+}
+
+impl<'a, T: Transport> Snapshot<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// Capture a JPEG image from the given video channel, returning the raw JPEG bytes.
+    pub async fn capture(&self, channel: Channel) -> Result<Vec<u8>> {
+        self.capture_with_options(&SnapshotOptions::new(channel))
+            .await
+    }
+
+    /// Capture a JPEG image with the given options, returning the raw JPEG bytes.
+    pub async fn capture_with_options(&self, options: &SnapshotOptions) -> Result<Vec<u8>> {
+        self.capture_with_headers(options)
+            .await
+            .map(WithHeaders::into_inner)
+    }
+
+    /// Capture a JPEG image with the given options, along with the HTTP response headers.
+    ///
+    /// The `Date:` header, available through `WithHeaders::date()`, indicates when the device
+    /// captured the image.
+    pub async fn capture_with_headers(
+        &self,
+        options: &SnapshotOptions,
+    ) -> Result<WithHeaders<Vec<u8>>> {
+        let (resp, body) = self
+            .0
+            .roundtrip(self.request(options)?, "image/jpeg")
+            .await
+            .map_404_to_feature_unavailable()?;
+        Ok(WithHeaders::new(body, resp.headers))
+    }
+
+    /// Capture a JPEG image from the given video channel, writing it to `file` as it arrives.
+    ///
+    /// Returns the number of bytes written.
+    #[cfg(feature = "tokio")]
+    pub async fn capture_to_file<P>(
+        &self,
+        channel: Channel,
+        file: &mut tokio::fs::File,
+        progress: P,
+    ) -> Result<u64>
+    where
+        P: FnMut(crate::DownloadProgress),
+    {
+        self.capture_to_file_with_options(&SnapshotOptions::new(channel), file, progress)
+            .await
+    }
+
+    /// Capture a JPEG image with the given options, writing it to `file` as it arrives.
+    ///
+    /// Returns the number of bytes written.
+    #[cfg(feature = "tokio")]
+    pub async fn capture_to_file_with_options<P>(
+        &self,
+        options: &SnapshotOptions,
+        file: &mut tokio::fs::File,
+        progress: P,
+    ) -> Result<u64>
+    where
+        P: FnMut(crate::DownloadProgress),
+    {
+        self.0
+            .download_to_file(self.request(options)?, "image/jpeg", file, progress)
+            .await
+            .map_404_to_feature_unavailable()
+    }
+
+    fn request(&self, options: &SnapshotOptions) -> Result<http::Request<Vec<u8>>> {
+        #[derive(Serialize)]
+        struct Req {
+            camera: Channel,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            resolution: Option<Resolution>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            compression: Option<u8>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            rotation: Option<Rotation>,
+        }
+
+        if matches!(options.compression, Some(c) if c > 100) {
+            return Err(Error::Other("JPEG compression must be between 0 and 100"));
+        }
+
+        Ok(http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for_args(
+                "/axis-cgi/jpg/image.cgi",
+                Req {
+                    camera: options.channel,
+                    resolution: options.resolution,
+                    compression: options.compression,
+                    rotation: options.rotation,
+                },
+            )?)
+            .body(Vec::new())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn capture() {
+        let device = crate::mock_client(|req| {
+            assert_eq!(req.uri().path(), "/axis-cgi/jpg/image.cgi");
+            assert_eq!(req.uri().query(), Some("camera=2"));
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "image/jpeg")
+                .body(vec![b"\xff\xd8\xff\xd9".to_vec()])
+        });
+
+        let jpeg = device
+            .snapshot()
+            .capture(Channel::new(2).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(jpeg, b"\xff\xd8\xff\xd9");
+    }
+
+    #[tokio::test]
+    async fn capture_with_headers() {
+        let device = crate::mock_client(|_| {
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "image/jpeg")
+                .header(http::header::DATE, "Fri, 09 Oct 2020 15:30:00 GMT")
+                .body(vec![b"\xff\xd8\xff\xd9".to_vec()])
+        });
+
+        let jpeg = device
+            .snapshot()
+            .capture_with_headers(&SnapshotOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(jpeg.value(), b"\xff\xd8\xff\xd9");
+        assert_eq!(
+            jpeg.date(),
+            Some(chrono::DateTime::parse_from_rfc3339("2020-10-09T15:30:00Z").unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn capture_with_options() {
+        let device = crate::mock_client(|req| {
+            assert_eq!(req.uri().path(), "/axis-cgi/jpg/image.cgi");
+            assert_eq!(
+                req.uri().query(),
+                Some("camera=1&resolution=640x480&compression=30&rotation=180")
+            );
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "image/jpeg")
+                .body(vec![b"\xff\xd8\xff\xd9".to_vec()])
+        });
+
+        let jpeg = device
+            .snapshot()
+            .capture_with_options(&SnapshotOptions {
+                resolution: Some(Resolution::new(640, 480)),
+                compression: Some(30),
+                rotation: Some(Rotation::Rotate180),
+                ..SnapshotOptions::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(jpeg, b"\xff\xd8\xff\xd9");
+
+        let device = crate::mock_client(|_| -> Result<http::Response<Vec<Vec<u8>>>, http::Error> {
+            panic!("no request expected")
+        });
+        assert!(device
+            .snapshot()
+            .capture_with_options(&SnapshotOptions {
+                compression: Some(101),
+                ..SnapshotOptions::default()
+            })
+            .await
+            .is_err());
+    }
+}
@@ -1,7 +1,9 @@
 //! The VAPIX system log interface at `/axis-cgi/systemlog.cgi`.
 
+use crate::v4::time_service::PosixTimeZone;
 use crate::*;
 use chrono::prelude::*;
+use futures::stream::{self, Stream};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::str::FromStr;
@@ -18,28 +20,28 @@ impl<'a, T: Transport> SystemLog<'a, T> {
     //
     // The level of information included in the log is set in the `Log.System` parameter group.
     pub async fn entries(&self) -> Result<Entries> {
+        self.entries_with_headers()
+            .await
+            .map(WithHeaders::into_inner)
+    }
+
+    // Retrieve system log information, along with the HTTP response headers.
+    pub async fn entries_with_headers(&self) -> Result<WithHeaders<Entries>> {
         let req = http::request::Builder::new()
             .method(http::Method::GET)
-            .uri(self.0.uri_for("/axis-cgi/systemlog.cgi").unwrap())
-            .body(Vec::new())
-            .unwrap();
+            .uri(self.0.uri_for("/axis-cgi/systemlog.cgi")?)
+            .body(Vec::new())?;
 
         let (resp, body) = self.0.roundtrip(req, "text/plain").await?;
+        let body = WithHeaders::new(body, resp.headers);
 
         // Use the HTTP Date: header returned with the logs to help parse the log timestamps
-        // If that's missing or un-parseable, use our system time
+        // If that's missing or un-parseable, use the client's clock
         // Clock drift isn't that big of a problem until we get to ±6 months.
-        let now = resp
-            .headers
-            .get(http::header::DATE)
-            .and_then(|v| v.to_str().ok())
-            .and_then(|d| DateTime::parse_from_rfc2822(d).ok())
-            .unwrap_or(Local::now().into());
-
-        Ok(Entries::new(
-            String::from_utf8_lossy(body.as_slice()).into_owned(),
-            now,
-        ))
+        let now = body.date().unwrap_or_else(|| self.0.now());
+
+        Ok(body
+            .map(|body| Entries::new(String::from_utf8_lossy(body.as_slice()).into_owned(), now)))
     }
 }
 
@@ -67,6 +69,14 @@ impl Entries {
     pub fn iter(&self) -> EntriesIter {
         EntriesIter(self.buffer.rsplit('\n'), None, self.generated_at)
     }
+
+    /// Iterate over the `Entries`, normalizing every timestamp to UTC.
+    ///
+    /// Entries logged without an offset are interpreted in `time_zone`, which should be the
+    /// device's configured time zone as returned by `v4::TimeService::time_zone()`.
+    pub fn iter_utc(&self, time_zone: &PosixTimeZone) -> UtcEntriesIter {
+        UtcEntriesIter(self.iter(), *time_zone)
+    }
 }
 
 /// An `Iterator` which parses `Entry` records.
@@ -108,6 +118,26 @@ impl<'a> Iterator for EntriesIter<'a> {
     }
 }
 
+/// An `Iterator` which parses `UtcEntry` records.
+pub struct UtcEntriesIter<'a>(EntriesIter<'a>, PosixTimeZone);
+
+impl<'a> Iterator for UtcEntriesIter<'a> {
+    type Item = Result<UtcEntry<'a>, EntryParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let time_zone = &self.1;
+        self.0.next().map(|result| {
+            result.map(|entry| UtcEntry {
+                timestamp: entry.timestamp.to_utc(time_zone),
+                hostname: entry.hostname,
+                level: entry.level,
+                source: entry.source,
+                message: entry.message,
+            })
+        })
+    }
+}
+
 impl<'a> IntoIterator for &'a Entries {
     type Item = Result<Entry<'a>, EntryParseError>;
     type IntoIter = EntriesIter<'a>;
@@ -216,6 +246,16 @@ impl std::fmt::Display for Timestamp {
     }
 }
 
+impl Timestamp {
+    /// Convert this timestamp to UTC, interpreting a `Naive` timestamp in `time_zone`.
+    pub fn to_utc(&self, time_zone: &PosixTimeZone) -> DateTime<Utc> {
+        match self {
+            Timestamp::Naive(dt) => time_zone.to_utc(dt),
+            Timestamp::FixedOffset(dt) => dt.with_timezone(&Utc),
+        }
+    }
+}
+
 impl PartialOrd for Timestamp {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match (self, other) {
@@ -239,6 +279,107 @@ pub struct Entry<'a> {
     pub message: &'a str,
 }
 
+/// A system log `Entry` whose timestamp has been normalized to UTC.
+#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UtcEntry<'a> {
+    pub timestamp: DateTime<Utc>,
+    pub hostname: &'a str,
+    pub level: Level,
+    pub source: Source<'a>,
+    pub message: &'a str,
+}
+
+/// A `UtcEntry` tagged with the device which logged it.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct TaggedEntry<'a, K> {
+    /// The caller-supplied key identifying the device, like its serial number.
+    pub device: K,
+    #[serde(flatten, borrow)]
+    pub entry: UtcEntry<'a>,
+}
+
+/// An entry which `merge()` could not parse, tagged with the device which logged it.
+#[derive(Debug, PartialEq)]
+pub struct TaggedEntryParseError<K> {
+    /// The caller-supplied key identifying the device.
+    pub device: K,
+    /// Why the entry could not be parsed.
+    pub error: EntryParseError,
+}
+
+impl<K: std::fmt::Display> std::fmt::Display for TaggedEntryParseError<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.device, self.error)
+    }
+}
+
+impl<K: std::fmt::Debug + std::fmt::Display> std::error::Error for TaggedEntryParseError<K> {}
+
+/// Merge system logs from several devices into a single stream in chronological order.
+///
+/// Each log is paired with a key identifying its device, which is attached to each of its
+/// entries, and with the device's time zone, in which entries logged without an offset are
+/// interpreted. Entries are sorted by their UTC timestamps. Entries with identical timestamps
+/// retain the order in which they were logged, and are otherwise ordered by the position of their
+/// log in `logs`.
+///
+/// Entries which could not be parsed are yielded as errors, ahead of every entry, since their
+/// place in the log is unknown.
+///
+/// ```ignore
+/// let merged: Vec<_> = system_log::merge(vec![
+///     ("ACCC8EF7D108", &a_entries, a_services.time_service.unwrap().time_zone().await?),
+///     ("00408CFB6888", &b_entries, b_services.time_service.unwrap().time_zone().await?),
+/// ])
+/// .collect()
+/// .await;
+/// ```
+pub fn merge<'a, K, I>(
+    logs: I,
+) -> impl Stream<Item = Result<TaggedEntry<'a, K>, TaggedEntryParseError<K>>> + 'a
+where
+    K: Clone + 'a,
+    I: IntoIterator<Item = (K, &'a Entries, PosixTimeZone)>,
+{
+    let mut errors = Vec::new();
+    let mut merged = Vec::new();
+    for (position, (device, log, time_zone)) in logs.into_iter().enumerate() {
+        for (index, entry) in log.iter_utc(&time_zone).enumerate() {
+            match entry {
+                Ok(entry) => merged.push((
+                    position,
+                    index,
+                    TaggedEntry {
+                        device: device.clone(),
+                        entry,
+                    },
+                )),
+                Err(error) => errors.push(Err(TaggedEntryParseError {
+                    device: device.clone(),
+                    error,
+                })),
+            }
+        }
+    }
+
+    // `Entries` iterate newest-first, so of two entries from one log with identical timestamps,
+    // the one iterated later was logged first
+    merged.sort_by(|(a_position, a_index, a), (b_position, b_index, b)| {
+        a.entry
+            .timestamp
+            .cmp(&b.entry.timestamp)
+            .then(a_position.cmp(b_position))
+            .then(b_index.cmp(a_index))
+    });
+
+    stream::iter(
+        errors
+            .into_iter()
+            .chain(merged.into_iter().map(|(_, _, tagged)| Ok(tagged))),
+    )
+}
+
 #[derive(Debug, Clone, PartialEq)]
 struct RawEntry<'a> {
     pub timestamp: RawTimestamp,
@@ -251,6 +392,14 @@ struct RawEntry<'a> {
 #[derive(Debug, PartialEq)]
 pub struct EntryParseError;
 
+impl std::fmt::Display for EntryParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("unparseable system log entry")
+    }
+}
+
+impl std::error::Error for EntryParseError {}
+
 impl<'a> RawEntry<'a> {
     fn cook(
         self,
@@ -281,15 +430,17 @@ impl<'a> RawEntry<'a> {
     }
 
     fn parse_old(s: &'a str) -> Result<Self, EntryParseError> {
-        if s.len() < 30 {
-            return Err(EntryParseError);
-        }
+        // The fixed-width prefix is ASCII, so check that before slicing it by byte offsets
+        let prefix = match s.get(0..27) {
+            Some(prefix) if s.len() >= 30 && prefix.is_ascii() => prefix,
+            _ => return Err(EntryParseError),
+        };
 
-        let level = &s[0..11];
-        let timestamp = &s[11..26];
+        let level = &prefix[0..11];
+        let timestamp = &prefix[11..26];
         let rest = &s[27..];
 
-        if &s[26..27] != " " {
+        if &prefix[26..27] != " " {
             return Err(EntryParseError);
         }
 
@@ -337,7 +488,7 @@ impl<'a> RawEntry<'a> {
 
         let timestamp = RawTimestamp::parse_new(timestamp)?;
 
-        if rest.len() < 13 {
+        if rest.len() < 13 || !rest.is_char_boundary(12) {
             return Err(EntryParseError);
         }
         let (level, rest) = rest.split_at(12);
@@ -382,6 +533,140 @@ fn parse_rest(rest: &str) -> Result<(Source, &str), EntryParseError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use futures::StreamExt;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn parse_arbitrary_lines(line in "\\PC*") {
+            let _ = RawEntry::parse(&line);
+        }
+
+        #[test]
+        fn parse_nearly_valid_lines(
+            line in "(<INFO    > [A-Z][a-z]{2} [ 1-3][0-9] [0-2][0-9]:[0-5][0-9]:[0-6][0-9] |\
+                      [0-9]{4}-[01][0-9]-[0-3][0-9]T[0-2][0-9]:[0-5][0-9]:[0-6][0-9]\\.[0-9]{3}[+-][01][0-9]:[0-5][0-9] )\
+                     .{0,8}( .{0,16}(: .{0,16})?)?"
+        ) {
+            let _ = RawEntry::parse(&line);
+        }
+
+        #[test]
+        fn iterate_arbitrary_logs(log in "(\\PC{0,40}\n){0,8}") {
+            let now = FixedOffset::east(0).ymd(2020, 1, 1).and_hms(0, 0, 0);
+            for entry in &Entries::new(log, now) {
+                let _ = entry;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn entries_with_headers() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(req.uri().path(), "/axis-cgi/systemlog.cgi");
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .header(http::header::DATE, "Fri, 09 Oct 2020 15:41:30 GMT")
+                .body(vec![
+                    b"<INFO    > Oct  9 15:41:26 axis-00408cfb6888 syslogd[23459]: 1.4.1: restart.\n"
+                        .to_vec(),
+                ])
+        });
+
+        let entries = client.system_log().entries_with_headers().await.unwrap();
+        assert_eq!(
+            entries.date(),
+            Some(Utc.ymd(2020, 10, 9).and_hms(15, 41, 30).into())
+        );
+
+        let parsed = entries
+            .iter()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("parse");
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(
+            parsed[0].timestamp,
+            Timestamp::Naive(NaiveDate::from_ymd(2020, 10, 9).and_hms(15, 41, 26))
+        );
+    }
+
+    #[test]
+    fn iter_utc() {
+        let log = "2020-10-09T10:30:02.425-05:00 axis-accc8ef7d108 [ INFO    ] systemd[1]: Started Rotate log files.\n\
+                   <INFO    > Oct  9 15:41:26 axis-00408cfb6888 syslogd[23459]: 1.4.1: restart.\n";
+        let entries = Entries::new(
+            log.to_string(),
+            Utc.ymd(2020, 10, 9).and_hms(15, 41, 30).into(),
+        );
+        let time_zone: PosixTimeZone = "EST5EDT,M3.2.0,M11.1.0".parse().unwrap();
+
+        let parsed = entries
+            .iter_utc(&time_zone)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("parse");
+        assert_eq!(
+            parsed
+                .iter()
+                .map(|entry| (entry.timestamp, entry.hostname))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Utc.ymd(2020, 10, 9).and_hms(19, 41, 26),
+                    "axis-00408cfb6888"
+                ),
+                (
+                    Utc.ymd(2020, 10, 9).and_hms_milli(15, 30, 2, 425),
+                    "axis-accc8ef7d108"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_logs() {
+        let generated_at = Utc.ymd(2020, 10, 9).and_hms(16, 0, 0).into();
+        let a = Entries::new(
+            "2020-10-09T10:30:02.000-05:00 a [ INFO    ] first\n\
+             2020-10-09T10:30:04.000-05:00 a [ INFO    ] third\n\
+             2020-10-09T10:30:04.000-05:00 a [ INFO    ] fourth\n"
+                .to_string(),
+            generated_at,
+        );
+        // logged without offsets, on a device whose clock is set to UTC-5
+        let b = Entries::new(
+            "<INFO    > Oct  9 10:30:04 b fifth\n\
+             garbage\n\
+             <INFO    > Oct  9 10:30:03 b second\n"
+                .to_string(),
+            generated_at,
+        );
+
+        let merged: Vec<_> = futures::executor::block_on(
+            merge(vec![
+                ("serial-a", &a, PosixTimeZone::utc()),
+                ("serial-b", &b, "EST5".parse().unwrap()),
+            ])
+            .collect(),
+        );
+        assert_eq!(
+            merged
+                .iter()
+                .map(|result| match result {
+                    Ok(tagged) => (tagged.device, tagged.entry.message),
+                    Err(e) => (e.device, "unparseable"),
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                ("serial-b", "unparseable"),
+                ("serial-a", "first"),
+                ("serial-b", "second"),
+                ("serial-a", "third"),
+                ("serial-a", "fourth"),
+                ("serial-b", "fifth"),
+            ]
+        );
+    }
 
     #[test]
     fn entries() {
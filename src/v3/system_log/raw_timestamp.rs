@@ -68,7 +68,7 @@ impl RawTimestamp {
     }
 
     pub(crate) fn parse_new(timestamp: &str) -> Result<Self, EntryParseError> {
-        if timestamp.len() != 29 {
+        if timestamp.len() != 29 || !timestamp.is_ascii() {
             return Err(EntryParseError);
         }
         let year = parse_year(&timestamp[0..4])?;
@@ -91,7 +91,7 @@ impl RawTimestamp {
     }
 
     pub(crate) fn parse_old(timestamp: &str) -> Result<Self, EntryParseError> {
-        if timestamp.len() != 15 {
+        if timestamp.len() != 15 || !timestamp.is_ascii() {
             return Err(EntryParseError);
         }
 
@@ -379,7 +379,8 @@ fn parse_colon_second(s: &str) -> Result<(u32, u32), EntryParseError> {
         ":57" => (57, 0),
         ":58" => (58, 0),
         ":59" => (59, 0),
-        ":60" => (60, 1000),
+        // chrono represents leap seconds as :59 plus 1000ms
+        ":60" => (59, 1000),
         _ => return Err(EntryParseError),
     })
 }
@@ -387,7 +388,7 @@ fn parse_colon_second(s: &str) -> Result<(u32, u32), EntryParseError> {
 fn parse_offset(s: &str) -> Result<i32, EntryParseError> {
     Ok(match s {
         "Z" => 0,
-        s if s.len() == 6 => {
+        s if s.len() == 6 && s.is_ascii() => {
             let neg = match &s[0..1] {
                 "-" => -1,
                 "+" => 1,
@@ -395,7 +396,7 @@ fn parse_offset(s: &str) -> Result<i32, EntryParseError> {
             };
             let hour = parse_hour(&s[1..3])? as i32;
             let minute = parse_colon_minute(&s[3..6])? as i32;
-            (neg * (hour * 60) + minute) * 60
+            neg * (hour * 60 + minute) * 60
         }
         _ => return Err(EntryParseError),
     })
@@ -409,6 +410,27 @@ fn with_year(year: i32, month: u8, day: u8, hms: NaiveTime) -> Option<NaiveDateT
 mod tests {
     use crate::v3::system_log::raw_timestamp::RawTimestamp;
     use chrono::NaiveTime;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn parse_arbitrary(timestamp in "\\PC{10,32}") {
+            let _ = RawTimestamp::parse_old(&timestamp);
+            let _ = RawTimestamp::parse_new(&timestamp);
+        }
+
+        #[test]
+        fn parse_nearly_valid_old(timestamp in "[A-Z][a-z]{2} [ 0-3][0-9] [0-2][0-9]:[0-6][0-9]:[0-6][0-9]") {
+            let _ = RawTimestamp::parse_old(&timestamp);
+        }
+
+        #[test]
+        fn parse_nearly_valid_new(
+            timestamp in "[0-9]{4}-[01][0-9]-[0-3][0-9]T[0-2][0-9]:[0-6][0-9]:[0-6][0-9]\\.[0-9]{3}([+-][0-2][0-9]:[0-6][0-9]|Z)"
+        ) {
+            let _ = RawTimestamp::parse_new(&timestamp);
+        }
+    }
 
     #[test]
     fn parse_old() {
@@ -420,5 +442,27 @@ mod tests {
                 NaiveTime::from_hms(0, 19, 57)
             ))
         );
+        assert_eq!(
+            RawTimestamp::parse_old("Dec 31 23:59:60"),
+            Ok(RawTimestamp::Partial(
+                12,
+                31,
+                NaiveTime::from_hms_milli(23, 59, 59, 1000)
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_new() {
+        use chrono::{FixedOffset, TimeZone};
+
+        assert_eq!(
+            RawTimestamp::parse_new("2020-10-09T17:30:02.123-03:30"),
+            Ok(RawTimestamp::FixedOffset(
+                FixedOffset::west(3 * 3600 + 30 * 60)
+                    .ymd(2020, 10, 9)
+                    .and_hms_milli(17, 30, 2, 123)
+            ))
+        );
     }
 }
@@ -0,0 +1,206 @@
+//! The VAPIX virtual input interface at `/axis-cgi/virtualinput/*`.
+//!
+//! Virtual inputs are device-side event sources which exist only in software. Action rules can be
+//! conditioned on a virtual input, so activating one triggers the same downstream behavior as a
+//! physical alarm would: recordings, notifications, and so on. This makes them useful for testing
+//! alarm pipelines end-to-end.
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+
+/// A device's virtual input interface.
+pub struct VirtualInputs<'a, T: Transport>(&'a Client<T>);
+
+impl<'a, T: Transport> VirtualInputs<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// Activate a virtual input, triggering any action rules which depend on it.
+    ///
+    /// Virtual inputs are numbered starting from 1. Returns `true` if the input was previously
+    /// inactive, or `false` if it was already active.
+    pub async fn activate(&self, port: u32) -> Result<bool> {
+        let state_changed = self
+            .call("/axis-cgi/virtualinput/activate.cgi", port)
+            .await
+            .map(|success| success.activate_success.map(|s| s.state_changed))
+            .and_then(|state_changed| {
+                state_changed.ok_or(Error::Other("response did not confirm activation"))
+            })?;
+        self.0
+            .audit(|| format!("activated virtual input {}", port))
+            .await;
+        Ok(state_changed)
+    }
+
+    /// Deactivate a virtual input.
+    ///
+    /// Returns `true` if the input was previously active, or `false` if it was already inactive.
+    pub async fn deactivate(&self, port: u32) -> Result<bool> {
+        let state_changed = self
+            .call("/axis-cgi/virtualinput/deactivate.cgi", port)
+            .await
+            .map(|success| success.deactivate_success.map(|s| s.state_changed))
+            .and_then(|state_changed| {
+                state_changed.ok_or(Error::Other("response did not confirm deactivation"))
+            })?;
+        self.0
+            .audit(|| format!("deactivated virtual input {}", port))
+            .await;
+        Ok(state_changed)
+    }
+
+    async fn call(&self, path: &str, port: u32) -> Result<Success> {
+        self.0.check_writable()?;
+        #[derive(Serialize)]
+        struct Req {
+            schemaversion: u32,
+            port: u32,
+        }
+
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for_args(
+                path,
+                Req {
+                    schemaversion: 1,
+                    port,
+                },
+            )?)
+            .body(Vec::new())?;
+
+        let (_resp, resp_body) = self
+            .0
+            .roundtrip(req, "text/xml")
+            .await
+            .map_404_to_feature_unavailable()?;
+
+        let resp: Response = crate::xml::from_slice(&resp_body)?;
+        match resp {
+            Response {
+                success: Some(success),
+                ..
+            } => Ok(success),
+            Response {
+                error: Some(ErrorResponse { general_error }),
+                ..
+            } => Err(general_error.into()),
+            _ => Err(Error::Other(
+                "response included neither `Success` nor `Error`",
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    #[serde(rename = "Success")]
+    success: Option<Success>,
+    #[serde(rename = "Error")]
+    error: Option<ErrorResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Success {
+    #[serde(rename = "ActivateSuccess")]
+    activate_success: Option<StateChange>,
+    #[serde(rename = "DeactivateSuccess")]
+    deactivate_success: Option<StateChange>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StateChange {
+    #[serde(rename = "StateChanged")]
+    state_changed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    #[serde(rename = "GeneralError")]
+    general_error: GeneralError,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeneralError {
+    #[serde(rename = "ErrorCode")]
+    code: u32,
+    #[serde(rename = "ErrorDescription")]
+    description: Option<String>,
+}
+
+impl From<GeneralError> for Error {
+    fn from(e: GeneralError) -> Self {
+        crate::CgiError {
+            code: e.code,
+            description: e.description,
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+
+    #[tokio::test]
+    async fn activate() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(req.method(), http::Method::GET);
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/virtualinput/activate.cgi?schemaversion=1&port=3")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/xml")
+                .body(vec![br#"<?xml version="1.0" encoding="UTF-8"?>
+<VirtualInputResponse xmlns="http://www.axis.com/vapix/http_cgi/virtualinput1" SchemaVersion="1.0">
+  <Success>
+    <ActivateSuccess>
+      <StateChanged>true</StateChanged>
+    </ActivateSuccess>
+  </Success>
+</VirtualInputResponse>
+"#
+                .to_vec()])
+        });
+
+        assert_eq!(client.virtual_inputs().activate(3).await.unwrap(), true);
+    }
+
+    #[tokio::test]
+    async fn deactivate_error() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/virtualinput/deactivate.cgi?schemaversion=1&port=99")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/xml")
+                .body(vec![br#"<?xml version="1.0" encoding="UTF-8"?>
+<VirtualInputResponse xmlns="http://www.axis.com/vapix/http_cgi/virtualinput1" SchemaVersion="1.0">
+  <Error>
+    <GeneralError>
+      <ErrorCode>4</ErrorCode>
+      <ErrorDescription>Invalid port</ErrorDescription>
+    </GeneralError>
+  </Error>
+</VirtualInputResponse>
+"#
+                .to_vec()])
+        });
+
+        match client.virtual_inputs().deactivate(99).await {
+            Err(Error::CgiError(e)) => {
+                assert_eq!(e.code, 4);
+                assert_eq!(e.description.as_deref(), Some("Invalid port"));
+                assert_eq!(e.to_string(), "Invalid port (code 4)");
+            }
+            other => panic!("expected a CGI error, got {:?}", other),
+        }
+    }
+}
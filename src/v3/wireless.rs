@@ -0,0 +1,386 @@
+//! Wireless network configuration for devices with Wi-Fi, as exposed by the VAPIX parameters
+//! interface.
+//!
+//! Settings live in the `Network.Wireless` parameter group, which is absent on wired-only
+//! devices. Secrets like passphrases are write-only: they can be set, but the device never
+//! returns them.
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const GROUP: &str = "Network.Wireless";
+const SSID: &str = "Network.Wireless.SSID";
+const BAND: &str = "Network.Wireless.Band";
+const KEY_MGMT: &str = "Network.Wireless.WPA.KeyMgmt";
+const PASSPHRASE: &str = "Network.Wireless.WPA.PSK.PassPhrase";
+const EAP_METHOD: &str = "Network.Wireless.WPA.EAP.Method";
+const EAP_IDENTITY: &str = "Network.Wireless.WPA.EAP.Identity";
+const EAP_PASSWORD: &str = "Network.Wireless.WPA.EAP.Password";
+const EAP_CA_CERTIFICATE: &str = "Network.Wireless.WPA.EAP.CACertificate";
+
+/// A device's wireless network configuration interface.
+pub struct Wireless<'a, T: Transport>(&'a Client<T>);
+
+/// A wireless network configuration.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WirelessConfiguration {
+    /// The name of the network to join.
+    pub ssid: String,
+    /// The frequency band on which to connect.
+    pub band: WirelessBand,
+    /// The network's security settings.
+    pub security: WirelessSecurity,
+}
+
+/// A Wi-Fi frequency band.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WirelessBand {
+    /// Use whichever band is available.
+    Auto,
+    /// The 2.4 GHz band.
+    TwoPointFourGHz,
+    /// The 5 GHz band.
+    FiveGHz,
+    /// A band this crate does not recognize, as the device's `Band` parameter value.
+    Other(String),
+}
+
+impl WirelessBand {
+    fn as_param(&self) -> &str {
+        match self {
+            WirelessBand::Auto => "auto",
+            WirelessBand::TwoPointFourGHz => "2.4",
+            WirelessBand::FiveGHz => "5",
+            WirelessBand::Other(value) => value,
+        }
+    }
+
+    fn from_param(value: &str) -> Self {
+        match value {
+            "auto" => WirelessBand::Auto,
+            "2.4" => WirelessBand::TwoPointFourGHz,
+            "5" => WirelessBand::FiveGHz,
+            other => WirelessBand::Other(other.to_string()),
+        }
+    }
+}
+
+/// Wireless network security settings.
+///
+/// Secrets are `None` when read from the device. When writing, `None` leaves the device's
+/// existing secret unchanged.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum WirelessSecurity {
+    /// An open network.
+    Open,
+    /// WPA2-Personal, using a pre-shared passphrase.
+    Wpa2Personal {
+        /// The network passphrase.
+        passphrase: Option<String>,
+    },
+    /// WPA2-Enterprise, using 802.1X authentication.
+    Wpa2Enterprise {
+        /// The EAP method.
+        method: EapMethod,
+        /// The identity with which to authenticate.
+        identity: String,
+        /// The password with which to authenticate, for password-based EAP methods.
+        password: Option<String>,
+        /// The name of the CA certificate installed on the device which is used to validate the
+        /// authentication server, if any.
+        #[serde(rename = "caCertificate")]
+        ca_certificate: Option<String>,
+    },
+    /// Security settings this crate does not recognize, like WPA3, identified by the device's
+    /// `KeyMgmt` parameter value.
+    ///
+    /// Writing this sets `KeyMgmt` back to the same value, leaving the device's other security
+    /// settings unchanged.
+    Other {
+        /// The `KeyMgmt` value, e.g. `"SAE"`.
+        #[serde(rename = "keyMgmt")]
+        key_mgmt: String,
+    },
+}
+
+/// An 802.1X EAP method.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EapMethod {
+    /// EAP-TLS, using a client certificate.
+    Tls,
+    /// PEAP, using a password.
+    Peap,
+    /// EAP-TTLS, using a password.
+    Ttls,
+    /// A method this crate does not recognize, as the device's `Method` parameter value.
+    Other(String),
+}
+
+impl EapMethod {
+    fn as_param(&self) -> &str {
+        match self {
+            EapMethod::Tls => "TLS",
+            EapMethod::Peap => "PEAP",
+            EapMethod::Ttls => "TTLS",
+            EapMethod::Other(value) => value,
+        }
+    }
+
+    fn from_param(value: &str) -> Self {
+        match value {
+            "TLS" => EapMethod::Tls,
+            "PEAP" => EapMethod::Peap,
+            "TTLS" => EapMethod::Ttls,
+            other => EapMethod::Other(other.to_string()),
+        }
+    }
+}
+
+impl<'a, T: Transport> Wireless<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// Retrieve the wireless network configuration.
+    ///
+    /// Returns `Error::FeatureUnavailable` if the device does not support Wi-Fi.
+    pub async fn configuration(&self) -> Result<WirelessConfiguration> {
+        let params = self
+            .0
+            .parameters()
+            .list(Some(&[&format!("root.{}", GROUP)[..]][..]))
+            .await?;
+        let params: BTreeMap<&str, &str> = params
+            .iter()
+            .map(|(k, v)| (k.trim_start_matches("root."), v.as_str()))
+            .collect();
+
+        configuration_from_params(&params).ok_or(Error::FeatureUnavailable)
+    }
+
+    /// Change the wireless network configuration.
+    pub async fn set_configuration(&self, configuration: &WirelessConfiguration) -> Result<()> {
+        self.0
+            .parameters()
+            .update(params_from_configuration(configuration))
+            .await
+    }
+}
+
+fn configuration_from_params(params: &BTreeMap<&str, &str>) -> Option<WirelessConfiguration> {
+    let ssid = params.get(SSID)?;
+    let get = |key| params.get(key).copied().unwrap_or("");
+
+    // Anything unrecognized is kept verbatim, so that writing a configuration back never weakens
+    // the device's security
+    let security = match get(KEY_MGMT) {
+        "none" => WirelessSecurity::Open,
+        "WPA-PSK" => WirelessSecurity::Wpa2Personal { passphrase: None },
+        "WPA-EAP" => WirelessSecurity::Wpa2Enterprise {
+            method: EapMethod::from_param(get(EAP_METHOD)),
+            identity: get(EAP_IDENTITY).to_string(),
+            password: None,
+            ca_certificate: Some(get(EAP_CA_CERTIFICATE))
+                .filter(|s| !s.is_empty())
+                .map(String::from),
+        },
+        other => WirelessSecurity::Other {
+            key_mgmt: other.to_string(),
+        },
+    };
+
+    Some(WirelessConfiguration {
+        ssid: ssid.to_string(),
+        band: WirelessBand::from_param(get(BAND)),
+        security,
+    })
+}
+
+fn params_from_configuration(configuration: &WirelessConfiguration) -> Vec<(&'static str, String)> {
+    let mut params = vec![
+        (SSID, configuration.ssid.clone()),
+        (BAND, configuration.band.as_param().to_string()),
+    ];
+
+    match &configuration.security {
+        WirelessSecurity::Open => params.push((KEY_MGMT, "none".into())),
+        WirelessSecurity::Wpa2Personal { passphrase } => {
+            params.push((KEY_MGMT, "WPA-PSK".into()));
+            if let Some(passphrase) = passphrase {
+                params.push((PASSPHRASE, passphrase.clone()));
+            }
+        }
+        WirelessSecurity::Wpa2Enterprise {
+            method,
+            identity,
+            password,
+            ca_certificate,
+        } => {
+            params.push((KEY_MGMT, "WPA-EAP".into()));
+            params.push((EAP_METHOD, method.as_param().into()));
+            params.push((EAP_IDENTITY, identity.clone()));
+            if let Some(password) = password {
+                params.push((EAP_PASSWORD, password.clone()));
+            }
+            params.push((
+                EAP_CA_CERTIFICATE,
+                ca_certificate.clone().unwrap_or_default(),
+            ));
+        }
+        WirelessSecurity::Other { key_mgmt } => params.push((KEY_MGMT, key_mgmt.clone())),
+    }
+
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn configuration() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path_and_query().map(|pq| pq.as_str()),
+                Some("/axis-cgi/param.cgi?action=list&group=root.Network.Wireless")
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.Network.Wireless.SSID=Warehouse\n\
+root.Network.Wireless.Band=5\n\
+root.Network.Wireless.WPA.KeyMgmt=WPA-EAP\n\
+root.Network.Wireless.WPA.EAP.Method=PEAP\n\
+root.Network.Wireless.WPA.EAP.Identity=camera01\n\
+root.Network.Wireless.WPA.EAP.Password=\n\
+root.Network.Wireless.WPA.EAP.CACertificate=corp-ca\n"
+                    .to_vec()])
+        });
+
+        let configuration = client.wireless().configuration().await.unwrap();
+        assert_eq!(
+            configuration,
+            WirelessConfiguration {
+                ssid: "Warehouse".into(),
+                band: WirelessBand::FiveGHz,
+                security: WirelessSecurity::Wpa2Enterprise {
+                    method: EapMethod::Peap,
+                    identity: "camera01".into(),
+                    password: None,
+                    ca_certificate: Some("corp-ca".into()),
+                },
+            }
+        );
+
+        let json = serde_json::to_value(&configuration).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "ssid": "Warehouse",
+                "band": "fiveGHz",
+                "security": {
+                    "wpa2Enterprise": {
+                        "method": "peap",
+                        "identity": "camera01",
+                        "password": null,
+                        "caCertificate": "corp-ca",
+                    },
+                },
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<WirelessConfiguration>(json).unwrap(),
+            configuration
+        );
+    }
+
+    #[tokio::test]
+    async fn wired_only() {
+        let client = crate::mock_client(|_| {
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![
+                    b"# Error: Error -1 getting param in group 'Network.Wireless'\n".to_vec(),
+                ])
+        });
+
+        match client.wireless().configuration().await {
+            Err(Error::FeatureUnavailable) => {}
+            other => panic!("expected FeatureUnavailable, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn params() {
+        let configuration = WirelessConfiguration {
+            ssid: "Office".into(),
+            band: WirelessBand::Auto,
+            security: WirelessSecurity::Wpa2Personal {
+                passphrase: Some("hunter22".into()),
+            },
+        };
+        assert_eq!(
+            params_from_configuration(&configuration),
+            vec![
+                (SSID, "Office".to_string()),
+                (BAND, "auto".to_string()),
+                (KEY_MGMT, "WPA-PSK".to_string()),
+                (PASSPHRASE, "hunter22".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unrecognized_settings_round_trip() {
+        let params: BTreeMap<&str, &str> = vec![
+            (SSID, "Office"),
+            (BAND, "6"),
+            (KEY_MGMT, "SAE"),
+            (EAP_METHOD, "PWD"),
+        ]
+        .into_iter()
+        .collect();
+        let configuration = configuration_from_params(&params).unwrap();
+        assert_eq!(
+            configuration,
+            WirelessConfiguration {
+                ssid: "Office".into(),
+                band: WirelessBand::Other("6".into()),
+                security: WirelessSecurity::Other {
+                    key_mgmt: "SAE".into()
+                },
+            }
+        );
+        assert_eq!(
+            params_from_configuration(&configuration),
+            vec![
+                (SSID, "Office".to_string()),
+                (BAND, "6".to_string()),
+                (KEY_MGMT, "SAE".to_string()),
+            ]
+        );
+
+        let params: BTreeMap<&str, &str> = vec![
+            (SSID, "Office"),
+            (BAND, "auto"),
+            (KEY_MGMT, "WPA-EAP"),
+            (EAP_METHOD, "PWD"),
+        ]
+        .into_iter()
+        .collect();
+        match configuration_from_params(&params).unwrap().security {
+            WirelessSecurity::Wpa2Enterprise { method, .. } => {
+                assert_eq!(method, EapMethod::Other("PWD".into()))
+            }
+            other => panic!("unexpected security {:?}", other),
+        }
+    }
+}
@@ -2,17 +2,48 @@
 //! Library](https://www.axis.com/vapix-library/), principally in the [network video API
 //! section](https://www.axis.com/vapix-library/subjects/t10037719/section/t10035974/display).
 
+use crate::capabilities::FirmwareVersion;
 use crate::v3::Parameters;
 use crate::*;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use basic_device_info::BasicDeviceInfo;
+pub use decoder::Decoder;
 use disk_management::DiskManagement;
+use disk_network_share::DiskNetworkShare;
+pub use events::Events;
+pub use firmware_management::FirmwareManagement;
+pub use guard::Guard;
+use guard::GuardKind;
+pub use io_port_management::IoPortManagement;
 pub(crate) use json_service::JsonService;
+use thermometry::Thermometry;
+pub use time_service::TimeService;
+pub use user_management::UserManagement;
+pub use webrtc::WebRtc;
 
 pub mod basic_device_info;
+pub mod decoder;
 pub mod disk_management;
+pub mod disk_network_share;
+pub mod events;
+pub mod firmware_management;
+pub mod guard;
+pub mod io_port_management;
 mod json_service;
+#[cfg(feature = "schema-validation")]
+pub mod schema_validation;
+pub mod thermometry;
+pub mod time_service;
+pub mod user_management;
+pub mod webrtc;
+
+/// Fields of a v4 response which this crate does not model, keyed by their JSON names.
+///
+/// Firmware updates regularly add fields to existing APIs. Typed responses keep any fields they
+/// don't recognize, so callers can use them before this crate catches up.
+pub type UnknownFields = serde_json::Map<String, serde_json::Value>;
 
 /// A list of available services supported by this device and by this library.
 ///
@@ -21,35 +52,32 @@ pub struct Services<'a, T: Transport> {
     pub parameters: Option<Parameters<'a, T>>,
     pub basic_device_info: Option<BasicDeviceInfo<'a, T>>,
     pub disk_management: Option<DiskManagement<'a, T>>,
+    pub disk_network_share: Option<DiskNetworkShare<'a, T>>,
+    pub fence_guard: Option<Guard<'a, T>>,
+    pub firmware_management: Option<FirmwareManagement<'a, T>>,
+    pub io_port_management: Option<IoPortManagement<'a, T>>,
+    pub loitering_guard: Option<Guard<'a, T>>,
+    pub motion_guard: Option<Guard<'a, T>>,
+    pub thermometry: Option<Thermometry<'a, T>>,
+    pub time_service: Option<TimeService<'a, T>>,
+    pub user_management: Option<UserManagement<'a, T>>,
 }
 
 impl<'a, T: Transport> Services<'a, T> {
     pub(crate) async fn new(device: &'a Client<T>) -> Result<Services<'a, T>> {
-        #[derive(Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        struct Resp {
-            api_list: Vec<AvailableApi>,
-        }
-
-        #[derive(Debug, Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        struct AvailableApi {
-            pub id: String,
-            pub version: String,
-        }
+        Ok(Services::from_apis(device, &discover(device).await?))
+    }
 
-        let resp: Resp = JsonService::new(device, "/axis-cgi/apidiscovery.cgi", "1.0".to_string())
-            .call_method_bare("getApiList")
-            .await
-            .map_404_to_feature_unavailable()?;
+    /// The services listed in `cache`, without making any requests.
+    pub(crate) fn from_cache(device: &'a Client<T>, cache: &ServiceCache) -> Services<'a, T> {
+        Services::from_apis(device, &cache.apis)
+    }
 
-        let mut services = Services {
-            parameters: None,
-            basic_device_info: None,
-            disk_management: None,
-        };
+    fn from_apis(device: &'a Client<T>, apis: &BTreeMap<String, String>) -> Services<'a, T> {
+        let mut services = Services::empty();
 
-        for AvailableApi { id, version } in resp.api_list {
+        for (id, version) in apis {
+            let version = version.clone();
             match id.as_str() {
                 "param-cgi" => services.parameters = Some(Parameters::new(device, version)),
                 "basic-device-info" => {
@@ -58,11 +86,129 @@ impl<'a, T: Transport> Services<'a, T> {
                 "disk-management" => {
                     services.disk_management = Some(DiskManagement::new(device, version))
                 }
+                "disk-network-share" => {
+                    services.disk_network_share = Some(DiskNetworkShare::new(device, version))
+                }
+                "fwmgr" => {
+                    services.firmware_management = Some(FirmwareManagement::new(device, version))
+                }
+                "io-port-management" => {
+                    services.io_port_management = Some(IoPortManagement::new(device, version))
+                }
+                "fenceguard" => {
+                    services.fence_guard = Some(Guard::new(device, GuardKind::FenceGuard, version))
+                }
+                "loiteringguard" => {
+                    services.loitering_guard =
+                        Some(Guard::new(device, GuardKind::LoiteringGuard, version))
+                }
+                "motionguard" => {
+                    services.motion_guard =
+                        Some(Guard::new(device, GuardKind::MotionGuard, version))
+                }
+                "thermometry" => services.thermometry = Some(Thermometry::new(device, version)),
+                "time-service" => services.time_service = Some(TimeService::new(device, version)),
+                "user-management" => {
+                    services.user_management = Some(UserManagement::new(device, version))
+                }
                 _ => (),
             }
         }
 
-        Ok(services)
+        services
+    }
+
+    /// The services assumed to exist on devices which predate API discovery: only `parameters`.
+    pub(crate) fn legacy(device: &'a Client<T>) -> Services<'a, T> {
+        Services {
+            parameters: Some(device.parameters()),
+            ..Services::empty()
+        }
+    }
+
+    fn empty() -> Services<'a, T> {
+        Services {
+            parameters: None,
+            basic_device_info: None,
+            disk_management: None,
+            disk_network_share: None,
+            fence_guard: None,
+            firmware_management: None,
+            io_port_management: None,
+            loitering_guard: None,
+            motion_guard: None,
+            thermometry: None,
+            time_service: None,
+            user_management: None,
+        }
+    }
+}
+
+/// Fetch the API list from `/axis-cgi/apidiscovery.cgi`, as a map of API IDs to versions.
+async fn discover<T: Transport>(device: &Client<T>) -> Result<BTreeMap<String, String>> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Resp {
+        api_list: Vec<AvailableApi>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct AvailableApi {
+        pub id: String,
+        pub version: String,
+    }
+
+    let resp: Resp = JsonService::new(device, "/axis-cgi/apidiscovery.cgi", "1.0".to_string())
+        .with_service_id("api-discovery")
+        .call_method_bare("getApiList")
+        .await
+        .map_404_to_feature_unavailable()?;
+
+    Ok(resp
+        .api_list
+        .into_iter()
+        .map(|AvailableApi { id, version }| (id, version))
+        .collect())
+}
+
+/// The result of API discovery on a device, which can be stored and loaded back with
+/// `Client::services_from_cache()` to skip discovery when connecting to a known device.
+///
+/// The services a device offers change only with its firmware, so a cache records the firmware
+/// version it was made with, and is stale once the device runs anything else.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceCache {
+    /// The firmware version the device was running when discovery took place, if it was known.
+    pub firmware_version: Option<FirmwareVersion>,
+    /// The APIs the device offered, as a map of API discovery IDs to versions.
+    ///
+    /// This includes APIs which this crate does not support.
+    pub apis: BTreeMap<String, String>,
+}
+
+impl ServiceCache {
+    /// Returns `true` if this cache describes a device running `firmware_version`.
+    ///
+    /// A cache made without knowing the firmware version is never current.
+    pub fn is_current(&self, firmware_version: FirmwareVersion) -> bool {
+        self.firmware_version == Some(firmware_version)
+    }
+
+    /// Run API discovery on `device`, and note its firmware version.
+    pub(crate) async fn new<T: Transport>(device: &Client<T>) -> Result<ServiceCache> {
+        let apis = discover(device).await?;
+        let firmware_version = device
+            .parameters()
+            .list(Some(&["Properties.Firmware.Version"][..]))
+            .await?
+            .get("Properties.Firmware.Version")
+            .and_then(|v| v.parse().ok());
+        Ok(ServiceCache {
+            firmware_version,
+            apis,
+        })
     }
 }
 
@@ -113,6 +259,15 @@ mod tests {
         assert!(services.parameters.is_none());
         assert!(services.basic_device_info.is_none());
         assert!(services.disk_management.is_none());
+        assert!(services.disk_network_share.is_none());
+        assert!(services.thermometry.is_none());
+        assert!(services.io_port_management.is_none());
+        assert!(services.fence_guard.is_none());
+        assert!(services.loitering_guard.is_none());
+        assert!(services.motion_guard.is_none());
+        assert!(services.time_service.is_none());
+        assert!(services.firmware_management.is_none());
+        assert!(services.user_management.is_none());
     }
 
     const TYPICAL_SERVICES_RESPONSE: &[u8] = br#"{"method": "getApiList", "apiVersion": "1.0", "data": {"apiList": [{"id": "privacy-mask", "version": "1.0", "name": "Privacy Masking", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "recording-storage-limit", "version": "1.0", "name": "Edge Recording storage limit", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "mdnssd", "version": "1.0", "name": "mDNS-SD", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "api-discovery", "version": "1.0", "name": "API Discovery Service", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "io-port-management", "version": "1.0", "name": "IO Port Management", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "stream-profiles", "version": "1.0", "name": "Stream Profiles", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "dynamicoverlay", "version": "1.0", "name": "Dynamic Overlay", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "disk-management", "version": "1.0", "name": "Edge storage Disk management", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "oak", "version": "1.0", "name": "OAK", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "mqtt-client", "version": "1.0", "name": "MQTT Client API", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "ntp", "version": "1.2", "name": "NTP", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "upnp", "version": "1.1", "name": "UPnP", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "network-settings", "version": "1.6", "name": "Network Settings", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "systemready", "version": "1.1", "name": "Systemready", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "time-service", "version": "1.0", "name": "Time API", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "disk-properties", "version": "1.1", "name": "Edge storage Disk properties", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "custom-firmware-certificate", "version": "1.0", "name": "Custom Firmware Certificate", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "recording", "version": "1.0", "name": "Edge Recording", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "basic-device-info", "version": "1.1", "name": "Basic Device Information", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "user-management", "version": "1.1", "name": "User Management", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "onscreencontrols", "version": "1.4", "name": "On-Screen Controls", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "shuttergain-cgi", "version": "2.0", "name": "Shuttergain CGI", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "packagemanager", "version": "1.4", "name": "Package Manager", "docLink": ""}, {"id": "overlayimage", "version": "1.0", "name": "Overlay image API", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "ptz-control", "version": "1.0", "name": "PTZ Control", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "capture-mode", "version": "1.0", "name": "Capture Mode", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "light-control", "version": "1.1", "name": "Light Control", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "disk-network-share", "version": "1.0", "name": "Edge storage Network share", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "recording-export", "version": "1.1", "name": "Export edge recording", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "guard-tour", "version": "1.0", "name": "Guard Tour", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "param-cgi", "version": "1.0", "name": "Legacy Parameter Handling", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "customhttpheader", "version": "1.0", "name": "Custom HTTP header", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}, {"id": "fwmgr", "version": "1.4", "name": "Firmware Management", "docLink": "https://www.axis.com/partner_pages/vapix_library/#/"}]}}
@@ -143,5 +298,93 @@ mod tests {
         assert!(services.parameters.is_some());
         assert!(services.basic_device_info.is_some());
         assert!(services.disk_management.is_some());
+        assert!(services.disk_network_share.is_some());
+        assert!(services.io_port_management.is_some());
+        assert!(services.time_service.is_some());
+        assert!(services.firmware_management.is_some());
+        assert!(services.user_management.is_some());
+    }
+
+    #[tokio::test]
+    async fn service_cache() {
+        let device = crate::mock_client(|req| match req.uri().path() {
+            "/axis-cgi/apidiscovery.cgi" => http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(vec![TYPICAL_SERVICES_RESPONSE.to_vec()]),
+            "/axis-cgi/param.cgi" => {
+                assert_eq!(
+                    req.uri().query(),
+                    Some("action=list&group=Properties.Firmware.Version")
+                );
+                http::Response::builder()
+                    .status(http::StatusCode::OK)
+                    .header(http::header::CONTENT_TYPE, "text/plain")
+                    .body(vec![b"Properties.Firmware.Version=9.80.1\n".to_vec()])
+            }
+            other => panic!("unexpected request {:?}", other),
+        });
+        let cache = device.service_cache().await.unwrap();
+        assert_eq!(cache.firmware_version, Some("9.80.1".parse().unwrap()));
+        assert_eq!(cache.apis.len(), 33);
+        assert_eq!(cache.apis["ntp"], "1.2");
+
+        // round trip through serde
+        let json = serde_json::to_string(&cache).unwrap();
+        assert!(json.starts_with(r#"{"firmwareVersion":"9.80.1","apis":{"#));
+        let cache: ServiceCache = serde_json::from_str(&json).unwrap();
+
+        // loading a current cache makes no requests
+        let device = crate::mock_client(|req| -> http::Result<http::Response<Vec<Vec<u8>>>> {
+            panic!("unexpected request {:?}", req.uri())
+        });
+        assert!(device
+            .services_from_cache(&cache, "9.80.2".parse().unwrap())
+            .is_none());
+        let services = device
+            .services_from_cache(&cache, "9.80.1".parse().unwrap())
+            .unwrap();
+        assert!(services.parameters.is_some());
+        assert!(services.user_management.is_some());
+        assert!(services.thermometry.is_none());
+
+        // without a firmware version, a cache is never current
+        let cache = ServiceCache {
+            firmware_version: None,
+            ..cache
+        };
+        assert!(!cache.is_current("9.80.1".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn with_services() {
+        let client = crate::mock_client(|req| match req.uri().path() {
+            "/axis-cgi/apidiscovery.cgi" => http::Response::builder()
+                .status(http::StatusCode::NOT_FOUND)
+                .body(vec![]),
+            "/axis-cgi/param.cgi" => http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![b"root.Network.HostName=lobby\n".to_vec()]),
+            other => panic!("unexpected request {:?}", other),
+        });
+
+        let host_name = client
+            .with_services(|services| {
+                Box::pin(async move {
+                    assert!(services.basic_device_info.is_none());
+                    let parameters = services
+                        .parameters
+                        .as_ref()
+                        .ok_or(Error::FeatureUnavailable)?;
+                    let params = parameters
+                        .list(Some(&["root.Network.HostName"][..]))
+                        .await?;
+                    Ok(params["root.Network.HostName"].clone())
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(host_name, "lobby");
     }
 }
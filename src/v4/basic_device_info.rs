@@ -1,6 +1,6 @@
 //! The [basic device info API](https://www.axis.com/vapix-library/subjects/t10037719/section/t10132180/display).
 
-use crate::v4::JsonService;
+use crate::v4::{JsonService, UnknownFields};
 use crate::*;
 use serde::{Deserialize, Serialize};
 
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 pub struct BasicDeviceInfo<'a, T: Transport>(JsonService<'a, T>);
 
 /// A set of basic device properties.
-#[derive(Debug, Deserialize, Clone, Eq, PartialEq)]
+#[derive(Debug, Deserialize, Clone, Eq, PartialEq, Serialize)]
 pub struct Properties {
     /// The brand of the device, likely `"AXIS"`.
     #[serde(rename = "Brand")]
@@ -52,15 +52,17 @@ pub struct Properties {
     /// TOOD
     #[serde(rename = "WebURL")]
     pub web_url: String,
+    /// Fields returned by the device which this crate does not model.
+    #[serde(flatten)]
+    pub extra: UnknownFields,
 }
 
 impl<'a, T: Transport> BasicDeviceInfo<'a, T> {
     pub(crate) fn new(client: &'a Client<T>, api_version: String) -> Self {
-        Self(JsonService::new(
-            client,
-            "/axis-cgi/basicdeviceinfo.cgi",
-            api_version,
-        ))
+        Self(
+            JsonService::new(client, "/axis-cgi/basicdeviceinfo.cgi", api_version)
+                .with_service_id("basic-device-info"),
+        )
     }
 
     /// Retreive `Properties`.
@@ -0,0 +1,279 @@
+//! The display control API of AXIS video decoders, like the AXIS T8705 and D1110, at
+//! `/axis-cgi/decoder.cgi`.
+//!
+//! A decoder shows video from other devices on its attached monitors. Each monitor shows either a
+//! single source, like a camera's RTSP stream, or a sequence which cycles through several sources.
+//! Sources and sequences are configured on the decoder, and this API chooses between them.
+//!
+//! Devices which are not decoders return HTTP 404, which is reported as
+//! `Error::FeatureUnavailable`.
+
+use crate::v4::{JsonService, UnknownFields};
+use crate::*;
+use serde::{Deserialize, Serialize};
+
+string_type!(
+    /// The identifier of a monitor attached to a decoder.
+    pub struct MonitorId
+);
+
+string_type!(
+    /// The identifier of a video source configured on a decoder.
+    pub struct SourceId
+);
+
+string_type!(
+    /// The identifier of a sequence configured on a decoder.
+    pub struct SequenceId
+);
+
+/// A video decoder's display control API.
+pub struct Decoder<'a, T: Transport>(JsonService<'a, T>);
+
+/// A monitor attached to a decoder.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Monitor {
+    /// The monitor's identifier.
+    pub id: MonitorId,
+    /// The monitor's name, like `HDMI 1`.
+    #[serde(default)]
+    pub name: String,
+    /// Is a display connected to this output?
+    #[serde(default)]
+    pub connected: bool,
+    /// What the monitor is showing.
+    #[serde(default)]
+    pub content: MonitorContent,
+    /// Fields returned by the device which this crate does not model.
+    #[serde(flatten)]
+    pub extra: UnknownFields,
+}
+
+/// What a monitor is showing.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id", rename_all = "camelCase")]
+pub enum MonitorContent {
+    /// Nothing.
+    None,
+    /// A single source.
+    Source(SourceId),
+    /// A sequence of sources.
+    Sequence(SequenceId),
+}
+
+impl Default for MonitorContent {
+    fn default() -> Self {
+        MonitorContent::None
+    }
+}
+
+/// A video source configured on a decoder.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Source {
+    /// The source's identifier.
+    pub id: SourceId,
+    /// The source's name.
+    #[serde(default)]
+    pub name: String,
+    /// The URI the decoder streams from, like `rtsp://192.0.2.10/axis-media/media.amp`.
+    pub uri: String,
+    /// Fields returned by the device which this crate does not model.
+    #[serde(flatten)]
+    pub extra: UnknownFields,
+}
+
+/// A sequence configured on a decoder, which shows each of its sources in turn.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sequence {
+    /// The sequence's identifier.
+    pub id: SequenceId,
+    /// The sequence's name.
+    #[serde(default)]
+    pub name: String,
+    /// The sources shown, in order.
+    #[serde(default)]
+    pub steps: Vec<SequenceStep>,
+    /// Fields returned by the device which this crate does not model.
+    #[serde(flatten)]
+    pub extra: UnknownFields,
+}
+
+/// One step of a `Sequence`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceStep {
+    /// The source to show.
+    pub source: SourceId,
+    /// How long to show it, in seconds.
+    pub duration: u32,
+}
+
+impl<'a, T: Transport> Decoder<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(
+            JsonService::new(device, "/axis-cgi/decoder.cgi", "1.0".to_string())
+                .with_service_id("decoder"),
+        )
+    }
+
+    /// List the monitors attached to the decoder, and what each is showing.
+    pub async fn monitors(&self) -> Result<Vec<Monitor>> {
+        #[derive(Deserialize)]
+        struct Resp {
+            monitors: Vec<Monitor>,
+        }
+        let resp: Resp = self
+            .0
+            .call_method_bare("getMonitors")
+            .await
+            .map_404_to_feature_unavailable()?;
+        Ok(resp.monitors)
+    }
+
+    /// List the video sources configured on the decoder.
+    pub async fn sources(&self) -> Result<Vec<Source>> {
+        #[derive(Deserialize)]
+        struct Resp {
+            sources: Vec<Source>,
+        }
+        let resp: Resp = self
+            .0
+            .call_method_bare("getSources")
+            .await
+            .map_404_to_feature_unavailable()?;
+        Ok(resp.sources)
+    }
+
+    /// List the sequences configured on the decoder.
+    pub async fn sequences(&self) -> Result<Vec<Sequence>> {
+        #[derive(Deserialize)]
+        struct Resp {
+            sequences: Vec<Sequence>,
+        }
+        let resp: Resp = self
+            .0
+            .call_method_bare("getSequences")
+            .await
+            .map_404_to_feature_unavailable()?;
+        Ok(resp.sequences)
+    }
+
+    /// Show a single source on a monitor, stopping any sequence it was showing.
+    pub async fn show_source(&self, monitor: &MonitorId, source: &SourceId) -> Result<()> {
+        self.set_content(monitor, MonitorContent::Source(source.clone()))
+            .await
+    }
+
+    /// Start a sequence on a monitor.
+    pub async fn start_sequence(&self, monitor: &MonitorId, sequence: &SequenceId) -> Result<()> {
+        self.set_content(monitor, MonitorContent::Sequence(sequence.clone()))
+            .await
+    }
+
+    /// Stop showing video on a monitor.
+    pub async fn clear(&self, monitor: &MonitorId) -> Result<()> {
+        self.set_content(monitor, MonitorContent::None).await
+    }
+
+    async fn set_content(&self, monitor: &MonitorId, content: MonitorContent) -> Result<()> {
+        #[derive(Serialize)]
+        struct Req<'a> {
+            monitor: &'a MonitorId,
+            content: MonitorContent,
+        }
+        self.0
+            .call_method_without_data("setMonitorContent", Req { monitor, content })
+            .await
+            .map_404_to_feature_unavailable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn display_control() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(req.uri().path(), "/axis-cgi/decoder.cgi");
+            let body: serde_json::Value = serde_json::from_slice(req.body()).unwrap();
+            let response = match body["method"].as_str().unwrap() {
+                "getMonitors" => serde_json::json!({
+                    "apiVersion": "1.0",
+                    "data": {"monitors": [
+                        {"id": "0", "name": "HDMI 1", "connected": true, "content": {"type": "sequence", "id": "lobby"}, "resolution": "1920x1080"},
+                        {"id": "1", "name": "HDMI 2", "content": {"type": "none"}}
+                    ]}
+                }),
+                "getSources" => serde_json::json!({
+                    "apiVersion": "1.0",
+                    "data": {"sources": [
+                        {"id": "entrance", "name": "Entrance", "uri": "rtsp://192.0.2.10/axis-media/media.amp"}
+                    ]}
+                }),
+                "getSequences" => serde_json::json!({
+                    "apiVersion": "1.0",
+                    "data": {"sequences": [
+                        {"id": "lobby", "name": "Lobby", "steps": [{"source": "entrance", "duration": 10}]}
+                    ]}
+                }),
+                "setMonitorContent" => {
+                    assert_eq!(
+                        body["params"],
+                        serde_json::json!({"monitor": "1", "content": {"type": "source", "id": "entrance"}})
+                    );
+                    serde_json::json!({"apiVersion": "1.0", "data": {}})
+                }
+                other => panic!("unexpected method {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(vec![response.to_string().into_bytes()])
+        });
+        let decoder = client.decoder();
+
+        let monitors = decoder.monitors().await.unwrap();
+        assert_eq!(monitors.len(), 2);
+        assert!(monitors[0].connected);
+        assert_eq!(
+            monitors[0].content,
+            MonitorContent::Sequence(SequenceId::new("lobby"))
+        );
+        assert_eq!(monitors[0].extra["resolution"], "1920x1080");
+        assert_eq!(monitors[1].content, MonitorContent::None);
+
+        let sources = decoder.sources().await.unwrap();
+        assert_eq!(sources[0].id, SourceId::new("entrance"));
+        let sequences = decoder.sequences().await.unwrap();
+        assert_eq!(sequences[0].steps[0].source, sources[0].id);
+        assert_eq!(sequences[0].steps[0].duration, 10);
+
+        decoder
+            .show_source(&monitors[1].id, &sources[0].id)
+            .await
+            .unwrap();
+
+        let client = client.with_read_only(true);
+        match client.decoder().clear(&monitors[0].id).await {
+            Err(Error::ReadOnlyMode) => {}
+            other => panic!("expected ReadOnlyMode, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn not_a_decoder() {
+        let client = crate::mock_client(|_| {
+            http::Response::builder()
+                .status(http::StatusCode::NOT_FOUND)
+                .body(vec![])
+        });
+        match client.decoder().monitors().await {
+            Err(Error::FeatureUnavailable) => {}
+            other => panic!("expected FeatureUnavailable, got {:?}", other),
+        }
+    }
+}
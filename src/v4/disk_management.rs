@@ -1,9 +1,11 @@
 //! The [disk management API](https://www.axis.com/vapix-library/subjects/t10037719/section/t10004596/display?section=t10004596-t10004496).
 
 use crate::*;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 string_type!(pub struct DiskId);
+string_type!(pub struct JobId);
 
 /// The disk management API.
 pub struct DiskManagement<'a, T: Transport>(&'a Client<T>, String);
@@ -17,19 +19,11 @@ impl<'a, T: Transport> DiskManagement<'a, T> {
     pub async fn list(&self) -> Result<Vec<DiskInfo>> {
         let req = http::Request::builder()
             .method(http::Method::GET)
-            .uri(
-                self.0
-                    .uri_for("/axis-cgi/disks/list.cgi?diskid=all")
-                    .unwrap(),
-            )
-            .body(Vec::new())
-            .unwrap();
+            .uri(self.0.uri_for("/axis-cgi/disks/list.cgi?diskid=all")?)
+            .body(Vec::new())?;
 
         let (_resp, resp_body) = self.0.roundtrip(req, "text/xml").await?;
 
-        let resp_body =
-            std::str::from_utf8(resp_body.as_slice()).map_err(|_| Error::Other("invalid UTF-8"))?;
-
         #[derive(Deserialize)]
         struct ListResponse {
             #[serde(rename = "disks")]
@@ -40,16 +34,508 @@ impl<'a, T: Transport> DiskManagement<'a, T> {
             #[serde(rename = "disk")]
             disks: Vec<DiskInfo>,
         }
-        let resp_body: ListResponse = quick_xml::de::from_str(resp_body)?;
+        let resp_body: ListResponse = crate::xml::from_slice(&resp_body)?;
 
         Ok(resp_body.container.disks)
     }
+
+    /// Start a storage performance test on a disk.
+    ///
+    /// The test runs in the background on the device. Poll its progress and retrieve the results
+    /// with `speed_test_status()`. Returns `Error::FeatureUnavailable` if the device's firmware
+    /// does not support performance tests.
+    pub async fn start_speed_test(&self, disk_id: &DiskId) -> Result<JobId> {
+        self.0.check_writable()?;
+        #[derive(Serialize)]
+        struct Req<'a> {
+            diskid: &'a str,
+        }
+
+        let job: RawJob = self
+            .call(
+                "/axis-cgi/disks/speedtest.cgi",
+                Req {
+                    diskid: disk_id.as_str(),
+                },
+            )
+            .await?;
+        Ok(job.job_id)
+    }
+
+    /// Retrieve the progress and, once finished, the results of a storage performance test.
+    pub async fn speed_test_status(&self, job_id: &JobId) -> Result<SpeedTestStatus> {
+        Ok(self.raw_job_status(job_id).await?.into())
+    }
+
+    async fn raw_job_status(&self, job_id: &JobId) -> Result<RawJob> {
+        #[derive(Serialize)]
+        struct Req<'a> {
+            jobid: &'a str,
+        }
+
+        self.call(
+            "/axis-cgi/disks/job.cgi",
+            Req {
+                jobid: job_id.as_str(),
+            },
+        )
+        .await
+    }
+
+    async fn start_job<R: Serialize>(&self, path: &str, args: R) -> Result<JobId> {
+        Ok(self.call(path, args).await?.job_id)
+    }
+
+    /// Start formatting a disk with the given filesystem, erasing everything on it.
+    ///
+    /// Formatting runs in the background on the device. Poll its progress with `job_status()` or
+    /// wait for it with `wait_for_job()`. Returns `Error::UnsupportedFilesystem` without changing
+    /// anything unless `filesystem` is `EXT4` or `VFAT`.
+    pub async fn format(&self, disk_id: &DiskId, filesystem: Filesystem) -> Result<JobId> {
+        self.0.check_writable()?;
+        match filesystem {
+            Filesystem::EXT4 | Filesystem::VFAT => {}
+            Filesystem::CIFS | Filesystem::None => {
+                return Err(Error::UnsupportedFilesystem(filesystem))
+            }
+        }
+        #[derive(Serialize)]
+        struct Req<'a> {
+            diskid: &'a str,
+            filesystem: Filesystem,
+        }
+
+        let job = self
+            .start_job(
+                "/axis-cgi/disks/format.cgi",
+                Req {
+                    diskid: disk_id.as_str(),
+                    filesystem,
+                },
+            )
+            .await?;
+        self.0
+            .audit(|| format!("started formatting disk {}", disk_id.as_str()))
+            .await;
+        Ok(job)
+    }
+
+    /// Start mounting a disk, making it available for recordings.
+    ///
+    /// Poll the job with `job_status()` or wait for it with `wait_for_job()`.
+    pub async fn mount(&self, disk_id: &DiskId) -> Result<JobId> {
+        self.mount_cgi(disk_id, "mount").await
+    }
+
+    /// Start unmounting a disk, so that it can be removed safely.
+    ///
+    /// Poll the job with `job_status()` or wait for it with `wait_for_job()`.
+    pub async fn unmount(&self, disk_id: &DiskId) -> Result<JobId> {
+        self.mount_cgi(disk_id, "unmount").await
+    }
+
+    async fn mount_cgi(&self, disk_id: &DiskId, action: &'static str) -> Result<JobId> {
+        self.0.check_writable()?;
+        #[derive(Serialize)]
+        struct Req<'a> {
+            action: &'static str,
+            diskid: &'a str,
+        }
+
+        let job = self
+            .start_job(
+                "/axis-cgi/disks/mount.cgi",
+                Req {
+                    action,
+                    diskid: disk_id.as_str(),
+                },
+            )
+            .await?;
+        self.0
+            .audit(|| format!("started to {} disk {}", action, disk_id.as_str()))
+            .await;
+        Ok(job)
+    }
+
+    /// Start checking a disk's filesystem for errors, repairing any found if `repair` is set.
+    ///
+    /// Poll the job with `job_status()` or wait for it with `wait_for_job()`.
+    pub async fn checkdisk(&self, disk_id: &DiskId, repair: bool) -> Result<JobId> {
+        self.0.check_writable()?;
+        #[derive(Serialize)]
+        struct Req<'a> {
+            diskid: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            repair: Option<&'static str>,
+        }
+
+        let job = self
+            .start_job(
+                "/axis-cgi/disks/checkdisk.cgi",
+                Req {
+                    diskid: disk_id.as_str(),
+                    repair: if repair { Some("yes") } else { None },
+                },
+            )
+            .await?;
+        self.0
+            .audit(|| format!("started checking disk {}", disk_id.as_str()))
+            .await;
+        Ok(job)
+    }
+
+    /// Retrieve the progress of a job started by `format()`, `mount()`, `unmount()`,
+    /// `checkdisk()`, or `start_speed_test()`.
+    pub async fn job_status(&self, job_id: &JobId) -> Result<JobStatus> {
+        Ok(self.raw_job_status(job_id).await?.into())
+    }
+
+    /// Poll a job with `job_status()` every `poll_interval` until it finishes, returning its final
+    /// status.
+    ///
+    /// Dropping the future stops polling without affecting the job.
+    pub async fn wait_for_job(&self, job_id: &JobId, poll_interval: Duration) -> Result<JobStatus> {
+        loop {
+            let status = self.job_status(job_id).await?;
+            if status.is_finished() {
+                return Ok(status);
+            }
+            futures_timer::Delay::new(poll_interval).await;
+        }
+    }
+
+    /// Lock a disk, preventing recordings on it from being removed, as while investigating an
+    /// incident.
+    ///
+    /// Returns `Error::UnsupportedFilesystem` without changing anything if the disk's filesystem
+    /// cannot be locked, like a network share's.
+    pub async fn lock(&self, disk_id: &DiskId) -> Result<()> {
+        self.set_locked(disk_id, true).await
+    }
+
+    /// Unlock a disk locked by `lock()`, allowing recordings to be removed again.
+    pub async fn unlock(&self, disk_id: &DiskId) -> Result<()> {
+        self.set_locked(disk_id, false).await
+    }
+
+    async fn set_locked(&self, disk_id: &DiskId, locked: bool) -> Result<()> {
+        self.0.check_writable()?;
+        let disk = self
+            .list()
+            .await?
+            .into_iter()
+            .find(|disk| disk.disk_id == disk_id.as_str())
+            .ok_or(Error::Other("no such disk"))?;
+        if !disk.filesystem.supports_locking() {
+            return Err(Error::UnsupportedFilesystem(disk.filesystem));
+        }
+
+        #[derive(Serialize)]
+        struct Req<'a> {
+            action: &'static str,
+            diskid: &'a str,
+        }
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for_args(
+                "/axis-cgi/disks/lock.cgi",
+                Req {
+                    action: if locked { "lock" } else { "unlock" },
+                    diskid: disk_id.as_str(),
+                },
+            )?)
+            .body(Vec::new())?;
+
+        let (_resp, resp_body) = self
+            .0
+            .roundtrip(req, "text/xml")
+            .await
+            .map_404_to_feature_unavailable()?;
+
+        #[derive(Deserialize)]
+        struct LockResponse {
+            disks: LockContainer,
+        }
+        #[derive(Deserialize)]
+        struct LockContainer {
+            disk: LockResult,
+        }
+        #[derive(Deserialize)]
+        struct LockResult {
+            result: String,
+        }
+        let resp_body: LockResponse = crate::xml::from_slice(&resp_body)?;
+        if resp_body.disks.disk.result != "OK" {
+            return Err(Error::Other("disk lock change failed"));
+        }
+
+        self.0
+            .audit(|| {
+                format!(
+                    "{} disk {}",
+                    if locked { "locked" } else { "unlocked" },
+                    disk_id.as_str()
+                )
+            })
+            .await;
+        Ok(())
+    }
+
+    /// Retrieve the retention limits of a storage group, as named by `DiskInfo::group`.
+    pub async fn group_limits(&self, group: &str) -> Result<StorageGroupLimits> {
+        let group = storage_group(group)?;
+        let params = self
+            .0
+            .parameters()
+            .list(Some(&[group.name().as_str()]))
+            .await?;
+        let param = |name: &str| {
+            params
+                .get(&group.parameter(name))
+                .map(|value| value.as_str())
+                .ok_or(Error::Other("storage group parameter missing"))
+        };
+
+        Ok(StorageGroupLimits {
+            cleanup_level: param("CleanupLevel")?
+                .parse()
+                .map_err(|_| Error::Other("invalid storage group cleanup level"))?,
+            cleanup_max_age: param("CleanupMaxAge")?
+                .parse()
+                .map_err(|_| Error::Other("invalid storage group cleanup max age"))?,
+            cleanup_policy: match param("CleanupPolicyActive")? {
+                "fifo" => CleanupPolicy::FIFO,
+                "none" => CleanupPolicy::None,
+                _ => return Err(Error::Other("invalid storage group cleanup policy")),
+            },
+        })
+    }
+
+    /// Change the retention limits of a storage group, as named by `DiskInfo::group`.
+    pub async fn set_group_limits(&self, group: &str, limits: &StorageGroupLimits) -> Result<()> {
+        let group = storage_group(group)?;
+        if limits.cleanup_level > 100 {
+            return Err(Error::Other(
+                "storage group cleanup level must be a percentage",
+            ));
+        }
+
+        self.0
+            .parameters()
+            .update(vec![
+                (
+                    group.parameter("CleanupLevel"),
+                    limits.cleanup_level.to_string(),
+                ),
+                (
+                    group.parameter("CleanupMaxAge"),
+                    limits.cleanup_max_age.to_string(),
+                ),
+                (
+                    group.parameter("CleanupPolicyActive"),
+                    match limits.cleanup_policy {
+                        CleanupPolicy::FIFO => "fifo",
+                        CleanupPolicy::None => "none",
+                    }
+                    .to_string(),
+                ),
+            ])
+            .await
+    }
+
+    async fn call<R: Serialize>(&self, path: &str, args: R) -> Result<RawJob> {
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.0.uri_for_args(path, args)?)
+            .body(Vec::new())?;
+
+        let (_resp, resp_body) = self
+            .0
+            .roundtrip(req, "text/xml")
+            .await
+            .map_404_to_feature_unavailable()?;
+
+        #[derive(Deserialize)]
+        struct JobResponse {
+            job: RawJob,
+        }
+        let resp_body: JobResponse = crate::xml::from_slice(&resp_body)?;
+
+        Ok(resp_body.job)
+    }
+}
+
+/// The `root.Storage.S{n}` parameter group for a storage group name like `S0`.
+fn storage_group(group: &str) -> Result<crate::v3::parameters::DynamicGroup> {
+    let group: crate::v3::parameters::DynamicGroup = format!("Storage.{}", group)
+        .parse()
+        .map_err(|_| Error::Other("invalid storage group"))?;
+    if group.parent() != "Storage" {
+        return Err(Error::Other("invalid storage group"));
+    }
+    Ok(group)
+}
+
+/// The retention limits of a storage group.
+///
+/// Each disk belongs to a storage group, named by `DiskInfo::group`, which limits how much of the
+/// disk recordings may occupy and for how long they are kept. Recordings are removed according
+/// to `cleanup_policy` once either limit is reached.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageGroupLimits {
+    /// The fill level, in percent of the disk, at which recordings are removed.
+    pub cleanup_level: u8,
+    /// The maximum age of a recording, in days.
+    pub cleanup_max_age: u16,
+    /// How recordings are removed once a limit is reached.
+    pub cleanup_policy: CleanupPolicy,
+}
+
+/// The status of a storage performance test.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedTestStatus {
+    /// The identifier of the test job.
+    pub job_id: JobId,
+    /// The disk being tested.
+    pub disk_id: DiskId,
+    /// The progress of the test, in percent.
+    pub progress: u8,
+    /// The results of the test, once it has finished.
+    pub result: Option<SpeedTestResult>,
+}
+
+impl SpeedTestStatus {
+    /// Has the test finished?
+    pub fn is_finished(&self) -> bool {
+        self.result.is_some()
+    }
+}
+
+/// The results of a storage performance test.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedTestResult {
+    /// The measured sustained write speed, in kilobytes per second.
+    pub write_speed: Option<u64>,
+    /// Did the device consider the disk fast enough for recording?
+    pub suitable: bool,
+    /// The status message reported by the device, e.g. `"OK"`.
+    pub message: String,
+}
+
+/// The status of a disk management job.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobStatus {
+    /// The identifier of the job.
+    pub job_id: JobId,
+    /// The disk the job operates on.
+    pub disk_id: DiskId,
+    /// What the job does.
+    pub operation: DiskOperation,
+    /// The progress of the job, in percent.
+    pub progress: u8,
+    /// The outcome reported by the device once the job has finished, e.g. `"OK"`.
+    pub result: Option<String>,
+}
+
+impl JobStatus {
+    /// Has the job finished?
+    ///
+    /// A job which fails may finish before reaching 100%, so this depends only on the presence of
+    /// a `result`.
+    pub fn is_finished(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// Did the job finish successfully?
+    pub fn succeeded(&self) -> bool {
+        self.is_finished() && self.result.as_deref() == Some("OK")
+    }
+}
+
+/// The operation performed by a disk management job.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiskOperation {
+    /// Formatting, started by `DiskManagement::format()`.
+    Format,
+    /// Mounting, started by `DiskManagement::mount()`.
+    Mount,
+    /// Unmounting, started by `DiskManagement::unmount()`.
+    Unmount,
+    /// Checking the filesystem, started by `DiskManagement::checkdisk()`.
+    CheckDisk,
+    /// Repairing the filesystem, started by `DiskManagement::checkdisk()`.
+    Repair,
+    /// A performance test, started by `DiskManagement::start_speed_test()`.
+    SpeedTest,
+    /// An operation this crate does not recognize.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Default for DiskOperation {
+    fn default() -> Self {
+        DiskOperation::Unknown
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawJob {
+    #[serde(rename = "jobid")]
+    job_id: JobId,
+    #[serde(rename = "diskid")]
+    disk_id: DiskId,
+    #[serde(default)]
+    operation: DiskOperation,
+    #[serde(default)]
+    progress: u8,
+    #[serde(default)]
+    result: Option<String>,
+    #[serde(rename = "writespeed", default)]
+    write_speed: Option<u64>,
+    #[serde(default, deserialize_with = "deserialize_optional_yesno")]
+    suitable: Option<bool>,
+}
+
+impl From<RawJob> for JobStatus {
+    fn from(job: RawJob) -> Self {
+        JobStatus {
+            job_id: job.job_id,
+            disk_id: job.disk_id,
+            operation: job.operation,
+            progress: job.progress,
+            result: job.result,
+        }
+    }
+}
+
+impl From<RawJob> for SpeedTestStatus {
+    fn from(job: RawJob) -> Self {
+        // A test which fails may report its result before reaching 100%
+        let (write_speed, suitable) = (job.write_speed, job.suitable);
+        let result = job.result.map(|message| SpeedTestResult {
+            write_speed,
+            suitable: suitable.unwrap_or(message == "OK"),
+            message,
+        });
+
+        SpeedTestStatus {
+            job_id: job.job_id,
+            disk_id: job.disk_id,
+            progress: job.progress,
+            result,
+        }
+    }
 }
 
 /// Information about a disk.
 ///
 /// A disk may be physically connected like an SD card, or it may be a network share.
-#[derive(Debug, Clone, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Eq, PartialEq, Serialize)]
 pub struct DiskInfo {
     /// The identifier for this disk.
     #[serde(rename = "diskid")]
@@ -78,7 +564,8 @@ pub struct DiskInfo {
     #[serde(rename = "cleanuppolicy")]
     pub cleanup_policy: CleanupPolicy,
 
-    /// TODO
+    /// Is the disk locked, preventing recordings from being removed? See
+    /// `DiskManagement::lock()`.
     #[serde(deserialize_with = "deserialize_yesno")]
     pub locked: bool, //"no"
 
@@ -105,7 +592,7 @@ pub struct DiskInfo {
 }
 
 /// The cleanup policy for a disk.
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize, Serialize)]
 pub enum CleanupPolicy {
     /// First in, first out.
     #[serde(rename = "fifo")]
@@ -115,7 +602,8 @@ pub enum CleanupPolicy {
     None,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize)]
+/// The filesystem of a disk.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize, Serialize)]
 pub enum Filesystem {
     #[serde(rename = "ext4")]
     EXT4,
@@ -127,6 +615,18 @@ pub enum Filesystem {
     None,
 }
 
+impl Filesystem {
+    /// Can disks with this filesystem be locked with `DiskManagement::lock()`?
+    ///
+    /// Network shares are managed by their server, and unformatted disks hold no recordings.
+    pub fn supports_locking(self) -> bool {
+        match self {
+            Filesystem::EXT4 | Filesystem::VFAT => true,
+            Filesystem::CIFS | Filesystem::None => false,
+        }
+    }
+}
+
 fn deserialize_yesno<'de, D>(d: D) -> Result<bool, D::Error>
 where
     D: serde::de::Deserializer<'de>,
@@ -136,7 +636,15 @@ where
         type Value = bool;
 
         fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-            f.write_str(r#""yes" or "no""#)
+            f.write_str(r#""yes", "no", or a boolean"#)
+        }
+
+        fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+        where
+            E: serde::de::Error,
+        {
+            // Accept `bool`s too, so that `DiskInfo` round-trips through self-describing formats
+            Ok(v)
         }
 
         fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
@@ -154,6 +662,13 @@ where
     d.deserialize_any(V)
 }
 
+fn deserialize_optional_yesno<'de, D>(d: D) -> Result<Option<bool>, D::Error>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    deserialize_yesno(d).map(Some)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,6 +687,88 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn group_limits() {
+        let client = crate::mock_client(|req| {
+            let body: &[u8] = match req.uri().path_and_query().unwrap().as_str() {
+                "/axis-cgi/param.cgi?action=list&group=root.Storage.S2" => {
+                    b"root.Storage.S2.AutoRepair=yes\n\
+root.Storage.S2.CleanupLevel=99\n\
+root.Storage.S2.CleanupMaxAge=1\n\
+root.Storage.S2.CleanupPolicyActive=none\n\
+root.Storage.S2.DiskID=NetworkShareN0\n"
+                }
+                "/axis-cgi/param.cgi?action=update&root.Storage.S2.CleanupLevel=80&root.Storage.S2.CleanupMaxAge=30&root.Storage.S2.CleanupPolicyActive=fifo" => {
+                    b"OK"
+                }
+                other => panic!("unexpected request {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![body.to_vec()])
+        });
+        let disk_management = DiskManagement::new(&client, "1.0".to_string());
+
+        assert_eq!(
+            disk_management.group_limits("S2").await.unwrap(),
+            StorageGroupLimits {
+                cleanup_level: 99,
+                cleanup_max_age: 1,
+                cleanup_policy: CleanupPolicy::None,
+            }
+        );
+
+        disk_management
+            .set_group_limits(
+                "S2",
+                &StorageGroupLimits {
+                    cleanup_level: 80,
+                    cleanup_max_age: 30,
+                    cleanup_policy: CleanupPolicy::FIFO,
+                },
+            )
+            .await
+            .unwrap();
+
+        assert!(disk_management.group_limits("S2.X").await.is_err());
+        assert!(disk_management.group_limits("").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn lock() {
+        let client = crate::mock_client(|req| {
+            let body: &[u8] = match req.uri().path_and_query().unwrap().as_str() {
+                "/axis-cgi/disks/list.cgi?diskid=all" => br#"<root><disks numberofdisks="2">
+<disk diskid="SD_DISK" name="" totalsize="116109036" freesize="75106020" cleanuplevel="99" cleanupmaxage="7" cleanuppolicy="fifo" locked="no" full="no" readonly="no" status="OK" filesystem="ext4" group="S0" requiredfilesystem="none" encryptionenabled="false" diskencrypted="false"/>
+<disk diskid="NetworkShare" name="" totalsize="0" freesize="0" cleanuplevel="90" cleanupmaxage="7" cleanuppolicy="fifo" locked="no" full="no" readonly="no" status="OK" filesystem="cifs" group="S1" requiredfilesystem="none" encryptionenabled="false" diskencrypted="false"/>
+</disks></root>"#,
+                "/axis-cgi/disks/lock.cgi?action=lock&diskid=SD_DISK" => {
+                    br#"<root><disks><disk diskid="SD_DISK" result="OK"/></disks></root>"#
+                }
+                "/axis-cgi/disks/lock.cgi?action=unlock&diskid=SD_DISK" => {
+                    br#"<root><disks><disk diskid="SD_DISK" result="Busy"/></disks></root>"#
+                }
+                other => panic!("unexpected request {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/xml")
+                .body(vec![body.to_vec()])
+        });
+        let disk_management = DiskManagement::new(&client, "1.0".into());
+
+        disk_management.lock(&DiskId::new("SD_DISK")).await.unwrap();
+        assert!(disk_management
+            .unlock(&DiskId::new("SD_DISK"))
+            .await
+            .is_err());
+        match disk_management.lock(&DiskId::new("NetworkShare")).await {
+            Err(Error::UnsupportedFilesystem(Filesystem::CIFS)) => {}
+            other => panic!("expected UnsupportedFilesystem, got {:?}", other),
+        }
+    }
+
     #[test]
     fn deserialize_list() {
         #[derive(Deserialize)]
@@ -215,5 +812,187 @@ mod tests {
                 disk_encrypted: false,
             }
         );
+
+        for disk in container.disks {
+            let json = serde_json::to_string(&disk).unwrap();
+            assert_eq!(serde_json::from_str::<DiskInfo>(&json).unwrap(), disk);
+        }
+    }
+
+    #[tokio::test]
+    async fn unusual_encodings() {
+        let client = crate::mock_client(|req| {
+            let body: &[u8] = match req.uri().path_and_query().map(|pq| pq.as_str()) {
+                Some("/axis-cgi/disks/list.cgi?diskid=all") => {
+                    include_bytes!("../../fixtures/xml/disks-utf8-bom.xml")
+                }
+                Some("/axis-cgi/disks/job.cgi?jobid=7") => {
+                    include_bytes!("../../fixtures/xml/job-utf16le-bom.xml")
+                }
+                other => panic!("unexpected request {:?}", other),
+            };
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/xml")
+                .body(vec![body.to_vec()])
+        });
+        let disk_management = DiskManagement::new(&client, "1.0".to_string());
+
+        let disks = disk_management.list().await.unwrap();
+        assert_eq!(disks.len(), 1);
+        assert_eq!(disks[0].disk_id, "SD_DISK");
+
+        let status = disk_management
+            .speed_test_status(&JobId::new("7"))
+            .await
+            .unwrap();
+        assert!(status.is_finished());
+    }
+
+    #[tokio::test]
+    async fn jobs() {
+        let polls = std::sync::atomic::AtomicUsize::new(0);
+        let client = crate::mock_client(move |req| {
+            let body = match req.uri().path_and_query().map(|pq| pq.as_str()) {
+                Some("/axis-cgi/disks/format.cgi?diskid=SD_DISK&filesystem=ext4") => {
+                    r#"<root><job jobid="1" diskid="SD_DISK" operation="format"/></root>"#
+                }
+                Some("/axis-cgi/disks/mount.cgi?action=unmount&diskid=SD_DISK") => {
+                    r#"<root><job jobid="2" diskid="SD_DISK" operation="unmount"/></root>"#
+                }
+                Some("/axis-cgi/disks/mount.cgi?action=mount&diskid=SD_DISK") => {
+                    r#"<root><job jobid="3" diskid="SD_DISK" operation="mount"/></root>"#
+                }
+                Some("/axis-cgi/disks/checkdisk.cgi?diskid=SD_DISK&repair=yes") => {
+                    r#"<root><job jobid="4" diskid="SD_DISK" operation="repair"/></root>"#
+                }
+                Some("/axis-cgi/disks/job.cgi?jobid=4") => {
+                    r#"<root><job jobid="4" diskid="SD_DISK" operation="repair" progress="30" result="Error"/></root>"#
+                }
+                Some("/axis-cgi/disks/job.cgi?jobid=1") => {
+                    match polls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                        0 => {
+                            r#"<root><job jobid="1" diskid="SD_DISK" operation="format" progress="40"/></root>"#
+                        }
+                        _ => {
+                            r#"<root><job jobid="1" diskid="SD_DISK" operation="format" progress="100" result="OK"/></root>"#
+                        }
+                    }
+                }
+                other => panic!("unexpected request {:?}", other),
+            };
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/xml")
+                .body(vec![body.as_bytes().to_vec()])
+        });
+
+        let disk_management = DiskManagement::new(&client, "1.0".into());
+        let disk_id = DiskId::new("SD_DISK");
+        match disk_management.format(&disk_id, Filesystem::CIFS).await {
+            Err(Error::UnsupportedFilesystem(Filesystem::CIFS)) => {}
+            other => panic!("expected UnsupportedFilesystem, got {:?}", other),
+        }
+
+        let job_id = disk_management
+            .format(&disk_id, Filesystem::EXT4)
+            .await
+            .unwrap();
+        let status = disk_management.job_status(&job_id).await.unwrap();
+        assert_eq!(status.operation, DiskOperation::Format);
+        assert_eq!(status.progress, 40);
+        assert!(!status.is_finished());
+
+        let status = disk_management
+            .wait_for_job(&job_id, Duration::from_millis(1))
+            .await
+            .unwrap();
+        assert!(status.succeeded());
+
+        assert_eq!(
+            disk_management.unmount(&disk_id).await.unwrap(),
+            JobId::new("2")
+        );
+        assert_eq!(
+            disk_management.mount(&disk_id).await.unwrap(),
+            JobId::new("3")
+        );
+        let job_id = disk_management.checkdisk(&disk_id, true).await.unwrap();
+        assert_eq!(job_id, JobId::new("4"));
+
+        // a job which fails part way through has finished, unsuccessfully
+        let status = disk_management
+            .wait_for_job(&job_id, Duration::from_millis(1))
+            .await
+            .unwrap();
+        assert_eq!(status.progress, 30);
+        assert!(status.is_finished());
+        assert!(!status.succeeded());
+
+        let client = client.with_read_only(true);
+        match DiskManagement::new(&client, "1.0".into())
+            .unmount(&disk_id)
+            .await
+        {
+            Err(Error::ReadOnlyMode) => {}
+            other => panic!("expected ReadOnlyMode, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn speed_test() {
+        let client = crate::mock_client(|req| {
+            let body = match req.uri().path_and_query().map(|pq| pq.as_str()) {
+                Some("/axis-cgi/disks/speedtest.cgi?diskid=SD_DISK") => {
+                    r#"<root><job jobid="7" diskid="SD_DISK" operation="speedtest"/></root>"#
+                }
+                Some("/axis-cgi/disks/job.cgi?jobid=7") => {
+                    r#"<root><job jobid="7" diskid="SD_DISK" operation="speedtest" progress="100" result="OK" writespeed="18432" suitable="yes"/></root>"#
+                }
+                Some("/axis-cgi/disks/job.cgi?jobid=8") => {
+                    r#"<root><job jobid="8" diskid="SD_DISK" operation="speedtest" progress="20" result="Error"/></root>"#
+                }
+                other => panic!("unexpected request {:?}", other),
+            };
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/xml")
+                .body(vec![body.as_bytes().to_vec()])
+        });
+
+        let disk_management = DiskManagement::new(&client, "1.0".into());
+        let job_id = disk_management
+            .start_speed_test(&DiskId::new("SD_DISK"))
+            .await
+            .unwrap();
+        assert_eq!(job_id, JobId::new("7"));
+
+        let status = disk_management.speed_test_status(&job_id).await.unwrap();
+        assert!(status.is_finished());
+        assert_eq!(
+            status.result,
+            Some(SpeedTestResult {
+                write_speed: Some(18432),
+                suitable: true,
+                message: "OK".into(),
+            })
+        );
+
+        let status = disk_management
+            .speed_test_status(&JobId::new("8"))
+            .await
+            .unwrap();
+        assert!(status.is_finished());
+        assert_eq!(
+            status.result,
+            Some(SpeedTestResult {
+                write_speed: None,
+                suitable: false,
+                message: "Error".into(),
+            })
+        );
     }
 }
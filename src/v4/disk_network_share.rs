@@ -0,0 +1,271 @@
+//! The network share API at `/axis-cgi/disks/networkshare.cgi`, which configures CIFS shares for
+//! recording.
+//!
+//! Each configured share appears as a disk in `DiskManagement::list()`, identified by the same
+//! `DiskId`, and is managed there like any other disk once added.
+
+use crate::v4::disk_management::DiskId;
+use crate::v4::{JsonService, UnknownFields};
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The network share API.
+pub struct DiskNetworkShare<'a, T: Transport> {
+    device: &'a Client<T>,
+    json: JsonService<'a, T>,
+}
+
+/// The settings of a network share.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkShareConfig {
+    /// The host name or IP address of the server.
+    pub address: String,
+    /// The name of the share on the server, optionally followed by a path within it.
+    pub share: String,
+    /// The user name to log in with, or empty to connect anonymously.
+    #[serde(default)]
+    pub username: String,
+    /// The password to log in with. Devices never report passwords, so this is `None` in listed
+    /// shares, and leaving it `None` in `modify()` keeps the current password.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// The CIFS protocol version to use, like `"3.0"`, or `None` to negotiate it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cifs_version: Option<String>,
+}
+
+impl fmt::Debug for NetworkShareConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NetworkShareConfig")
+            .field("address", &self.address)
+            .field("share", &self.share)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "[redacted]"))
+            .field("cifs_version", &self.cifs_version)
+            .finish()
+    }
+}
+
+/// A network share configured on the device.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkShare {
+    /// The disk identifier of the share, as listed by `DiskManagement::list()`.
+    pub disk_id: DiskId,
+    /// The share's settings.
+    #[serde(flatten)]
+    pub config: NetworkShareConfig,
+    /// The state of the device's connection to the share.
+    #[serde(default)]
+    pub status: NetworkShareStatus,
+    /// Fields returned by the device which this crate does not model.
+    #[serde(flatten)]
+    pub extra: UnknownFields,
+}
+
+/// The state of a device's connection to a network share.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkShareStatus {
+    /// The share is mounted and available for recording.
+    Connected,
+    /// The device is not connected to the share.
+    Disconnected,
+    /// The server could not be reached.
+    Unreachable,
+    /// The server rejected the credentials.
+    AuthenticationFailed,
+    /// A state this crate does not recognize.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Default for NetworkShareStatus {
+    fn default() -> Self {
+        NetworkShareStatus::Unknown
+    }
+}
+
+/// The outcome of `DiskNetworkShare::test()`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkShareTestResult {
+    /// What the device found when connecting to the share.
+    pub status: NetworkShareStatus,
+    /// Could the device write to the share?
+    #[serde(default)]
+    pub writable: bool,
+    /// Fields returned by the device which this crate does not model.
+    #[serde(flatten)]
+    pub extra: UnknownFields,
+}
+
+impl NetworkShareTestResult {
+    /// Is the share usable for recording?
+    pub fn is_usable(&self) -> bool {
+        self.status == NetworkShareStatus::Connected && self.writable
+    }
+}
+
+impl<'a, T: Transport> DiskNetworkShare<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>, api_version: String) -> Self {
+        Self {
+            device,
+            json: JsonService::new(device, "/axis-cgi/disks/networkshare.cgi", api_version)
+                .with_service_id("disk-network-share"),
+        }
+    }
+
+    /// List the network shares configured on the device, and the state of each.
+    pub async fn list(&self) -> Result<Vec<NetworkShare>> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Resp {
+            network_shares: Vec<NetworkShare>,
+        }
+        let resp: Resp = self.json.call_method_bare("getNetworkShares").await?;
+        Ok(resp.network_shares)
+    }
+
+    /// Add a network share, returning the disk identifier assigned to it.
+    pub async fn add(&self, config: &NetworkShareConfig) -> Result<DiskId> {
+        self.device.check_writable()?;
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Resp {
+            disk_id: DiskId,
+        }
+        let resp: Resp = self.json.call_method("addNetworkShare", config).await?;
+        self.device
+            .audit(|| {
+                format!(
+                    "added network share {} as {}",
+                    config.address,
+                    resp.disk_id.as_str()
+                )
+            })
+            .await;
+        Ok(resp.disk_id)
+    }
+
+    /// Change the settings of a network share.
+    pub async fn modify(&self, disk_id: &DiskId, config: &NetworkShareConfig) -> Result<()> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Req<'a> {
+            disk_id: &'a DiskId,
+            #[serde(flatten)]
+            config: &'a NetworkShareConfig,
+        }
+        self.json
+            .call_method_without_data("modifyNetworkShare", Req { disk_id, config })
+            .await
+    }
+
+    /// Remove a network share. Recordings stored on the share are left on the server.
+    pub async fn remove(&self, disk_id: &DiskId) -> Result<()> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Req<'a> {
+            disk_id: &'a DiskId,
+        }
+        self.json
+            .call_method_without_data("removeNetworkShare", Req { disk_id })
+            .await
+    }
+
+    /// Check whether the device can connect to and write to a share, without adding it.
+    pub async fn test(&self, config: &NetworkShareConfig) -> Result<NetworkShareTestResult> {
+        self.json.call_method("testNetworkShare", config).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn network_shares() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(req.uri().path(), "/axis-cgi/disks/networkshare.cgi");
+            let body: serde_json::Value = serde_json::from_slice(req.body()).unwrap();
+            let response = match body["method"].as_str().unwrap() {
+                "getNetworkShares" => serde_json::json!({
+                    "apiVersion": "1.0",
+                    "data": {"networkShares": [{
+                        "diskId": "NetworkShare",
+                        "address": "nas.example.com",
+                        "share": "recordings/lobby",
+                        "username": "camera",
+                        "status": "authenticationFailed",
+                        "mountPoint": "/var/spool/storage/NetworkShare"
+                    }]}
+                }),
+                "testNetworkShare" => {
+                    assert_eq!(body["params"]["password"], "hunter2");
+                    serde_json::json!({
+                        "apiVersion": "1.0",
+                        "data": {"status": "connected", "writable": true}
+                    })
+                }
+                "addNetworkShare" => {
+                    assert_eq!(
+                        body["params"],
+                        serde_json::json!({
+                            "address": "nas.example.com",
+                            "share": "recordings/lobby",
+                            "username": "camera",
+                            "password": "hunter2"
+                        })
+                    );
+                    serde_json::json!({"apiVersion": "1.0", "data": {"diskId": "NetworkShare"}})
+                }
+                "modifyNetworkShare" => {
+                    assert_eq!(body["params"]["diskId"], "NetworkShare");
+                    assert_eq!(body["params"]["cifsVersion"], "3.0");
+                    assert!(body["params"].get("password").is_none());
+                    serde_json::json!({"apiVersion": "1.0", "data": {}})
+                }
+                other => panic!("unexpected method {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(vec![response.to_string().into_bytes()])
+        });
+        let shares = DiskNetworkShare::new(&client, "1.0".into());
+
+        let list = shares.list().await.unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].disk_id, DiskId::new("NetworkShare"));
+        assert_eq!(list[0].config.share, "recordings/lobby");
+        assert_eq!(list[0].config.password, None);
+        assert_eq!(list[0].status, NetworkShareStatus::AuthenticationFailed);
+        assert!(list[0].extra.contains_key("mountPoint"));
+
+        let mut config = NetworkShareConfig {
+            password: Some("hunter2".into()),
+            ..list[0].config.clone()
+        };
+        assert!(!format!("{:?}", config).contains("hunter2"));
+        assert!(shares.test(&config).await.unwrap().is_usable());
+        let disk_id = shares.add(&config).await.unwrap();
+
+        config.password = None;
+        config.cifs_version = Some("3.0".into());
+        shares.modify(&disk_id, &config).await.unwrap();
+
+        let client = client.with_read_only(true);
+        let shares = DiskNetworkShare::new(&client, "1.0".into());
+        match shares.add(&config).await {
+            Err(Error::ReadOnlyMode) => {}
+            other => panic!("expected ReadOnlyMode, got {:?}", other),
+        }
+        match shares.remove(&disk_id).await {
+            Err(Error::ReadOnlyMode) => {}
+            other => panic!("expected ReadOnlyMode, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,882 @@
+//! The event service at `/vapix/services`, which declares the device's event topics and delivers
+//! event notifications.
+//!
+//! Notifications are delivered through an ONVIF pull point subscription: the client subscribes
+//! to a set of topics, then long-polls the device for messages. Unlike the RTSP metadata stream
+//! or the event WebSocket, this needs only request/response HTTP, so it works over any
+//! `Transport`.
+//!
+//! Each poll waits up to `PULL_TIMEOUT` for events, so the client's streaming timeouts apply
+//! rather than its usual timeouts.
+
+use crate::*;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, Stream};
+use quick_xml::events::{BytesStart, Event};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+pub use crate::v3::audio_detection::AUDIO_DETECTION_TOPIC;
+pub use crate::v3::shock_detection::SHOCK_DETECTION_TOPIC;
+
+/// The topic of I/O port state changes, with the port number as the `port` source item and the
+/// port state as the `state` data item.
+pub const IO_PORT_TOPIC: &str = "tns1:Device/tnsaxis:IO/Port";
+
+/// The topic of virtual input state changes, with the input number as the `port` source item and
+/// the input state as the `active` data item.
+pub const VIRTUAL_INPUT_TOPIC: &str = "tns1:Device/tnsaxis:IO/VirtualInput";
+
+/// The topic of motion detected in a video source, with the detection state as the `State` data
+/// item.
+pub const MOTION_ALARM_TOPIC: &str = "tns1:VideoSource/MotionAlarm";
+
+/// The topic of SD card health reports, with the disk as the `disk_id` source item and the card's
+/// wear, in percent of its rated lifetime, as the `wear` data item.
+pub const STORAGE_ALERT_TOPIC: &str = "tns1:Device/tnsaxis:Storage/Alert";
+
+/// How long each poll waits for events before returning empty-handed.
+pub const PULL_TIMEOUT: Duration = Duration::from_secs(10);
+
+const SERVICES_PATH: &str = "/vapix/services";
+const SUBSCRIPTION_LIFETIME: Duration = Duration::from_secs(60);
+const MESSAGE_LIMIT: u32 = 100;
+
+const NAMESPACES: &str = concat!(
+    r#"xmlns:soap="http://www.w3.org/2003/05/soap-envelope" "#,
+    r#"xmlns:wsa="http://www.w3.org/2005/08/addressing" "#,
+    r#"xmlns:wsnt="http://docs.oasis-open.org/wsn/b-2" "#,
+    r#"xmlns:tev="http://www.onvif.org/ver10/events/wsdl" "#,
+    r#"xmlns:aev="http://www.axis.com/vapix/ws/event1" "#,
+    r#"xmlns:tns1="http://www.onvif.org/ver10/topics" "#,
+    r#"xmlns:tnsaxis="http://www.axis.com/2009/event/topics""#,
+);
+
+/// A device's event service.
+pub struct Events<'a, T: Transport>(&'a Client<T>);
+
+/// An event topic declared by the device.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDeclaration {
+    /// The topic, like `tns1:Device/tnsaxis:IO/Port`, as passed to `Events::subscribe()`.
+    pub topic: String,
+    /// The name of the topic to display to the user, if any.
+    pub nice_name: Option<String>,
+    /// Does this event describe a state which persists, like an input being active, rather than a
+    /// momentary occurrence?
+    pub is_property: bool,
+    /// The items identifying what produced the event, like a port number.
+    pub source: Vec<ItemDeclaration>,
+    /// The items describing the event itself, like a port state.
+    pub data: Vec<ItemDeclaration>,
+}
+
+/// An item included in the notifications for an event topic.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemDeclaration {
+    /// The name of the item.
+    pub name: String,
+    /// The name of the item to display to the user, if any.
+    pub nice_name: Option<String>,
+    /// The XML schema type of the item's value, like `xsd:boolean`.
+    pub value_type: Option<String>,
+    /// The values this item can take on this device, if the device enumerates them.
+    pub values: Vec<String>,
+}
+
+/// How a notification relates to the state described by a property event.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum PropertyOperation {
+    /// The current state, reported when a subscription starts.
+    Initialized,
+    /// The state changed.
+    Changed,
+    /// The property no longer exists.
+    Deleted,
+}
+
+/// An event notification received from the device.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventNotification {
+    /// The topic of the event, like `tns1:Device/tnsaxis:IO/Port`.
+    pub topic: String,
+    /// The time at which the event occurred, if reported by the device.
+    pub utc_time: Option<DateTime<Utc>>,
+    /// For property events, how this notification relates to the property's state.
+    pub property_operation: Option<PropertyOperation>,
+    /// The items identifying what produced the event, like `port=1`.
+    pub source: BTreeMap<String, String>,
+    /// The items describing the event itself, like `state=1`.
+    pub data: BTreeMap<String, String>,
+}
+
+impl StreamItem for EventNotification {
+    type Resume = ();
+
+    fn byte_len(&self) -> usize {
+        self.topic.len()
+            + self
+                .source
+                .iter()
+                .chain(self.data.iter())
+                .map(|(k, v)| k.len() + v.len())
+                .sum::<usize>()
+    }
+}
+
+impl<'a, T: Transport> Events<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// List the event topics which this device can produce.
+    pub async fn declarations(&self) -> Result<Vec<EventDeclaration>> {
+        let body = self
+            .call(
+                "http://www.axis.com/vapix/ws/event1/GetEventInstances",
+                &envelope(None, "", "<aev:GetEventInstances/>"),
+            )
+            .await
+            .map_404_to_feature_unavailable()?;
+        parse_declarations(&body)
+    }
+
+    /// Subscribe to notifications for the given topics, like `IO_PORT_TOPIC`.
+    ///
+    /// Each topic matches exactly, including any subtopics. The subscription expires unless it is
+    /// polled regularly, so an abandoned subscription is eventually cleaned up by the device.
+    pub async fn subscribe<S: AsRef<str>>(&self, topics: &[S]) -> Result<Subscription<'a, T>> {
+        let filter = topics
+            .iter()
+            .map(|topic| escape(topic.as_ref()))
+            .collect::<Vec<_>>()
+            .join("|");
+        let body = format!(
+            "<tev:CreatePullPointSubscription>\
+             <tev:Filter><wsnt:TopicExpression \
+             Dialect=\"http://www.onvif.org/ver10/tev/topicExpression/ConcreteSet\">{}\
+             </wsnt:TopicExpression></tev:Filter>\
+             <tev:InitialTerminationTime>{}</tev:InitialTerminationTime>\
+             </tev:CreatePullPointSubscription>",
+            filter,
+            xsd_duration(SUBSCRIPTION_LIFETIME),
+        );
+
+        let response = self
+            .call(
+                "http://www.onvif.org/ver10/events/wsdl/EventPortType/CreatePullPointSubscriptionRequest",
+                &envelope(None, "", &body),
+            )
+            .await
+            .map_404_to_feature_unavailable()?;
+        let (address, reference_parameters) = parse_subscription_reference(&response)?;
+
+        // Devices report their address as they see it, which may not be reachable from here, so
+        // only the path is used
+        let path = address
+            .parse::<http::Uri>()
+            .ok()
+            .and_then(|uri| uri.path_and_query().map(|pq| pq.as_str().to_string()))
+            .unwrap_or_else(|| SERVICES_PATH.to_string());
+
+        Ok(Subscription {
+            device: self.0,
+            path,
+            address,
+            reference_parameters,
+            renewed_at: Instant::now(),
+        })
+    }
+
+    /// Subscribe to notifications for the given topics, subscribing again according to `policy`
+    /// whenever the subscription fails.
+    ///
+    /// Events which occur while the subscription is being replaced are lost. Property events are
+    /// reported again as `PropertyOperation::Initialized`, so current states are never missed.
+    ///
+    /// Cancelling the `CancelHandle` given to `Reconnect::with_cancel()` ends the current
+    /// subscription on the device before ending the stream, whereas dropping the stream leaves
+    /// the subscription until it expires.
+    pub fn supervise(
+        &self,
+        topics: Vec<String>,
+        policy: Reconnect,
+    ) -> impl Stream<Item = Result<EventNotification>> + 'a {
+        let device = self.0;
+        let cancel = policy.cancel_handle();
+        policy.supervise(move |_| {
+            let topics = topics.clone();
+            let cancel = cancel.clone();
+            async move {
+                let subscription = Events(device).subscribe(&topics).await?;
+                Ok(subscription.into_stream_until(cancel))
+            }
+        })
+    }
+
+    async fn call(&self, action: &str, envelope: &str) -> Result<Vec<u8>> {
+        let (_, body) = self
+            .0
+            .roundtrip(request(self.0, SERVICES_PATH, action, envelope)?, SOAP)
+            .await?;
+        Ok(body)
+    }
+}
+
+/// An active subscription to event notifications.
+///
+/// Dropping a subscription leaves it on the device until it expires, about a minute after it was
+/// last polled. Call `unsubscribe()`, or use `into_stream_until()`, to end it promptly.
+pub struct Subscription<'a, T: Transport> {
+    device: &'a Client<T>,
+    path: String,
+    address: String,
+    reference_parameters: String,
+    renewed_at: Instant,
+}
+
+impl<'a, T: Transport> Subscription<'a, T> {
+    /// Wait for the next notifications, returning once at least one arrives or after
+    /// `PULL_TIMEOUT`, in which case the result is empty.
+    ///
+    /// The subscription is renewed as needed.
+    pub async fn pull(&mut self) -> Result<Vec<EventNotification>> {
+        if self.renewed_at.elapsed() >= SUBSCRIPTION_LIFETIME / 2 {
+            self.renew().await?;
+        }
+
+        let body = format!(
+            "<tev:PullMessages><tev:Timeout>{}</tev:Timeout>\
+             <tev:MessageLimit>{}</tev:MessageLimit></tev:PullMessages>",
+            xsd_duration(PULL_TIMEOUT),
+            MESSAGE_LIMIT,
+        );
+        let response = self
+            .call(
+                "http://www.onvif.org/ver10/events/wsdl/PullPointSubscription/PullMessagesRequest",
+                &body,
+            )
+            .await?;
+        parse_notifications(&response)
+    }
+
+    /// End the subscription.
+    pub async fn unsubscribe(self) -> Result<()> {
+        self.call(
+            "http://docs.oasis-open.org/wsn/bw-2/SubscriptionManager/UnsubscribeRequest",
+            "<wsnt:Unsubscribe/>",
+        )
+        .await
+        .map(|_| ())
+    }
+
+    /// Poll the subscription continuously, yielding each notification as it arrives.
+    ///
+    /// The stream ends after the first error. Dropping the stream abandons the subscription, which
+    /// remains on the device until it expires; use `into_stream_until()` to end it promptly.
+    pub fn into_stream(self) -> impl Stream<Item = Result<EventNotification>> + 'a {
+        self.into_stream_until(CancelHandle::new())
+    }
+
+    /// Poll the subscription continuously like `into_stream()`, until `cancel` is cancelled.
+    ///
+    /// On cancellation, any poll in progress is abandoned, the subscription is ended on the device,
+    /// and then the stream ends. Failing to end the subscription is not reported, since the device
+    /// cleans it up once it expires regardless.
+    pub fn into_stream_until(
+        self,
+        cancel: CancelHandle,
+    ) -> impl Stream<Item = Result<EventNotification>> + 'a {
+        stream::unfold(Some((self, VecDeque::new())), move |state| {
+            let cancel = cancel.clone();
+            async move {
+                let (mut subscription, mut pending) = state?;
+                loop {
+                    if cancel.is_cancelled() {
+                        let _ = subscription.unsubscribe().await;
+                        return None;
+                    }
+                    if let Some(notification) = pending.pop_front() {
+                        return Some((Ok(notification), Some((subscription, pending))));
+                    }
+                    match cancel.run(subscription.pull()).await {
+                        Ok(notifications) => pending.extend(notifications),
+                        Err(Error::Cancelled) => {}
+                        Err(e) => return Some((Err(e), None)),
+                    }
+                }
+            }
+        })
+    }
+
+    async fn renew(&mut self) -> Result<()> {
+        let body = format!(
+            "<wsnt:Renew><wsnt:TerminationTime>{}</wsnt:TerminationTime></wsnt:Renew>",
+            xsd_duration(SUBSCRIPTION_LIFETIME),
+        );
+        self.call(
+            "http://docs.oasis-open.org/wsn/bw-2/SubscriptionManager/RenewRequest",
+            &body,
+        )
+        .await?;
+        self.renewed_at = Instant::now();
+        Ok(())
+    }
+
+    async fn call(&self, action: &str, body: &str) -> Result<Vec<u8>> {
+        let envelope = envelope(
+            Some((action, &self.address)),
+            &self.reference_parameters,
+            body,
+        );
+        let (_, body) = self
+            .device
+            .roundtrip_streaming(request(self.device, &self.path, action, &envelope)?, SOAP)
+            .await?;
+        Client::<T>::read_body(body).await
+    }
+}
+
+const SOAP: &str = "application/soap+xml";
+
+fn request<T: Transport>(
+    device: &Client<T>,
+    path: &str,
+    action: &str,
+    envelope: &str,
+) -> Result<http::Request<Vec<u8>>> {
+    Ok(http::Request::builder()
+        .method(http::Method::POST)
+        .uri(device.uri_for(path)?)
+        .header(
+            http::header::CONTENT_TYPE,
+            format!("{}; charset=utf-8; action=\"{}\"", SOAP, action),
+        )
+        .body(envelope.as_bytes().to_vec())?)
+}
+
+/// Build a SOAP envelope. Requests to a subscription are addressed to it with WS-Addressing
+/// headers, echoing the reference parameters the device returned when the subscription was made.
+fn envelope(addressing: Option<(&str, &str)>, reference_parameters: &str, body: &str) -> String {
+    let header = match addressing {
+        Some((action, to)) => format!(
+            "<wsa:Action>{}</wsa:Action><wsa:To>{}</wsa:To>{}",
+            action,
+            escape(to),
+            reference_parameters
+        ),
+        None => String::new(),
+    };
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\
+         <soap:Envelope {}><soap:Header>{}</soap:Header><soap:Body>{}</soap:Body></soap:Envelope>",
+        NAMESPACES, header, body
+    )
+}
+
+fn escape(text: &str) -> Cow<'_, str> {
+    if !text.contains(&['&', '<', '>', '"'][..]) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;"),
+    )
+}
+
+fn xsd_duration(duration: Duration) -> String {
+    format!("PT{}S", duration.as_secs())
+}
+
+fn xml_error(e: quick_xml::Error) -> Error {
+    quick_xml::DeError::Xml(e).into()
+}
+
+/// Strip the namespace prefix from an element or attribute name.
+fn local_name(name: &[u8]) -> &[u8] {
+    match name.iter().rposition(|&b| b == b':') {
+        Some(i) => &name[i + 1..],
+        None => name,
+    }
+}
+
+/// Find an attribute by its local name, ignoring its namespace prefix.
+fn attribute(e: &BytesStart, name: &[u8]) -> Result<Option<String>> {
+    for attr in e.attributes() {
+        let attr = attr.map_err(xml_error)?;
+        if local_name(attr.key) == name && !attr.key.starts_with(b"xmlns") {
+            let value = attr.unescaped_value().map_err(xml_error)?;
+            return Ok(Some(String::from_utf8_lossy(&value).into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Section {
+    Source,
+    Data,
+    Other,
+}
+
+/// A walker over the topic tree in a `GetEventInstancesResponse`.
+///
+/// Every element in the tree is a topic path segment, except for the message descriptions inside
+/// `MessageInstance`. Elements marked `wstop:topic="true"` are topics which produce events.
+#[derive(Default)]
+struct DeclarationParser {
+    declarations: Vec<EventDeclaration>,
+    in_topic_set: bool,
+    path: Vec<String>,
+    topics: Vec<Option<usize>>,
+    message_depth: usize,
+    section: Option<Section>,
+    item: Option<ItemDeclaration>,
+    in_value: bool,
+}
+
+impl DeclarationParser {
+    fn start(&mut self, e: &BytesStart) -> Result<()> {
+        let name = local_name(e.name());
+        if !self.in_topic_set {
+            self.in_topic_set = name == b"TopicSet";
+            return Ok(());
+        }
+
+        if self.message_depth > 0 {
+            self.message_depth += 1;
+            match name {
+                b"SourceInstance" => self.section = Some(Section::Source),
+                b"DataInstance" => self.section = Some(Section::Data),
+                b"SimpleItemInstance" => {
+                    self.item = attribute(e, b"Name")?.map(|name| ItemDeclaration {
+                        name,
+                        nice_name: None,
+                        value_type: None,
+                        values: Vec::new(),
+                    });
+                    if let Some(item) = &mut self.item {
+                        item.nice_name = attribute(e, b"NiceName")?;
+                        item.value_type = attribute(e, b"Type")?;
+                    }
+                }
+                b"Value" => self.in_value = true,
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        if name == b"MessageInstance" {
+            self.message_depth = 1;
+            let is_property = attribute(e, b"isProperty")?.as_deref() == Some("true");
+            if let Some(declaration) = self.current() {
+                declaration.is_property = is_property;
+            }
+            return Ok(());
+        }
+
+        self.path
+            .push(String::from_utf8_lossy(e.name()).into_owned());
+        let topic = if attribute(e, b"topic")?.as_deref() == Some("true") {
+            self.declarations.push(EventDeclaration {
+                topic: self.path.join("/"),
+                nice_name: attribute(e, b"NiceName")?,
+                is_property: false,
+                source: Vec::new(),
+                data: Vec::new(),
+            });
+            Some(self.declarations.len() - 1)
+        } else {
+            None
+        };
+        self.topics.push(topic);
+        Ok(())
+    }
+
+    fn end(&mut self, name: &[u8]) {
+        let name = local_name(name);
+        if !self.in_topic_set {
+            return;
+        }
+
+        if self.message_depth > 0 {
+            self.message_depth -= 1;
+            match name {
+                b"SourceInstance" | b"DataInstance" => self.section = None,
+                b"SimpleItemInstance" => {
+                    let section = self.section;
+                    if let (Some(item), Some(declaration)) = (self.item.take(), self.current()) {
+                        match section {
+                            Some(Section::Source) => declaration.source.push(item),
+                            Some(Section::Data) => declaration.data.push(item),
+                            _ => {}
+                        }
+                    }
+                }
+                b"Value" => self.in_value = false,
+                _ => {}
+            }
+            return;
+        }
+
+        if name == b"TopicSet" {
+            self.in_topic_set = false;
+        } else {
+            self.path.pop();
+            self.topics.pop();
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if let (true, Some(item)) = (self.in_value, &mut self.item) {
+            let text = text.trim();
+            if !text.is_empty() {
+                item.values.push(text.to_string());
+            }
+        }
+    }
+
+    /// The declaration of the nearest enclosing topic.
+    fn current(&mut self) -> Option<&mut EventDeclaration> {
+        let index = self.topics.iter().rev().flatten().next().copied()?;
+        self.declarations.get_mut(index)
+    }
+}
+
+fn parse_declarations(body: &[u8]) -> Result<Vec<EventDeclaration>> {
+    let text = crate::xml::decode(body)?;
+    let mut reader = quick_xml::Reader::from_str(&text);
+    let mut parser = DeclarationParser::default();
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event(&mut buf).map_err(xml_error)? {
+            Event::Start(e) => parser.start(&e)?,
+            Event::Empty(e) => {
+                parser.start(&e)?;
+                parser.end(e.name());
+            }
+            Event::End(e) => parser.end(e.name()),
+            Event::Text(e) => {
+                parser.text(&String::from_utf8_lossy(&e.unescaped().map_err(xml_error)?))
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(parser.declarations)
+}
+
+/// Extract the address and the raw reference parameters of a new subscription.
+fn parse_subscription_reference(body: &[u8]) -> Result<(String, String)> {
+    let text = crate::xml::decode(body)?;
+    let mut reader = quick_xml::Reader::from_str(&text);
+    let mut buf = Vec::new();
+    let mut address = None;
+    let mut reference_parameters = String::new();
+    let mut in_address = false;
+    loop {
+        match reader.read_event(&mut buf).map_err(xml_error)? {
+            Event::Start(e) if local_name(e.name()) == b"Address" => in_address = true,
+            Event::End(e) if local_name(e.name()) == b"Address" => in_address = false,
+            Event::Text(e) if in_address => {
+                let value = e.unescaped().map_err(xml_error)?;
+                address = Some(String::from_utf8_lossy(&value).trim().to_string());
+            }
+            Event::Start(e) if local_name(e.name()) == b"ReferenceParameters" => {
+                let name = e.name().to_vec();
+                let start = reader.buffer_position();
+                let mut skip = Vec::new();
+                reader.read_to_end(&name, &mut skip).map_err(xml_error)?;
+                // The reader stops after the end tag, whose length is known
+                let end = reader.buffer_position() - (name.len() + 3);
+                reference_parameters = text[start..end].trim().to_string();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    let address = address.ok_or(Error::Other("subscription response has no address"))?;
+    Ok((address, reference_parameters))
+}
+
+fn parse_notifications(body: &[u8]) -> Result<Vec<EventNotification>> {
+    let text = crate::xml::decode(body)?;
+    let mut reader = quick_xml::Reader::from_str(&text);
+    let mut buf = Vec::new();
+    let mut notifications = Vec::new();
+    let mut current: Option<EventNotification> = None;
+    let mut in_topic = false;
+    let mut section = None;
+    loop {
+        let (e, empty) = match reader.read_event(&mut buf).map_err(xml_error)? {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(e) => {
+                match local_name(e.name()) {
+                    b"NotificationMessage" => notifications.extend(current.take()),
+                    b"Topic" => in_topic = false,
+                    b"Source" | b"Data" | b"Key" => section = None,
+                    _ => {}
+                }
+                buf.clear();
+                continue;
+            }
+            Event::Text(e) => {
+                if let (true, Some(notification)) = (in_topic, &mut current) {
+                    let topic = e.unescaped().map_err(xml_error)?;
+                    notification
+                        .topic
+                        .push_str(String::from_utf8_lossy(&topic).trim());
+                }
+                buf.clear();
+                continue;
+            }
+            Event::Eof => break,
+            _ => {
+                buf.clear();
+                continue;
+            }
+        };
+
+        let notification = match &mut current {
+            Some(notification) => notification,
+            None => {
+                if local_name(e.name()) == b"NotificationMessage" && !empty {
+                    current = Some(EventNotification {
+                        topic: String::new(),
+                        utc_time: None,
+                        property_operation: None,
+                        source: BTreeMap::new(),
+                        data: BTreeMap::new(),
+                    });
+                }
+                buf.clear();
+                continue;
+            }
+        };
+
+        match local_name(e.name()) {
+            b"Topic" => in_topic = !empty,
+            b"Message" => {
+                if let Some(utc_time) = attribute(&e, b"UtcTime")? {
+                    notification.utc_time = DateTime::parse_from_rfc3339(&utc_time)
+                        .ok()
+                        .map(|t| t.with_timezone(&Utc));
+                }
+                notification.property_operation =
+                    match attribute(&e, b"PropertyOperation")?.as_deref() {
+                        Some("Initialized") => Some(PropertyOperation::Initialized),
+                        Some("Changed") => Some(PropertyOperation::Changed),
+                        Some("Deleted") => Some(PropertyOperation::Deleted),
+                        _ => notification.property_operation,
+                    };
+            }
+            b"Source" if !empty => section = Some(Section::Source),
+            b"Data" if !empty => section = Some(Section::Data),
+            b"Key" if !empty => section = Some(Section::Other),
+            b"SimpleItem" => {
+                if let (Some(name), Some(value)) =
+                    (attribute(&e, b"Name")?, attribute(&e, b"Value")?)
+                {
+                    match section {
+                        Some(Section::Source) => {
+                            notification.source.insert(name, value);
+                        }
+                        Some(Section::Data) => {
+                            notification.data.insert(name, value);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(notifications)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use futures::StreamExt;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn declarations() {
+        let declarations = parse_declarations(include_bytes!(
+            "../../fixtures/events/get-event-instances.xml"
+        ))
+        .unwrap();
+        let topics: Vec<&str> = declarations.iter().map(|d| d.topic.as_str()).collect();
+        assert_eq!(
+            topics,
+            vec![
+                IO_PORT_TOPIC,
+                VIRTUAL_INPUT_TOPIC,
+                MOTION_ALARM_TOPIC,
+                "tns1:Device/tnsaxis:Status/SystemReady"
+            ]
+        );
+
+        let port = &declarations[0];
+        assert_eq!(port.nice_name.as_deref(), Some("Input and output ports"));
+        assert!(port.is_property);
+        assert_eq!(
+            port.source,
+            vec![ItemDeclaration {
+                name: "port".into(),
+                nice_name: Some("Port".into()),
+                value_type: Some("xsd:int".into()),
+                values: vec!["0".into(), "1".into()],
+            }]
+        );
+        assert_eq!(port.data.len(), 1);
+        assert_eq!(port.data[0].name, "state");
+
+        let system_ready = &declarations[3];
+        assert!(system_ready.source.is_empty());
+        assert_eq!(system_ready.data[0].name, "ready");
+    }
+
+    #[test]
+    fn notifications() {
+        let notifications =
+            parse_notifications(include_bytes!("../../fixtures/events/pull-messages.xml")).unwrap();
+        assert_eq!(notifications.len(), 2);
+
+        let port = &notifications[0];
+        assert_eq!(port.topic, IO_PORT_TOPIC);
+        assert_eq!(
+            port.utc_time,
+            Some(Utc.ymd(2020, 10, 9).and_hms_micro(15, 30, 4, 123456))
+        );
+        assert_eq!(port.property_operation, Some(PropertyOperation::Changed));
+        assert_eq!(port.source.get("port").map(String::as_str), Some("1"));
+        assert_eq!(port.data.get("state").map(String::as_str), Some("1"));
+
+        let motion = &notifications[1];
+        assert_eq!(motion.topic, MOTION_ALARM_TOPIC);
+        assert_eq!(
+            motion.property_operation,
+            Some(PropertyOperation::Initialized)
+        );
+        assert_eq!(motion.data.get("State").map(String::as_str), Some("false"));
+    }
+
+    #[tokio::test]
+    async fn subscribe() {
+        let device = crate::mock_client(|req| {
+            assert_eq!(req.method(), http::Method::POST);
+            assert_eq!(req.uri().path(), "/vapix/services");
+            let body = String::from_utf8(req.body().clone()).unwrap();
+            let response: &[u8] = if body.contains("<tev:CreatePullPointSubscription>") {
+                assert!(body.contains(
+                    ">tns1:Device/tnsaxis:IO/Port|tns1:VideoSource/MotionAlarm</wsnt:TopicExpression>"
+                ));
+                include_bytes!("../../fixtures/events/create-pull-point-subscription.xml")
+            } else if body.contains("<tev:PullMessages>") {
+                assert!(body.contains("<wsa:To>http://192.168.0.90/vapix/services</wsa:To>"));
+                assert!(body.contains(
+                    r#"<dom0:SubscriptionId xmlns:dom0="http://www.axis.com/2009/event">12</dom0:SubscriptionId>"#
+                ));
+                include_bytes!("../../fixtures/events/pull-messages.xml")
+            } else {
+                panic!("unexpected request {}", body);
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(
+                    http::header::CONTENT_TYPE,
+                    "application/soap+xml; charset=utf-8",
+                )
+                .body(vec![response.to_vec()])
+        });
+
+        let subscription = device
+            .events()
+            .subscribe(&[IO_PORT_TOPIC, MOTION_ALARM_TOPIC])
+            .await
+            .unwrap();
+        let notifications: Vec<EventNotification> = subscription
+            .into_stream()
+            .take(3)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        let topics: Vec<&str> = notifications.iter().map(|n| n.topic.as_str()).collect();
+        assert_eq!(
+            topics,
+            vec![IO_PORT_TOPIC, MOTION_ALARM_TOPIC, IO_PORT_TOPIC]
+        );
+    }
+
+    #[tokio::test]
+    async fn cancel() {
+        let unsubscribed = Arc::new(AtomicBool::new(false));
+        let device = crate::mock_client({
+            let unsubscribed = unsubscribed.clone();
+            move |req| {
+                let body = String::from_utf8(req.body().clone()).unwrap();
+                let response: &[u8] = if body.contains("<tev:CreatePullPointSubscription>") {
+                    include_bytes!("../../fixtures/events/create-pull-point-subscription.xml")
+                } else if body.contains("<tev:PullMessages>") {
+                    include_bytes!("../../fixtures/events/pull-messages.xml")
+                } else if body.contains("<wsnt:Unsubscribe/>") {
+                    unsubscribed.store(true, Ordering::SeqCst);
+                    b"<Envelope/>"
+                } else {
+                    panic!("unexpected request {}", body);
+                };
+                http::Response::builder()
+                    .status(http::StatusCode::OK)
+                    .header(
+                        http::header::CONTENT_TYPE,
+                        "application/soap+xml; charset=utf-8",
+                    )
+                    .body(vec![response.to_vec()])
+            }
+        });
+
+        let cancel = CancelHandle::new();
+        let mut stream = Box::pin(
+            device
+                .events()
+                .subscribe(&[IO_PORT_TOPIC])
+                .await
+                .unwrap()
+                .into_stream_until(cancel.clone()),
+        );
+        assert!(stream.next().await.unwrap().is_ok());
+        assert!(!unsubscribed.load(Ordering::SeqCst));
+
+        cancel.cancel();
+        assert!(stream.next().await.is_none());
+        assert!(unsubscribed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn unavailable() {
+        let device = crate::mock_client(|_| {
+            http::Response::builder()
+                .status(http::StatusCode::NOT_FOUND)
+                .body(vec![])
+        });
+        match device.events().declarations().await {
+            Err(Error::FeatureUnavailable) => {}
+            other => panic!("expected FeatureUnavailable, got {:?}", other),
+        }
+    }
+}
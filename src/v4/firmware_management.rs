@@ -0,0 +1,340 @@
+//! The [firmware management API](https://www.axis.com/vapix-library/subjects/t10175981/section/t10116183/display),
+//! which upgrades, commits, and rolls back the device's firmware.
+//!
+//! Devices keep the previous firmware after an upgrade. Depending on `UpgradeOptions`, the new
+//! firmware is committed automatically or must be committed with `commit()`, and an upgrade
+//! which is never committed is rolled back.
+
+use crate::v4::{JsonService, UnknownFields};
+use crate::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// The firmware management API.
+pub struct FirmwareManagement<'a, T: Transport>(JsonService<'a, T>);
+
+/// The state of the device's firmware.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirmwareStatus {
+    /// The version of the running firmware.
+    pub active_firmware_version: String,
+    /// The part number of the running firmware, if reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_firmware_part: Option<String>,
+    /// The version of the firmware which `rollback()` would restore, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inactive_firmware_version: Option<String>,
+    /// Has the running firmware been committed? Uncommitted firmware may be rolled back
+    /// automatically.
+    pub is_committed: bool,
+    /// When the firmware was last upgraded, if ever.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_upgrade_at: Option<DateTime<Utc>>,
+    /// Fields returned by the device which this crate does not model.
+    #[serde(flatten)]
+    pub extra: UnknownFields,
+}
+
+/// How to perform a firmware upgrade.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct UpgradeOptions {
+    /// When to commit the new firmware.
+    pub auto_commit: AutoCommit,
+    /// When to roll back the new firmware if it has not been committed.
+    pub auto_rollback: AutoRollback,
+    /// Which settings to reset to their factory defaults during the upgrade.
+    pub factory_default_mode: FactoryDefaultMode,
+}
+
+/// When to commit new firmware.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AutoCommit {
+    /// Use the device's configured behavior.
+    Default,
+    /// Never; call `commit()` once satisfied with the new firmware.
+    Never,
+    /// As soon as the device boots the new firmware.
+    Boot,
+    /// Once the device has finished starting up with the new firmware.
+    Started,
+}
+
+impl Default for AutoCommit {
+    fn default() -> Self {
+        AutoCommit::Default
+    }
+}
+
+/// When to roll back new firmware which has not been committed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AutoRollback {
+    /// Use the device's configured behavior.
+    Default,
+    /// Never.
+    Never,
+    /// After the given number of minutes.
+    AfterMinutes(u32),
+}
+
+impl Default for AutoRollback {
+    fn default() -> Self {
+        AutoRollback::Default
+    }
+}
+
+/// Which settings to reset to their factory defaults.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FactoryDefaultMode {
+    /// Keep all settings.
+    None,
+    /// Reset all settings except network settings, like the IP address.
+    Soft,
+    /// Reset all settings, including network settings.
+    Hard,
+}
+
+impl Default for FactoryDefaultMode {
+    fn default() -> Self {
+        FactoryDefaultMode::None
+    }
+}
+
+/// How to wait for the device to come back after a firmware upgrade.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct UpgradeWait {
+    /// How long to wait between checks of the device's status.
+    pub poll_interval: Duration,
+    /// How long to wait in total before giving up.
+    pub timeout: Duration,
+}
+
+impl Default for UpgradeWait {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(10),
+            timeout: Duration::from_secs(600),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct NoParams {}
+
+impl<'a, T: Transport> FirmwareManagement<'a, T> {
+    pub(crate) fn new(client: &'a Client<T>, api_version: String) -> Self {
+        Self(
+            JsonService::new(client, "/axis-cgi/firmwaremanagement.cgi", api_version)
+                .with_service_id("fwmgr"),
+        )
+    }
+
+    /// Retrieve the state of the device's firmware.
+    pub async fn status(&self) -> Result<FirmwareStatus> {
+        self.0.call_method_bare("status").await
+    }
+
+    /// Upload and install a firmware image, returning the version of the new firmware.
+    ///
+    /// The device restarts into the new firmware shortly after responding. Use
+    /// `wait_for_version()` to wait until it is running again.
+    ///
+    /// The image may be in memory, or streamed from disk with `RequestBody::from_file()`.
+    /// Dropping the future, for example through `CancelHandle::run()`, abandons the upload if the
+    /// image has not yet been sent. Once the device has received the whole image, it installs it
+    /// regardless.
+    pub async fn upgrade<B: Into<RequestBody>>(
+        &self,
+        firmware: B,
+        options: &UpgradeOptions,
+    ) -> Result<String> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Req {
+            auto_commit: &'static str,
+            auto_rollback: String,
+            factory_default_mode: FactoryDefaultMode,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Resp {
+            firmware_version: String,
+        }
+
+        let req = Req {
+            auto_commit: match options.auto_commit {
+                AutoCommit::Default => "default",
+                AutoCommit::Never => "never",
+                AutoCommit::Boot => "boot",
+                AutoCommit::Started => "started",
+            },
+            auto_rollback: match options.auto_rollback {
+                AutoRollback::Default => "default".to_string(),
+                AutoRollback::Never => "never".to_string(),
+                AutoRollback::AfterMinutes(minutes) => minutes.to_string(),
+            },
+            factory_default_mode: options.factory_default_mode,
+        };
+        let resp: Resp = self
+            .0
+            .call_method_with_file("upgrade", req, firmware.into())
+            .await?;
+        Ok(resp.firmware_version)
+    }
+
+    /// Commit the running firmware, so that it is not rolled back.
+    pub async fn commit(&self) -> Result<()> {
+        self.0.call_method_without_data("commit", NoParams {}).await
+    }
+
+    /// Restore the previous firmware. The device restarts shortly after responding.
+    pub async fn rollback(&self) -> Result<()> {
+        self.0
+            .call_method_without_data("rollback", NoParams {})
+            .await
+    }
+
+    /// Reset the device's settings to their factory defaults. The device restarts shortly after
+    /// responding.
+    pub async fn factory_default(&self, mode: FactoryDefaultMode) -> Result<()> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Req {
+            factory_default_mode: FactoryDefaultMode,
+        }
+        self.0
+            .call_method_without_data(
+                "factoryDefault",
+                Req {
+                    factory_default_mode: mode,
+                },
+            )
+            .await
+    }
+
+    /// Wait for the device to run the firmware with the given version, as after `upgrade()` or
+    /// `rollback()`.
+    ///
+    /// Requests fail while the device restarts, so errors are ignored until `wait.timeout`
+    /// elapses, at which point the most recent error is returned. Dropping the future stops
+    /// waiting without affecting the device.
+    pub async fn wait_for_version(
+        &self,
+        version: &str,
+        wait: &UpgradeWait,
+    ) -> Result<FirmwareStatus> {
+        let started = Instant::now();
+        loop {
+            let result = self.status().await;
+            match &result {
+                Ok(status) if status.active_firmware_version == version => return result,
+                _ => {}
+            }
+
+            if started.elapsed() + wait.poll_interval > wait.timeout {
+                return match result {
+                    Ok(_) => Err(Error::Other(
+                        "device did not start the expected firmware version",
+                    )),
+                    Err(e) => Err(e),
+                };
+            }
+            futures_timer::Delay::new(wait.poll_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn upgrade() {
+        let statuses = Arc::new(AtomicUsize::new(0));
+        let client = crate::mock_client({
+            let statuses = statuses.clone();
+            move |req| {
+                // Streamed uploads can't be repeated, so the client authenticates beforehand
+                if req.uri().path() == "/axis-cgi/param.cgi" {
+                    return http::Response::builder()
+                        .status(http::StatusCode::OK)
+                        .header(http::header::CONTENT_TYPE, "text/plain")
+                        .body(vec![b"root.Brand.ProdNbr=P1448-LE\n".to_vec()]);
+                }
+
+                assert_eq!(req.uri().path(), "/axis-cgi/firmwaremanagement.cgi");
+                let content_type = req.headers()[http::header::CONTENT_TYPE].to_str().unwrap();
+                let body = if content_type.starts_with("multipart/form-data") {
+                    assert_eq!(
+                        req.headers()[http::header::CONTENT_LENGTH],
+                        req.body().len().to_string().as_str()
+                    );
+                    let body = String::from_utf8_lossy(req.body()).into_owned();
+                    assert!(body.contains(
+                        r#"{"apiVersion":"1.4","method":"upgrade","params":{"autoCommit":"never","autoRollback":"30","factoryDefaultMode":"none"}}"#
+                    ));
+                    assert!(body.contains("name=\"fileData\""));
+                    assert!(body.contains("\r\n\r\nFIRMWARE\r\n--fileboundary--"));
+                    r#"{"apiVersion":"1.4","data":{"firmwareVersion":"10.2.0"}}"#
+                } else {
+                    let body: serde_json::Value = serde_json::from_slice(req.body()).unwrap();
+                    match body["method"].as_str() {
+                        // The device is restarting at first
+                        Some("status") if statuses.fetch_add(1, Ordering::SeqCst) == 0 => {
+                            return http::Response::builder()
+                                .status(http::StatusCode::SERVICE_UNAVAILABLE)
+                                .body(vec![]);
+                        }
+                        Some("status") => {
+                            r#"{"apiVersion":"1.4","data":{"activeFirmwareVersion":"10.2.0","inactiveFirmwareVersion":"9.80.1","isCommitted":false,"lastUpgradeAt":"2021-02-03T04:05:06Z","activeFirmwarePart":"1"}}"#
+                        }
+                        Some("commit") => r#"{"apiVersion":"1.4"}"#,
+                        other => panic!("unexpected method {:?}", other),
+                    }
+                };
+                http::Response::builder()
+                    .status(http::StatusCode::OK)
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(vec![body.as_bytes().to_vec()])
+            }
+        })
+        .with_retry_policy(crate::RetryPolicy::never());
+        let firmware = FirmwareManagement::new(&client, "1.4".into());
+
+        let version = firmware
+            .upgrade(
+                RequestBody::from_stream(
+                    futures::stream::iter(vec![Ok(b"FIRM".to_vec()), Ok(b"WARE".to_vec())]),
+                    Some(8),
+                ),
+                &UpgradeOptions {
+                    auto_commit: AutoCommit::Never,
+                    auto_rollback: AutoRollback::AfterMinutes(30),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(version, "10.2.0");
+
+        let status = firmware
+            .wait_for_version(
+                &version,
+                &UpgradeWait {
+                    poll_interval: Duration::from_millis(1),
+                    timeout: Duration::from_secs(5),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(statuses.load(Ordering::SeqCst), 2);
+        assert_eq!(status.inactive_firmware_version.as_deref(), Some("9.80.1"));
+        assert!(!status.is_committed);
+
+        firmware.commit().await.unwrap();
+    }
+}
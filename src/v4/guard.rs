@@ -0,0 +1,314 @@
+//! The configuration API shared by AXIS Fence Guard, AXIS Loitering Guard, and AXIS Motion Guard.
+//!
+//! Each of these ACAPs exposes a JSON API at `/local/{application}/control.cgi`, which returns
+//! HTTP 404 if the application is not installed. Devices which report an installed application
+//! through `apidiscovery.cgi` provide it in `Services` with the API version they support; otherwise
+//! `Client::guard()` accesses it at an API version chosen by the caller.
+
+use crate::v4::{JsonService, UnknownFields};
+use crate::*;
+use serde::{Deserialize, Serialize};
+
+/// A guard application.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum GuardKind {
+    /// AXIS Fence Guard, which triggers when objects cross virtual lines.
+    FenceGuard,
+    /// AXIS Loitering Guard, which triggers when objects remain in an area for too long.
+    LoiteringGuard,
+    /// AXIS Motion Guard, which triggers when objects move within an area.
+    MotionGuard,
+}
+
+impl GuardKind {
+    /// The application's identifier in API discovery.
+    pub(crate) fn api_id(&self) -> &'static str {
+        match self {
+            GuardKind::FenceGuard => "fenceguard",
+            GuardKind::LoiteringGuard => "loiteringguard",
+            GuardKind::MotionGuard => "motionguard",
+        }
+    }
+
+    fn path(&self) -> &'static str {
+        match self {
+            GuardKind::FenceGuard => "/local/fenceguard/control.cgi",
+            GuardKind::LoiteringGuard => "/local/loiteringguard/control.cgi",
+            GuardKind::MotionGuard => "/local/motionguard/control.cgi",
+        }
+    }
+}
+
+/// A guard application's configuration API.
+pub struct Guard<'a, T: Transport>(JsonService<'a, T>);
+
+impl<'a, T: Transport> Guard<'a, T> {
+    pub(crate) fn new(client: &'a Client<T>, kind: GuardKind, api_version: String) -> Self {
+        Self(JsonService::new(client, kind.path(), api_version).with_service_id(kind.api_id()))
+    }
+
+    /// Retrieve the application's configuration.
+    pub async fn configuration(&self) -> Result<GuardConfiguration> {
+        self.0
+            .call_method_bare("getConfiguration")
+            .await
+            .map_404_to_feature_unavailable()
+    }
+
+    /// Replace the application's configuration.
+    pub async fn set_configuration(&self, configuration: &GuardConfiguration) -> Result<()> {
+        self.0
+            .call_method_without_data("setConfiguration", configuration)
+            .await
+            .map_404_to_feature_unavailable()
+    }
+}
+
+/// A guard application's configuration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GuardConfiguration {
+    /// The video channels available for analysis.
+    #[serde(default)]
+    pub cameras: Vec<GuardCamera>,
+    /// The detection profiles.
+    #[serde(default)]
+    pub profiles: Vec<GuardProfile>,
+    /// The version of the configuration, incremented by the device on each change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub configuration_status: Option<u32>,
+    /// Fields returned by the device which this crate does not model. These are sent back
+    /// unchanged by `Guard::set_configuration()`.
+    #[serde(flatten)]
+    pub extra: UnknownFields,
+}
+
+/// A video channel available for analysis.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GuardCamera {
+    /// The channel identifier, as referenced by `GuardProfile::camera`.
+    pub id: Channel,
+    /// Is analysis active on this channel?
+    #[serde(default)]
+    pub active: bool,
+    /// Fields returned by the device which this crate does not model. These are sent back
+    /// unchanged by `Guard::set_configuration()`.
+    #[serde(flatten)]
+    pub extra: UnknownFields,
+}
+
+/// A detection profile, combining a set of triggers with a set of filters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GuardProfile {
+    /// The display name of the profile.
+    pub name: String,
+    /// The profile's unique identifier.
+    pub uid: u32,
+    /// The channel to analyze, referencing `GuardCamera::id`.
+    pub camera: Channel,
+    /// Conditions which must be met for an alarm to be raised.
+    #[serde(default)]
+    pub triggers: Vec<GuardTrigger>,
+    /// Filters which suppress detections, used to tune sensitivity.
+    #[serde(default)]
+    pub filters: Vec<GuardFilter>,
+    /// Fields returned by the device which this crate does not model. These are sent back
+    /// unchanged by `Guard::set_configuration()`.
+    #[serde(flatten)]
+    pub extra: UnknownFields,
+}
+
+/// A trigger condition.
+///
+/// Coordinates are normalized such that `[-1.0, -1.0]` is the bottom left of the image and
+/// `[1.0, 1.0]` is the top right.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GuardTrigger {
+    /// The type of trigger.
+    #[serde(rename = "type")]
+    pub trigger_type: GuardTriggerType,
+    /// The points describing the line or the area.
+    pub data: Vec<[f64; 2]>,
+    /// For lines, the direction of crossing which raises an alarm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alarm_direction: Option<AlarmDirection>,
+    /// For areas, the number of seconds an object must remain before raising an alarm.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<u32>,
+    /// Fields returned by the device which this crate does not model. These are sent back
+    /// unchanged by `Guard::set_configuration()`.
+    #[serde(flatten)]
+    pub extra: UnknownFields,
+}
+
+/// The type of a trigger condition.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum GuardTriggerType {
+    /// A virtual fence, i.e. a line which objects cross.
+    Fence,
+    /// An area within which objects are detected.
+    IncludeArea,
+}
+
+/// The direction in which an object crosses a fence.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AlarmDirection {
+    /// From the left side of the line to the right side, as seen from the line's first point.
+    LeftToRight,
+    /// From the right side of the line to the left side, as seen from the line's first point.
+    RightToLeft,
+}
+
+/// A filter which suppresses detections.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum GuardFilter {
+    /// Ignore objects smaller than the given percentages of the image.
+    SizePercentage {
+        /// The minimum width, in percent of the image width.
+        width: u32,
+        /// The minimum height, in percent of the image height.
+        height: u32,
+    },
+    /// Ignore objects which are visible for less than the given number of seconds.
+    TimeShortLivedLimit {
+        /// The minimum time, in seconds.
+        time: u32,
+    },
+    /// Ignore objects which move less than the given distance, like swaying vegetation.
+    DistanceSwayingObject {
+        /// The minimum distance, in percent of the image size.
+        distance: u32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn configuration() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(req.method(), http::Method::POST);
+            assert_eq!(req.uri().path(), "/local/fenceguard/control.cgi");
+            assert_eq!(
+                req.body().as_slice(),
+                &br#"{"apiVersion":"1.3","method":"getConfiguration"}"#[..]
+            );
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(vec![br#"{"apiVersion":"1.3","method":"getConfiguration","data":{
+                    "cameras":[{"id":1,"active":true}],
+                    "profiles":[{"name":"Profile 1","uid":1,"camera":1,"perspectiveOn":false,
+                        "triggers":[{"type":"fence","data":[[0.0,-0.7],[0.0,0.7]],"alarmDirection":"leftToRight"}],
+                        "filters":[{"type":"timeShortLivedLimit","time":1},{"type":"sizePercentage","width":5,"height":10}]}],
+                    "configurationStatus":3}}"#
+                    .to_vec()])
+        });
+
+        let configuration = client
+            .guard(GuardKind::FenceGuard, "1.3")
+            .configuration()
+            .await
+            .unwrap();
+        assert_eq!(configuration.configuration_status, Some(3));
+        assert_eq!(configuration.profiles.len(), 1);
+
+        let profile = &configuration.profiles[0];
+        assert_eq!(
+            profile.triggers,
+            vec![GuardTrigger {
+                trigger_type: GuardTriggerType::Fence,
+                data: vec![[0.0, -0.7], [0.0, 0.7]],
+                alarm_direction: Some(AlarmDirection::LeftToRight),
+                time: None,
+                extra: Default::default(),
+            }]
+        );
+
+        // Fields this crate doesn't model are kept, and written back with the configuration
+        assert_eq!(
+            profile.extra.get("perspectiveOn"),
+            Some(&serde_json::Value::Bool(false))
+        );
+        assert_eq!(
+            serde_json::to_value(profile).unwrap()["perspectiveOn"],
+            serde_json::Value::Bool(false)
+        );
+        assert_eq!(
+            profile.filters,
+            vec![
+                GuardFilter::TimeShortLivedLimit { time: 1 },
+                GuardFilter::SizePercentage {
+                    width: 5,
+                    height: 10
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn not_installed() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(req.uri().path(), "/local/loiteringguard/control.cgi");
+            http::Response::builder()
+                .status(http::StatusCode::NOT_FOUND)
+                .body(vec![])
+        });
+
+        match client
+            .guard(GuardKind::LoiteringGuard, "1.3")
+            .configuration()
+            .await
+        {
+            Err(Error::FeatureUnavailable) => {}
+            other => panic!("expected FeatureUnavailable, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn set_configuration() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(req.method(), http::Method::POST);
+            assert_eq!(req.uri().path(), "/local/loiteringguard/control.cgi");
+            let body: serde_json::Value = serde_json::from_slice(req.body()).unwrap();
+            assert_eq!(body["apiVersion"], "1.1");
+            assert_eq!(body["method"], "setConfiguration");
+            assert_eq!(body["params"]["profiles"][0]["camera"], 1);
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(vec![
+                    br#"{"apiVersion":"1.1","method":"setConfiguration","data":{}}"#.to_vec(),
+                ])
+        });
+
+        // the API version comes from API discovery
+        let cache = crate::v4::ServiceCache {
+            firmware_version: None,
+            apis: vec![("loiteringguard".to_string(), "1.1".to_string())]
+                .into_iter()
+                .collect(),
+        };
+        let services = crate::v4::Services::from_cache(&client, &cache);
+        let guard = services.loitering_guard.as_ref().unwrap();
+        assert!(services.fence_guard.is_none());
+
+        let configuration: GuardConfiguration = serde_json::from_value(serde_json::json!({
+            "cameras": [{"id": 1, "active": true}],
+            "profiles": [{"name": "Profile 1", "uid": 1, "camera": 1, "triggers": [], "filters": []}],
+        }))
+        .unwrap();
+        guard.set_configuration(&configuration).await.unwrap();
+    }
+}
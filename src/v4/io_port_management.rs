@@ -0,0 +1,167 @@
+//! The I/O port management API at `/axis-cgi/io/portmanagement.cgi`.
+
+use crate::v3::io_ports::{IoPort, IoPortDirection};
+use crate::v4::JsonService;
+use crate::*;
+use serde::{Deserialize, Serialize};
+
+/// The I/O port management API.
+pub struct IoPortManagement<'a, T: Transport>(JsonService<'a, T>);
+
+impl<'a, T: Transport> IoPortManagement<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>, api_version: String) -> Self {
+        Self(
+            JsonService::new(device, "/axis-cgi/io/portmanagement.cgi", api_version)
+                .with_service_id("io-port-management"),
+        )
+    }
+
+    /// List the device's I/O ports and their current states.
+    pub async fn list(&self) -> Result<Vec<IoPort>> {
+        Ok(self
+            .raw_ports()
+            .await?
+            .into_iter()
+            .filter_map(RawPort::into_io_port)
+            .collect())
+    }
+
+    /// Activate or deactivate an output port.
+    pub async fn set_active(&self, index: u32, active: bool) -> Result<()> {
+        #[derive(Serialize)]
+        struct Req {
+            ports: Vec<SetPort>,
+        }
+        #[derive(Serialize)]
+        struct SetPort {
+            port: String,
+            state: &'static str,
+        }
+
+        let port = index.to_string();
+        let normal_state = self
+            .raw_ports()
+            .await?
+            .into_iter()
+            .find(|raw| raw.port == port)
+            .ok_or(Error::Other("no such I/O port"))?
+            .normal_state;
+        let state = match (normal_state.as_str(), active) {
+            ("open", true) | ("closed", false) => "closed",
+            ("closed", true) | ("open", false) => "open",
+            _ => return Err(Error::Other("I/O port has an unknown normal state")),
+        };
+
+        self.0
+            .call_method_without_data(
+                "setPorts",
+                Req {
+                    ports: vec![SetPort { port, state }],
+                },
+            )
+            .await
+    }
+
+    async fn raw_ports(&self) -> Result<Vec<RawPort>> {
+        #[derive(Deserialize)]
+        struct Resp {
+            items: Vec<RawPort>,
+        }
+
+        let resp: Resp = self.0.call_method_bare("getPorts").await?;
+        Ok(resp.items)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPort {
+    port: String,
+    #[serde(default)]
+    configurable: bool,
+    #[serde(default)]
+    name: String,
+    direction: String,
+    state: String,
+    normal_state: String,
+}
+
+impl RawPort {
+    fn into_io_port(self) -> Option<IoPort> {
+        Some(IoPort {
+            index: self.port.parse().ok()?,
+            name: self.name,
+            direction: match self.direction.as_str() {
+                "input" => IoPortDirection::Input,
+                "output" => IoPortDirection::Output,
+                _ => return None,
+            },
+            configurable: self.configurable,
+            active: self.state != self.normal_state,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn list_and_set() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(req.uri().path(), "/axis-cgi/io/portmanagement.cgi");
+            let body: serde_json::Value = serde_json::from_slice(req.body()).unwrap();
+            let response = match body["method"].as_str().unwrap() {
+                "getPorts" => serde_json::json!({
+                    "apiVersion": "1.0",
+                    "method": "getPorts",
+                    "data": {
+                        "numberOfPorts": 2,
+                        "items": [
+                            {
+                                "port": "0",
+                                "configurable": false,
+                                "usage": "Door",
+                                "name": "Door",
+                                "direction": "input",
+                                "state": "closed",
+                                "normalState": "open"
+                            },
+                            {
+                                "port": "1",
+                                "configurable": true,
+                                "usage": "",
+                                "name": "Lock",
+                                "direction": "output",
+                                "state": "open",
+                                "normalState": "open"
+                            }
+                        ]
+                    }
+                }),
+                "setPorts" => {
+                    assert_eq!(
+                        body["params"],
+                        serde_json::json!({"ports": [{"port": "1", "state": "closed"}]})
+                    );
+                    serde_json::json!({"apiVersion": "1.0", "method": "setPorts", "data": {}})
+                }
+                other => panic!("unexpected method {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(vec![serde_json::to_vec(&response).unwrap()])
+        });
+        let io_ports = IoPortManagement::new(&client, "1.0".to_string());
+
+        let ports = io_ports.list().await.unwrap();
+        assert_eq!(ports.len(), 2);
+        assert!(ports[0].active);
+        assert_eq!(ports[0].direction, IoPortDirection::Input);
+        assert!(!ports[1].active);
+        assert_eq!(ports[1].name, "Lock");
+
+        io_ports.set_active(1, true).await.unwrap();
+    }
+}
@@ -3,20 +3,51 @@ use serde::{Deserialize, Serialize};
 
 pub(crate) struct JsonService<'a, T: Transport> {
     device: &'a Client<T>,
-    uri: http::Uri,
+    path_and_query: String,
     api_version: String,
+    service_id: Option<&'static str>,
 }
 
 impl<'a, T: Transport> JsonService<'a, T> {
     pub fn new(device: &'a Client<T>, path_and_query: &str, api_version: String) -> Self {
         Self {
             device,
-            uri: device.uri_for(path_and_query).unwrap(),
+            path_and_query: path_and_query.to_string(),
             api_version,
+            service_id: None,
         }
     }
 
-    async fn inner<RQ, RS>(&self, method: &str, request: Option<RQ>) -> Result<RS>
+    fn path(&self) -> &str {
+        self.path_and_query.splitn(2, '?').next().unwrap_or("")
+    }
+
+    /// Identify this service in error messages by its API discovery ID.
+    pub fn with_service_id(mut self, service_id: &'static str) -> Self {
+        self.service_id = Some(service_id);
+        self
+    }
+
+    async fn inner<RQ, RS>(&self, method: &str, request: Option<RQ>) -> Result<Option<RS>>
+    where
+        RQ: serde::Serialize,
+        RS: serde::de::DeserializeOwned,
+    {
+        self.inner_without_context(method, request, None)
+            .await
+            .map_err(|e| {
+                e.with_context(|| {
+                    crate::error::ErrorContext::new(self.service_id, Some(method), self.path())
+                })
+            })
+    }
+
+    async fn inner_without_context<RQ, RS>(
+        &self,
+        method: &str,
+        request: Option<RQ>,
+        file: Option<RequestBody>,
+    ) -> Result<Option<RS>>
     where
         RQ: serde::Serialize,
         RS: serde::de::DeserializeOwned,
@@ -36,34 +67,65 @@ impl<'a, T: Transport> JsonService<'a, T> {
             data: Option<RS>,
         }
 
-        let req = {
-            let json_request = Req {
-                api_version: &self.api_version,
-                method,
-                params: request.as_ref(),
-            };
-            let json_request = serde_json::to_vec(&json_request).unwrap();
+        let json_request = Req {
+            api_version: &self.api_version,
+            method,
+            params: request.as_ref(),
+        };
+        let json_request = serde_json::to_vec(&json_request)?;
 
-            http::Request::builder()
-                .method(http::method::Method::POST)
-                .uri(&self.uri)
-                .header(http::header::CONTENT_TYPE, "application/json")
-                .body(json_request)
-                .unwrap()
+        let builder = http::Request::builder()
+            .method(http::method::Method::POST)
+            .uri(self.device.uri_for(&self.path_and_query)?);
+        let (_resp, resp_body) = match file {
+            None => {
+                let req = builder
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .body(json_request)?;
+                self.device.roundtrip(req, "application/json").await?
+            }
+            Some(file) => {
+                let mut prefix = b"--fileboundary\r\n\
+                Content-Disposition: form-data; name=\"data\"\r\n\
+                Content-Type: application/json\r\n\
+                \r\n"
+                    .to_vec();
+                prefix.extend_from_slice(&json_request);
+                prefix.extend_from_slice(
+                    b"\r\n--fileboundary\r\n\
+                    Content-Disposition: form-data; name=\"fileData\"; filename=\"file\"\r\n\
+                    Content-Type: application/octet-stream\r\n\
+                    \r\n",
+                );
+                let body = file.framed(prefix, b"\r\n--fileboundary--\r\n\r\n".to_vec());
+                let req = builder
+                    .header(
+                        http::header::CONTENT_TYPE,
+                        "multipart/form-data; boundary=fileboundary",
+                    )
+                    .body(body)?;
+                self.device
+                    .roundtrip_upload(req, "application/json")
+                    .await?
+            }
         };
 
-        let (_resp, resp_body) = self.device.roundtrip(req, "application/json").await?;
+        #[cfg(feature = "schema-validation")]
+        {
+            if let Some(service_id) = self.service_id {
+                self.device
+                    .check_schema(service_id, method, resp_body.as_slice());
+            }
+        }
 
-        let resp_body: Resp<RS> = serde_json::from_slice(resp_body.as_slice())?;
+        let resp_body: Resp<RS> = serde_json::from_slice(resp_body.as_slice()).map_err(|e| {
+            Error::UnparseableResponseError(UnparseableResponseError::json(e, resp_body.as_slice()))
+        })?;
 
         if let Some(e) = resp_body.error {
             return Err(e.into());
         }
-        if let Some(d) = resp_body.data {
-            Ok(d)
-        } else {
-            Err(Error::Other("response included neither `data` nor `error`"))
-        }
+        Ok(resp_body.data)
     }
 
     pub async fn call_method<RQ, RS>(&self, method: &str, params: RQ) -> Result<RS>
@@ -71,7 +133,56 @@ impl<'a, T: Transport> JsonService<'a, T> {
         RQ: serde::Serialize,
         RS: serde::de::DeserializeOwned,
     {
-        self.inner(method, Some(params)).await
+        self.inner(method, Some(params)).await?.ok_or_else(|| {
+            Error::Other("response included neither `data` nor `error`").with_context(|| {
+                crate::error::ErrorContext::new(self.service_id, Some(method), self.path())
+            })
+        })
+    }
+
+    /// Call a method which may or may not return `data`, discarding any `data` returned.
+    pub async fn call_method_without_data<RQ>(&self, method: &str, params: RQ) -> Result<()>
+    where
+        RQ: serde::Serialize,
+    {
+        self.device.check_writable()?;
+        let _: Option<serde::de::IgnoredAny> = self.inner(method, Some(params)).await?;
+        self.device
+            .audit(|| format!("called {} on {}", method, self.path()))
+            .await;
+        Ok(())
+    }
+
+    /// Call a method which uploads a file, sending the request as `multipart/form-data` with the
+    /// JSON request in a `data` part and the file in a `fileData` part.
+    pub async fn call_method_with_file<RQ, RS>(
+        &self,
+        method: &str,
+        params: RQ,
+        file: RequestBody,
+    ) -> Result<RS>
+    where
+        RQ: serde::Serialize,
+        RS: serde::de::DeserializeOwned,
+    {
+        self.device.check_writable()?;
+        let context =
+            || crate::error::ErrorContext::new(self.service_id, Some(method), self.path());
+        let file_len = file.content_length();
+        let data = self
+            .inner_without_context(method, Some(params), Some(file))
+            .await
+            .map_err(|e| e.with_context(context))?
+            .ok_or_else(|| {
+                Error::Other("response included neither `data` nor `error`").with_context(context)
+            })?;
+        self.device
+            .audit(|| match file_len {
+                Some(len) => format!("uploaded {} bytes with {} on {}", len, method, self.path()),
+                None => format!("uploaded a file with {} on {}", method, self.path()),
+            })
+            .await;
+        Ok(data)
     }
 
     pub async fn call_method_bare<RS>(&self, method: &str) -> Result<RS>
@@ -79,6 +190,169 @@ impl<'a, T: Transport> JsonService<'a, T> {
         RS: serde::de::DeserializeOwned,
     {
         let params: Option<()> = None;
-        self.inner(method, params).await
+        self.inner(method, params).await?.ok_or_else(|| {
+            Error::Other("response included neither `data` nor `error`").with_context(|| {
+                crate::error::ErrorContext::new(self.service_id, Some(method), self.path())
+            })
+        })
+    }
+
+    /// Call several methods in sequence, returning each call's `data` or error.
+    ///
+    /// Calls are made one after another on the same `Client`, so the transport can reuse its
+    /// connection and digest authentication is negotiated at most once. A failed call does not
+    /// prevent subsequent calls from being made.
+    ///
+    /// Methods named `get…` or `list…` are taken to be reads. Any other method may change the
+    /// device, so it fails with `Error::ReadOnlyMode` in read-only mode and is audited once it
+    /// succeeds.
+    pub async fn call_sequence<'m, I>(&self, calls: I) -> Vec<Result<serde_json::Value>>
+    where
+        I: IntoIterator<Item = (&'m str, Option<serde_json::Value>)>,
+    {
+        let mut results = Vec::new();
+        for (method, params) in calls {
+            let mutating = !is_read_method(method);
+            if mutating {
+                if let Err(e) = self.device.check_writable() {
+                    results.push(Err(e));
+                    continue;
+                }
+            }
+
+            let result = self
+                .inner(method, params)
+                .await
+                .map(|data| data.unwrap_or(serde_json::Value::Null));
+            if mutating && result.is_ok() {
+                self.device
+                    .audit(|| format!("called {} on {}", method, self.path()))
+                    .await;
+            }
+            results.push(result);
+        }
+        results
+    }
+}
+
+/// Is `method` a read, like `getAreaStatus` or `listUsers`, by the naming convention of VAPIX JSON
+/// services?
+fn is_read_method(method: &str) -> bool {
+    ["get", "list"].iter().any(|prefix| {
+        method.strip_prefix(prefix).map_or(false, |rest| {
+            rest.chars().next().map_or(true, char::is_uppercase)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn call_sequence() {
+        let client = crate::mock_client(|req| {
+            let body: serde_json::Value = serde_json::from_slice(req.body()).unwrap();
+            let body = match body["method"].as_str() {
+                Some("first") => {
+                    assert_eq!(body["params"]["n"], 1);
+                    r#"{"apiVersion":"1.0","data":{"n":1}}"#
+                }
+                Some("second") => {
+                    assert!(body.get("params").is_none());
+                    r#"{"apiVersion":"1.0","error":{"code":2001,"message":"nope"}}"#
+                }
+                Some("third") => r#"{"apiVersion":"1.0"}"#,
+                other => panic!("unexpected method {:?}", other),
+            };
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(vec![body.as_bytes().to_vec()])
+        });
+
+        let results = client
+            .call_sequence(
+                "/axis-cgi/example.cgi",
+                "1.0",
+                vec![
+                    ("first", Some(serde_json::json!({ "n": 1 }))),
+                    ("second", None),
+                    ("third", None),
+                ],
+            )
+            .await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &serde_json::json!({ "n": 1 }));
+        match &results[1] {
+            Err(Error::InsufficientPrivileges { required: None }) => {}
+            other => panic!("expected InsufficientPrivileges, got {:?}", other),
+        }
+        assert_eq!(results[2].as_ref().unwrap(), &serde_json::Value::Null);
+
+        // none of these methods are reads, so a read-only client sends nothing
+        let results = client
+            .with_read_only(true)
+            .call_sequence("/axis-cgi/example.cgi", "1.0", vec![("first", None)])
+            .await;
+        assert!(matches!(results.as_slice(), [Err(Error::ReadOnlyMode)]));
+    }
+
+    #[test]
+    fn read_methods() {
+        assert!(is_read_method("getAreaStatus"));
+        assert!(is_read_method("listUsers"));
+        assert!(is_read_method("get"));
+        assert!(!is_read_method("setAreaStatus"));
+        assert!(!is_read_method("getaway"));
+        assert!(!is_read_method("removeUser"));
+    }
+
+    #[tokio::test]
+    async fn call_sequence_guards_writes() {
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests_ = requests.clone();
+        let client = crate::mock_client(move |req| {
+            let request = match req.uri().path() {
+                "/axis-cgi/example.cgi" => {
+                    let body: serde_json::Value = serde_json::from_slice(req.body()).unwrap();
+                    body["method"].as_str().unwrap().to_string()
+                }
+                _ => req.uri().path_and_query().unwrap().to_string(),
+            };
+            requests_.lock().unwrap().push(request);
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(vec![br#"{"apiVersion":"1.0","data":{}}"#.to_vec()])
+        })
+        .with_audit_log("/local/auditlog/log.cgi");
+        let calls = || vec![("getFoo", None), ("setFoo", None), ("listFoos", None)];
+
+        let results = JsonService::new(&client, "/axis-cgi/example.cgi", "1.0".into())
+            .call_sequence(calls())
+            .await;
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(
+            std::mem::take(&mut *requests.lock().unwrap()),
+            vec![
+                "getFoo",
+                "setFoo",
+                "/local/auditlog/log.cgi?message=vapix%3A+called+setFoo+on+%2Faxis-cgi%2Fexample.cgi",
+                "listFoos",
+            ]
+        );
+
+        let client = client.with_read_only(true);
+        let results = JsonService::new(&client, "/axis-cgi/example.cgi", "1.0".into())
+            .call_sequence(calls())
+            .await;
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(Error::ReadOnlyMode)));
+        assert!(results[2].is_ok());
+        assert_eq!(*requests.lock().unwrap(), vec!["getFoo", "listFoos"]);
     }
 }
@@ -0,0 +1,280 @@
+//! Validation of JSON service responses against bundled schemas, for detecting firmware response
+//! drift during development.
+//!
+//! With the `schema-validation` feature enabled, the `data` of each JSON service response for
+//! which this crate bundles a schema is checked before it is deserialized. Divergences are passed
+//! to the observer set with `Client::with_schema_observer()`; without one, nothing is checked.
+//! Divergences never cause the call to fail.
+//!
+//! Schemas are written in a small subset of [JSON Schema](https://json-schema.org/): `type`,
+//! `properties`, `required`, `additionalProperties`, and `items`. They describe what this crate
+//! expects, so `additionalProperties: false` marks objects where new fields deserve a look.
+
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+
+/// A difference between a response and the bundled schema for it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SchemaDivergence {
+    /// The API discovery ID of the service which responded, e.g. `"basic-device-info"`.
+    pub service: &'static str,
+    /// The method which was called.
+    pub method: String,
+    /// The location of the divergence within the response's `data`, as a JSON pointer like
+    /// `/apiList/3/status`.
+    pub pointer: String,
+    /// How the response diverged.
+    pub kind: DivergenceKind,
+}
+
+/// How a response diverged from its schema.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum DivergenceKind {
+    /// A field the schema requires was absent.
+    Missing,
+    /// A field the schema does not describe was present.
+    Unexpected,
+    /// A value had a different type than the schema expects.
+    WrongType {
+        /// The type or types the schema allows, e.g. `"string"`.
+        expected: String,
+        /// The type found, e.g. `"null"`.
+        actual: &'static str,
+    },
+}
+
+impl fmt::Display for SchemaDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}: ", self.service, self.method)?;
+        let pointer = if self.pointer.is_empty() {
+            "data"
+        } else {
+            &self.pointer
+        };
+        match &self.kind {
+            DivergenceKind::Missing => write!(f, "{} is missing", pointer),
+            DivergenceKind::Unexpected => write!(f, "{} is not in the schema", pointer),
+            DivergenceKind::WrongType { expected, actual } => {
+                write!(f, "{} is {}, expected {}", pointer, actual, expected)
+            }
+        }
+    }
+}
+
+type ObserverFn = Arc<dyn Fn(&SchemaDivergence) + Send + Sync>;
+
+/// The destination for divergences, stored on each `Client`.
+#[derive(Clone, Default)]
+pub(crate) struct Observer(Option<ObserverFn>);
+
+impl Observer {
+    pub(crate) fn new<F: Fn(&SchemaDivergence) + Send + Sync + 'static>(f: F) -> Self {
+        Self(Some(Arc::new(f)))
+    }
+
+    fn observe(&self, divergence: &SchemaDivergence) {
+        if let Some(f) = &self.0 {
+            f(divergence);
+        }
+    }
+}
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Observer").field(&self.0.is_some()).finish()
+    }
+}
+
+/// The bundled schema for the `data` of a method's response, if any.
+///
+/// The bundled schemas are checked by the `bundled_schemas_parse` test, so a schema which fails to
+/// parse is treated as absent rather than panicking.
+fn schema_for(service: &str, method: &str) -> Option<Value> {
+    let schema = match (service, method) {
+        ("api-discovery", "getApiList") => include_str!("schemas/api-discovery.getApiList.json"),
+        ("basic-device-info", "getProperties") => {
+            include_str!("schemas/basic-device-info.getProperties.json")
+        }
+        _ => return None,
+    };
+    serde_json::from_str(schema).ok()
+}
+
+/// Check a JSON service response body against the bundled schema for its method, reporting each
+/// divergence to `observer`.
+///
+/// Bodies which aren't JSON, or which lack `data`, are left for the caller to report.
+pub(crate) fn check(observer: &Observer, service: &'static str, method: &str, body: &[u8]) {
+    if observer.0.is_none() {
+        return;
+    }
+    let schema = match schema_for(service, method) {
+        Some(schema) => schema,
+        None => return,
+    };
+    let data = match serde_json::from_slice::<Value>(body) {
+        Ok(Value::Object(mut body)) => match body.remove("data") {
+            Some(data) => data,
+            None => return,
+        },
+        _ => return,
+    };
+
+    let mut divergences = Vec::new();
+    validate(&schema, &data, "", &mut divergences);
+    for (pointer, kind) in divergences {
+        observer.observe(&SchemaDivergence {
+            service,
+            method: method.to_string(),
+            pointer,
+            kind,
+        });
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    match (expected, type_name(value)) {
+        ("number", "integer") => true,
+        (expected, actual) => expected == actual,
+    }
+}
+
+/// Append `key` to a JSON pointer, escaping it per RFC 6901.
+fn push_pointer(pointer: &str, key: &str) -> String {
+    format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"))
+}
+
+fn validate(schema: &Value, value: &Value, pointer: &str, out: &mut Vec<(String, DivergenceKind)>) {
+    let expected: Vec<&str> = match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(ts)) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    };
+    if !expected.is_empty() && !expected.iter().any(|t| type_matches(t, value)) {
+        out.push((
+            pointer.to_string(),
+            DivergenceKind::WrongType {
+                expected: expected.join(" or "),
+                actual: type_name(value),
+            },
+        ));
+        return;
+    }
+
+    match value {
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(key) {
+                        out.push((push_pointer(pointer, key), DivergenceKind::Missing));
+                    }
+                }
+            }
+            for (key, value) in object {
+                let child = push_pointer(pointer, key);
+                match (
+                    properties.and_then(|p| p.get(key)),
+                    schema.get("additionalProperties"),
+                ) {
+                    (Some(schema), _) => validate(schema, value, &child, out),
+                    (None, Some(Value::Bool(false))) => {
+                        out.push((child, DivergenceKind::Unexpected))
+                    }
+                    (None, Some(schema @ Value::Object(_))) => validate(schema, value, &child, out),
+                    (None, _) => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate(schema, item, &push_pointer(pointer, &i.to_string()), out);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn bundled_schemas_parse() {
+        assert!(schema_for("api-discovery", "getApiList").is_some());
+        assert!(schema_for("basic-device-info", "getProperties").is_some());
+        assert!(schema_for("basic-device-info", "getAllProperties").is_none());
+    }
+
+    #[tokio::test]
+    async fn divergences_are_observed() {
+        let client = crate::mock_client(|_| {
+            let body = serde_json::json!({
+                "apiVersion": "1.0",
+                "data": {"apiList": [
+                    {"id": "basic-device-info", "version": "1.1", "name": "Basic Device Information"},
+                    {"id": "fwmgr", "version": 1, "status": "beta"},
+                    {"version": "1.0"}
+                ]}
+            });
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(vec![body.to_string().into_bytes()])
+        });
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let observed_ = observed.clone();
+        let client = client.with_schema_observer(move |d| {
+            observed_.lock().unwrap().push(d.clone());
+        });
+
+        // divergences are reported, but don't fail the call
+        let response: Result<serde_json::Value, _> =
+            crate::v4::JsonService::new(&client, "/axis-cgi/apidiscovery.cgi", "1.0".into())
+                .with_service_id("api-discovery")
+                .call_method_bare("getApiList")
+                .await;
+        assert!(response.is_ok());
+
+        let observed = observed.lock().unwrap();
+        let summary: Vec<(&str, &DivergenceKind)> = observed
+            .iter()
+            .map(|d| (d.pointer.as_str(), &d.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("/apiList/1/status", &DivergenceKind::Unexpected),
+                (
+                    "/apiList/1/version",
+                    &DivergenceKind::WrongType {
+                        expected: "string".into(),
+                        actual: "integer",
+                    }
+                ),
+                ("/apiList/2/id", &DivergenceKind::Missing),
+            ]
+        );
+        assert_eq!(
+            observed[0].to_string(),
+            "api-discovery getApiList: /apiList/1/status is not in the schema"
+        );
+    }
+}
@@ -0,0 +1,332 @@
+//! The thermometry API provided by AXIS thermal cameras.
+
+use crate::v4::{JsonService, UnknownFields};
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The thermometry API.
+pub struct Thermometry<'a, T: Transport>(JsonService<'a, T>);
+
+/// A unit of temperature.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureUnit {
+    /// Degrees Celsius.
+    Celsius,
+    /// Degrees Fahrenheit.
+    Fahrenheit,
+}
+
+/// A temperature, expressed in a particular unit.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Temperature {
+    /// The numeric value of the temperature.
+    pub value: f64,
+    /// The unit in which `value` is expressed.
+    pub unit: TemperatureUnit,
+}
+
+impl Temperature {
+    /// Construct a `Temperature` in degrees Celsius.
+    pub fn celsius(value: f64) -> Self {
+        Self {
+            value,
+            unit: TemperatureUnit::Celsius,
+        }
+    }
+
+    /// Construct a `Temperature` in degrees Fahrenheit.
+    pub fn fahrenheit(value: f64) -> Self {
+        Self {
+            value,
+            unit: TemperatureUnit::Fahrenheit,
+        }
+    }
+
+    /// Convert this temperature to the given unit.
+    pub fn to_unit(self, unit: TemperatureUnit) -> Self {
+        let value = match (self.unit, unit) {
+            (TemperatureUnit::Celsius, TemperatureUnit::Fahrenheit) => {
+                self.value * 9.0 / 5.0 + 32.0
+            }
+            (TemperatureUnit::Fahrenheit, TemperatureUnit::Celsius) => {
+                (self.value - 32.0) * 5.0 / 9.0
+            }
+            _ => self.value,
+        };
+        Self { value, unit }
+    }
+
+    /// The temperature in degrees Celsius.
+    pub fn as_celsius(self) -> f64 {
+        self.to_unit(TemperatureUnit::Celsius).value
+    }
+
+    /// The temperature in degrees Fahrenheit.
+    pub fn as_fahrenheit(self) -> f64 {
+        self.to_unit(TemperatureUnit::Fahrenheit).value
+    }
+}
+
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.unit {
+            TemperatureUnit::Celsius => write!(f, "{} °C", self.value),
+            TemperatureUnit::Fahrenheit => write!(f, "{} °F", self.value),
+        }
+    }
+}
+
+/// A temperature measurement area.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThermometryArea {
+    /// The area's identifier.
+    pub id: u32,
+    /// The display name of the area.
+    #[serde(default)]
+    pub name: String,
+    /// Is this area enabled?
+    #[serde(default)]
+    pub enabled: bool,
+    /// The kind of temperature change which raises an alarm.
+    pub detection_type: DetectionType,
+    /// Which aggregate of the area's temperatures is compared against the threshold.
+    pub measurement: Measurement,
+    /// The alarm threshold, expressed in the device's configured `TemperatureUnit`.
+    pub threshold: f64,
+    /// The number of seconds the condition must persist before raising an alarm.
+    #[serde(default)]
+    pub delay: u32,
+    /// The PTZ preset to which this area belongs, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset_nbr: Option<i32>,
+    /// The polygon describing the area, in normalized coordinates from -1.0 to 1.0.
+    #[serde(default)]
+    pub polygon: Vec<[f64; 2]>,
+    /// Fields returned by the device which this crate does not model. These are sent back
+    /// unchanged by `Thermometry::update_area()`.
+    #[serde(flatten)]
+    pub extra: UnknownFields,
+}
+
+/// The kind of temperature change which raises an alarm.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DetectionType {
+    /// The temperature rose above the threshold.
+    Above,
+    /// The temperature fell below the threshold.
+    Below,
+    /// The temperature is increasing faster than the threshold.
+    Increasing,
+    /// The temperature is decreasing faster than the threshold.
+    Decreasing,
+}
+
+/// The aggregate of an area's temperatures which is compared against a threshold.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Measurement {
+    /// The hottest point in the area.
+    Maximum,
+    /// The coldest point in the area.
+    Minimum,
+    /// The average of the area.
+    Average,
+}
+
+/// The current temperature readings for an area.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AreaStatus {
+    /// The area's identifier.
+    pub id: u32,
+    /// The hottest point in the area.
+    pub maximum: Temperature,
+    /// The coldest point in the area.
+    pub minimum: Temperature,
+    /// The average of the area.
+    pub average: Temperature,
+    /// Is the area's alarm condition currently met?
+    pub triggered: bool,
+    /// Fields returned by the device which this crate does not model.
+    #[serde(flatten)]
+    pub extra: UnknownFields,
+}
+
+impl<'a, T: Transport> Thermometry<'a, T> {
+    pub(crate) fn new(client: &'a Client<T>, api_version: String) -> Self {
+        Self(
+            JsonService::new(
+                client,
+                "/axis-cgi/thermometry/thermometryarea.cgi",
+                api_version,
+            )
+            .with_service_id("thermometry"),
+        )
+    }
+
+    /// Retrieve the unit in which the device reports temperatures.
+    pub async fn temperature_unit(&self) -> Result<TemperatureUnit> {
+        #[derive(Deserialize)]
+        struct Resp {
+            unit: TemperatureUnit,
+        }
+
+        let resp: Resp = self.0.call_method_bare("getTemperatureScale").await?;
+        Ok(resp.unit)
+    }
+
+    /// Set the unit in which the device reports temperatures.
+    pub async fn set_temperature_unit(&self, unit: TemperatureUnit) -> Result<()> {
+        #[derive(Serialize)]
+        struct Req {
+            unit: TemperatureUnit,
+        }
+
+        self.0
+            .call_method_without_data("setTemperatureScale", Req { unit })
+            .await
+    }
+
+    /// List the configured measurement areas.
+    pub async fn areas(&self) -> Result<Vec<ThermometryArea>> {
+        #[derive(Deserialize)]
+        struct Resp {
+            areas: Vec<ThermometryArea>,
+        }
+
+        let resp: Resp = self.0.call_method_bare("listAreas").await?;
+        Ok(resp.areas)
+    }
+
+    /// Update a measurement area, e.g. to change its alarm threshold.
+    ///
+    /// `area.threshold` is interpreted in the device's configured `TemperatureUnit`. Use
+    /// `set_alarm_threshold()` to specify a threshold in a particular unit.
+    pub async fn update_area(&self, area: &ThermometryArea) -> Result<()> {
+        self.0.call_method_without_data("updateArea", area).await
+    }
+
+    /// Change the alarm threshold of a measurement area.
+    pub async fn set_alarm_threshold(
+        &self,
+        area_id: u32,
+        detection_type: DetectionType,
+        measurement: Measurement,
+        threshold: Temperature,
+    ) -> Result<()> {
+        let unit = self.temperature_unit().await?;
+        let mut area = self
+            .areas()
+            .await?
+            .into_iter()
+            .find(|area| area.id == area_id)
+            .ok_or(Error::Other("no such thermometry area"))?;
+
+        area.detection_type = detection_type;
+        area.measurement = measurement;
+        area.threshold = threshold.to_unit(unit).value;
+        self.update_area(&area).await
+    }
+
+    /// Read the current temperatures of every measurement area.
+    ///
+    /// This makes two requests: one to determine the device's temperature unit, and one to read
+    /// the temperatures.
+    pub async fn area_status(&self) -> Result<Vec<AreaStatus>> {
+        #[derive(Deserialize)]
+        struct Resp {
+            areas: Vec<RawAreaStatus>,
+        }
+
+        #[derive(Deserialize)]
+        struct RawAreaStatus {
+            id: u32,
+            max: f64,
+            min: f64,
+            avg: f64,
+            #[serde(default)]
+            triggered: bool,
+            #[serde(flatten)]
+            extra: UnknownFields,
+        }
+
+        let unit = self.temperature_unit().await?;
+        let resp: Resp = self.0.call_method_bare("getAreaStatus").await?;
+
+        let temperature = |value| Temperature { value, unit };
+        Ok(resp
+            .areas
+            .into_iter()
+            .map(|raw| AreaStatus {
+                id: raw.id,
+                maximum: temperature(raw.max),
+                minimum: temperature(raw.min),
+                average: temperature(raw.avg),
+                triggered: raw.triggered,
+                extra: raw.extra,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temperature_conversion() {
+        assert_eq!(Temperature::celsius(100.0).as_fahrenheit(), 212.0);
+        assert_eq!(Temperature::fahrenheit(32.0).as_celsius(), 0.0);
+        assert_eq!(
+            Temperature::celsius(-40.0).to_unit(TemperatureUnit::Fahrenheit),
+            Temperature::fahrenheit(-40.0)
+        );
+        assert_eq!(Temperature::celsius(21.5).to_string(), "21.5 °C");
+    }
+
+    #[tokio::test]
+    async fn area_status() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(
+                req.uri().path(),
+                "/axis-cgi/thermometry/thermometryarea.cgi"
+            );
+            let body: serde_json::Value = serde_json::from_slice(req.body()).unwrap();
+            let data = match body["method"].as_str() {
+                Some("getTemperatureScale") => r#"{"unit":"fahrenheit"}"#,
+                Some("getAreaStatus") => {
+                    r#"{"areas":[{"id":1,"max":98.6,"min":50.0,"avg":72.5,"triggered":true,"maxPosition":[0.5,-0.25]}]}"#
+                }
+                other => panic!("unexpected method {:?}", other),
+            };
+
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(vec![
+                    format!(r#"{{"apiVersion":"1.0","data":{}}}"#, data).into_bytes()
+                ])
+        });
+
+        let thermometry = Thermometry::new(&client, "1.0".to_string());
+        let status = thermometry.area_status().await.unwrap();
+        assert_eq!(
+            status,
+            vec![AreaStatus {
+                id: 1,
+                maximum: Temperature::fahrenheit(98.6),
+                minimum: Temperature::fahrenheit(50.0),
+                average: Temperature::fahrenheit(72.5),
+                triggered: true,
+                extra: serde_json::json!({"maxPosition": [0.5, -0.25]})
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+            }]
+        );
+    }
+}
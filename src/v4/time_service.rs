@@ -0,0 +1,527 @@
+//! The [time API](https://www.axis.com/vapix-library/subjects/t10175981/section/t10036011/display),
+//! including the device's configured time zone.
+
+use crate::v4::{JsonService, UnknownFields};
+use crate::*;
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The time API.
+pub struct TimeService<'a, T: Transport>(JsonService<'a, T>);
+
+/// The device's clock and time zone settings.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeInfo {
+    /// The device's current time.
+    pub date_time: DateTime<Utc>,
+    /// The device's current time, expressed in its local time zone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_date_time: Option<DateTime<FixedOffset>>,
+    /// The configured IANA time zone, like `"Europe/Stockholm"`, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+    /// The configured time zone as a POSIX `TZ` string, like `"CET-1CEST,M3.5.0,M10.5.0/3"`.
+    pub posix_time_zone: String,
+    /// Does the device observe daylight saving time?
+    #[serde(default)]
+    pub dst_enabled: bool,
+    /// The last year the device's clock can represent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_supported_year: Option<i32>,
+    /// Fields returned by the device which this crate does not model.
+    #[serde(flatten)]
+    pub extra: UnknownFields,
+}
+
+impl TimeInfo {
+    /// Parse the device's time zone rules.
+    ///
+    /// Daylight saving time rules are discarded if the device has DST disabled.
+    pub fn posix_time_zone(&self) -> Result<PosixTimeZone, TimeZoneParseError> {
+        let tz: PosixTimeZone = self.posix_time_zone.parse()?;
+        Ok(tz.with_dst_enabled(self.dst_enabled))
+    }
+}
+
+impl<'a, T: Transport> TimeService<'a, T> {
+    pub(crate) fn new(client: &'a Client<T>, api_version: String) -> Self {
+        Self(
+            JsonService::new(client, "/axis-cgi/time.cgi", api_version)
+                .with_service_id("time-service"),
+        )
+    }
+
+    /// Retrieve the device's clock and time zone settings.
+    pub async fn info(&self) -> Result<TimeInfo> {
+        self.0.call_method_bare("getAll").await
+    }
+
+    /// Retrieve the device's time zone rules, e.g. to interpret timestamps which the device
+    /// expresses in local time.
+    pub async fn time_zone(&self) -> Result<PosixTimeZone> {
+        self.info()
+            .await?
+            .posix_time_zone()
+            .map_err(|_| Error::Other("device reported an unparseable POSIX time zone"))
+    }
+}
+
+/// A time zone described by a POSIX `TZ` string, like `"EST5EDT,M3.2.0,M11.1.0"`.
+///
+/// AXIS devices report their time zone in this form, which describes the standard offset and
+/// any yearly daylight saving time rule without requiring a time zone database.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PosixTimeZone {
+    std_offset: FixedOffset,
+    dst: Option<DstRule>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+struct DstRule {
+    offset: FixedOffset,
+    start: Transition,
+    end: Transition,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+struct Transition {
+    date: TransitionDate,
+    /// Seconds after local midnight, which may be negative or exceed one day.
+    time: i32,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+enum TransitionDate {
+    /// `Jn`: day 1 through 365, never counting February 29.
+    Julian(u16),
+    /// `n`: day 0 through 365, counting February 29.
+    DayOfYear(u16),
+    /// `Mm.w.d`: weekday `d` of week `w` (5 meaning last) of month `m`.
+    MonthWeekDay { month: u32, week: u32, weekday: u32 },
+}
+
+impl PosixTimeZone {
+    /// A time zone which is always UTC.
+    pub fn utc() -> Self {
+        Self {
+            std_offset: FixedOffset::east(0),
+            dst: None,
+        }
+    }
+
+    /// Discard the daylight saving time rule unless `enabled`.
+    pub(crate) fn with_dst_enabled(mut self, enabled: bool) -> Self {
+        if !enabled {
+            self.dst = None;
+        }
+        self
+    }
+
+    /// The offset from UTC outside of daylight saving time.
+    pub fn standard_offset(&self) -> FixedOffset {
+        self.std_offset
+    }
+
+    /// The offset from UTC during daylight saving time, if this time zone observes it.
+    pub fn dst_offset(&self) -> Option<FixedOffset> {
+        self.dst.map(|dst| dst.offset)
+    }
+
+    /// The offset from UTC in effect at a given local time.
+    ///
+    /// Local times which are skipped when daylight saving time starts, or which occur twice when
+    /// it ends, are taken to be in daylight saving time.
+    pub fn offset_at_local(&self, local: &NaiveDateTime) -> FixedOffset {
+        let dst = match self.dst {
+            Some(dst) => dst,
+            None => return self.std_offset,
+        };
+
+        // Transitions are expressed in the wall clock time in effect just before them
+        let start = dst.start.local_date_time(local.year());
+        let end = dst.end.local_date_time(local.year());
+        if in_dst(local, &start, &end) {
+            dst.offset
+        } else {
+            self.std_offset
+        }
+    }
+
+    /// The offset from UTC in effect at a given instant.
+    pub fn offset_at_utc(&self, utc: &NaiveDateTime) -> FixedOffset {
+        let dst = match self.dst {
+            Some(dst) => dst,
+            None => return self.std_offset,
+        };
+
+        let start = dst.start.local_date_time(utc.year()) - self.std_offset;
+        let end = dst.end.local_date_time(utc.year()) - dst.offset;
+        if in_dst(utc, &start, &end) {
+            dst.offset
+        } else {
+            self.std_offset
+        }
+    }
+
+    /// Interpret a local time in this time zone.
+    pub fn from_local(&self, local: &NaiveDateTime) -> DateTime<FixedOffset> {
+        let offset = self.offset_at_local(local);
+        offset.from_utc_datetime(&(*local - offset))
+    }
+
+    /// Convert a local time in this time zone to UTC.
+    pub fn to_utc(&self, local: &NaiveDateTime) -> DateTime<Utc> {
+        self.from_local(local).with_timezone(&Utc)
+    }
+}
+
+fn in_dst(t: &NaiveDateTime, start: &NaiveDateTime, end: &NaiveDateTime) -> bool {
+    if start <= end {
+        start <= t && t < end
+    } else {
+        // Southern hemisphere: DST spans the new year
+        t >= start || t < end
+    }
+}
+
+impl Transition {
+    fn local_date_time(&self, year: i32) -> NaiveDateTime {
+        let date = match self.date {
+            TransitionDate::Julian(day) => {
+                let leap = NaiveDate::from_ymd_opt(year, 2, 29).is_some();
+                let day = if leap && day >= 60 { day } else { day - 1 };
+                NaiveDate::from_yo(year, 1) + chrono::Duration::days(i64::from(day))
+            }
+            TransitionDate::DayOfYear(day) => {
+                NaiveDate::from_yo(year, 1) + chrono::Duration::days(i64::from(day))
+            }
+            TransitionDate::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            } => {
+                let first = NaiveDate::from_ymd(year, month, 1);
+                let first_weekday = first.weekday().num_days_from_sunday();
+                let mut day = 1 + (weekday + 7 - first_weekday) % 7 + (week - 1) * 7;
+                while NaiveDate::from_ymd_opt(year, month, day).is_none() {
+                    day -= 7;
+                }
+                NaiveDate::from_ymd(year, month, day)
+            }
+        };
+        date.and_hms(0, 0, 0) + chrono::Duration::seconds(i64::from(self.time))
+    }
+}
+
+/// An error returned when parsing a `PosixTimeZone` fails.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TimeZoneParseError;
+
+impl fmt::Display for TimeZoneParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid POSIX time zone")
+    }
+}
+
+impl std::error::Error for TimeZoneParseError {}
+
+impl FromStr for PosixTimeZone {
+    type Err = TimeZoneParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut p = TzParser(s.trim());
+
+        p.name()?;
+        // POSIX offsets are positive west of Greenwich
+        let std_offset = FixedOffset::west_opt(p.offset()?).ok_or(TimeZoneParseError)?;
+
+        let dst = if p.0.is_empty() {
+            None
+        } else {
+            p.name()?;
+            let offset = if p.0.is_empty() || p.0.starts_with(',') {
+                std_offset.local_minus_utc() + 3600
+            } else {
+                -p.offset()?
+            };
+            let offset = FixedOffset::east_opt(offset).ok_or(TimeZoneParseError)?;
+
+            let (start, end) = if p.0.is_empty() {
+                // The rule POSIX leaves implementation-defined; this is the one glibc uses
+                (
+                    Transition {
+                        date: TransitionDate::MonthWeekDay {
+                            month: 3,
+                            week: 2,
+                            weekday: 0,
+                        },
+                        time: 7200,
+                    },
+                    Transition {
+                        date: TransitionDate::MonthWeekDay {
+                            month: 11,
+                            week: 1,
+                            weekday: 0,
+                        },
+                        time: 7200,
+                    },
+                )
+            } else {
+                p.expect(',')?;
+                let start = p.transition()?;
+                p.expect(',')?;
+                let end = p.transition()?;
+                (start, end)
+            };
+
+            Some(DstRule { offset, start, end })
+        };
+
+        if !p.0.is_empty() {
+            return Err(TimeZoneParseError);
+        }
+
+        Ok(PosixTimeZone { std_offset, dst })
+    }
+}
+
+struct TzParser<'a>(&'a str);
+
+impl<'a> TzParser<'a> {
+    fn expect(&mut self, c: char) -> Result<(), TimeZoneParseError> {
+        if self.0.starts_with(c) {
+            self.0 = &self.0[c.len_utf8()..];
+            Ok(())
+        } else {
+            Err(TimeZoneParseError)
+        }
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, f: F) -> &'a str {
+        let end = self.0.find(|c| !f(c)).unwrap_or(self.0.len());
+        let (taken, rest) = self.0.split_at(end);
+        self.0 = rest;
+        taken
+    }
+
+    /// A zone abbreviation, either alphabetic like `CET` or quoted like `<+0530>`.
+    fn name(&mut self) -> Result<&'a str, TimeZoneParseError> {
+        let name = if self.0.starts_with('<') {
+            self.expect('<')?;
+            let name = self.take_while(|c| c != '>');
+            self.expect('>')?;
+            name
+        } else {
+            self.take_while(|c| c.is_ascii_alphabetic())
+        };
+        if name.len() < 3 {
+            return Err(TimeZoneParseError);
+        }
+        Ok(name)
+    }
+
+    fn number(&mut self) -> Result<i32, TimeZoneParseError> {
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() || digits.len() > 3 {
+            return Err(TimeZoneParseError);
+        }
+        digits.parse().map_err(|_| TimeZoneParseError)
+    }
+
+    /// `[+-]hh[:mm[:ss]]`, as seconds.
+    fn offset(&mut self) -> Result<i32, TimeZoneParseError> {
+        let sign = if self.expect('-').is_ok() {
+            -1
+        } else {
+            let _ = self.expect('+');
+            1
+        };
+
+        let hours = self.number()?;
+        let mut minutes = 0;
+        let mut seconds = 0;
+        if self.expect(':').is_ok() {
+            minutes = self.number()?;
+            if self.expect(':').is_ok() {
+                seconds = self.number()?;
+            }
+        }
+        if hours > 167 || minutes > 59 || seconds > 59 {
+            return Err(TimeZoneParseError);
+        }
+        Ok(sign * (hours * 3600 + minutes * 60 + seconds))
+    }
+
+    fn transition(&mut self) -> Result<Transition, TimeZoneParseError> {
+        let date = if self.expect('J').is_ok() {
+            match self.number()? {
+                day @ 1..=365 => TransitionDate::Julian(day as u16),
+                _ => return Err(TimeZoneParseError),
+            }
+        } else if self.expect('M').is_ok() {
+            let month = self.number()? as u32;
+            self.expect('.')?;
+            let week = self.number()? as u32;
+            self.expect('.')?;
+            let weekday = self.number()? as u32;
+            if !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6 {
+                return Err(TimeZoneParseError);
+            }
+            TransitionDate::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            }
+        } else {
+            match self.number()? {
+                day @ 0..=365 => TransitionDate::DayOfYear(day as u16),
+                _ => return Err(TimeZoneParseError),
+            }
+        };
+
+        let time = if self.expect('/').is_ok() {
+            self.offset()?
+        } else {
+            7200
+        };
+
+        Ok(Transition { date, time })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        let tz: PosixTimeZone = "CET-1CEST,M3.5.0,M10.5.0/3".parse().unwrap();
+        assert_eq!(tz.standard_offset(), FixedOffset::east(3600));
+        assert_eq!(tz.dst_offset(), Some(FixedOffset::east(7200)));
+
+        let tz: PosixTimeZone = "<+0530>-5:30".parse().unwrap();
+        assert_eq!(tz.standard_offset(), FixedOffset::east(19800));
+        assert_eq!(tz.dst_offset(), None);
+
+        let tz: PosixTimeZone = "EST5EDT".parse().unwrap();
+        assert_eq!(tz.standard_offset(), FixedOffset::west(5 * 3600));
+        assert_eq!(tz.dst_offset(), Some(FixedOffset::west(4 * 3600)));
+
+        for bad in &[
+            "",
+            "UTC",
+            "X0",
+            "CET-1CEST,M3.5.0",
+            "CET-1CEST,M13.1.0,M10.5.0",
+            "EST5x",
+        ] {
+            assert_eq!(
+                bad.parse::<PosixTimeZone>(),
+                Err(TimeZoneParseError),
+                "{:?}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn northern_hemisphere() {
+        let tz: PosixTimeZone = "CET-1CEST,M3.5.0,M10.5.0/3".parse().unwrap();
+        assert_eq!(
+            tz.to_utc(&local("2020-01-15 12:00:00")).to_rfc3339(),
+            "2020-01-15T11:00:00+00:00"
+        );
+        assert_eq!(
+            tz.to_utc(&local("2020-07-15 12:00:00")).to_rfc3339(),
+            "2020-07-15T10:00:00+00:00"
+        );
+
+        // 2020-03-29 02:00 CET became 03:00 CEST
+        assert_eq!(
+            tz.offset_at_local(&local("2020-03-29 01:59:59")),
+            FixedOffset::east(3600)
+        );
+        assert_eq!(
+            tz.offset_at_local(&local("2020-03-29 03:00:00")),
+            FixedOffset::east(7200)
+        );
+        // 2020-10-25 03:00 CEST became 02:00 CET
+        assert_eq!(
+            tz.offset_at_local(&local("2020-10-25 02:59:59")),
+            FixedOffset::east(7200)
+        );
+        assert_eq!(
+            tz.offset_at_local(&local("2020-10-25 03:00:00")),
+            FixedOffset::east(3600)
+        );
+
+        assert_eq!(
+            tz.offset_at_utc(&local("2020-10-25 00:59:59")),
+            FixedOffset::east(7200)
+        );
+        assert_eq!(
+            tz.offset_at_utc(&local("2020-10-25 01:00:00")),
+            FixedOffset::east(3600)
+        );
+    }
+
+    #[test]
+    fn southern_hemisphere() {
+        let tz: PosixTimeZone = "AEST-10AEDT,M10.1.0,M4.1.0/3".parse().unwrap();
+        assert_eq!(
+            tz.offset_at_local(&local("2020-01-15 12:00:00")),
+            FixedOffset::east(11 * 3600)
+        );
+        assert_eq!(
+            tz.offset_at_local(&local("2020-07-15 12:00:00")),
+            FixedOffset::east(10 * 3600)
+        );
+    }
+
+    #[test]
+    fn julian_days() {
+        // Day 60 is always March 1 for `Jn`, but February 29 in leap years for `n`
+        let tz: PosixTimeZone = "AAA0BBB,J60/0,J300/0".parse().unwrap();
+        assert_eq!(
+            tz.offset_at_local(&local("2020-02-29 12:00:00")),
+            FixedOffset::east(0)
+        );
+        let tz: PosixTimeZone = "AAA0BBB,59/0,J300/0".parse().unwrap();
+        assert_eq!(
+            tz.offset_at_local(&local("2020-02-29 12:00:00")),
+            FixedOffset::east(3600)
+        );
+    }
+
+    #[tokio::test]
+    async fn info() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(req.uri().path(), "/axis-cgi/time.cgi");
+            assert_eq!(
+                req.body().as_slice(),
+                &br#"{"apiVersion":"1.0","method":"getAll"}"#[..]
+            );
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(vec![br#"{"apiVersion":"1.0","method":"getAll","data":{"dateTime":"2020-10-09T15:30:02Z","dstEnabled":false,"localDateTime":"2020-10-09T17:30:02+02:00","maxSupportedYear":2037,"posixTimeZone":"CET-1CEST,M3.5.0,M10.5.0/3","timeZone":"Europe/Stockholm"}}"#.to_vec()])
+        });
+
+        let time = TimeService::new(&client, "1.0".to_string());
+        let info = time.info().await.unwrap();
+        assert_eq!(info.time_zone.as_deref(), Some("Europe/Stockholm"));
+        assert_eq!(info.max_supported_year, Some(2037));
+
+        // DST is disabled, so the DST rule is ignored
+        let tz = time.time_zone().await.unwrap();
+        assert_eq!(tz.standard_offset(), FixedOffset::east(3600));
+        assert_eq!(tz.dst_offset(), None);
+    }
+}
@@ -0,0 +1,377 @@
+//! User accounts, managed through the user management API where the device offers it, or
+//! otherwise through the legacy interface at `/axis-cgi/pwdgrp.cgi`.
+//!
+//! Every account has a role, which determines what it may do, and optionally PTZ control. The two
+//! interfaces describe accounts identically, so callers needn't know which one a device uses.
+
+use crate::v4::JsonService;
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A device's user accounts.
+pub struct UserManagement<'a, T: Transport> {
+    device: &'a Client<T>,
+    json: Option<JsonService<'a, T>>,
+}
+
+/// The role of a user account, from least to most privileged.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UserRole {
+    /// May view video and images.
+    Viewer,
+    /// May also change most settings, but not manage users or system settings.
+    Operator,
+    /// May do anything.
+    Admin,
+}
+
+impl UserRole {
+    /// The `pwdgrp.cgi` security groups which grant this role, and every lesser role.
+    fn security_groups(self) -> &'static str {
+        match self {
+            UserRole::Viewer => "viewer",
+            UserRole::Operator => "operator:viewer",
+            UserRole::Admin => "admin:operator:viewer",
+        }
+    }
+}
+
+/// A user account.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    /// The account's user name.
+    pub name: String,
+    /// The account's role.
+    #[serde(rename = "privileges")]
+    pub role: UserRole,
+    /// May the account control PTZ cameras?
+    #[serde(default)]
+    pub ptz: bool,
+}
+
+impl<'a, T: Transport> UserManagement<'a, T> {
+    /// Manage users through `pwdgrp.cgi`, which every device supports.
+    pub(crate) fn legacy(device: &'a Client<T>) -> Self {
+        Self { device, json: None }
+    }
+
+    /// Manage users through the user management API.
+    pub(crate) fn new(device: &'a Client<T>, api_version: String) -> Self {
+        Self {
+            device,
+            json: Some(
+                JsonService::new(device, "/axis-cgi/usermanagement.cgi", api_version)
+                    .with_service_id("user-management"),
+            ),
+        }
+    }
+
+    /// List the device's user accounts, in order of user name.
+    ///
+    /// Neither `getUsers` nor `pwdgrp.cgi` accepts an offset or a page size, and both return every
+    /// account in a single response, so there is no paginated variant of this method.
+    pub async fn list(&self) -> Result<Vec<User>> {
+        if let Some(json) = &self.json {
+            #[derive(Deserialize)]
+            struct Resp {
+                users: Vec<User>,
+            }
+            let mut users = json.call_method_bare::<Resp>("getUsers").await?.users;
+            users.sort_by(|a, b| a.name.cmp(&b.name));
+            return Ok(users);
+        }
+
+        let body = self.pwdgrp(&[("action", "get")]).await?;
+        Ok(users_from_groups(&parse_groups(&body)))
+    }
+
+    /// Create a user account with the given password.
+    pub async fn add(&self, user: &User, password: &str) -> Result<()> {
+        if let Some(json) = &self.json {
+            return json
+                .call_method_without_data("addUser", UserRequest::new(user, Some(password)))
+                .await;
+        }
+
+        self.device.check_writable()?;
+        let sgrp = security_groups(user);
+        self.pwdgrp(&[
+            ("action", "add"),
+            ("user", &user.name),
+            ("pwd", password),
+            ("grp", "users"),
+            ("sgrp", &sgrp),
+        ])
+        .await?;
+        self.device
+            .audit(|| format!("added user {:?} as {:?}", user.name, user.role))
+            .await;
+        Ok(())
+    }
+
+    /// Change a user account's role and PTZ control, and its password if `password` is given.
+    pub async fn update(&self, user: &User, password: Option<&str>) -> Result<()> {
+        if let Some(json) = &self.json {
+            return json
+                .call_method_without_data("updateUser", UserRequest::new(user, password))
+                .await;
+        }
+
+        self.device.check_writable()?;
+        let sgrp = security_groups(user);
+        let mut args = vec![("action", "update"), ("user", &user.name), ("sgrp", &sgrp)];
+        if let Some(password) = password {
+            args.push(("pwd", password));
+        }
+        self.pwdgrp(&args).await?;
+        self.device
+            .audit(|| format!("updated user {:?} as {:?}", user.name, user.role))
+            .await;
+        Ok(())
+    }
+
+    /// Remove a user account.
+    pub async fn remove(&self, name: &str) -> Result<()> {
+        if let Some(json) = &self.json {
+            #[derive(Serialize)]
+            struct Req<'a> {
+                name: &'a str,
+            }
+            return json
+                .call_method_without_data("removeUser", Req { name })
+                .await;
+        }
+
+        self.device.check_writable()?;
+        self.pwdgrp(&[("action", "remove"), ("user", name)]).await?;
+        self.device
+            .audit(|| format!("removed user {:?}", name))
+            .await;
+        Ok(())
+    }
+
+    async fn pwdgrp(&self, args: &[(&str, &str)]) -> Result<String> {
+        let req = http::Request::builder()
+            .method(http::Method::GET)
+            .uri(self.device.uri_for_args("/axis-cgi/pwdgrp.cgi", args)?)
+            .body(Vec::new())?;
+
+        let (_resp, body) = self
+            .device
+            .roundtrip(req, "text/plain")
+            .await
+            .map_404_to_feature_unavailable()?;
+        let body = String::from_utf8_lossy(&body).into_owned();
+
+        // Failures are reported in plain text, like `Error: account already exists`
+        if body.trim_start().starts_with("Error") {
+            return Err(Error::Other("pwdgrp.cgi request failed"));
+        }
+        Ok(body)
+    }
+}
+
+#[derive(Serialize)]
+struct UserRequest<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    password: Option<&'a str>,
+    privileges: UserRole,
+    ptz: bool,
+}
+
+impl<'a> UserRequest<'a> {
+    fn new(user: &'a User, password: Option<&'a str>) -> Self {
+        Self {
+            name: &user.name,
+            password,
+            privileges: user.role,
+            ptz: user.ptz,
+        }
+    }
+}
+
+fn security_groups(user: &User) -> String {
+    let groups = user.role.security_groups();
+    if user.ptz {
+        format!("{}:ptz", groups)
+    } else {
+        groups.to_string()
+    }
+}
+
+/// Parse the `group="user1,user2"` lines returned by `pwdgrp.cgi?action=get`.
+fn parse_groups(body: &str) -> BTreeMap<String, Vec<String>> {
+    body.lines()
+        .filter_map(|line| {
+            let mut parts = line.trim().splitn(2, '=');
+            let group = parts.next()?.trim();
+            let members = parts.next()?.trim().trim_matches('"');
+            let members = members
+                .split(',')
+                .map(str::trim)
+                .filter(|member| !member.is_empty())
+                .map(str::to_string)
+                .collect();
+            Some((group.to_string(), members))
+        })
+        .collect()
+}
+
+/// Assemble user accounts from their security group memberships.
+///
+/// Accounts are listed in `digusers`, or in `users` on older firmware, and their role is the most
+/// privileged security group they belong to.
+fn users_from_groups(groups: &BTreeMap<String, Vec<String>>) -> Vec<User> {
+    let in_group = |group: &str, name: &str| {
+        groups
+            .get(group)
+            .map(|members| members.iter().any(|member| member == name))
+            .unwrap_or(false)
+    };
+
+    let mut names: Vec<&String> = groups
+        .get("digusers")
+        .or_else(|| groups.get("users"))
+        .map(|members| members.iter().collect())
+        .unwrap_or_default();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .map(|name| User {
+            name: name.clone(),
+            role: if in_group("admin", name) {
+                UserRole::Admin
+            } else if in_group("operator", name) {
+                UserRole::Operator
+            } else {
+                UserRole::Viewer
+            },
+            ptz: in_group("ptz", name),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn pwdgrp() {
+        let client = crate::mock_client(|req| {
+            let body: &[u8] = match req.uri().query().unwrap() {
+                "action=get" => {
+                    b"admin=\"root\"\r\n\
+operator=\"root,installer\"\r\n\
+viewer=\"root,installer,guard\"\r\n\
+digusers=\"root,installer,guard\"\r\n\
+ptz=\"root,guard\"\r\n"
+                }
+                "action=add&user=guard&pwd=hunter2&grp=users&sgrp=viewer%3Aptz" => {
+                    b"Created account guard.\r\n"
+                }
+                "action=update&user=guard&sgrp=operator%3Aviewer" => b"Modified account guard.\r\n",
+                "action=remove&user=nobody" => b"Error: account nobody does not exist\r\n",
+                other => panic!("unexpected query {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "text/plain")
+                .body(vec![body.to_vec()])
+        });
+        let users = client.user_management();
+
+        assert_eq!(
+            users.list().await.unwrap(),
+            vec![
+                User {
+                    name: "guard".into(),
+                    role: UserRole::Viewer,
+                    ptz: true,
+                },
+                User {
+                    name: "installer".into(),
+                    role: UserRole::Operator,
+                    ptz: false,
+                },
+                User {
+                    name: "root".into(),
+                    role: UserRole::Admin,
+                    ptz: true,
+                },
+            ]
+        );
+
+        let mut guard = User {
+            name: "guard".into(),
+            role: UserRole::Viewer,
+            ptz: true,
+        };
+        users.add(&guard, "hunter2").await.unwrap();
+        guard.role = UserRole::Operator;
+        guard.ptz = false;
+        users.update(&guard, None).await.unwrap();
+        assert!(users.remove("nobody").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn json() {
+        let client = crate::mock_client(|req| {
+            assert_eq!(req.uri().path(), "/axis-cgi/usermanagement.cgi");
+            let body: serde_json::Value = serde_json::from_slice(req.body()).unwrap();
+            let body = match body["method"].as_str() {
+                Some("getUsers") => {
+                    r#"{"apiVersion":"1.1","data":{"users":[{"name":"root","privileges":"admin","ptz":true},{"name":"guard","privileges":"viewer"}]}}"#
+                }
+                Some("addUser") => {
+                    assert_eq!(
+                        body["params"],
+                        serde_json::json!({
+                            "name": "installer",
+                            "password": "hunter2",
+                            "privileges": "operator",
+                            "ptz": false,
+                        })
+                    );
+                    r#"{"apiVersion":"1.1","data":{}}"#
+                }
+                other => panic!("unexpected method {:?}", other),
+            };
+            http::Response::builder()
+                .status(http::StatusCode::OK)
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(vec![body.as_bytes().to_vec()])
+        });
+        let users = UserManagement::new(&client, "1.1".into());
+
+        let list = users.list().await.unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].name, "guard");
+        assert_eq!(list[0].role, UserRole::Viewer);
+        assert!(!list[0].ptz);
+        assert_eq!(list[1].role, UserRole::Admin);
+
+        users
+            .add(
+                &User {
+                    name: "installer".into(),
+                    role: UserRole::Operator,
+                    ptz: false,
+                },
+                "hunter2",
+            )
+            .await
+            .unwrap();
+
+        let client = client.with_read_only(true);
+        match UserManagement::legacy(&client).remove("guard").await {
+            Err(Error::ReadOnlyMode) => {}
+            other => panic!("expected ReadOnlyMode, got {:?}", other),
+        }
+    }
+}
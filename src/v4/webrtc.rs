@@ -0,0 +1,252 @@
+//! The WebRTC signaling API, added in AXIS OS 11.
+//!
+//! WebRTC sessions are negotiated over a WebSocket at `/vapix/ws-data-stream?sources=webrtc`.
+//! The client initiates a session for a video channel, after which the device sends an SDP
+//! offer, the client replies with an SDP answer, and both sides exchange ICE candidates as they
+//! are discovered. Media then flows peer-to-peer, bypassing this crate entirely.
+//!
+//! `Transport` is request/response HTTP and cannot carry a WebSocket, so the caller opens the
+//! signaling socket itself, authenticating the upgrade request with the same credentials as this
+//! client. This module provides the URI and the typed messages exchanged over it.
+
+use crate::*;
+use serde::{Deserialize, Serialize};
+
+const SIGNALING_PATH: &str = "/vapix/ws-data-stream?sources=webrtc";
+const API_VERSION: &str = "1.0";
+
+string_type!(
+    /// The identifier of a WebRTC session, chosen by the client.
+    pub struct SessionId
+);
+
+/// A device's WebRTC signaling interface.
+pub struct WebRtc<'a, T: Transport>(&'a Client<T>);
+
+impl<'a, T: Transport> WebRtc<'a, T> {
+    pub(crate) fn new(device: &'a Client<T>) -> Self {
+        Self(device)
+    }
+
+    /// The URI of the signaling WebSocket.
+    pub fn signaling_uri(&self) -> Result<http::Uri> {
+        self.0.websocket_uri_for(SIGNALING_PATH)
+    }
+
+    /// Build the message which starts a live view session for a video channel.
+    pub fn init_session(&self, session_id: SessionId, channel: Channel) -> SignalingMessage {
+        SignalingMessage::new(
+            MessageType::Request,
+            Signal::InitSession(InitSession {
+                session_type: SessionType::Live,
+                session_id,
+                video_receive: VideoReceive { channel },
+            }),
+        )
+    }
+}
+
+/// A message sent or received over the signaling WebSocket.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalingMessage {
+    /// The version of the signaling API.
+    pub api_version: String,
+    /// The kind of message.
+    #[serde(rename = "type")]
+    pub message_type: MessageType,
+    /// The message's method and parameters.
+    #[serde(flatten)]
+    pub signal: Signal,
+}
+
+impl SignalingMessage {
+    /// Construct a message using the supported API version.
+    pub fn new(message_type: MessageType, signal: Signal) -> Self {
+        Self {
+            api_version: API_VERSION.to_string(),
+            message_type,
+            signal,
+        }
+    }
+
+    /// Build an SDP answer to an offer received from the device.
+    pub fn answer(session_id: SessionId, sdp: String) -> Self {
+        Self::new(
+            MessageType::Signaling,
+            Signal::SetSdpAnswer(SessionDescription {
+                session_id,
+                sdp_type: SdpType::Answer,
+                sdp,
+            }),
+        )
+    }
+
+    /// Build a message carrying a locally discovered ICE candidate.
+    pub fn ice_candidate(candidate: IceCandidate) -> Self {
+        Self::new(MessageType::Signaling, Signal::AddIceCandidate(candidate))
+    }
+
+    /// Parse a message received from the signaling WebSocket.
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Serialize this message for sending over the signaling WebSocket.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+/// The kind of a signaling message.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MessageType {
+    /// A request from the client.
+    Request,
+    /// A response from the device.
+    Response,
+    /// An SDP or ICE exchange, in either direction.
+    Signaling,
+}
+
+/// The method and parameters of a signaling message.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "camelCase")]
+pub enum Signal {
+    /// Start a session.
+    InitSession(InitSession),
+    /// An SDP offer, sent by the device.
+    SetSdpOffer(SessionDescription),
+    /// An SDP answer, sent by the client.
+    SetSdpAnswer(SessionDescription),
+    /// An ICE candidate, sent by either side.
+    AddIceCandidate(IceCandidate),
+}
+
+/// Parameters for starting a session.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitSession {
+    /// The kind of session.
+    #[serde(rename = "type")]
+    pub session_type: SessionType,
+    /// The identifier of the session.
+    pub session_id: SessionId,
+    /// The video to receive.
+    pub video_receive: VideoReceive,
+}
+
+/// The kind of a WebRTC session.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SessionType {
+    /// Live view.
+    Live,
+}
+
+/// The video received in a session.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoReceive {
+    /// The video channel.
+    pub channel: Channel,
+}
+
+/// An SDP offer or answer.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDescription {
+    /// The session to which this description applies.
+    pub session_id: SessionId,
+    /// Whether this is an offer or an answer.
+    #[serde(rename = "type")]
+    pub sdp_type: SdpType,
+    /// The session description itself.
+    pub sdp: String,
+}
+
+/// The role of an SDP session description.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SdpType {
+    /// An offer.
+    Offer,
+    /// An answer.
+    Answer,
+}
+
+/// An ICE candidate, in the shape used by the browser `RTCIceCandidate` API.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IceCandidate {
+    /// The session to which this candidate applies.
+    pub session_id: SessionId,
+    /// The candidate line, e.g. `"candidate:1 1 UDP 2122252543 192.168.0.90 50000 typ host"`.
+    pub candidate: String,
+    /// The media stream identification tag of the associated media section.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sdp_mid: Option<String>,
+    /// The index of the associated media section.
+    #[serde(
+        rename = "sdpMLineIndex",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sdp_m_line_index: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signaling() {
+        let client = crate::mock_client(|_| -> Result<http::Response<Vec<Vec<u8>>>, http::Error> {
+            unreachable!()
+        });
+        let webrtc = client.webrtc();
+
+        assert_eq!(
+            webrtc.signaling_uri().unwrap(),
+            "ws://1.2.3.4/vapix/ws-data-stream?sources=webrtc"
+        );
+
+        let init = webrtc.init_session("abc".into(), Channel::first());
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&init.to_json().unwrap()).unwrap(),
+            serde_json::json!({
+                "apiVersion": "1.0",
+                "type": "request",
+                "method": "initSession",
+                "params": {
+                    "type": "live",
+                    "sessionId": "abc",
+                    "videoReceive": { "channel": 1 },
+                },
+            })
+        );
+
+        let offer = SignalingMessage::from_json(
+            r#"{"apiVersion":"1.0","type":"signaling","method":"setSdpOffer","params":{"sessionId":"abc","type":"offer","sdp":"v=0\r\n"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            offer.signal,
+            Signal::SetSdpOffer(SessionDescription {
+                session_id: "abc".into(),
+                sdp_type: SdpType::Offer,
+                sdp: "v=0\r\n".into(),
+            })
+        );
+
+        let candidate = SignalingMessage::from_json(
+            r#"{"apiVersion":"1.0","type":"signaling","method":"addIceCandidate","params":{"sessionId":"abc","candidate":"candidate:1 1 UDP 2122252543 192.168.0.90 50000 typ host","sdpMLineIndex":0}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            SignalingMessage::from_json(&candidate.to_json().unwrap()).unwrap(),
+            candidate
+        );
+    }
+}
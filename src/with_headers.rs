@@ -0,0 +1,117 @@
+use chrono::prelude::*;
+
+/// A value returned by the device, along with the HTTP response headers which accompanied it.
+///
+/// Some endpoints communicate useful information via headers, like the `Date:` at which a response
+/// was generated or the `Content-Disposition:` filename of a download. Methods which return
+/// `WithHeaders<T>` preserve those headers for the caller.
+#[derive(Debug, Clone)]
+pub struct WithHeaders<T> {
+    value: T,
+    headers: http::HeaderMap,
+}
+
+impl<T> WithHeaders<T> {
+    pub(crate) fn new(value: T, headers: http::HeaderMap) -> Self {
+        Self { value, headers }
+    }
+
+    /// Returns a reference to the value.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns the response headers.
+    pub fn headers(&self) -> &http::HeaderMap {
+        &self.headers
+    }
+
+    /// Returns a single response header, if present.
+    pub fn header<K: http::header::AsHeaderName>(&self, name: K) -> Option<&http::HeaderValue> {
+        self.headers.get(name)
+    }
+
+    /// Returns the time at which the device generated the response, as indicated by the `Date:`
+    /// header.
+    ///
+    /// Returns `None` if the header is absent or unparseable.
+    pub fn date(&self) -> Option<DateTime<FixedOffset>> {
+        self.headers
+            .get(http::header::DATE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|d| DateTime::parse_from_rfc2822(d).ok())
+    }
+
+    /// Returns the filename suggested by the `Content-Disposition:` header, if any.
+    pub fn content_disposition_filename(&self) -> Option<&str> {
+        self.headers
+            .get(http::header::CONTENT_DISPOSITION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| {
+                v.split(';')
+                    .map(str::trim)
+                    .find(|param| param.starts_with("filename="))
+            })
+            .map(|param| param["filename=".len()..].trim_matches('"'))
+    }
+
+    /// Transform the value, retaining the headers.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> WithHeaders<U> {
+        WithHeaders {
+            value: f(self.value),
+            headers: self.headers,
+        }
+    }
+
+    /// Unwraps the value, discarding the headers.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Unwraps the value and the headers.
+    pub fn into_parts(self) -> (T, http::HeaderMap) {
+        (self.value, self.headers)
+    }
+}
+
+impl<T> std::ops::Deref for WithHeaders<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accessors() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert(
+            http::header::DATE,
+            http::HeaderValue::from_static("Fri, 09 Oct 2020 15:30:02 GMT"),
+        );
+        headers.insert(
+            http::header::CONTENT_DISPOSITION,
+            http::HeaderValue::from_static(r#"attachment; filename="recording.mkv""#),
+        );
+
+        let value = WithHeaders::new(42u32, headers);
+        assert_eq!(*value, 42);
+        assert_eq!(
+            value.date(),
+            Some(Utc.ymd(2020, 10, 9).and_hms(15, 30, 2).into())
+        );
+        assert_eq!(value.content_disposition_filename(), Some("recording.mkv"));
+
+        let value = value.map(|v| v.to_string());
+        assert_eq!(value.value(), "42");
+        assert!(value.header(http::header::DATE).is_some());
+
+        let value = WithHeaders::new((), http::HeaderMap::new());
+        assert_eq!(value.date(), None);
+        assert_eq!(value.content_disposition_filename(), None);
+    }
+}
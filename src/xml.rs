@@ -0,0 +1,112 @@
+//! Lenient decoding of XML response bodies.
+//!
+//! `quick_xml` expects UTF-8 text starting with the document itself, but some firmware prefixes
+//! documents with a byte order mark, leading whitespace, or an XML declaration naming another
+//! encoding. `decode()` normalizes all of these, so every XML endpoint tolerates the same
+//! variations.
+
+use crate::{Error, Result};
+use std::borrow::Cow;
+
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+const UTF16LE_BOM: &[u8] = b"\xff\xfe";
+const UTF16BE_BOM: &[u8] = b"\xfe\xff";
+
+/// Strip a UTF-8 byte order mark, if present.
+pub(crate) fn strip_bom(body: &[u8]) -> &[u8] {
+    body.strip_prefix(UTF8_BOM).unwrap_or(body)
+}
+
+/// Decode an XML document into UTF-8 text, removing any byte order mark and XML declaration.
+///
+/// UTF-16 is recognized by its byte order mark. Otherwise, documents are assumed to be UTF-8,
+/// except that a document which isn't valid UTF-8 and declares a Latin-1 encoding is decoded as
+/// Latin-1. Firmware often declares `ISO-8859-1` while sending UTF-8, so valid UTF-8 wins.
+pub(crate) fn decode(body: &[u8]) -> Result<Cow<'_, str>> {
+    let text: Cow<str> = if let Some(body) = body.strip_prefix(UTF16LE_BOM) {
+        Cow::Owned(decode_utf16(body, u16::from_le_bytes)?)
+    } else if let Some(body) = body.strip_prefix(UTF16BE_BOM) {
+        Cow::Owned(decode_utf16(body, u16::from_be_bytes)?)
+    } else {
+        let body = strip_bom(body);
+        match std::str::from_utf8(body) {
+            Ok(text) => Cow::Borrowed(text),
+            Err(_) if declares_latin1(body) => {
+                Cow::Owned(body.iter().map(|&b| b as char).collect())
+            }
+            Err(_) => return Err(Error::Other("invalid UTF-8")),
+        }
+    };
+
+    Ok(match text {
+        Cow::Borrowed(text) => Cow::Borrowed(strip_declaration(text)),
+        Cow::Owned(text) => Cow::Owned(strip_declaration(&text).to_string()),
+    })
+}
+
+/// Deserialize an XML document after normalizing it with `decode()`.
+pub(crate) fn from_slice<T: serde::de::DeserializeOwned>(body: &[u8]) -> Result<T> {
+    Ok(quick_xml::de::from_str(&decode(body)?)?)
+}
+
+fn decode_utf16(body: &[u8], from_bytes: fn([u8; 2]) -> u16) -> Result<String> {
+    let pairs = body.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return Err(Error::Other("invalid UTF-16"));
+    }
+    let units = pairs.map(|pair| from_bytes([pair[0], pair[1]]));
+    std::char::decode_utf16(units)
+        .collect::<std::result::Result<String, _>>()
+        .map_err(|_| Error::Other("invalid UTF-16"))
+}
+
+fn declares_latin1(body: &[u8]) -> bool {
+    let prolog = body.split(|&b| b == b'>').next().unwrap_or(&[]);
+    let prolog = String::from_utf8_lossy(prolog).to_ascii_lowercase();
+    prolog.contains("<?xml")
+        && [
+            "iso-8859-1",
+            "iso8859-1",
+            "latin1",
+            "latin-1",
+            "windows-1252",
+        ]
+        .iter()
+        .any(|encoding| prolog.contains(encoding))
+}
+
+fn strip_declaration(text: &str) -> &str {
+    let text = text.trim_start();
+    if text.starts_with("<?xml") {
+        if let Some(end) = text.find("?>") {
+            return text[end + 2..].trim_start();
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_variations() {
+        assert_eq!(decode(b"<root/>").unwrap(), "<root/>");
+        assert_eq!(
+            decode(b"\xef\xbb\xbf\r\n<?xml version=\"1.0\"?>\n<root/>").unwrap(),
+            "<root/>"
+        );
+        assert_eq!(
+            decode(b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><a>caf\xe9</a>").unwrap(),
+            "<a>caf\u{e9}</a>"
+        );
+        assert_eq!(
+            decode("<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><a>café</a>".as_bytes())
+                .unwrap(),
+            "<a>caf\u{e9}</a>"
+        );
+        assert_eq!(decode(b"\xfe\xff\x00<\x00a\x00/\x00>").unwrap(), "<a/>");
+        assert!(decode(b"<a>caf\xe9</a>").is_err());
+        assert!(decode(b"\xff\xfe<").is_err());
+    }
+}